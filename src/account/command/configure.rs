@@ -5,10 +5,18 @@ use email::imap::config::ImapAuthConfig;
 #[cfg(feature = "smtp")]
 use email::smtp::config::SmtpAuthConfig;
 use log::info;
-#[cfg(any(feature = "imap", feature = "smtp"))]
+#[cfg(any(feature = "imap", feature = "jmap", feature = "pop", feature = "smtp"))]
 use log::{debug, warn};
 
-#[cfg(any(feature = "imap", feature = "smtp", feature = "pgp"))]
+#[cfg(feature = "jmap")]
+use crate::jmap::JmapAuthConfig;
+#[cfg(any(
+    feature = "imap",
+    feature = "jmap",
+    feature = "pop",
+    feature = "smtp",
+    feature = "pgp"
+))]
 use crate::ui::prompt;
 use crate::{account::arg::name::AccountNameArg, config::TomlConfig, printer::Printer};
 
@@ -50,6 +58,22 @@ impl AccountConfigureCommand {
                 }
             }
 
+            #[cfg(feature = "jmap")]
+            if let Some(ref config) = account_config.jmap {
+                if let Err(err) = config.auth.reset().await {
+                    warn!("error while resetting jmap secrets: {err}");
+                    debug!("error while resetting jmap secrets: {err:?}");
+                }
+            }
+
+            #[cfg(feature = "pop")]
+            if let Some(ref config) = account_config.pop {
+                if let Err(err) = config.passwd.reset().await {
+                    warn!("error while resetting pop secrets: {err}");
+                    debug!("error while resetting pop secrets: {err:?}");
+                }
+            }
+
             #[cfg(feature = "smtp")]
             if let Some(ref config) = account_config.smtp {
                 let reset = match &config.auth {
@@ -82,6 +106,23 @@ impl AccountConfigureCommand {
             }?;
         }
 
+        #[cfg(feature = "jmap")]
+        if let Some(ref config) = account_config.jmap {
+            let label = match &config.auth {
+                JmapAuthConfig::Passwd(_) => "JMAP password",
+                JmapAuthConfig::Bearer(_) => "JMAP API token",
+            };
+            config.auth.configure(|| prompt::passwd(label)).await?;
+        }
+
+        #[cfg(feature = "pop")]
+        if let Some(ref config) = account_config.pop {
+            config
+                .passwd
+                .configure(|| prompt::passwd("POP password"))
+                .await?;
+        }
+
         #[cfg(feature = "smtp")]
         if let Some(ref config) = account_config.smtp {
             match &config.auth {
@@ -1,21 +1,31 @@
 use anyhow::Result;
 use clap::{ArgAction, Parser};
+#[cfg(feature = "search")]
+use email::account::config::AccountConfig;
 use email::{
     account::sync::{AccountSyncBuilder, AccountSyncProgressEvent},
     folder::sync::FolderSyncStrategy,
 };
 use indicatif::{MultiProgress, ProgressBar, ProgressFinish, ProgressStyle};
 use log::info;
+#[cfg(feature = "search")]
+use log::{debug, warn};
 use once_cell::sync::Lazy;
 use std::{
     collections::{HashMap, HashSet},
     sync::Mutex,
 };
 
+#[cfg(feature = "search")]
+use crate::account::config::TomlAccountConfig;
+#[cfg(feature = "search")]
+use crate::search;
 use crate::{
     account::arg::name::OptionalAccountNameArg, backend::BackendBuilder, config::TomlConfig,
     printer::Printer,
 };
+#[cfg(feature = "pop")]
+use crate::{backend::BackendKind, pop};
 
 static MAIN_PROGRESS_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
     ProgressStyle::with_template(" {spinner:.dim} {msg:.dim}\n {wide_bar:.cyan/blue} \n").unwrap()
@@ -100,8 +110,44 @@ impl AccountSyncCommand {
             config.clone().into_account_configs(account, true)?;
         let account_name = account_config.name.as_str();
 
+        // POP accounts only have an inbox and no flags, so they are
+        // synchronized by downloading new messages
+        #[cfg(feature = "pop")]
+        if let (Some(BackendKind::Pop), Some(pop_config)) =
+            (&toml_account_config.backend, &toml_account_config.pop)
+        {
+            let report = pop::sync::sync(&account_config, pop_config, self.dry_run).await?;
+
+            if self.dry_run {
+                if !report.fetched.is_empty() {
+                    printer.print_log("Messages patch:")?;
+                    for uidl in &report.fetched {
+                        printer.print_log(format!(" - download message {uidl}"))?;
+                    }
+                    printer.print_log("")?;
+                }
+
+                let hunks_count = report.fetched.len() + report.deleted;
+                printer.print(format!(
+                    "Estimated patch length for account {account_name} to be synchronized: {hunks_count}"
+                ))?;
+            } else {
+                printer.print_log(format!(
+                    "{} new message(s) downloaded, {} deleted from server",
+                    report.fetched.len(),
+                    report.deleted,
+                ))?;
+                printer.print(format!("Account {account_name} successfully synchronized!"))?;
+
+                #[cfg(feature = "search")]
+                update_search_index(&toml_account_config, &account_config).await;
+            }
+
+            return Ok(());
+        }
+
         let backend_builder =
-            BackendBuilder::new(toml_account_config, account_config.clone()).await?;
+            BackendBuilder::new(toml_account_config.clone(), account_config.clone()).await?;
         let sync_builder = AccountSyncBuilder::new(backend_builder.into())
             .await?
             .with_some_folders_strategy(strategy)
@@ -248,6 +294,33 @@ impl AccountSyncCommand {
             printer.print(format!("Account {account_name} successfully synchronized!"))?;
         }
 
+        #[cfg(feature = "search")]
+        if !self.dry_run {
+            update_search_index(&toml_account_config, &account_config).await;
+        }
+
         Ok(())
     }
 }
+
+/// Update the search index of the given account from its freshly
+/// synchronized Maildir. Failures are only logged, since the
+/// synchronization itself succeeded.
+#[cfg(feature = "search")]
+async fn update_search_index(
+    toml_account_config: &TomlAccountConfig,
+    account_config: &AccountConfig,
+) {
+    let account_name = &account_config.name;
+    let report = search::update_index(toml_account_config, account_config).await;
+
+    match report {
+        Ok(report) => {
+            debug!("search index of account {account_name} updated: {report:?}");
+        }
+        Err(err) => {
+            warn!("cannot update search index of account {account_name}: {err}");
+            debug!("{err:?}");
+        }
+    }
+}
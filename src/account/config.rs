@@ -11,13 +11,25 @@ use email::account::sync::config::SyncConfig;
 use email::imap::config::ImapConfig;
 #[cfg(feature = "maildir")]
 use email::maildir::config::MaildirConfig;
+#[cfg(feature = "notmuch")]
+use email::notmuch::NotmuchConfig;
 #[cfg(feature = "sendmail")]
 use email::sendmail::config::SendmailConfig;
 #[cfg(feature = "smtp")]
 use email::smtp::config::SmtpConfig;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "envelope-list")]
+use std::collections::HashMap;
 use std::{collections::HashSet, path::PathBuf};
 
+#[cfg(feature = "jmap")]
+use crate::jmap::JmapConfig;
+#[cfg(feature = "mbox")]
+use crate::mbox::MboxConfig;
+#[cfg(any(feature = "message-get", feature = "message-read"))]
+use crate::message::html::HtmlRenderer;
+#[cfg(feature = "pop")]
+use crate::pop::PopConfig;
 use crate::{
     backend::BackendKind, envelope::config::EnvelopeConfig, flag::config::FlagConfig,
     folder::config::FolderConfig, message::config::MessageConfig,
@@ -47,10 +59,16 @@ pub struct TomlAccountConfig {
 
     #[cfg(feature = "imap")]
     pub imap: Option<ImapConfig>,
+    #[cfg(feature = "jmap")]
+    pub jmap: Option<JmapConfig>,
     #[cfg(feature = "maildir")]
     pub maildir: Option<MaildirConfig>,
+    #[cfg(feature = "mbox")]
+    pub mbox: Option<MboxConfig>,
     #[cfg(feature = "notmuch")]
     pub notmuch: Option<NotmuchConfig>,
+    #[cfg(feature = "pop")]
+    pub pop: Option<PopConfig>,
     #[cfg(feature = "smtp")]
     pub smtp: Option<SmtpConfig>,
     #[cfg(feature = "sendmail")]
@@ -121,6 +139,22 @@ impl TomlAccountConfig {
             .or(self.backend.as_ref())
     }
 
+    #[cfg(feature = "envelope-list")]
+    pub fn list_envelopes_columns(&self) -> Option<&Vec<String>> {
+        self.envelope
+            .as_ref()
+            .and_then(|envelope| envelope.list.as_ref())
+            .and_then(|list| list.columns.as_ref())
+    }
+
+    #[cfg(feature = "envelope-list")]
+    pub fn list_envelopes_colors(&self) -> Option<&HashMap<String, String>> {
+        self.envelope
+            .as_ref()
+            .and_then(|envelope| envelope.list.as_ref())
+            .and_then(|list| list.colors.as_ref())
+    }
+
     #[cfg(feature = "envelope-watch")]
     pub fn watch_envelopes_kind(&self) -> Option<&BackendKind> {
         self.envelope
@@ -184,6 +218,22 @@ impl TomlAccountConfig {
             .or(self.backend.as_ref())
     }
 
+    #[cfg(any(feature = "message-get", feature = "message-read"))]
+    pub fn read_messages_html_renderer(&self) -> Option<&HtmlRenderer> {
+        self.message
+            .as_ref()
+            .and_then(|message| message.read.as_ref())
+            .and_then(|get| get.html_renderer.as_ref())
+    }
+
+    #[cfg(any(feature = "message-get", feature = "message-read"))]
+    pub fn read_messages_pager(&self) -> Option<&str> {
+        self.message
+            .as_ref()
+            .and_then(|message| message.read.as_ref())
+            .and_then(|get| get.pager.as_deref())
+    }
+
     #[cfg(feature = "message-copy")]
     pub fn copy_messages_kind(&self) -> Option<&BackendKind> {
         self.message
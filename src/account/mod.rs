@@ -92,6 +92,14 @@ impl From<Iter<'_, String, TomlAccountConfig>> for Accounts {
                     backends.push_str("imap");
                 }
 
+                #[cfg(feature = "jmap")]
+                if account.jmap.is_some() {
+                    if !backends.is_empty() {
+                        backends.push_str(", ")
+                    }
+                    backends.push_str("jmap");
+                }
+
                 #[cfg(feature = "maildir")]
                 if account.maildir.is_some() {
                     if !backends.is_empty() {
@@ -100,14 +108,30 @@ impl From<Iter<'_, String, TomlAccountConfig>> for Accounts {
                     backends.push_str("maildir");
                 }
 
+                #[cfg(feature = "mbox")]
+                if account.mbox.is_some() {
+                    if !backends.is_empty() {
+                        backends.push_str(", ")
+                    }
+                    backends.push_str("mbox");
+                }
+
                 #[cfg(feature = "notmuch")]
-                if account.imap.is_some() {
+                if account.notmuch.is_some() {
                     if !backends.is_empty() {
                         backends.push_str(", ")
                     }
                     backends.push_str("notmuch");
                 }
 
+                #[cfg(feature = "pop")]
+                if account.pop.is_some() {
+                    if !backends.is_empty() {
+                        backends.push_str(", ")
+                    }
+                    backends.push_str("pop");
+                }
+
                 #[cfg(feature = "smtp")]
                 if account.smtp.is_some() {
                     if !backends.is_empty() {
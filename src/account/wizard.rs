@@ -55,16 +55,31 @@ pub(crate) async fn configure() -> Result<Option<(String, TomlAccountConfig)>> {
             config.imap = Some(imap_config);
             config.backend = Some(BackendKind::Imap);
         }
+        #[cfg(feature = "jmap")]
+        Some(BackendConfig::Jmap(jmap_config)) => {
+            config.jmap = Some(jmap_config);
+            config.backend = Some(BackendKind::Jmap);
+        }
         #[cfg(feature = "maildir")]
         Some(BackendConfig::Maildir(mdir_config)) => {
             config.maildir = Some(mdir_config);
             config.backend = Some(BackendKind::Maildir);
         }
+        #[cfg(feature = "mbox")]
+        Some(BackendConfig::Mbox(mbox_config)) => {
+            config.mbox = Some(mbox_config);
+            config.backend = Some(BackendKind::Mbox);
+        }
         #[cfg(feature = "notmuch")]
         Some(BackendConfig::Notmuch(notmuch_config)) => {
             config.notmuch = Some(notmuch_config);
             config.backend = Some(BackendKind::Notmuch);
         }
+        #[cfg(feature = "pop")]
+        Some(BackendConfig::Pop(pop_config)) => {
+            config.pop = Some(pop_config);
+            config.backend = Some(BackendKind::Pop);
+        }
         _ => (),
     };
 
@@ -84,6 +99,21 @@ pub(crate) async fn configure() -> Result<Option<(String, TomlAccountConfig)>> {
                 });
             }
         }
+        #[cfg(feature = "jmap")]
+        Some(BackendConfig::Jmap(jmap_config)) => {
+            config.jmap = Some(jmap_config);
+
+            #[cfg(feature = "message-send")]
+            {
+                config.message = Some(MessageConfig {
+                    send: Some(MessageSendConfig {
+                        backend: Some(BackendKind::Jmap),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                });
+            }
+        }
         #[cfg(feature = "sendmail")]
         Some(BackendConfig::Sendmail(sendmail_config)) => {
             config.sendmail = Some(sendmail_config);
@@ -3,7 +3,14 @@ use email::imap::config::ImapConfig;
 #[cfg(feature = "maildir")]
 use email::maildir::config::MaildirConfig;
 #[cfg(feature = "notmuch")]
-use email::notmuch::config::NotmuchConfig;
+use email::notmuch::NotmuchConfig;
+
+#[cfg(feature = "jmap")]
+use crate::jmap::JmapConfig;
+#[cfg(feature = "mbox")]
+use crate::mbox::MboxConfig;
+#[cfg(feature = "pop")]
+use crate::pop::PopConfig;
 #[cfg(feature = "sendmail")]
 use email::sendmail::config::SendmailConfig;
 #[cfg(feature = "smtp")]
@@ -13,10 +20,16 @@ use email::smtp::config::SmtpConfig;
 pub enum BackendConfig {
     #[cfg(feature = "imap")]
     Imap(ImapConfig),
+    #[cfg(feature = "jmap")]
+    Jmap(JmapConfig),
     #[cfg(feature = "maildir")]
     Maildir(MaildirConfig),
+    #[cfg(feature = "mbox")]
+    Mbox(MboxConfig),
     #[cfg(feature = "notmuch")]
     Notmuch(NotmuchConfig),
+    #[cfg(feature = "pop")]
+    Pop(PopConfig),
     #[cfg(feature = "smtp")]
     Smtp(SmtpConfig),
     #[cfg(feature = "sendmail")]
@@ -2,9 +2,24 @@ pub mod config;
 #[cfg(feature = "wizard")]
 pub(crate) mod wizard;
 
+#[cfg(feature = "message-send")]
+use anyhow::anyhow;
+#[cfg(any(
+    feature = "envelope-list",
+    feature = "envelope-get",
+    feature = "cache-prune",
+    feature = "search"
+))]
+use anyhow::bail;
 use anyhow::Result;
 use async_trait::async_trait;
-use std::ops::Deref;
+#[cfg(any(feature = "search", feature = "message-send"))]
+use log::debug;
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
+use std::collections::HashMap;
+#[cfg(feature = "cache-prune")]
+use std::collections::HashSet;
+use std::{ops::Deref, path::PathBuf};
 
 use email::account::config::AccountConfig;
 #[cfg(all(feature = "envelope-get", feature = "imap"))]
@@ -89,8 +104,119 @@ use email::{
 };
 use serde::{Deserialize, Serialize};
 
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
+use crate::envelope::details::{self, EnvelopeDetails};
+#[cfg(all(feature = "envelope-list", feature = "imap"))]
+use crate::envelope::search::imap::SearchEnvelopesImap;
+#[cfg(all(feature = "envelope-list", feature = "maildir"))]
+use crate::envelope::search::maildir::SearchEnvelopesMaildir;
+#[cfg(feature = "envelope-thread")]
+use crate::envelope::thread::{self, ThreadedEnvelopes};
+#[cfg(feature = "envelope-get")]
+use crate::envelope::FullEnvelope;
 #[cfg(feature = "envelope-list")]
-use crate::envelope::Envelopes;
+use crate::envelope::{
+    search::{EnvelopesCursor, SearchEnvelopesOptions},
+    Envelopes,
+};
+#[cfg(all(feature = "flag-add", feature = "jmap"))]
+use crate::jmap::AddFlagsJmap;
+#[cfg(all(feature = "message-add", feature = "jmap"))]
+use crate::jmap::AddMessageJmap;
+#[cfg(all(feature = "message-copy", feature = "jmap"))]
+use crate::jmap::CopyMessagesJmap;
+#[cfg(all(feature = "folder-expunge", feature = "jmap"))]
+use crate::jmap::ExpungeFolderJmap;
+#[cfg(all(feature = "envelope-get", feature = "jmap"))]
+use crate::jmap::GetEnvelopeJmap;
+#[cfg(all(feature = "envelope-list", feature = "jmap"))]
+use crate::jmap::ListEnvelopesJmap;
+#[cfg(all(feature = "folder-list", feature = "jmap"))]
+use crate::jmap::ListFoldersJmap;
+#[cfg(all(feature = "message-move", feature = "jmap"))]
+use crate::jmap::MoveMessagesJmap;
+#[cfg(all(feature = "message-peek", feature = "jmap"))]
+use crate::jmap::PeekMessagesJmap;
+#[cfg(all(feature = "flag-remove", feature = "jmap"))]
+use crate::jmap::RemoveFlagsJmap;
+#[cfg(all(feature = "flag-set", feature = "jmap"))]
+use crate::jmap::SetFlagsJmap;
+#[cfg(feature = "jmap")]
+use crate::jmap::{JmapSession, JmapSessionBuilder};
+#[cfg(all(feature = "flag-add", feature = "mbox"))]
+use crate::mbox::AddFlagsMbox;
+#[cfg(all(feature = "folder-add", feature = "mbox"))]
+use crate::mbox::AddFolderMbox;
+#[cfg(all(feature = "message-add", feature = "mbox"))]
+use crate::mbox::AddMessageMbox;
+#[cfg(all(feature = "message-copy", feature = "mbox"))]
+use crate::mbox::CopyMessagesMbox;
+#[cfg(all(feature = "folder-delete", feature = "mbox"))]
+use crate::mbox::DeleteFolderMbox;
+#[cfg(all(feature = "folder-expunge", feature = "mbox"))]
+use crate::mbox::ExpungeFolderMbox;
+#[cfg(all(feature = "envelope-get", feature = "mbox"))]
+use crate::mbox::GetEnvelopeMbox;
+#[cfg(all(feature = "envelope-list", feature = "mbox"))]
+use crate::mbox::ListEnvelopesMbox;
+#[cfg(all(feature = "folder-list", feature = "mbox"))]
+use crate::mbox::ListFoldersMbox;
+#[cfg(all(feature = "message-move", feature = "mbox"))]
+use crate::mbox::MoveMessagesMbox;
+#[cfg(all(feature = "message-peek", feature = "mbox"))]
+use crate::mbox::PeekMessagesMbox;
+#[cfg(all(feature = "folder-purge", feature = "mbox"))]
+use crate::mbox::PurgeFolderMbox;
+#[cfg(all(feature = "flag-remove", feature = "mbox"))]
+use crate::mbox::RemoveFlagsMbox;
+#[cfg(all(feature = "flag-set", feature = "mbox"))]
+use crate::mbox::SetFlagsMbox;
+#[cfg(feature = "mbox")]
+use crate::mbox::{MboxSession, MboxSessionBuilder};
+#[cfg(all(feature = "message-edit", feature = "imap"))]
+use crate::message::draft;
+#[cfg(all(feature = "flag-add", feature = "notmuch"))]
+use crate::notmuch::AddFlagsNotmuch;
+#[cfg(all(feature = "folder-add", feature = "notmuch"))]
+use crate::notmuch::AddFolderNotmuch;
+#[cfg(all(feature = "message-add", feature = "notmuch"))]
+use crate::notmuch::AddMessageNotmuch;
+#[cfg(all(feature = "message-copy", feature = "notmuch"))]
+use crate::notmuch::CopyMessagesNotmuch;
+#[cfg(all(feature = "folder-delete", feature = "notmuch"))]
+use crate::notmuch::DeleteFolderNotmuch;
+#[cfg(all(feature = "folder-expunge", feature = "notmuch"))]
+use crate::notmuch::ExpungeFolderNotmuch;
+#[cfg(all(feature = "envelope-get", feature = "notmuch"))]
+use crate::notmuch::GetEnvelopeNotmuch;
+#[cfg(all(feature = "envelope-list", feature = "notmuch"))]
+use crate::notmuch::ListEnvelopesNotmuch;
+#[cfg(all(feature = "folder-list", feature = "notmuch"))]
+use crate::notmuch::ListFoldersNotmuch;
+#[cfg(all(feature = "message-move", feature = "notmuch"))]
+use crate::notmuch::MoveMessagesNotmuch;
+#[cfg(all(feature = "message-peek", feature = "notmuch"))]
+use crate::notmuch::PeekMessagesNotmuch;
+#[cfg(all(feature = "folder-purge", feature = "notmuch"))]
+use crate::notmuch::PurgeFolderNotmuch;
+#[cfg(all(feature = "flag-remove", feature = "notmuch"))]
+use crate::notmuch::RemoveFlagsNotmuch;
+#[cfg(all(feature = "flag-set", feature = "notmuch"))]
+use crate::notmuch::SetFlagsNotmuch;
+#[cfg(all(feature = "envelope-watch", feature = "notmuch"))]
+use crate::notmuch::WatchNotmuchEnvelopes;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::{NotmuchSession, NotmuchSessionBuilder};
+#[cfg(all(feature = "message-get", feature = "pop"))]
+use crate::pop::GetMessagesPop;
+#[cfg(all(feature = "envelope-list", feature = "pop"))]
+use crate::pop::ListEnvelopesPop;
+#[cfg(all(feature = "message-peek", feature = "pop"))]
+use crate::pop::PeekMessagesPop;
+#[cfg(feature = "pop")]
+use crate::pop::{PopSession, PopSessionBuilder};
+#[cfg(feature = "search")]
+use crate::search::{self, SearchIndex, SearchIndexReport};
 use crate::{account::config::TomlAccountConfig, cache::IdMapper};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
@@ -98,13 +224,19 @@ use crate::{account::config::TomlAccountConfig, cache::IdMapper};
 pub enum BackendKind {
     #[cfg(feature = "imap")]
     Imap,
+    #[cfg(feature = "jmap")]
+    Jmap,
     #[cfg(feature = "maildir")]
     Maildir,
     #[cfg(feature = "account-sync")]
     #[serde(skip_deserializing)]
     MaildirForSync,
+    #[cfg(feature = "mbox")]
+    Mbox,
     #[cfg(feature = "notmuch")]
     Notmuch,
+    #[cfg(feature = "pop")]
+    Pop,
     #[cfg(feature = "smtp")]
     Smtp,
     #[cfg(feature = "sendmail")]
@@ -117,12 +249,18 @@ impl ToString for BackendKind {
         let kind = match self {
             #[cfg(feature = "imap")]
             Self::Imap => "IMAP",
+            #[cfg(feature = "jmap")]
+            Self::Jmap => "JMAP",
             #[cfg(feature = "maildir")]
             Self::Maildir => "Maildir",
             #[cfg(feature = "account-sync")]
             Self::MaildirForSync => "Maildir",
+            #[cfg(feature = "mbox")]
+            Self::Mbox => "Mbox",
             #[cfg(feature = "notmuch")]
             Self::Notmuch => "Notmuch",
+            #[cfg(feature = "pop")]
+            Self::Pop => "POP",
             #[cfg(feature = "smtp")]
             Self::Smtp => "SMTP",
             #[cfg(feature = "sendmail")]
@@ -138,10 +276,18 @@ impl ToString for BackendKind {
 pub struct BackendContextBuilder {
     #[cfg(feature = "imap")]
     pub imap: Option<ImapSessionBuilder>,
+    #[cfg(feature = "jmap")]
+    pub jmap: Option<JmapSessionBuilder>,
     #[cfg(feature = "maildir")]
     pub maildir: Option<MaildirSessionBuilder>,
     #[cfg(feature = "account-sync")]
     pub maildir_for_sync: Option<MaildirSessionBuilder>,
+    #[cfg(feature = "mbox")]
+    pub mbox: Option<MboxSessionBuilder>,
+    #[cfg(feature = "notmuch")]
+    pub notmuch: Option<NotmuchSessionBuilder>,
+    #[cfg(feature = "pop")]
+    pub pop: Option<PopSessionBuilder>,
     #[cfg(feature = "smtp")]
     pub smtp: Option<SmtpClientBuilder>,
     #[cfg(feature = "sendmail")]
@@ -171,6 +317,14 @@ impl BackendContextBuilder {
                     None => None,
                 }
             },
+            #[cfg(feature = "jmap")]
+            jmap: toml_account_config
+                .jmap
+                .as_ref()
+                .filter(|_| kinds.contains(&&BackendKind::Jmap))
+                .map(|jmap_config| {
+                    JmapSessionBuilder::new(account_config.clone(), jmap_config.clone())
+                }),
             #[cfg(feature = "maildir")]
             maildir: toml_account_config
                 .maildir
@@ -185,6 +339,14 @@ impl BackendContextBuilder {
             })
             .filter(|_| kinds.contains(&&BackendKind::MaildirForSync))
             .map(|mdir_config| MaildirSessionBuilder::new(account_config.clone(), mdir_config)),
+            #[cfg(feature = "mbox")]
+            mbox: toml_account_config
+                .mbox
+                .as_ref()
+                .filter(|_| kinds.contains(&&BackendKind::Mbox))
+                .map(|mbox_config| {
+                    MboxSessionBuilder::new(account_config.clone(), mbox_config.clone())
+                }),
             #[cfg(feature = "notmuch")]
             notmuch: toml_account_config
                 .notmuch
@@ -193,6 +355,14 @@ impl BackendContextBuilder {
                 .map(|notmuch_config| {
                     NotmuchSessionBuilder::new(account_config.clone(), notmuch_config.clone())
                 }),
+            #[cfg(feature = "pop")]
+            pop: toml_account_config
+                .pop
+                .as_ref()
+                .filter(|_| kinds.contains(&&BackendKind::Pop))
+                .map(|pop_config| {
+                    PopSessionBuilder::new(account_config.clone(), pop_config.clone())
+                }),
             #[cfg(feature = "smtp")]
             smtp: toml_account_config
                 .smtp
@@ -226,6 +396,11 @@ impl email::backend::BackendContextBuilder for BackendContextBuilder {
             ctx.imap = Some(imap.build().await?);
         }
 
+        #[cfg(feature = "jmap")]
+        if let Some(jmap) = self.jmap {
+            ctx.jmap = Some(jmap.build().await?);
+        }
+
         #[cfg(feature = "maildir")]
         if let Some(maildir) = self.maildir {
             ctx.maildir = Some(maildir.build().await?);
@@ -236,11 +411,21 @@ impl email::backend::BackendContextBuilder for BackendContextBuilder {
             ctx.maildir_for_sync = Some(maildir.build().await?);
         }
 
+        #[cfg(feature = "mbox")]
+        if let Some(mbox) = self.mbox {
+            ctx.mbox = Some(mbox.build().await?);
+        }
+
         #[cfg(feature = "notmuch")]
         if let Some(notmuch) = self.notmuch {
             ctx.notmuch = Some(notmuch.build().await?);
         }
 
+        #[cfg(feature = "pop")]
+        if let Some(pop) = self.pop {
+            ctx.pop = Some(pop.build().await?);
+        }
+
         #[cfg(feature = "smtp")]
         if let Some(smtp) = self.smtp {
             ctx.smtp = Some(smtp.build().await?);
@@ -255,20 +440,40 @@ impl email::backend::BackendContextBuilder for BackendContextBuilder {
     }
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct BackendContext {
     #[cfg(feature = "imap")]
     pub imap: Option<ImapSessionSync>,
+    #[cfg(feature = "jmap")]
+    pub jmap: Option<JmapSession>,
     #[cfg(feature = "maildir")]
     pub maildir: Option<MaildirSessionSync>,
     #[cfg(feature = "account-sync")]
     pub maildir_for_sync: Option<MaildirSessionSync>,
+    #[cfg(feature = "mbox")]
+    pub mbox: Option<MboxSession>,
+    #[cfg(feature = "notmuch")]
+    pub notmuch: Option<NotmuchSession>,
+    #[cfg(feature = "pop")]
+    pub pop: Option<PopSession>,
     #[cfg(feature = "smtp")]
     pub smtp: Option<SmtpClientSync>,
     #[cfg(feature = "sendmail")]
     pub sendmail: Option<SendmailContext>,
 }
 
+/// An already built context can be used as its own builder. This
+/// allows the [`Backend`] to keep a copy of the context, so that
+/// features not covered by the lib can reuse the same sessions.
+#[async_trait]
+impl email::backend::BackendContextBuilder for BackendContext {
+    type Context = Self;
+
+    async fn build(self) -> Result<Self::Context> {
+        Ok(self)
+    }
+}
+
 pub struct BackendBuilder {
     toml_account_config: TomlAccountConfig,
     builder: email::backend::BackendBuilder<BackendContextBuilder>,
@@ -284,10 +489,18 @@ impl BackendBuilder {
 
         #[cfg(feature = "imap")]
         let is_imap_used = used_backends.contains(&BackendKind::Imap);
+        #[cfg(feature = "jmap")]
+        let is_jmap_used = used_backends.contains(&BackendKind::Jmap);
         #[cfg(feature = "maildir")]
         let is_maildir_used = used_backends.contains(&BackendKind::Maildir);
         #[cfg(feature = "account-sync")]
         let is_maildir_for_sync_used = used_backends.contains(&BackendKind::MaildirForSync);
+        #[cfg(feature = "mbox")]
+        let is_mbox_used = used_backends.contains(&BackendKind::Mbox);
+        #[cfg(feature = "notmuch")]
+        let is_notmuch_used = used_backends.contains(&BackendKind::Notmuch);
+        #[cfg(feature = "pop")]
+        let is_pop_used = used_backends.contains(&BackendKind::Pop);
 
         let backend_ctx_builder = BackendContextBuilder {
             #[cfg(feature = "imap")]
@@ -306,6 +519,14 @@ impl BackendBuilder {
                     None => None,
                 }
             },
+            #[cfg(feature = "jmap")]
+            jmap: toml_account_config
+                .jmap
+                .as_ref()
+                .filter(|_| is_jmap_used)
+                .map(|jmap_config| {
+                    JmapSessionBuilder::new(account_config.clone(), jmap_config.clone())
+                }),
             #[cfg(feature = "maildir")]
             maildir: toml_account_config
                 .maildir
@@ -320,480 +541,665 @@ impl BackendBuilder {
             })
             .filter(|_| is_maildir_for_sync_used)
             .map(|mdir_config| MaildirSessionBuilder::new(account_config.clone(), mdir_config)),
+            #[cfg(feature = "mbox")]
+            mbox: toml_account_config
+                .mbox
+                .as_ref()
+                .filter(|_| is_mbox_used)
+                .map(|mbox_config| {
+                    MboxSessionBuilder::new(account_config.clone(), mbox_config.clone())
+                }),
+            #[cfg(feature = "notmuch")]
+            notmuch: toml_account_config
+                .notmuch
+                .as_ref()
+                .filter(|_| is_notmuch_used)
+                .map(|notmuch_config| {
+                    NotmuchSessionBuilder::new(account_config.clone(), notmuch_config.clone())
+                }),
+            #[cfg(feature = "pop")]
+            pop: toml_account_config
+                .pop
+                .as_ref()
+                .filter(|_| is_pop_used)
+                .map(|pop_config| {
+                    PopSessionBuilder::new(account_config.clone(), pop_config.clone())
+                }),
             ..Default::default()
         };
 
-        #[allow(unused_mut)]
-        let mut backend_builder =
-            email::backend::BackendBuilder::new(account_config.clone(), backend_ctx_builder);
+        let backend_builder = with_features(
+            &toml_account_config,
+            email::backend::BackendBuilder::new(account_config.clone(), backend_ctx_builder),
+        );
 
-        #[cfg(feature = "folder-add")]
-        match toml_account_config.add_folder_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder
-                    .with_add_folder(|ctx| ctx.maildir.as_ref().and_then(AddFolderMaildir::new));
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_add_folder(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(AddFolderMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_add_folder(|ctx| ctx.imap.as_ref().and_then(AddFolderImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder
-                    .with_add_folder(|ctx| ctx.notmuch.as_ref().and_then(AddFolderNotmuch::new));
-            }
-            _ => (),
-        }
+        Ok(Self {
+            toml_account_config,
+            builder: backend_builder,
+        })
+    }
 
-        #[cfg(feature = "folder-list")]
-        match toml_account_config.list_folders_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder.with_list_folders(|ctx| {
-                    ctx.maildir.as_ref().and_then(ListFoldersMaildir::new)
-                });
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_list_folders(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(ListFoldersMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_list_folders(|ctx| ctx.imap.as_ref().and_then(ListFoldersImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_list_folders(|ctx| {
-                    ctx.notmuch.as_ref().and_then(ListFoldersNotmuch::new)
-                });
-            }
-            _ => (),
+    pub async fn build(self) -> Result<Backend> {
+        let account_config = self.builder.account_config;
+        let context =
+            email::backend::BackendContextBuilder::build(self.builder.context_builder).await?;
+        let backend = with_features(
+            &self.toml_account_config,
+            email::backend::BackendBuilder::new(account_config, context.clone()),
+        )
+        .build()
+        .await?;
+
+        Ok(Backend {
+            toml_account_config: self.toml_account_config,
+            context,
+            backend,
+        })
+    }
+}
+
+/// Set up the list envelopes feature, which is needed by
+/// [`Backend::list_envelopes`] for backends without search support.
+#[cfg(feature = "envelope-list")]
+#[allow(unused_mut)]
+fn with_list_envelopes<B>(
+    toml_account_config: &TomlAccountConfig,
+    mut backend_builder: email::backend::BackendBuilder<B>,
+) -> email::backend::BackendBuilder<B>
+where
+    B: email::backend::BackendContextBuilder<Context = BackendContext>,
+{
+    match toml_account_config.list_envelopes_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder.with_list_envelopes(|ctx| {
+                ctx.maildir.as_ref().and_then(ListEnvelopesMaildir::new)
+            });
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_list_envelopes(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(ListEnvelopesMaildir::new)
+            });
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_list_envelopes(|ctx| ctx.imap.as_ref().and_then(ListEnvelopesImap::new));
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder = backend_builder
+                .with_list_envelopes(|ctx| ctx.jmap.as_ref().and_then(ListEnvelopesJmap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_list_envelopes(|ctx| ctx.mbox.as_ref().and_then(ListEnvelopesMbox::new));
         }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder.with_list_envelopes(|ctx| {
+                ctx.notmuch.as_ref().and_then(ListEnvelopesNotmuch::new)
+            });
+        }
+        #[cfg(feature = "pop")]
+        Some(BackendKind::Pop) => {
+            backend_builder = backend_builder
+                .with_list_envelopes(|ctx| ctx.pop.as_ref().and_then(ListEnvelopesPop::new));
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "folder-expunge")]
-        match toml_account_config.expunge_folder_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder.with_expunge_folder(|ctx| {
-                    ctx.maildir.as_ref().and_then(ExpungeFolderMaildir::new)
-                });
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_expunge_folder(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(ExpungeFolderMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_expunge_folder(|ctx| ctx.imap.as_ref().and_then(ExpungeFolderImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_expunge_folder(|ctx| {
-                    ctx.notmuch.as_ref().and_then(ExpungeFolderNotmuch::new)
-                });
-            }
-            _ => (),
-        }
-
-        #[cfg(feature = "folder-purge")]
-        match toml_account_config.purge_folder_kind() {
-            // TODO
-            // #[cfg(feature = "maildir")]
-            // Some(BackendKind::Maildir) => {
-            //     backend_builder = backend_builder
-            //         .with_purge_folder(|ctx| ctx.maildir.as_ref().and_then(PurgeFolderMaildir::new));
-            // }
-            // TODO
-            // #[cfg(feature = "account-sync")]
-            // Some(BackendKind::MaildirForSync) => {
-            //     backend_builder = backend_builder
-            //         .with_purge_folder(|ctx| ctx.maildir_for_sync.as_ref().and_then(PurgeFolderMaildir::new));
-            // }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_purge_folder(|ctx| ctx.imap.as_ref().and_then(PurgeFolderImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_purge_folder(|ctx| {
-                    ctx.notmuch.as_ref().and_then(PurgeFolderNotmuch::new)
-                });
-            }
-            _ => (),
+    backend_builder
+}
+
+/// Set up the features of the given backend builder, using the
+/// backend configured for each of them.
+///
+/// Features only need the built context, so they can be set up on
+/// builders of contexts as well as on already built contexts.
+#[allow(unused_mut, unused_variables)]
+fn with_features<B>(
+    toml_account_config: &TomlAccountConfig,
+    mut backend_builder: email::backend::BackendBuilder<B>,
+) -> email::backend::BackendBuilder<B>
+where
+    B: email::backend::BackendContextBuilder<Context = BackendContext>,
+{
+    #[cfg(feature = "folder-add")]
+    match toml_account_config.add_folder_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_add_folder(|ctx| ctx.maildir.as_ref().and_then(AddFolderMaildir::new));
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_add_folder(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(AddFolderMaildir::new)
+            });
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_add_folder(|ctx| ctx.imap.as_ref().and_then(AddFolderImap::new));
         }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_add_folder(|ctx| ctx.mbox.as_ref().and_then(AddFolderMbox::new));
+        }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_add_folder(|ctx| ctx.notmuch.as_ref().and_then(AddFolderNotmuch::new));
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "folder-delete")]
-        match toml_account_config.delete_folder_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder.with_delete_folder(|ctx| {
-                    ctx.maildir.as_ref().and_then(DeleteFolderMaildir::new)
-                });
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_delete_folder(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(DeleteFolderMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_delete_folder(|ctx| ctx.imap.as_ref().and_then(DeleteFolderImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_delete_folder(|ctx| {
-                    ctx.notmuch.as_ref().and_then(DeleteFolderNotmuch::new)
-                });
-            }
-            _ => (),
+    #[cfg(feature = "folder-list")]
+    match toml_account_config.list_folders_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_list_folders(|ctx| ctx.maildir.as_ref().and_then(ListFoldersMaildir::new));
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_list_folders(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(ListFoldersMaildir::new)
+            });
         }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_list_folders(|ctx| ctx.imap.as_ref().and_then(ListFoldersImap::new));
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder = backend_builder
+                .with_list_folders(|ctx| ctx.jmap.as_ref().and_then(ListFoldersJmap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_list_folders(|ctx| ctx.mbox.as_ref().and_then(ListFoldersMbox::new));
+        }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_list_folders(|ctx| ctx.notmuch.as_ref().and_then(ListFoldersNotmuch::new));
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "envelope-list")]
-        match toml_account_config.list_envelopes_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder.with_list_envelopes(|ctx| {
-                    ctx.maildir.as_ref().and_then(ListEnvelopesMaildir::new)
-                });
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_list_envelopes(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(ListEnvelopesMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_list_envelopes(|ctx| ctx.imap.as_ref().and_then(ListEnvelopesImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_list_envelopes(|ctx| {
-                    ctx.notmuch.as_ref().and_then(ListEnvelopesNotmuch::new)
-                });
-            }
-            _ => (),
+    #[cfg(feature = "folder-expunge")]
+    match toml_account_config.expunge_folder_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder.with_expunge_folder(|ctx| {
+                ctx.maildir.as_ref().and_then(ExpungeFolderMaildir::new)
+            });
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_expunge_folder(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(ExpungeFolderMaildir::new)
+            });
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_expunge_folder(|ctx| ctx.imap.as_ref().and_then(ExpungeFolderImap::new));
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder = backend_builder
+                .with_expunge_folder(|ctx| ctx.jmap.as_ref().and_then(ExpungeFolderJmap::new));
         }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_expunge_folder(|ctx| ctx.mbox.as_ref().and_then(ExpungeFolderMbox::new));
+        }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder.with_expunge_folder(|ctx| {
+                ctx.notmuch.as_ref().and_then(ExpungeFolderNotmuch::new)
+            });
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "envelope-watch")]
-        match toml_account_config.watch_envelopes_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder.with_watch_envelopes(|ctx| {
-                    ctx.maildir.as_ref().and_then(WatchMaildirEnvelopes::new)
-                });
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_watch_envelopes(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(WatchMaildirEnvelopes::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder.with_watch_envelopes(|ctx| {
-                    ctx.imap.as_ref().and_then(WatchImapEnvelopes::new)
-                });
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_watch_envelopes(|ctx| {
-                    ctx.notmuch.as_ref().and_then(WatchNotmuchEnvelopes::new)
-                });
-            }
-            _ => (),
+    #[cfg(feature = "folder-purge")]
+    match toml_account_config.purge_folder_kind() {
+        // TODO
+        // #[cfg(feature = "maildir")]
+        // Some(BackendKind::Maildir) => {
+        //     backend_builder = backend_builder
+        //         .with_purge_folder(|ctx| ctx.maildir.as_ref().and_then(PurgeFolderMaildir::new));
+        // }
+        // TODO
+        // #[cfg(feature = "account-sync")]
+        // Some(BackendKind::MaildirForSync) => {
+        //     backend_builder = backend_builder
+        //         .with_purge_folder(|ctx| ctx.maildir_for_sync.as_ref().and_then(PurgeFolderMaildir::new));
+        // }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_purge_folder(|ctx| ctx.imap.as_ref().and_then(PurgeFolderImap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_purge_folder(|ctx| ctx.mbox.as_ref().and_then(PurgeFolderMbox::new));
+        }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_purge_folder(|ctx| ctx.notmuch.as_ref().and_then(PurgeFolderNotmuch::new));
         }
+        _ => (),
+    }
 
-        #[cfg(feature = "envelope-get")]
-        match toml_account_config.get_envelope_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder.with_get_envelope(|ctx| {
-                    ctx.maildir.as_ref().and_then(GetEnvelopeMaildir::new)
-                });
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_get_envelope(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(GetEnvelopeMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_get_envelope(|ctx| ctx.imap.as_ref().and_then(GetEnvelopeImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_get_envelope(|ctx| {
-                    ctx.notmuch.as_ref().and_then(GetEnvelopeNotmuch::new)
-                });
-            }
-            _ => (),
+    #[cfg(feature = "folder-delete")]
+    match toml_account_config.delete_folder_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_delete_folder(|ctx| ctx.maildir.as_ref().and_then(DeleteFolderMaildir::new));
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_delete_folder(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(DeleteFolderMaildir::new)
+            });
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_delete_folder(|ctx| ctx.imap.as_ref().and_then(DeleteFolderImap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_delete_folder(|ctx| ctx.mbox.as_ref().and_then(DeleteFolderMbox::new));
         }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_delete_folder(|ctx| ctx.notmuch.as_ref().and_then(DeleteFolderNotmuch::new));
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "flag-add")]
-        match toml_account_config.add_flags_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder
-                    .with_add_flags(|ctx| ctx.maildir.as_ref().and_then(AddFlagsMaildir::new));
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_add_flags(|ctx| {
-                    ctx.maildir_for_sync.as_ref().and_then(AddFlagsMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_add_flags(|ctx| ctx.imap.as_ref().and_then(AddFlagsImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder
-                    .with_add_flags(|ctx| ctx.notmuch.as_ref().and_then(AddFlagsNotmuch::new));
-            }
-            _ => (),
+    #[cfg(feature = "envelope-list")]
+    {
+        backend_builder = with_list_envelopes(toml_account_config, backend_builder);
+    }
+
+    #[cfg(feature = "envelope-watch")]
+    match toml_account_config.watch_envelopes_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder.with_watch_envelopes(|ctx| {
+                ctx.maildir.as_ref().and_then(WatchMaildirEnvelopes::new)
+            });
         }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_watch_envelopes(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(WatchMaildirEnvelopes::new)
+            });
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_watch_envelopes(|ctx| ctx.imap.as_ref().and_then(WatchImapEnvelopes::new));
+        }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder.with_watch_envelopes(|ctx| {
+                ctx.notmuch.as_ref().and_then(WatchNotmuchEnvelopes::new)
+            });
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "flag-set")]
-        match toml_account_config.set_flags_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder
-                    .with_set_flags(|ctx| ctx.maildir.as_ref().and_then(SetFlagsMaildir::new));
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_set_flags(|ctx| {
-                    ctx.maildir_for_sync.as_ref().and_then(SetFlagsMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_set_flags(|ctx| ctx.imap.as_ref().and_then(SetFlagsImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder
-                    .with_set_flags(|ctx| ctx.notmuch.as_ref().and_then(SetFlagsNotmuch::new));
-            }
-            _ => (),
+    #[cfg(feature = "envelope-get")]
+    match toml_account_config.get_envelope_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_get_envelope(|ctx| ctx.maildir.as_ref().and_then(GetEnvelopeMaildir::new));
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_get_envelope(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(GetEnvelopeMaildir::new)
+            });
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_get_envelope(|ctx| ctx.imap.as_ref().and_then(GetEnvelopeImap::new));
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder = backend_builder
+                .with_get_envelope(|ctx| ctx.jmap.as_ref().and_then(GetEnvelopeJmap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_get_envelope(|ctx| ctx.mbox.as_ref().and_then(GetEnvelopeMbox::new));
         }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_get_envelope(|ctx| ctx.notmuch.as_ref().and_then(GetEnvelopeNotmuch::new));
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "flag-remove")]
-        match toml_account_config.remove_flags_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder.with_remove_flags(|ctx| {
-                    ctx.maildir.as_ref().and_then(RemoveFlagsMaildir::new)
-                });
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_remove_flags(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(RemoveFlagsMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_remove_flags(|ctx| ctx.imap.as_ref().and_then(RemoveFlagsImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_remove_flags(|ctx| {
-                    ctx.notmuch.as_ref().and_then(RemoveFlagsNotmuch::new)
-                });
-            }
-            _ => (),
+    #[cfg(feature = "flag-add")]
+    match toml_account_config.add_flags_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_add_flags(|ctx| ctx.maildir.as_ref().and_then(AddFlagsMaildir::new));
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder
+                .with_add_flags(|ctx| ctx.maildir_for_sync.as_ref().and_then(AddFlagsMaildir::new));
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder =
+                backend_builder.with_add_flags(|ctx| ctx.imap.as_ref().and_then(AddFlagsImap::new));
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder =
+                backend_builder.with_add_flags(|ctx| ctx.jmap.as_ref().and_then(AddFlagsJmap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder =
+                backend_builder.with_add_flags(|ctx| ctx.mbox.as_ref().and_then(AddFlagsMbox::new));
+        }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_add_flags(|ctx| ctx.notmuch.as_ref().and_then(AddFlagsNotmuch::new));
         }
+        _ => (),
+    }
 
-        #[cfg(feature = "message-add")]
-        match toml_account_config.add_message_kind() {
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new))
-                    .with_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new));
-            }
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder
-                    .with_add_message(|ctx| ctx.maildir.as_ref().and_then(AddMaildirMessage::new));
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_add_message(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(AddMaildirMessage::new)
-                });
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_add_raw_message(|ctx| {
-                    ctx.notmuch.as_ref().and_then(AddRawMessageNotmuch::new)
-                });
-            }
-            _ => (),
+    #[cfg(feature = "flag-set")]
+    match toml_account_config.set_flags_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_set_flags(|ctx| ctx.maildir.as_ref().and_then(SetFlagsMaildir::new));
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder
+                .with_set_flags(|ctx| ctx.maildir_for_sync.as_ref().and_then(SetFlagsMaildir::new));
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder =
+                backend_builder.with_set_flags(|ctx| ctx.imap.as_ref().and_then(SetFlagsImap::new));
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder =
+                backend_builder.with_set_flags(|ctx| ctx.jmap.as_ref().and_then(SetFlagsJmap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder =
+                backend_builder.with_set_flags(|ctx| ctx.mbox.as_ref().and_then(SetFlagsMbox::new));
         }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_set_flags(|ctx| ctx.notmuch.as_ref().and_then(SetFlagsNotmuch::new));
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "message-peek")]
-        match toml_account_config.peek_messages_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder.with_peek_messages(|ctx| {
-                    ctx.maildir.as_ref().and_then(PeekMessagesMaildir::new)
-                });
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_peek_messages(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(PeekMessagesMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_peek_messages(|ctx| ctx.imap.as_ref().and_then(PeekMessagesImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_peek_messages(|ctx| {
-                    ctx.notmuch.as_ref().and_then(PeekMessagesNotmuch::new)
-                });
-            }
-            _ => (),
+    #[cfg(feature = "flag-remove")]
+    match toml_account_config.remove_flags_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_remove_flags(|ctx| ctx.maildir.as_ref().and_then(RemoveFlagsMaildir::new));
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_remove_flags(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(RemoveFlagsMaildir::new)
+            });
         }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_remove_flags(|ctx| ctx.imap.as_ref().and_then(RemoveFlagsImap::new));
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder = backend_builder
+                .with_remove_flags(|ctx| ctx.jmap.as_ref().and_then(RemoveFlagsJmap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_remove_flags(|ctx| ctx.mbox.as_ref().and_then(RemoveFlagsMbox::new));
+        }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_remove_flags(|ctx| ctx.notmuch.as_ref().and_then(RemoveFlagsNotmuch::new));
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "message-get")]
-        match toml_account_config.get_messages_kind() {
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_get_messages(|ctx| ctx.imap.as_ref().and_then(GetMessagesImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_get_messages(|ctx| {
-                    ctx.notmuch.as_ref().and_then(GetMessagesNotmuch::new)
-                });
-            }
-            _ => (),
+    #[cfg(feature = "message-add")]
+    match toml_account_config.add_message_kind() {
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new))
+                .with_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new));
+        }
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_add_message(|ctx| ctx.maildir.as_ref().and_then(AddMaildirMessage::new));
         }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_add_message(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(AddMaildirMessage::new)
+            });
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder = backend_builder
+                .with_add_message(|ctx| ctx.jmap.as_ref().and_then(AddMessageJmap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_add_message(|ctx| ctx.mbox.as_ref().and_then(AddMessageMbox::new));
+        }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_add_message(|ctx| ctx.notmuch.as_ref().and_then(AddMessageNotmuch::new));
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "message-copy")]
-        match toml_account_config.copy_messages_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder.with_copy_messages(|ctx| {
-                    ctx.maildir.as_ref().and_then(CopyMessagesMaildir::new)
-                });
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_copy_messages(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(CopyMessagesMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_copy_messages(|ctx| ctx.imap.as_ref().and_then(CopyMessagesImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_copy_messages(|ctx| {
-                    ctx.notmuch.as_ref().and_then(CopyMessagesNotmuch::new)
-                });
-            }
-            _ => (),
+    #[cfg(feature = "message-peek")]
+    match toml_account_config.peek_messages_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_peek_messages(|ctx| ctx.maildir.as_ref().and_then(PeekMessagesMaildir::new));
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_peek_messages(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(PeekMessagesMaildir::new)
+            });
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_peek_messages(|ctx| ctx.imap.as_ref().and_then(PeekMessagesImap::new));
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder = backend_builder
+                .with_peek_messages(|ctx| ctx.jmap.as_ref().and_then(PeekMessagesJmap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_peek_messages(|ctx| ctx.mbox.as_ref().and_then(PeekMessagesMbox::new));
         }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_peek_messages(|ctx| ctx.notmuch.as_ref().and_then(PeekMessagesNotmuch::new));
+        }
+        #[cfg(feature = "pop")]
+        Some(BackendKind::Pop) => {
+            backend_builder = backend_builder
+                .with_peek_messages(|ctx| ctx.pop.as_ref().and_then(PeekMessagesPop::new));
+        }
+        _ => (),
+    }
 
-        #[cfg(feature = "message-move")]
-        match toml_account_config.move_messages_kind() {
-            #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                backend_builder = backend_builder.with_move_messages(|ctx| {
-                    ctx.maildir.as_ref().and_then(MoveMessagesMaildir::new)
-                });
-            }
-            #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                backend_builder = backend_builder.with_move_messages(|ctx| {
-                    ctx.maildir_for_sync
-                        .as_ref()
-                        .and_then(MoveMessagesMaildir::new)
-                });
-            }
-            #[cfg(feature = "imap")]
-            Some(BackendKind::Imap) => {
-                backend_builder = backend_builder
-                    .with_move_messages(|ctx| ctx.imap.as_ref().and_then(MoveMessagesImap::new));
-            }
-            #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                backend_builder = backend_builder.with_move_messages(|ctx| {
-                    ctx.notmuch.as_ref().and_then(MoveMessagesNotmuch::new)
-                });
-            }
-            _ => (),
+    #[cfg(feature = "message-get")]
+    match toml_account_config.get_messages_kind() {
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_get_messages(|ctx| ctx.imap.as_ref().and_then(GetMessagesImap::new));
+        }
+        #[cfg(feature = "pop")]
+        Some(BackendKind::Pop) => {
+            backend_builder = backend_builder
+                .with_get_messages(|ctx| ctx.pop.as_ref().and_then(GetMessagesPop::new));
         }
+        _ => (),
+    }
 
-        Ok(Self {
-            toml_account_config,
-            builder: backend_builder,
-        })
+    #[cfg(feature = "message-copy")]
+    match toml_account_config.copy_messages_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_copy_messages(|ctx| ctx.maildir.as_ref().and_then(CopyMessagesMaildir::new));
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_copy_messages(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(CopyMessagesMaildir::new)
+            });
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_copy_messages(|ctx| ctx.imap.as_ref().and_then(CopyMessagesImap::new));
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder = backend_builder
+                .with_copy_messages(|ctx| ctx.jmap.as_ref().and_then(CopyMessagesJmap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_copy_messages(|ctx| ctx.mbox.as_ref().and_then(CopyMessagesMbox::new));
+        }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_copy_messages(|ctx| ctx.notmuch.as_ref().and_then(CopyMessagesNotmuch::new));
+        }
+        _ => (),
     }
 
-    pub async fn build(self) -> Result<Backend> {
-        Ok(Backend {
-            toml_account_config: self.toml_account_config,
-            backend: self.builder.build().await?,
-        })
+    #[cfg(feature = "message-move")]
+    match toml_account_config.move_messages_kind() {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => {
+            backend_builder = backend_builder
+                .with_move_messages(|ctx| ctx.maildir.as_ref().and_then(MoveMessagesMaildir::new));
+        }
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => {
+            backend_builder = backend_builder.with_move_messages(|ctx| {
+                ctx.maildir_for_sync
+                    .as_ref()
+                    .and_then(MoveMessagesMaildir::new)
+            });
+        }
+        #[cfg(feature = "imap")]
+        Some(BackendKind::Imap) => {
+            backend_builder = backend_builder
+                .with_move_messages(|ctx| ctx.imap.as_ref().and_then(MoveMessagesImap::new));
+        }
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => {
+            backend_builder = backend_builder
+                .with_move_messages(|ctx| ctx.jmap.as_ref().and_then(MoveMessagesJmap::new));
+        }
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => {
+            backend_builder = backend_builder
+                .with_move_messages(|ctx| ctx.mbox.as_ref().and_then(MoveMessagesMbox::new));
+        }
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => {
+            backend_builder = backend_builder
+                .with_move_messages(|ctx| ctx.notmuch.as_ref().and_then(MoveMessagesNotmuch::new));
+        }
+        _ => (),
     }
+
+    backend_builder
 }
 
 impl Deref for BackendBuilder {
@@ -809,9 +1215,54 @@ impl From<BackendBuilder> for email::backend::BackendBuilder<BackendContextBuild
     }
 }
 
+/// Return the directory from which the id mapper database of the
+/// given backend is searched, or `None` if the backend does not need
+/// aliases.
+#[allow(unused_variables)]
+pub fn id_mapper_db_dir(
+    toml_account_config: &TomlAccountConfig,
+    account_config: &AccountConfig,
+    backend_kind: Option<&BackendKind>,
+) -> Result<Option<PathBuf>> {
+    let db_dir = match backend_kind {
+        #[cfg(feature = "maildir")]
+        Some(BackendKind::Maildir) => toml_account_config
+            .maildir
+            .as_ref()
+            .map(|mdir_config| mdir_config.root_dir.clone()),
+        #[cfg(feature = "account-sync")]
+        Some(BackendKind::MaildirForSync) => Some(account_config.get_sync_dir()?),
+        #[cfg(feature = "jmap")]
+        Some(BackendKind::Jmap) => toml_account_config
+            .jmap
+            .as_ref()
+            .map(|_| crate::jmap::id_mapper_db_dir(account_config))
+            .transpose()?,
+        #[cfg(feature = "mbox")]
+        Some(BackendKind::Mbox) => toml_account_config
+            .mbox
+            .as_ref()
+            .map(|mbox_config| mbox_config.root_dir.clone()),
+        #[cfg(feature = "notmuch")]
+        Some(BackendKind::Notmuch) => toml_account_config
+            .notmuch
+            .as_ref()
+            .map(|notmuch_config| notmuch_config.db_path.clone()),
+        #[cfg(feature = "pop")]
+        Some(BackendKind::Pop) => toml_account_config
+            .pop
+            .as_ref()
+            .map(|_| crate::pop::id_mapper_db_dir(account_config))
+            .transpose()?,
+        _ => None,
+    };
+
+    Ok(db_dir)
+}
+
 pub struct Backend {
-    #[allow(unused)]
     toml_account_config: TomlAccountConfig,
+    context: BackendContext,
     backend: email::backend::Backend<BackendContext>,
 }
 
@@ -820,78 +1271,431 @@ impl Backend {
         toml_account_config: &TomlAccountConfig,
         account_config: &AccountConfig,
         backend_kinds: impl IntoIterator<Item = &BackendKind>,
-        with_features: impl Fn(&mut email::backend::BackendBuilder<BackendContextBuilder>),
+        with_features: impl Fn(&mut email::backend::BackendBuilder<BackendContext>),
     ) -> Result<Self> {
         let backend_kinds = backend_kinds.into_iter().collect();
         let backend_ctx_builder =
             BackendContextBuilder::new(toml_account_config, account_config, backend_kinds).await?;
+        let backend_ctx = email::backend::BackendContextBuilder::build(backend_ctx_builder).await?;
         let mut backend_builder =
-            email::backend::BackendBuilder::new(account_config.clone(), backend_ctx_builder);
+            email::backend::BackendBuilder::new(account_config.clone(), backend_ctx.clone());
+
+        #[cfg(feature = "envelope-list")]
+        {
+            backend_builder = with_list_envelopes(toml_account_config, backend_builder);
+        }
 
         with_features(&mut backend_builder);
 
         Ok(Self {
             toml_account_config: toml_account_config.clone(),
+            context: backend_ctx,
             backend: backend_builder.build().await?,
         })
     }
 
+    /// Return the TOML configuration of the account the backend has
+    /// been built for.
+    pub fn toml_account_config(&self) -> &TomlAccountConfig {
+        &self.toml_account_config
+    }
+
+    /// Return the context of the backend, so that features not
+    /// covered by the lib can reuse the same sessions.
+    pub fn context(&self) -> &BackendContext {
+        &self.context
+    }
+
     #[allow(unused)]
     fn build_id_mapper(
         &self,
         folder: &str,
         backend_kind: Option<&BackendKind>,
     ) -> Result<IdMapper> {
-        #[allow(unused_mut)]
-        let mut id_mapper = IdMapper::Dummy;
+        let account_config = &self.backend.account_config;
+
+        match id_mapper_db_dir(&self.toml_account_config, account_config, backend_kind)? {
+            Some(db_dir) => IdMapper::new(account_config, folder, db_dir),
+            None => Ok(IdMapper::Dummy),
+        }
+    }
+
+    /// Remove the aliases of the given folder whose messages do not
+    /// exist anymore, and return the amount of removed aliases.
+    #[cfg(feature = "cache-prune")]
+    pub async fn prune_id_mapper(
+        &self,
+        folder: &str,
+        backend_kind: Option<&BackendKind>,
+    ) -> Result<usize> {
+        let id_mapper = self.build_id_mapper(folder, backend_kind)?;
+
+        if matches!(id_mapper, IdMapper::Dummy) {
+            return Ok(0);
+        }
 
-        match backend_kind {
+        let ids: HashSet<String> = match backend_kind {
             #[cfg(feature = "maildir")]
-            Some(BackendKind::Maildir) => {
-                if let Some(mdir_config) = &self.toml_account_config.maildir {
-                    id_mapper = IdMapper::new(
-                        &self.backend.account_config,
-                        folder,
-                        mdir_config.root_dir.clone(),
-                    )?;
+            Some(BackendKind::Maildir) => match &self.context.maildir {
+                Some(session) => {
+                    let mdir = session.lock().await.get_maildir_from_folder_name(folder)?;
+                    mdir.list_cur()
+                        .chain(mdir.list_new())
+                        .map(|entry| Ok(entry?.id().to_owned()))
+                        .collect::<Result<_>>()?
                 }
-            }
+                None => bail!("cannot prune id mapper: maildir session not initialized"),
+            },
             #[cfg(feature = "account-sync")]
-            Some(BackendKind::MaildirForSync) => {
-                id_mapper = IdMapper::new(
-                    &self.backend.account_config,
-                    folder,
-                    self.backend.account_config.get_sync_dir()?,
-                )?;
-            }
+            Some(BackendKind::MaildirForSync) => match &self.context.maildir_for_sync {
+                Some(session) => {
+                    let mdir = session.lock().await.get_maildir_from_folder_name(folder)?;
+                    mdir.list_cur()
+                        .chain(mdir.list_new())
+                        .map(|entry| Ok(entry?.id().to_owned()))
+                        .collect::<Result<_>>()?
+                }
+                None => bail!("cannot prune id mapper: maildir session not initialized"),
+            },
+            #[cfg(feature = "jmap")]
+            Some(BackendKind::Jmap) => match &self.context.jmap {
+                Some(session) => {
+                    let mailbox_id = session.mailbox_id(folder).await?;
+                    session
+                        .query_emails(serde_json::json!({ "inMailbox": mailbox_id }), 0, 0)
+                        .await?
+                        .into_iter()
+                        .collect()
+                }
+                None => bail!("cannot prune id mapper: jmap session not initialized"),
+            },
+            #[cfg(feature = "mbox")]
+            Some(BackendKind::Mbox) => match &self.context.mbox {
+                Some(session) => session
+                    .read_folder(folder)?
+                    .ids()
+                    .map(ToOwned::to_owned)
+                    .collect(),
+                None => bail!("cannot prune id mapper: mbox session not initialized"),
+            },
             #[cfg(feature = "notmuch")]
-            Some(BackendKind::Notmuch) => {
-                if let Some(notmuch_config) = &self.toml_account_config.notmuch {
-                    id_mapper = IdMapper::new(
-                        &self.backend.account_config,
-                        folder,
-                        mdir_config.root_dir.clone(),
-                    )?;
+            Some(BackendKind::Notmuch) => match &self.context.notmuch {
+                Some(session) => {
+                    let db = session.open_db()?;
+                    let query = db.create_query(&session.folder(folder).query())?;
+                    let ids = query
+                        .search_messages()?
+                        .map(|msg| msg.id().to_string())
+                        .collect();
+                    db.close()?;
+                    ids
                 }
-            }
-            _ => (),
+                None => bail!("cannot prune id mapper: notmuch session not initialized"),
+            },
+            #[cfg(feature = "pop")]
+            Some(BackendKind::Pop) => match &self.context.pop {
+                Some(session) => {
+                    session.check_folder(folder)?;
+                    session
+                        .uidls()
+                        .await?
+                        .into_iter()
+                        .map(|(_, uidl)| uidl)
+                        .collect()
+                }
+                None => bail!("cannot prune id mapper: pop session not initialized"),
+            },
+            _ => bail!("cannot prune id mapper: backend not supported"),
         };
 
-        Ok(id_mapper)
+        id_mapper.prune(&ids)
     }
 
+    /// Get the details of the given envelopes, which are not covered
+    /// by the lib envelopes (all recipients, size, attachment,
+    /// additional headers etc).
+    ///
+    /// Backends that do not support details get default ones.
+    #[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
+    async fn get_envelopes_details(
+        &self,
+        folder: &str,
+        backend_kind: Option<&BackendKind>,
+        ids: &[String],
+        headers: &[String],
+    ) -> Result<HashMap<String, EnvelopeDetails>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let details = match backend_kind {
+            #[cfg(feature = "imap")]
+            Some(BackendKind::Imap) => match &self.context.imap {
+                Some(session) => {
+                    details::imap::get_envelopes_details(session, folder, ids, headers).await?
+                }
+                None => bail!("cannot get envelope details: imap session not initialized"),
+            },
+            #[cfg(feature = "maildir")]
+            Some(BackendKind::Maildir) => match &self.context.maildir {
+                Some(session) => {
+                    details::maildir::get_envelopes_details(session, folder, ids, headers).await?
+                }
+                None => bail!("cannot get envelope details: maildir session not initialized"),
+            },
+            #[cfg(feature = "account-sync")]
+            Some(BackendKind::MaildirForSync) => match &self.context.maildir_for_sync {
+                Some(session) => {
+                    details::maildir::get_envelopes_details(session, folder, ids, headers).await?
+                }
+                None => bail!("cannot get envelope details: maildir session not initialized"),
+            },
+            #[cfg(feature = "jmap")]
+            Some(BackendKind::Jmap) => match &self.context.jmap {
+                Some(session) => {
+                    details::jmap::get_envelopes_details(session, ids, headers).await?
+                }
+                None => bail!("cannot get envelope details: jmap session not initialized"),
+            },
+            #[cfg(feature = "mbox")]
+            Some(BackendKind::Mbox) => match &self.context.mbox {
+                Some(session) => {
+                    details::mbox::get_envelopes_details(session, folder, ids, headers).await?
+                }
+                None => bail!("cannot get envelope details: mbox session not initialized"),
+            },
+            #[cfg(feature = "notmuch")]
+            Some(BackendKind::Notmuch) => match &self.context.notmuch {
+                Some(session) => {
+                    details::notmuch::get_envelopes_details(session, ids, headers).await?
+                }
+                None => bail!("cannot get envelope details: notmuch session not initialized"),
+            },
+            _ => HashMap::new(),
+        };
+
+        Ok(details)
+    }
+
+    /// List envelopes matching the given search options.
+    ///
+    /// Envelopes are searched directly from the backend session, in
+    /// order to know the total amount of matching envelopes. Backends
+    /// without search support fall back to the regular listing.
     #[cfg(feature = "envelope-list")]
     pub async fn list_envelopes(
         &self,
         folder: &str,
+        opts: &SearchEnvelopesOptions,
         page_size: usize,
         page: usize,
+        after: Option<&EnvelopesCursor>,
+        headers: &[String],
     ) -> Result<Envelopes> {
         let backend_kind = self.toml_account_config.list_envelopes_kind();
         let id_mapper = self.build_id_mapper(folder, backend_kind)?;
-        let envelopes = self.backend.list_envelopes(folder, page_size, page).await?;
-        let envelopes = Envelopes::from_backend(&self.account_config, &id_mapper, envelopes)?;
-        Ok(envelopes)
+
+        let found = match backend_kind {
+            #[cfg(feature = "imap")]
+            Some(BackendKind::Imap) => match &self.context.imap {
+                Some(session) => {
+                    SearchEnvelopesImap::new(session, opts.clone())
+                        .search(folder, page_size, page, after)
+                        .await?
+                }
+                None => bail!("cannot list envelopes: imap session not initialized"),
+            },
+            #[cfg(feature = "maildir")]
+            Some(BackendKind::Maildir) => match &self.context.maildir {
+                Some(session) => {
+                    SearchEnvelopesMaildir::new(session, opts.clone())
+                        .search(folder, page_size, page, after)
+                        .await?
+                }
+                None => bail!("cannot list envelopes: maildir session not initialized"),
+            },
+            #[cfg(feature = "account-sync")]
+            Some(BackendKind::MaildirForSync) => match &self.context.maildir_for_sync {
+                Some(session) => {
+                    SearchEnvelopesMaildir::new(session, opts.clone())
+                        .search(folder, page_size, page, after)
+                        .await?
+                }
+                None => bail!("cannot list envelopes: maildir session not initialized"),
+            },
+            _ => {
+                if !opts.is_empty() || after.is_some() {
+                    bail!("cannot search envelopes: feature not available for this backend");
+                }
+
+                let envelopes = self.backend.list_envelopes(folder, page_size, page).await?;
+                let ids: Vec<String> = envelopes.iter().map(|e| e.id.clone()).collect();
+                let details = self
+                    .get_envelopes_details(folder, backend_kind, &ids, headers)
+                    .await?;
+                let envelopes = Envelopes::from_backend(
+                    &self.account_config,
+                    &id_mapper,
+                    folder,
+                    envelopes,
+                    &details,
+                )?;
+
+                return Ok(envelopes.with_pagination(Some(page), page_size, None, None));
+            }
+        };
+
+        let ids: Vec<String> = found.envelopes.iter().map(|e| e.id.clone()).collect();
+        let details = self
+            .get_envelopes_details(folder, backend_kind, &ids, headers)
+            .await?;
+        let envelopes = Envelopes::from_backend(
+            &self.account_config,
+            &id_mapper,
+            folder,
+            found.envelopes,
+            &details,
+        )?;
+
+        Ok(envelopes.with_pagination(
+            after.is_none().then_some(page),
+            page_size,
+            Some(found.total),
+            found.next,
+        ))
+    }
+
+    #[cfg(feature = "envelope-get")]
+    pub async fn get_envelope(&self, folder: &str, id: usize) -> Result<FullEnvelope> {
+        let backend_kind = self.toml_account_config.get_envelope_kind();
+        let id_mapper = self.build_id_mapper(folder, backend_kind)?;
+        let ids = [id_mapper.get_id(id)?];
+        let envelope = self
+            .backend
+            .get_envelope(folder, &Id::single(ids[0].clone()))
+            .await?;
+
+        let mut details = self
+            .get_envelopes_details(folder, backend_kind, &ids, &[])
+            .await?;
+        let details = details.remove(&envelope.id).unwrap_or_default();
+
+        FullEnvelope::from_backend(&self.account_config, &id_mapper, folder, &envelope, details)
+    }
+
+    #[cfg(feature = "envelope-thread")]
+    pub async fn thread_envelopes(&self, folder: &str) -> Result<ThreadedEnvelopes> {
+        let backend_kind = self.toml_account_config.list_envelopes_kind();
+        let id_mapper = self.build_id_mapper(folder, backend_kind)?;
+
+        let threads = match backend_kind {
+            #[cfg(feature = "imap")]
+            Some(BackendKind::Imap) => match &self.context.imap {
+                Some(session) => thread::imap::thread_envelopes(session, folder).await?,
+                None => bail!("cannot thread envelopes: imap session not initialized"),
+            },
+            #[cfg(feature = "maildir")]
+            Some(BackendKind::Maildir) => match &self.context.maildir {
+                Some(session) => thread::maildir::thread_envelopes(session, folder).await?,
+                None => bail!("cannot thread envelopes: maildir session not initialized"),
+            },
+            #[cfg(feature = "account-sync")]
+            Some(BackendKind::MaildirForSync) => match &self.context.maildir_for_sync {
+                Some(session) => thread::maildir::thread_envelopes(session, folder).await?,
+                None => bail!("cannot thread envelopes: maildir session not initialized"),
+            },
+            #[cfg(feature = "mbox")]
+            Some(BackendKind::Mbox) => match &self.context.mbox {
+                Some(session) => thread::mbox::thread_envelopes(session, folder).await?,
+                None => bail!("cannot thread envelopes: mbox session not initialized"),
+            },
+            #[cfg(feature = "notmuch")]
+            Some(BackendKind::Notmuch) => match &self.context.notmuch {
+                Some(session) => thread::notmuch::thread_envelopes(session, folder).await?,
+                None => bail!("cannot thread envelopes: notmuch session not initialized"),
+            },
+            _ => bail!("cannot thread envelopes: feature not available for this backend"),
+        };
+
+        let mut ids = Vec::new();
+        threads
+            .iter()
+            .for_each(|thread| thread.collect_ids(&mut ids));
+        let details = self
+            .get_envelopes_details(folder, backend_kind, &ids, &[])
+            .await?;
+
+        ThreadedEnvelopes::from_backend(&self.account_config, &id_mapper, folder, threads, &details)
+    }
+
+    #[cfg(feature = "search")]
+    pub async fn update_search_index(&self, index: &SearchIndex) -> Result<SearchIndexReport> {
+        match &self.context.maildir_for_sync {
+            Some(session) => index.update(session).await,
+            None => bail!("cannot update search index: sync maildir session not initialized"),
+        }
+    }
+
+    /// Search envelopes of the synchronized Maildir matching the
+    /// given full-text query, sorted by relevance.
+    #[cfg(feature = "search")]
+    pub async fn search_envelopes(
+        &self,
+        index: &SearchIndex,
+        query: &str,
+        folder: Option<&str>,
+        page_size: usize,
+        page: usize,
+        headers: &[String],
+    ) -> Result<Envelopes> {
+        let Some(session) = &self.context.maildir_for_sync else {
+            bail!("cannot search envelopes: sync maildir session not initialized");
+        };
+
+        let backend_kind = Some(&BackendKind::MaildirForSync);
+        let (hits, total) = index.search(query, folder, page_size, page)?;
+        let hits = search::read_hits(session, hits, headers).await?;
+
+        // aliases are created folder by folder, in one go
+        let mut aliases = HashMap::new();
+        for hit in &hits {
+            if aliases.contains_key(&hit.folder) {
+                continue;
+            }
+
+            let id_mapper = self.build_id_mapper(&hit.folder, backend_kind)?;
+            let folder_aliases = id_mapper.get_or_create_message_aliases(
+                hits.iter().filter(|h| h.folder == hit.folder).map(|h| {
+                    (
+                        h.envelope.id.as_str(),
+                        crate::envelope::message_id(&h.envelope),
+                    )
+                }),
+            )?;
+            aliases.insert(hit.folder.clone(), folder_aliases);
+        }
+
+        let envelopes = hits.iter().map(|hit| {
+            let alias = aliases
+                .get_mut(&hit.folder)
+                .and_then(|aliases| aliases.remove(&hit.envelope.id))
+                .unwrap_or_default();
+
+            crate::envelope::Envelope::from_backend_with_alias(
+                &self.account_config,
+                alias,
+                &hit.folder,
+                &hit.envelope,
+                &hit.details,
+            )
+        });
+
+        Ok(Envelopes::from_iter(envelopes).with_pagination(
+            Some(page),
+            page_size,
+            Some(total),
+            None,
+        ))
     }
 
     #[cfg(feature = "flag-add")]
@@ -951,6 +1755,53 @@ impl Backend {
         Ok(id)
     }
 
+    #[cfg(feature = "message-add")]
+    pub async fn add_message_with_flags(
+        &self,
+        folder: &str,
+        email: &[u8],
+        flags: &Flags,
+    ) -> Result<SingleId> {
+        let backend_kind = self.toml_account_config.add_message_kind();
+        let id_mapper = self.build_id_mapper(folder, backend_kind)?;
+        let id = self
+            .backend
+            .add_message_with_flags(folder, email, flags)
+            .await?;
+        id_mapper.create_alias(&*id)?;
+        Ok(id)
+    }
+
+    /// Deliver the given raw message, without saving a copy of it.
+    ///
+    /// Unlike [`email::backend::Backend::send_message`], delivery and
+    /// copy are split, so that a message is never considered as
+    /// undelivered because its copy could not be saved.
+    #[cfg(feature = "message-send")]
+    pub async fn deliver_message(&self, raw_msg: &[u8]) -> Result<()> {
+        self.backend
+            .send_message
+            .as_ref()
+            .ok_or_else(|| anyhow!("cannot send message: feature not available"))?
+            .send_message(raw_msg)
+            .await
+    }
+
+    /// Save a copy of the given sent raw message to the sent folder,
+    /// if enabled by the account configuration.
+    #[cfg(feature = "message-send")]
+    pub async fn save_copy_sent_message(&self, raw_msg: &[u8]) -> Result<()> {
+        #[cfg(feature = "message-add")]
+        if self.backend.account_config.should_save_copy_sent_message() {
+            let folder = self.backend.account_config.get_sent_folder_alias();
+            debug!("saving copy of sent message to {folder}");
+            self.add_message_with_flags(&folder, raw_msg, &Flags::from_iter([Flag::Seen]))
+                .await?;
+        }
+
+        Ok(())
+    }
+
     #[cfg(feature = "message-peek")]
     pub async fn peek_messages(&self, folder: &str, ids: &[usize]) -> Result<Messages> {
         let backend_kind = self.toml_account_config.get_messages_kind();
@@ -1004,6 +1855,30 @@ impl Backend {
         let ids = Id::multiple(id_mapper.get_ids(ids)?);
         self.backend.delete_messages(folder, &ids).await
     }
+
+    /// Remove the given messages for good when the backend can
+    /// expunge single messages, otherwise delete them.
+    ///
+    /// Only IMAP servers supporting UIDPLUS can expunge single
+    /// messages. Expunging the whole folder is not an option, since
+    /// it would also remove other messages flagged as deleted.
+    #[cfg(feature = "message-edit")]
+    pub async fn remove_messages(&self, folder: &str, ids: &[usize]) -> Result<()> {
+        let backend_kind = self.toml_account_config.delete_messages_kind();
+        let id_mapper = self.build_id_mapper(folder, backend_kind)?;
+        let ids = id_mapper.get_ids(ids)?;
+
+        #[cfg(feature = "imap")]
+        if let (Some(BackendKind::Imap), Some(session)) = (backend_kind, &self.context.imap) {
+            if draft::imap::expunge_messages(session, folder, &ids).await? {
+                return Ok(());
+            }
+        }
+
+        self.backend
+            .delete_messages(folder, &Id::multiple(ids))
+            .await
+    }
 }
 
 impl Deref for Backend {
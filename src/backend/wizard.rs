@@ -6,10 +6,16 @@ use std::sync::OnceLock;
 
 #[cfg(feature = "imap")]
 use crate::imap;
+#[cfg(feature = "jmap")]
+use crate::jmap;
 #[cfg(feature = "maildir")]
 use crate::maildir;
+#[cfg(feature = "mbox")]
+use crate::mbox;
 #[cfg(feature = "notmuch")]
 use crate::notmuch;
+#[cfg(feature = "pop")]
+use crate::pop;
 #[cfg(feature = "sendmail")]
 use crate::sendmail;
 #[cfg(feature = "smtp")]
@@ -21,15 +27,23 @@ use super::{config::BackendConfig, BackendKind};
 const DEFAULT_BACKEND_KINDS: &[BackendKind] = &[
     #[cfg(feature = "imap")]
     BackendKind::Imap,
+    #[cfg(feature = "jmap")]
+    BackendKind::Jmap,
     #[cfg(feature = "maildir")]
     BackendKind::Maildir,
+    #[cfg(feature = "mbox")]
+    BackendKind::Mbox,
     #[cfg(feature = "notmuch")]
     BackendKind::Notmuch,
+    #[cfg(feature = "pop")]
+    BackendKind::Pop,
 ];
 
 const SEND_MESSAGE_BACKEND_KINDS: &[BackendKind] = &[
     #[cfg(feature = "smtp")]
     BackendKind::Smtp,
+    #[cfg(feature = "jmap")]
+    BackendKind::Jmap,
     #[cfg(feature = "sendmail")]
     BackendKind::Sendmail,
 ];
@@ -67,10 +81,20 @@ pub(crate) async fn configure(
         Some(kind) if kind == BackendKind::Imap => {
             Some(imap::wizard::configure(account_name, email).await?)
         }
+        #[cfg(feature = "jmap")]
+        Some(kind) if kind == BackendKind::Jmap => {
+            Some(jmap::wizard::configure(account_name, email).await?)
+        }
         #[cfg(feature = "maildir")]
         Some(kind) if kind == BackendKind::Maildir => Some(maildir::wizard::configure()?),
+        #[cfg(feature = "mbox")]
+        Some(kind) if kind == BackendKind::Mbox => Some(mbox::wizard::configure()?),
         #[cfg(feature = "notmuch")]
         Some(kind) if kind == BackendKind::Notmuch => Some(notmuch::wizard::configure()?),
+        #[cfg(feature = "pop")]
+        Some(kind) if kind == BackendKind::Pop => {
+            Some(pop::wizard::configure(account_name, email).await?)
+        }
         _ => None,
     };
 
@@ -93,6 +117,10 @@ pub(crate) async fn configure_sender(
         Some(kind) if kind == BackendKind::Smtp => {
             Some(smtp::wizard::configure(account_name, email).await?)
         }
+        #[cfg(feature = "jmap")]
+        Some(kind) if kind == BackendKind::Jmap => {
+            Some(jmap::wizard::configure(account_name, email).await?)
+        }
         #[cfg(feature = "sendmail")]
         Some(kind) if kind == BackendKind::Sendmail => Some(sendmail::wizard::configure()?),
         _ => None,
@@ -0,0 +1,92 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag, backend::id_mapper_db_dir, cache::IdMapperDatabase,
+    config::TomlConfig, printer::Printer,
+};
+
+/// Migrate the id mapper cache after a rename.
+///
+/// Aliases are bound to the name of the account and to the name of
+/// the folder. When one of them is renamed in the configuration, this
+/// command moves the previous aliases to the new name so that they
+/// keep pointing to the same messages. Aliases created under the new
+/// name in the meantime are replaced.
+#[derive(Debug, Parser)]
+pub struct CacheMigrateCommand {
+    /// The previous name of the account.
+    ///
+    /// When no folder is given, all the folders of the previous
+    /// account are migrated to the current account.
+    #[arg(long = "from-account", value_name = "ACCOUNT")]
+    pub from_account: Option<String>,
+
+    /// The previous name of the folder.
+    #[arg(long = "from-folder", value_name = "FOLDER", requires = "folder_name")]
+    pub from_folder: Option<String>,
+
+    /// The current name of the folder.
+    #[arg(name = "folder_name", value_name = "FOLDER", requires = "from_folder")]
+    pub folder: Option<String>,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl CacheMigrateCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing cache migrate command");
+
+        if self.from_account.is_none() && self.from_folder.is_none() {
+            bail!("cannot migrate cache: missing previous account or folder name");
+        }
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let account = &account_config.name;
+        let from_account = self.from_account.as_deref().unwrap_or(account);
+        let backend_kind = toml_account_config.backend.as_ref();
+        let Some(db_dir) = id_mapper_db_dir(&toml_account_config, &account_config, backend_kind)?
+        else {
+            bail!("cannot migrate cache: account {account} does not use id aliases");
+        };
+
+        let db = IdMapperDatabase::open(db_dir)?;
+
+        let folders = match (self.from_folder, self.folder) {
+            (Some(from_folder), Some(folder)) => {
+                vec![(from_folder, account_config.get_folder_alias(&folder))]
+            }
+            _ => db
+                .folders(from_account)?
+                .into_iter()
+                .map(|folder| (folder.clone(), folder))
+                .collect(),
+        };
+
+        let mut migrated = 0;
+        for (from_folder, folder) in folders {
+            if db.migrate_table(from_account, &from_folder, account, &folder)? {
+                migrated += 1;
+            }
+        }
+
+        if migrated == 0 {
+            bail!("cannot migrate cache: no alias found for account {from_account}");
+        }
+
+        printer.print(format!("{migrated} folder(s) successfully migrated!"))
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(feature = "cache-migrate")]
+mod migrate;
+#[cfg(feature = "cache-prune")]
+mod prune;
+#[cfg(feature = "cache-reset")]
+mod reset;
+#[cfg(feature = "cache-stats")]
+mod stats;
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use crate::{config::TomlConfig, printer::Printer};
+
+#[cfg(feature = "cache-migrate")]
+use self::migrate::CacheMigrateCommand;
+#[cfg(feature = "cache-prune")]
+use self::prune::CachePruneCommand;
+#[cfg(feature = "cache-reset")]
+use self::reset::CacheResetCommand;
+#[cfg(feature = "cache-stats")]
+use self::stats::CacheStatsCommand;
+
+/// Manage the id mapper cache.
+///
+/// Some backends (Maildir, mbox, Notmuch) identify messages with long
+/// internal ids. Those ids are mapped to short numeric aliases, which
+/// are stored in a SQLite database. This subcommand allows you to
+/// inspect and maintain this database.
+#[derive(Debug, Subcommand)]
+pub enum CacheSubcommand {
+    #[cfg(feature = "cache-stats")]
+    #[command(alias = "stat")]
+    Stats(CacheStatsCommand),
+
+    #[cfg(feature = "cache-prune")]
+    #[command()]
+    Prune(CachePruneCommand),
+
+    #[cfg(feature = "cache-migrate")]
+    #[command()]
+    Migrate(CacheMigrateCommand),
+
+    #[cfg(feature = "cache-reset")]
+    #[command(alias = "clear")]
+    Reset(CacheResetCommand),
+}
+
+impl CacheSubcommand {
+    #[allow(unused)]
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        match self {
+            #[cfg(feature = "cache-stats")]
+            Self::Stats(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "cache-prune")]
+            Self::Prune(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "cache-migrate")]
+            Self::Migrate(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "cache-reset")]
+            Self::Reset(cmd) => cmd.execute(printer, config).await,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use log::{info, warn};
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{id_mapper_db_dir, Backend},
+    cache::IdMapperDatabase,
+    config::TomlConfig,
+    printer::Printer,
+};
+
+/// Prune the id mapper cache.
+///
+/// Aliases of messages that do not exist anymore in the backend
+/// folder are removed. When no folder is given, all the folders of
+/// the account known by the cache are pruned.
+#[derive(Debug, Parser)]
+pub struct CachePruneCommand {
+    /// The name of the folder to prune.
+    #[arg(name = "folder_name", value_name = "FOLDER")]
+    pub folder: Option<String>,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl CachePruneCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing cache prune command");
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let account = &account_config.name;
+        let backend_kind = toml_account_config.backend.as_ref();
+        let Some(db_dir) = id_mapper_db_dir(&toml_account_config, &account_config, backend_kind)?
+        else {
+            bail!("cannot prune cache: account {account} does not use id aliases");
+        };
+
+        let folders = match self.folder {
+            Some(folder) => vec![folder],
+            None => IdMapperDatabase::open(db_dir)?.folders(account)?,
+        };
+
+        let backend =
+            Backend::new(&toml_account_config, &account_config, backend_kind, |_| ()).await?;
+
+        let mut pruned = 0;
+        for folder in &folders {
+            match backend.prune_id_mapper(folder, backend_kind).await {
+                Ok(count) => pruned += count,
+                Err(err) if folders.len() > 1 => {
+                    warn!("cannot prune cache of folder {folder}, skipping it: {err}");
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        printer.print(format!("{pruned} alias(es) successfully pruned!"))
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use dialoguer::Confirm;
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag, backend::id_mapper_db_dir, cache::IdMapperDatabase,
+    config::TomlConfig, printer::Printer,
+};
+
+/// Reset the id mapper cache.
+///
+/// All the aliases of the given folder are removed, or the aliases of
+/// all the folders of the account if no folder is given. New aliases
+/// are created the next time envelopes are listed, which means that
+/// previous aliases should not be used anymore.
+#[derive(Debug, Parser)]
+pub struct CacheResetCommand {
+    /// The name of the folder to reset.
+    #[arg(name = "folder_name", value_name = "FOLDER")]
+    pub folder: Option<String>,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl CacheResetCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing cache reset command");
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let account = &account_config.name;
+        let backend_kind = toml_account_config.backend.as_ref();
+        let Some(db_dir) = id_mapper_db_dir(&toml_account_config, &account_config, backend_kind)?
+        else {
+            bail!("cannot reset cache: account {account} does not use id aliases");
+        };
+
+        let confirm_msg = match &self.folder {
+            Some(folder) => format!("Do you really want to reset the cache of the folder {folder}? All its aliases will change."),
+            None => format!("Do you really want to reset the cache of the account {account}? All its aliases will change."),
+        };
+        let confirm = Confirm::new()
+            .with_prompt(confirm_msg)
+            .default(false)
+            .report(false)
+            .interact_opt()?;
+        if let Some(false) | None = confirm {
+            return Ok(());
+        };
+
+        let db = IdMapperDatabase::open(db_dir)?;
+
+        match self.folder {
+            Some(folder) => {
+                db.drop_table(account, &account_config.get_folder_alias(&folder))?;
+                printer.print(format!("Cache of folder {folder} successfully reset!"))
+            }
+            None => {
+                for folder in db.folders(account)? {
+                    db.drop_table(account, &folder)?;
+                }
+                printer.print(format!("Cache of account {account} successfully reset!"))
+            }
+        }
+    }
+}
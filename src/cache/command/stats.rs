@@ -0,0 +1,103 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use log::info;
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::id_mapper_db_dir,
+    cache::{IdMapperDatabase, IdMapperTableStats},
+    config::TomlConfig,
+    printer::{PrintTable, PrintTableOpts, Printer, WriteColor},
+    ui::{arg::max_width::TableMaxWidthFlag, Cell, Row, Table},
+};
+
+/// Show statistics about the id mapper cache.
+///
+/// This command displays the path of the id mapper database used by
+/// the account, as well as the tables it contains with their amount
+/// of aliases.
+#[derive(Debug, Parser)]
+pub struct CacheStatsCommand {
+    #[command(flatten)]
+    pub table: TableMaxWidthFlag,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl CacheStatsCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing cache stats command");
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let backend_kind = toml_account_config.backend.as_ref();
+        let Some(db_dir) = id_mapper_db_dir(&toml_account_config, &account_config, backend_kind)?
+        else {
+            let account = &account_config.name;
+            bail!("cannot get cache stats: account {account} does not use id aliases");
+        };
+
+        let db = IdMapperDatabase::open(db_dir)?;
+        let stats = CacheStats {
+            path: db.path().to_owned(),
+            tables: db.stats()?,
+        };
+
+        printer.print_table(
+            Box::new(stats),
+            PrintTableOpts {
+                format: &account_config.get_message_read_format(),
+                max_width: self.table.max_width,
+            },
+        )
+    }
+}
+
+impl Table for IdMapperTableStats {
+    fn head() -> Row {
+        Row::new()
+            .cell(Cell::new("TABLE").bold().underline().white())
+            .cell(Cell::new("ACCOUNT").bold().underline().white())
+            .cell(Cell::new("FOLDER").bold().underline().white())
+            .cell(Cell::new("ALIASES").bold().underline().white())
+    }
+
+    fn row(&self) -> Row {
+        Row::new()
+            .cell(Cell::new(&self.table).red())
+            .cell(Cell::new(self.account.as_deref().unwrap_or("?")).blue())
+            .cell(Cell::new(self.folder.as_deref().unwrap_or("?")).green())
+            .cell(Cell::new(self.aliases.to_string()).yellow())
+    }
+}
+
+/// The id mapper database statistics.
+#[derive(Clone, Debug, Serialize)]
+struct CacheStats {
+    path: PathBuf,
+    tables: Vec<IdMapperTableStats>,
+}
+
+impl PrintTable for CacheStats {
+    fn print_table(&self, writer: &mut dyn WriteColor, opts: PrintTableOpts) -> Result<()> {
+        writeln!(writer)?;
+        writeln!(writer, "Database: {}", self.path.display())?;
+        writeln!(writer)?;
+        Table::print(writer, &self.tables, opts)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
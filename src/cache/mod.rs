@@ -1,13 +1,240 @@
 pub mod arg;
 pub mod args;
+#[cfg(feature = "cache-subcmd")]
+pub mod command;
 
 use anyhow::{anyhow, Context, Result};
 use email::account::config::AccountConfig;
 use log::{debug, trace};
-use std::path::{Path, PathBuf};
+use rusqlite::OptionalExtension;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 const ID_MAPPER_DB_FILE_NAME: &str = ".id-mapper.sqlite";
 
+/// The table keeping track of the account and the folder each id
+/// mapper table belongs to, since table names are digests.
+const ID_MAPPER_REGISTRY_TABLE: &str = "id_mappers";
+
+/// The version of the id mapper database, stored in its `user_version`
+/// pragma. Databases created before the registry existed have the
+/// version 0.
+const ID_MAPPER_DB_VERSION: i64 = 1;
+
+/// Return the name of the id mapper table of the given account and
+/// folder alias.
+pub fn id_mapper_table_name(account: &str, folder: &str) -> String {
+    let digest = md5::compute(account.to_owned() + folder);
+    format!("id_mapper_{digest:x}")
+}
+
+fn create_registry_table(conn: &rusqlite::Connection) -> Result<()> {
+    let query = format!(
+        "CREATE TABLE IF NOT EXISTS {ID_MAPPER_REGISTRY_TABLE} (
+            name TEXT PRIMARY KEY,
+            account TEXT NOT NULL,
+            folder TEXT NOT NULL
+        )",
+    );
+    trace!("create registry table query: {query:#?}");
+
+    conn.execute(&query, [])
+        .context("cannot create id mapper registry table")?;
+
+    Ok(())
+}
+
+fn table_exists(conn: &rusqlite::Connection, table: &str) -> Result<bool> {
+    let query = "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?";
+    trace!("table exists query: {query:#?}");
+
+    let count: i64 = conn
+        .query_row(query, [table], |row| row.get(0))
+        .with_context(|| format!("cannot check existence of id mapper table {table}"))?;
+
+    Ok(count > 0)
+}
+
+/// Create the given id mapper table with its message_id index, and
+/// register it.
+fn create_table(
+    conn: &rusqlite::Connection,
+    table: &str,
+    account: &str,
+    folder: &str,
+) -> Result<()> {
+    debug!("creating id mapper table {table}…");
+
+    let tx = conn
+        .unchecked_transaction()
+        .with_context(|| format!("cannot create id mapper table {table}"))?;
+
+    let queries = [
+        format!(
+            "CREATE TABLE IF NOT EXISTS {table} (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                internal_id TEXT UNIQUE,
+                message_id TEXT
+            )",
+        ),
+        format!("CREATE INDEX IF NOT EXISTS {table}_message_id ON {table} (message_id)"),
+    ];
+
+    for query in queries {
+        trace!("create table query: {query:#?}");
+        tx.execute(&query, [])
+            .with_context(|| format!("cannot create id mapper table {table}"))?;
+    }
+
+    create_registry_table(&tx)?;
+    register_table(&tx, table, account, folder)?;
+
+    tx.commit()
+        .with_context(|| format!("cannot create id mapper table {table}"))?;
+
+    Ok(())
+}
+
+fn register_table(
+    conn: &rusqlite::Connection,
+    table: &str,
+    account: &str,
+    folder: &str,
+) -> Result<()> {
+    let query = format!("INSERT OR REPLACE INTO {ID_MAPPER_REGISTRY_TABLE} VALUES (?, ?, ?)");
+    trace!("register table query: {query:#?}");
+
+    conn.execute(&query, [table, account, folder])
+        .with_context(|| format!("cannot register id mapper table {table}"))?;
+
+    Ok(())
+}
+
+/// Upgrade a database created by a previous version, whose version
+/// is lower than [`ID_MAPPER_DB_VERSION`].
+///
+/// Existing tables get the message_id column and its index. Only the
+/// given table can be registered, since the account and the folder of
+/// the other ones cannot be recovered from their digest.
+fn migrate_db(conn: &rusqlite::Connection, table: &str, account: &str, folder: &str) -> Result<()> {
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .context("cannot get id mapper database version")?;
+
+    if version >= ID_MAPPER_DB_VERSION {
+        return Ok(());
+    }
+
+    debug!("migrating id mapper database from version {version}…");
+
+    let tx = conn
+        .unchecked_transaction()
+        .context("cannot start id mapper database migration")?;
+
+    let query = r"SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE 'id\_mapper\_%' ESCAPE '\'";
+    trace!("select tables query: {query:#?}");
+
+    let mut stmt = tx.prepare(query).context("cannot list id mapper tables")?;
+    let tables: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .context("cannot list id mapper tables")?
+        .collect::<rusqlite::Result<_>>()
+        .context("cannot list id mapper tables")?;
+    drop(stmt);
+
+    for name in &tables {
+        let query =
+            format!("SELECT COUNT(*) FROM pragma_table_info('{name}') WHERE name = 'message_id'");
+        trace!("table info query: {query:#?}");
+
+        let has_message_id: i64 = tx
+            .query_row(&query, [], |row| row.get(0))
+            .with_context(|| format!("cannot get info of id mapper table {name}"))?;
+
+        if has_message_id == 0 {
+            let query = format!("ALTER TABLE {name} ADD COLUMN message_id TEXT");
+            trace!("alter table query: {query:#?}");
+
+            tx.execute(&query, []).with_context(|| {
+                format!("cannot add message_id column to id mapper table {name}")
+            })?;
+        }
+
+        let query = format!("CREATE INDEX IF NOT EXISTS {name}_message_id ON {name} (message_id)");
+        trace!("create index query: {query:#?}");
+
+        tx.execute(&query, [])
+            .with_context(|| format!("cannot create message_id index of id mapper table {name}"))?;
+    }
+
+    create_registry_table(&tx)?;
+
+    if tables.iter().any(|name| name == table) {
+        register_table(&tx, table, account, folder)?;
+    }
+
+    let query = format!("PRAGMA user_version = {ID_MAPPER_DB_VERSION}");
+    tx.execute_batch(&query)
+        .context("cannot set id mapper database version")?;
+
+    tx.commit().context("cannot migrate id mapper database")?;
+
+    Ok(())
+}
+
+/// Build the query finding the alias of a Message-ID in the other
+/// tables of the account, as long as it is not used in the given
+/// table. Return `None` if the account has no other table.
+fn build_siblings_query(conn: &rusqlite::Connection, table: &str) -> Result<Option<String>> {
+    let query = format!(
+        "SELECT name FROM {ID_MAPPER_REGISTRY_TABLE}
+         WHERE account = (SELECT account FROM {ID_MAPPER_REGISTRY_TABLE} WHERE name = ?1)
+         AND name != ?1
+         AND name IN (SELECT name FROM sqlite_master WHERE type = 'table')",
+    );
+    trace!("select sibling tables query: {query:#?}");
+
+    let mut stmt = conn
+        .prepare(&query)
+        .with_context(|| format!("cannot list sibling tables of id mapper table {table}"))?;
+    let siblings: Vec<String> = stmt
+        .query_map([table], |row| row.get(0))
+        .with_context(|| format!("cannot list sibling tables of id mapper table {table}"))?
+        .collect::<rusqlite::Result<_>>()
+        .with_context(|| format!("cannot list sibling tables of id mapper table {table}"))?;
+
+    if siblings.is_empty() {
+        return Ok(None);
+    }
+
+    let union = siblings
+        .iter()
+        .map(|sibling| format!("SELECT id, message_id FROM {sibling}"))
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+
+    Ok(Some(format!(
+        "SELECT id FROM ({union}) WHERE message_id = ?
+         AND id NOT IN (SELECT id FROM {table})
+         ORDER BY id DESC LIMIT 1",
+    )))
+}
+
+fn find_free_alias(
+    conn: &rusqlite::Connection,
+    query: &str,
+    message_id: &str,
+) -> Result<Option<i64>> {
+    trace!("select alias query: {query:#?}");
+
+    conn.prepare_cached(query)
+        .and_then(|mut stmt| stmt.query_row([message_id], |row| row.get(0)).optional())
+        .with_context(|| format!("cannot find alias of message {message_id} in other folders"))
+}
+
 #[derive(Debug)]
 pub enum IdMapper {
     Dummy,
@@ -37,24 +264,18 @@ impl IdMapper {
 
     pub fn new(account_config: &AccountConfig, folder: &str, db_path: PathBuf) -> Result<Self> {
         let folder = account_config.get_folder_alias(folder);
-        let digest = md5::compute(account_config.name.clone() + &folder);
-        let table = format!("id_mapper_{digest:x}");
-        debug!("creating id mapper table {table} at {db_path:?}…");
+        let table = id_mapper_table_name(&account_config.name, &folder);
+        debug!("opening id mapper table {table} at {db_path:?}…");
 
         let db_path = Self::find_closest_db_path(db_path);
         let conn = rusqlite::Connection::open(&db_path)
             .with_context(|| format!("cannot open id mapper database at {db_path:?}"))?;
 
-        let query = format!(
-            "CREATE TABLE IF NOT EXISTS {table} (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                internal_id TEXT UNIQUE
-            )",
-        );
-        trace!("create table query: {query:#?}");
+        migrate_db(&conn, &table, &account_config.name, &folder)?;
 
-        conn.execute(&query, [])
-            .context("cannot create id mapper table")?;
+        if !table_exists(&conn, &table)? {
+            create_table(&conn, &table, &account_config.name, &folder)?;
+        }
 
         Ok(Self::Mapper(table, conn))
     }
@@ -120,6 +341,129 @@ impl IdMapper {
         }
     }
 
+    /// Same as [`IdMapper::get_or_create_alias`], but also records
+    /// the Message-ID of the message, if any.
+    ///
+    /// When the message is not known yet, the alias it has in another
+    /// folder of the same account is reused if available, so that
+    /// moved messages keep a recognisable alias.
+    pub fn get_or_create_message_alias<I>(&self, id: I, message_id: Option<&str>) -> Result<String>
+    where
+        I: AsRef<str>,
+    {
+        let id = id.as_ref();
+        let mut aliases = self.get_or_create_message_aliases([(id, message_id)])?;
+
+        aliases
+            .remove(id)
+            .ok_or_else(|| anyhow!("cannot get alias for id {id}"))
+    }
+
+    /// Same as [`IdMapper::get_or_create_message_alias`], for the
+    /// given ids and Message-IDs at once. All the lookups run inside
+    /// the same transaction. Return the aliases indexed by id.
+    pub fn get_or_create_message_aliases<'a, I>(&self, ids: I) -> Result<HashMap<String, String>>
+    where
+        I: IntoIterator<Item = (&'a str, Option<&'a str>)>,
+    {
+        let Self::Mapper(table, conn) = self else {
+            return Ok(ids
+                .into_iter()
+                .map(|(id, _)| (id.to_owned(), id.to_owned()))
+                .collect());
+        };
+
+        let tx = conn
+            .unchecked_transaction()
+            .context("cannot start id mapper transaction")?;
+
+        // the query searching for aliases in the other folders is
+        // built only when a new message shows up
+        let mut siblings_query = None;
+        let mut aliases = HashMap::new();
+
+        for (id, message_id) in ids {
+            debug!("getting alias for id {id} with message id {message_id:?}…");
+
+            let query = format!("SELECT id, message_id FROM {table} WHERE internal_id = ?");
+            trace!("select query: {query:#?}");
+
+            let row: Option<(i64, Option<String>)> = tx
+                .prepare_cached(&query)
+                .and_then(|mut stmt| {
+                    stmt.query_row([id], |row| Ok((row.get(0)?, row.get(1)?)))
+                        .optional()
+                })
+                .with_context(|| format!("cannot get alias for id {id}"))?;
+
+            if let Some((alias, prev_message_id)) = row {
+                debug!("found alias {alias} for id {id}");
+
+                if message_id.is_some() && prev_message_id.as_deref() != message_id {
+                    let query = format!("UPDATE {table} SET message_id = ? WHERE id = ?");
+                    trace!("update query: {query:#?}");
+
+                    tx.prepare_cached(&query)
+                        .and_then(|mut stmt| stmt.execute(rusqlite::params![message_id, alias]))
+                        .with_context(|| format!("cannot record message id of alias {alias}"))?;
+                }
+
+                aliases.insert(id.to_owned(), alias.to_string());
+                continue;
+            }
+
+            let free_alias = match message_id {
+                Some(message_id) => {
+                    if siblings_query.is_none() {
+                        siblings_query = Some(build_siblings_query(&tx, table)?);
+                    }
+
+                    match siblings_query.as_ref().unwrap() {
+                        Some(query) => find_free_alias(&tx, query, message_id)?,
+                        None => None,
+                    }
+                }
+                None => None,
+            };
+
+            let alias = match free_alias {
+                Some(alias) => {
+                    debug!("reusing alias {alias} of message {message_id:?}");
+
+                    let query = format!(
+                        "INSERT INTO {table} (id, internal_id, message_id) VALUES (?, ?, ?)"
+                    );
+                    trace!("insert query: {query:#?}");
+
+                    tx.prepare_cached(&query)
+                        .and_then(|mut stmt| stmt.execute(rusqlite::params![alias, id, message_id]))
+                        .with_context(|| format!("cannot create id alias for id {id}"))?;
+
+                    alias.to_string()
+                }
+                None => {
+                    let query =
+                        format!("INSERT INTO {table} (internal_id, message_id) VALUES (?, ?)");
+                    trace!("insert query: {query:#?}");
+
+                    tx.prepare_cached(&query)
+                        .and_then(|mut stmt| stmt.execute(rusqlite::params![id, message_id]))
+                        .with_context(|| format!("cannot create id alias for id {id}"))?;
+
+                    let alias = tx.last_insert_rowid().to_string();
+                    debug!("created alias {alias} for id {id}");
+                    alias
+                }
+            };
+
+            aliases.insert(id.to_owned(), alias);
+        }
+
+        tx.commit().context("cannot commit id mapper transaction")?;
+
+        Ok(aliases)
+    }
+
     pub fn get_id<A>(&self, alias: A) -> Result<String>
     where
         A: ToString,
@@ -166,4 +510,356 @@ impl IdMapper {
             .map(|alias| self.get_id(alias))
             .collect()
     }
+
+    /// Remove aliases whose internal id is not part of the given
+    /// existing ids, and return the amount of removed aliases.
+    pub fn prune(&self, ids: &HashSet<String>) -> Result<usize> {
+        let Self::Mapper(table, conn) = self else {
+            return Ok(0);
+        };
+
+        debug!("pruning id mapper table {table}…");
+
+        let query = format!("SELECT internal_id FROM {table}");
+        trace!("select query: {query:#?}");
+
+        let mut stmt = conn
+            .prepare(&query)
+            .with_context(|| format!("cannot list ids of id mapper table {table}"))?;
+        let stale_ids: Vec<String> = stmt
+            .query_map([], |row| row.get(0))
+            .with_context(|| format!("cannot list ids of id mapper table {table}"))?
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .with_context(|| format!("cannot list ids of id mapper table {table}"))?
+            .into_iter()
+            .filter(|id| !ids.contains(id))
+            .collect();
+
+        let query = format!("DELETE FROM {table} WHERE internal_id = ?");
+        trace!("delete query: {query:#?}");
+
+        for id in &stale_ids {
+            conn.execute(&query, [id])
+                .with_context(|| format!("cannot delete alias of id {id}"))?;
+        }
+
+        debug!("pruned {} aliases from table {table}", stale_ids.len());
+        Ok(stale_ids.len())
+    }
+}
+
+/// Statistics about an id mapper table.
+#[derive(Clone, Debug, Serialize)]
+pub struct IdMapperTableStats {
+    pub table: String,
+    /// The account the table belongs to, unknown for tables created
+    /// before the registry existed.
+    pub account: Option<String>,
+    /// The folder alias the table belongs to, unknown for tables
+    /// created before the registry existed.
+    pub folder: Option<String>,
+    pub aliases: usize,
+}
+
+/// The id mapper database, used to inspect and maintain all the id
+/// mapper tables at once.
+#[derive(Debug)]
+pub struct IdMapperDatabase {
+    path: PathBuf,
+    conn: rusqlite::Connection,
+}
+
+impl IdMapperDatabase {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let path = IdMapper::find_closest_db_path(dir);
+        debug!("opening id mapper database at {path:?}…");
+
+        let conn = rusqlite::Connection::open(&path)
+            .with_context(|| format!("cannot open id mapper database at {path:?}"))?;
+        create_registry_table(&conn)?;
+
+        Ok(Self { path, conn })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return the names of all the id mapper tables.
+    fn tables(&self) -> Result<Vec<String>> {
+        let query = r"SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE 'id\_mapper\_%' ESCAPE '\' ORDER BY name";
+        trace!("select tables query: {query:#?}");
+
+        let mut stmt = self
+            .conn
+            .prepare(query)
+            .context("cannot list id mapper tables")?;
+        let tables = stmt
+            .query_map([], |row| row.get(0))
+            .context("cannot list id mapper tables")?
+            .collect::<rusqlite::Result<_>>()
+            .context("cannot list id mapper tables")?;
+
+        Ok(tables)
+    }
+
+    /// Return the account and the folder alias the given table
+    /// belongs to, if registered.
+    fn table_owner(&self, table: &str) -> Result<Option<(String, String)>> {
+        let query =
+            format!("SELECT account, folder FROM {ID_MAPPER_REGISTRY_TABLE} WHERE name = ?");
+        trace!("select owner query: {query:#?}");
+
+        let mut stmt = self
+            .conn
+            .prepare(&query)
+            .with_context(|| format!("cannot get owner of id mapper table {table}"))?;
+        let owners: Vec<(String, String)> = stmt
+            .query_map([table], |row| Ok((row.get(0)?, row.get(1)?)))
+            .with_context(|| format!("cannot get owner of id mapper table {table}"))?
+            .collect::<rusqlite::Result<_>>()
+            .with_context(|| format!("cannot get owner of id mapper table {table}"))?;
+
+        Ok(owners.into_iter().next())
+    }
+
+    pub fn stats(&self) -> Result<Vec<IdMapperTableStats>> {
+        self.tables()?
+            .into_iter()
+            .map(|table| {
+                let query = format!("SELECT COUNT(*) FROM {table}");
+                trace!("count query: {query:#?}");
+
+                let aliases: i64 = self
+                    .conn
+                    .query_row(&query, [], |row| row.get(0))
+                    .with_context(|| format!("cannot count aliases of id mapper table {table}"))?;
+                let (account, folder) = match self.table_owner(&table)? {
+                    Some((account, folder)) => (Some(account), Some(folder)),
+                    None => (None, None),
+                };
+
+                Ok(IdMapperTableStats {
+                    table,
+                    account,
+                    folder,
+                    aliases: aliases as usize,
+                })
+            })
+            .collect()
+    }
+
+    /// Return the folder aliases registered for the given account.
+    pub fn folders(&self, account: &str) -> Result<Vec<String>> {
+        let query = format!(
+            "SELECT folder FROM {ID_MAPPER_REGISTRY_TABLE} WHERE account = ? ORDER BY folder"
+        );
+        trace!("select folders query: {query:#?}");
+
+        let mut stmt = self
+            .conn
+            .prepare(&query)
+            .with_context(|| format!("cannot list id mapper folders of account {account}"))?;
+        let folders = stmt
+            .query_map([account], |row| row.get(0))
+            .with_context(|| format!("cannot list id mapper folders of account {account}"))?
+            .collect::<rusqlite::Result<_>>()
+            .with_context(|| format!("cannot list id mapper folders of account {account}"))?;
+
+        Ok(folders)
+    }
+
+    /// Drop the id mapper table of the given account and folder
+    /// alias. Return `false` if the table did not exist.
+    pub fn drop_table(&self, account: &str, folder: &str) -> Result<bool> {
+        let table = id_mapper_table_name(account, folder);
+        debug!("dropping id mapper table {table}…");
+
+        let exists = self.tables()?.contains(&table);
+
+        let query = format!("DROP TABLE IF EXISTS {table}");
+        trace!("drop table query: {query:#?}");
+
+        self.conn
+            .execute(&query, [])
+            .with_context(|| format!("cannot drop id mapper table {table}"))?;
+
+        let query = format!("DELETE FROM {ID_MAPPER_REGISTRY_TABLE} WHERE name = ?");
+        trace!("unregister table query: {query:#?}");
+
+        self.conn
+            .execute(&query, [&table])
+            .with_context(|| format!("cannot unregister id mapper table {table}"))?;
+
+        Ok(exists)
+    }
+
+    /// Move the aliases of a renamed account or folder alias to its
+    /// new name. Aliases created under the new name in the meantime
+    /// are replaced. Return `false` if there was nothing to migrate.
+    pub fn migrate_table(
+        &self,
+        from_account: &str,
+        from_folder: &str,
+        account: &str,
+        folder: &str,
+    ) -> Result<bool> {
+        let from_table = id_mapper_table_name(from_account, from_folder);
+        let table = id_mapper_table_name(account, folder);
+        debug!("migrating id mapper table {from_table} to {table}…");
+
+        if from_table == table || !self.tables()?.contains(&from_table) {
+            return Ok(false);
+        }
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .context("cannot start id mapper migration")?;
+
+        let queries = [
+            format!("DROP TABLE IF EXISTS {table}"),
+            format!("DROP INDEX IF EXISTS {from_table}_message_id"),
+            format!("ALTER TABLE {from_table} RENAME TO {table}"),
+        ];
+
+        for query in queries {
+            trace!("migration query: {query:#?}");
+            tx.execute(&query, [])
+                .with_context(|| format!("cannot migrate id mapper table {from_table}"))?;
+        }
+
+        let query = format!("DELETE FROM {ID_MAPPER_REGISTRY_TABLE} WHERE name = ?");
+        tx.execute(&query, [&from_table])
+            .with_context(|| format!("cannot unregister id mapper table {from_table}"))?;
+
+        let query = format!("INSERT OR REPLACE INTO {ID_MAPPER_REGISTRY_TABLE} VALUES (?, ?, ?)");
+        tx.execute(&query, [&table, account, folder])
+            .with_context(|| format!("cannot register id mapper table {table}"))?;
+
+        tx.commit()
+            .with_context(|| format!("cannot migrate id mapper table {from_table}"))?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use email::account::config::AccountConfig;
+
+    use super::{id_mapper_table_name, IdMapper, IdMapperDatabase, ID_MAPPER_REGISTRY_TABLE};
+
+    fn account_config() -> AccountConfig {
+        AccountConfig {
+            name: "account".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn register_table_on_creation() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = account_config();
+
+        IdMapper::new(&config, "INBOX", dir.path().to_owned()).unwrap();
+
+        let db = IdMapperDatabase::open(dir.path()).unwrap();
+        let stats = db.stats().unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].table, id_mapper_table_name("account", "INBOX"));
+        assert_eq!(stats[0].account.as_deref(), Some("account"));
+        assert_eq!(stats[0].folder.as_deref(), Some("INBOX"));
+
+        // existing tables are not registered again
+        let query = format!("DELETE FROM {ID_MAPPER_REGISTRY_TABLE}");
+        db.conn.execute(&query, []).unwrap();
+        IdMapper::new(&config, "INBOX", dir.path().to_owned()).unwrap();
+        assert_eq!(db.folders("account").unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn migrate_legacy_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = id_mapper_table_name("account", "INBOX");
+
+        let conn = rusqlite::Connection::open(dir.path().join(".id-mapper.sqlite")).unwrap();
+        let query = format!(
+            "CREATE TABLE {table} (id INTEGER PRIMARY KEY AUTOINCREMENT, internal_id TEXT UNIQUE)"
+        );
+        conn.execute(&query, []).unwrap();
+        let query = format!("INSERT INTO {table} (internal_id) VALUES ('1')");
+        conn.execute(&query, []).unwrap();
+
+        let mapper = IdMapper::new(&account_config(), "INBOX", dir.path().to_owned()).unwrap();
+        assert_eq!(mapper.get_or_create_alias("1").unwrap(), "1");
+
+        let db = IdMapperDatabase::open(dir.path()).unwrap();
+        assert_eq!(db.folders("account").unwrap(), vec!["INBOX".to_owned()]);
+    }
+
+    #[test]
+    fn keep_alias_across_move() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = account_config();
+
+        let inbox = IdMapper::new(&config, "INBOX", dir.path().to_owned()).unwrap();
+        let aliases = inbox
+            .get_or_create_message_aliases([
+                ("1", Some("<a@localhost>")),
+                ("2", Some("<b@localhost>")),
+                ("3", None),
+            ])
+            .unwrap();
+        assert_eq!(aliases["1"], "1");
+        assert_eq!(aliases["2"], "2");
+        assert_eq!(aliases["3"], "3");
+
+        // the moved message gets a new id but keeps its alias, while
+        // messages without Message-ID cannot be recognised
+        let archive = IdMapper::new(&config, "Archive", dir.path().to_owned()).unwrap();
+        let aliases = archive
+            .get_or_create_message_aliases([("10", Some("<a@localhost>")), ("11", None)])
+            .unwrap();
+        assert_eq!(aliases["10"], "1");
+        assert_eq!(aliases["11"], "2");
+
+        // aliases already used in the folder are not reused
+        let alias = archive
+            .get_or_create_message_alias("12", Some("<b@localhost>"))
+            .unwrap();
+        assert_eq!(alias, "3");
+
+        // known ids keep their alias
+        let alias = archive
+            .get_or_create_message_alias("10", Some("<a@localhost>"))
+            .unwrap();
+        assert_eq!(alias, "1");
+        let alias = inbox.get_or_create_message_alias("3", None).unwrap();
+        assert_eq!(alias, "3");
+    }
+
+    #[test]
+    fn reset_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = account_config();
+
+        for folder in ["INBOX", "Sent"] {
+            let mapper = IdMapper::new(&config, folder, dir.path().to_owned()).unwrap();
+            mapper.create_alias("a").unwrap();
+            mapper.create_alias("b").unwrap();
+        }
+
+        let db = IdMapperDatabase::open(dir.path()).unwrap();
+        assert_eq!(db.folders("account").unwrap(), vec!["INBOX", "Sent"]);
+
+        assert!(db.drop_table("account", "INBOX").unwrap());
+        assert!(!db.drop_table("account", "INBOX").unwrap());
+        assert_eq!(db.folders("account").unwrap(), vec!["Sent"]);
+
+        // aliases start over once the table is reset
+        let mapper = IdMapper::new(&config, "INBOX", dir.path().to_owned()).unwrap();
+        assert_eq!(mapper.get_or_create_alias("b").unwrap(), "1");
+        assert_eq!(db.folders("account").unwrap(), vec!["INBOX", "Sent"]);
+    }
 }
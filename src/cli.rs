@@ -4,6 +4,8 @@ use std::path::PathBuf;
 
 #[cfg(feature = "account-subcmd")]
 use crate::account::command::AccountSubcommand;
+#[cfg(feature = "cache-subcmd")]
+use crate::cache::command::CacheSubcommand;
 #[cfg(feature = "envelope-subcmd")]
 use crate::envelope::command::EnvelopeSubcommand;
 #[cfg(feature = "flag-subcmd")]
@@ -14,8 +16,14 @@ use crate::folder::command::FolderSubcommand;
 use crate::message::attachment::command::AttachmentSubcommand;
 #[cfg(feature = "message-subcmd")]
 use crate::message::command::MessageSubcommand;
+#[cfg(feature = "draft-subcmd")]
+use crate::message::draft::command::DraftSubcommand;
 #[cfg(feature = "template-subcmd")]
 use crate::message::template::command::TemplateSubcommand;
+#[cfg(feature = "outbox-subcmd")]
+use crate::outbox::command::OutboxSubcommand;
+#[cfg(feature = "search")]
+use crate::search::command::SearchCommand;
 #[allow(unused)]
 use crate::{
     completion::command::CompletionGenerateCommand,
@@ -124,6 +132,23 @@ pub enum HimalayaCommand {
     #[command(alias = "templates", alias = "tpls", alias = "tpl")]
     Template(TemplateSubcommand),
 
+    #[cfg(feature = "draft-subcmd")]
+    #[command(subcommand)]
+    #[command(alias = "drafts")]
+    Draft(DraftSubcommand),
+
+    #[cfg(feature = "search")]
+    #[command(arg_required_else_help = true)]
+    Search(SearchCommand),
+
+    #[cfg(feature = "outbox-subcmd")]
+    #[command(subcommand)]
+    Outbox(OutboxSubcommand),
+
+    #[cfg(feature = "cache-subcmd")]
+    #[command(subcommand)]
+    Cache(CacheSubcommand),
+
     #[command(arg_required_else_help = true)]
     #[command(alias = "manuals", alias = "mans")]
     Manual(ManualGenerateCommand),
@@ -176,6 +201,26 @@ impl HimalayaCommand {
                 let config = TomlConfig::from_some_path_or_default(config_path).await?;
                 cmd.execute(printer, &config).await
             }
+            #[cfg(feature = "draft-subcmd")]
+            Self::Draft(cmd) => {
+                let config = TomlConfig::from_some_path_or_default(config_path).await?;
+                cmd.execute(printer, &config).await
+            }
+            #[cfg(feature = "search")]
+            Self::Search(cmd) => {
+                let config = TomlConfig::from_some_path_or_default(config_path).await?;
+                cmd.execute(printer, &config).await
+            }
+            #[cfg(feature = "outbox-subcmd")]
+            Self::Outbox(cmd) => {
+                let config = TomlConfig::from_some_path_or_default(config_path).await?;
+                cmd.execute(printer, &config).await
+            }
+            #[cfg(feature = "cache-subcmd")]
+            Self::Cache(cmd) => {
+                let config = TomlConfig::from_some_path_or_default(config_path).await?;
+                cmd.execute(printer, &config).await
+            }
             Self::Manual(cmd) => cmd.execute(printer).await,
             Self::Completion(cmd) => cmd.execute().await,
         }
@@ -170,6 +170,20 @@ impl TomlConfig {
                 .replace_undefined_keyring_entries(&account_name);
         }
 
+        #[cfg(feature = "jmap")]
+        if let Some(jmap_config) = toml_account_config.jmap.as_mut() {
+            jmap_config
+                .auth
+                .replace_undefined_keyring_entries(&account_name);
+        }
+
+        #[cfg(feature = "pop")]
+        if let Some(pop_config) = toml_account_config.pop.as_mut() {
+            pop_config
+                .passwd
+                .set_keyring_entry_if_undefined(format!("{account_name}-pop-passwd"));
+        }
+
         #[cfg(feature = "smtp")]
         if let Some(smtp_config) = toml_account_config.smtp.as_mut() {
             smtp_config
@@ -7,7 +7,7 @@ use email::account::PgpConfig;
 #[cfg(feature = "pgp-native")]
 use email::account::{NativePgpConfig, NativePgpSecretKey, SignedSecretKey};
 #[cfg(feature = "notmuch")]
-use email::backend::NotmuchConfig;
+use email::notmuch::NotmuchConfig;
 #[cfg(feature = "imap")]
 use email::imap::config::{ImapAuthConfig, ImapConfig};
 #[cfg(feature = "smtp")]
@@ -140,9 +140,28 @@ fn pretty_serialize(config: &TomlConfig) -> Result<String> {
             }
         }
 
+        #[cfg(feature = "jmap")]
+        {
+            set_table_dotted(item, "jmap");
+            if let Some(item) = get_table_mut(item, "jmap") {
+                set_tables_dotted(item, ["passwd", "bearer"]);
+            }
+        }
+
+        #[cfg(feature = "mbox")]
+        set_table_dotted(item, "mbox");
+
         #[cfg(feature = "notmuch")]
         set_table_dotted(item, "notmuch");
 
+        #[cfg(feature = "pop")]
+        {
+            set_table_dotted(item, "pop");
+            if let Some(item) = get_table_mut(item, "pop") {
+                set_tables_dotted(item, ["passwd"]);
+            }
+        }
+
         #[cfg(feature = "smtp")]
         {
             set_table_dotted(item, "smtp");
@@ -0,0 +1,274 @@
+//! Module dedicated to envelope table columns.
+//!
+//! The envelope listing table is made of columns that can be picked
+//! and ordered by the user, from the `envelope.list.columns` account
+//! option or from the `--columns` argument.
+
+use anyhow::{anyhow, bail, Error, Result};
+use std::{collections::HashMap, fmt, str::FromStr};
+use termcolor::Color;
+
+use crate::{
+    flag::Flag,
+    ui::{human_size, Cell, Row},
+};
+
+use super::Envelope;
+
+/// A column of the envelope listing table.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum EnvelopeColumn {
+    Id,
+    Flags,
+    Subject,
+    From,
+    To,
+    Date,
+    Size,
+    Account,
+    Folder,
+
+    /// Any other header, lowercased.
+    Header(String),
+}
+
+impl EnvelopeColumn {
+    /// The default columns, when none are configured.
+    pub fn defaults() -> Vec<Self> {
+        vec![Self::Id, Self::Flags, Self::Subject, Self::From, Self::Date]
+    }
+
+    pub fn title(&self) -> String {
+        self.to_string().to_uppercase()
+    }
+
+    pub fn default_color(&self) -> Color {
+        match self {
+            Self::Id => Color::Red,
+            Self::Flags => Color::White,
+            Self::Subject => Color::Green,
+            Self::From | Self::To => Color::Blue,
+            Self::Date => Color::Yellow,
+            Self::Size | Self::Account | Self::Folder => Color::Cyan,
+            Self::Header(_) => Color::White,
+        }
+    }
+
+    /// Return `true` if the column can shrink when the table
+    /// exceeds the maximum width.
+    pub fn is_shrinkable(&self) -> bool {
+        matches!(self, Self::Subject | Self::To | Self::Header(_))
+    }
+
+    pub fn value(&self, envelope: &Envelope) -> String {
+        match self {
+            Self::Id => envelope.id.clone(),
+            Self::Flags => {
+                let mut flags = String::new();
+                flags.push_str(if envelope.flags.contains(&Flag::Seen) {
+                    " "
+                } else {
+                    "✷"
+                });
+                flags.push_str(if envelope.flags.contains(&Flag::Answered) {
+                    "↵"
+                } else {
+                    " "
+                });
+                flags.push_str(if envelope.flags.contains(&Flag::Flagged) {
+                    "⚑"
+                } else {
+                    " "
+                });
+                flags.push_str(if envelope.has_attachment { "📎" } else { " " });
+                flags
+            }
+            Self::Subject => envelope.subject.clone(),
+            Self::From => match &envelope.from.name {
+                Some(name) => name.clone(),
+                None => envelope.from.addr.clone(),
+            },
+            Self::To => envelope
+                .to
+                .iter()
+                .map(|mbox| mbox.name.as_ref().unwrap_or(&mbox.addr).as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Self::Date => envelope.date.clone(),
+            Self::Size => human_size(envelope.size),
+            Self::Account => envelope.account.clone(),
+            Self::Folder => envelope.folder.clone(),
+            Self::Header(name) => envelope.headers.get(name).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for EnvelopeColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id => write!(f, "id"),
+            Self::Flags => write!(f, "flags"),
+            Self::Subject => write!(f, "subject"),
+            Self::From => write!(f, "from"),
+            Self::To => write!(f, "to"),
+            Self::Date => write!(f, "date"),
+            Self::Size => write!(f, "size"),
+            Self::Account => write!(f, "account"),
+            Self::Folder => write!(f, "folder"),
+            Self::Header(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// Parse a column from its name. Names that do not match a known
+/// column are considered as header names.
+impl FromStr for EnvelopeColumn {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        let name = name.trim().to_lowercase();
+
+        match name.as_str() {
+            "id" => Ok(Self::Id),
+            "flags" => Ok(Self::Flags),
+            "subject" => Ok(Self::Subject),
+            "from" => Ok(Self::From),
+            "to" => Ok(Self::To),
+            "date" => Ok(Self::Date),
+            "size" => Ok(Self::Size),
+            "account" => Ok(Self::Account),
+            "folder" => Ok(Self::Folder),
+            "" => bail!("cannot parse envelope column: empty name"),
+            header => {
+                // header field names are printable US-ASCII characters
+                // except colon, see RFC 5322 section 2.2
+                let is_valid = header
+                    .chars()
+                    .all(|c| c.is_ascii_graphic() && !matches!(c, ':' | '(' | ')' | '"'));
+
+                if !is_valid {
+                    bail!("cannot parse envelope column {header}: invalid header name");
+                }
+
+                Ok(Self::Header(header.to_owned()))
+            }
+        }
+    }
+}
+
+/// The columns of the envelope listing table, with their colours.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnvelopeColumns {
+    columns: Vec<EnvelopeColumn>,
+    colors: HashMap<EnvelopeColumn, Color>,
+}
+
+impl Default for EnvelopeColumns {
+    fn default() -> Self {
+        Self {
+            columns: EnvelopeColumn::defaults(),
+            colors: HashMap::new(),
+        }
+    }
+}
+
+impl EnvelopeColumns {
+    /// Build columns from their names and their colour overrides.
+    ///
+    /// Colours are given by column name, and can be either a colour
+    /// name (`red`, `green`, `blue`…), an ANSI 256 code or an RGB
+    /// triple (`0,128,255`).
+    pub fn new<N, C>(names: N, colors: C) -> Result<Self>
+    where
+        N: IntoIterator,
+        N::Item: AsRef<str>,
+        C: IntoIterator<Item = (String, String)>,
+    {
+        let columns = names
+            .into_iter()
+            .map(|name| name.as_ref().parse())
+            .collect::<Result<Vec<_>>>()?;
+
+        let colors = colors
+            .into_iter()
+            .map(|(name, color)| {
+                let column = name.parse()?;
+                let color = color.parse::<Color>().map_err(|err| {
+                    anyhow!("cannot parse color of envelope column {name}: {err}")
+                })?;
+                Ok((column, color))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            columns: if columns.is_empty() {
+                EnvelopeColumn::defaults()
+            } else {
+                columns
+            },
+            colors,
+        })
+    }
+
+    /// Insert the given column right after the id column, unless
+    /// the column is already present.
+    pub fn insert(&mut self, column: EnvelopeColumn) {
+        if self.columns.contains(&column) {
+            return;
+        }
+
+        let index = self
+            .columns
+            .iter()
+            .position(|column| *column == EnvelopeColumn::Id)
+            .map(|index| index + 1)
+            .unwrap_or_default();
+
+        self.columns.insert(index, column);
+    }
+
+    /// Return the names of the headers that need to be fetched in
+    /// order to fill the header columns.
+    pub fn headers(&self) -> Vec<String> {
+        self.columns
+            .iter()
+            .filter_map(|column| match column {
+                EnvelopeColumn::Header(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn color(&self, column: &EnvelopeColumn) -> Color {
+        self.colors
+            .get(column)
+            .copied()
+            .unwrap_or_else(|| column.default_color())
+    }
+
+    pub fn head(&self) -> Row {
+        self.columns.iter().fold(Row::new(), |row, column| {
+            let cell = Cell::new(column.title()).bold().underline().white();
+            row.cell(if column.is_shrinkable() {
+                cell.shrinkable()
+            } else {
+                cell
+            })
+        })
+    }
+
+    pub fn row(&self, envelope: &Envelope) -> Row {
+        let unseen = !envelope.flags.contains(&Flag::Seen);
+
+        self.columns.iter().fold(Row::new(), |row, column| {
+            let cell = Cell::new(column.value(envelope))
+                .bold_if(unseen)
+                .fg(self.color(column));
+            row.cell(if column.is_shrinkable() {
+                cell.shrinkable()
+            } else {
+                cell
+            })
+        })
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use clap::Parser;
+#[cfg(feature = "imap")]
+use email::envelope::get::imap::GetEnvelopeImap;
+#[cfg(feature = "maildir")]
+use email::envelope::get::maildir::GetEnvelopeMaildir;
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::GetEnvelopeJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::GetEnvelopeMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::GetEnvelopeNotmuch;
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{Backend, BackendKind},
+    config::TomlConfig,
+    envelope::arg::ids::EnvelopeIdArg,
+    folder::arg::name::FolderNameOptionalFlag,
+    printer::{PrintTableOpts, Printer},
+    ui::arg::max_width::TableMaxWidthFlag,
+};
+
+/// Get an envelope.
+///
+/// This command allows you to display a single envelope with all its
+/// parsed headers: recipients, carbon copies, Message-ID,
+/// In-Reply-To, size and flags.
+#[derive(Debug, Parser)]
+pub struct GetEnvelopeCommand {
+    #[command(flatten)]
+    pub folder: FolderNameOptionalFlag,
+
+    #[command(flatten)]
+    pub envelope: EnvelopeIdArg,
+
+    #[command(flatten)]
+    pub table: TableMaxWidthFlag,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl GetEnvelopeCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing get envelope command");
+
+        let folder = &self.folder.name;
+        let id = self.envelope.id;
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let get_envelope_kind = toml_account_config.get_envelope_kind();
+
+        let backend = Backend::new(
+            &toml_account_config,
+            &account_config,
+            get_envelope_kind,
+            |builder| match get_envelope_kind {
+                #[cfg(feature = "imap")]
+                Some(BackendKind::Imap) => {
+                    builder
+                        .set_get_envelope(|ctx| ctx.imap.as_ref().and_then(GetEnvelopeImap::new));
+                }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_get_envelope(|ctx| ctx.jmap.as_ref().and_then(GetEnvelopeJmap::new));
+                }
+                #[cfg(feature = "maildir")]
+                Some(BackendKind::Maildir) => {
+                    builder.set_get_envelope(|ctx| {
+                        ctx.maildir.as_ref().and_then(GetEnvelopeMaildir::new)
+                    });
+                }
+                #[cfg(feature = "account-sync")]
+                Some(BackendKind::MaildirForSync) => {
+                    builder.set_get_envelope(|ctx| {
+                        ctx.maildir_for_sync
+                            .as_ref()
+                            .and_then(GetEnvelopeMaildir::new)
+                    });
+                }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_get_envelope(|ctx| ctx.mbox.as_ref().and_then(GetEnvelopeMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_get_envelope(|ctx| {
+                        ctx.notmuch.as_ref().and_then(GetEnvelopeNotmuch::new)
+                    });
+                }
+                _ => (),
+            },
+        )
+        .await?;
+
+        let envelope = backend.get_envelope(folder, id).await?;
+
+        printer.print_table(
+            Box::new(envelope),
+            PrintTableOpts {
+                format: &account_config.get_message_read_format(),
+                max_width: self.table.max_width,
+            },
+        )?;
+
+        Ok(())
+    }
+}
@@ -1,19 +1,21 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Parser;
-#[cfg(feature = "imap")]
-use email::envelope::list::imap::ListEnvelopesImap;
-#[cfg(feature = "maildir")]
-use email::envelope::list::maildir::ListEnvelopesMaildir;
-#[cfg(feature = "notmuch")]
-use email::envelope::list::notmuch::ListEnvelopesNotmuch;
-use log::info;
+use log::{debug, info};
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
 use crate::{
     account::arg::name::AccountNameFlag,
-    backend::{Backend, BackendKind},
+    backend::Backend,
     config::TomlConfig,
+    envelope::{
+        column::{EnvelopeColumn, EnvelopeColumns},
+        search::{
+            paginate, EnvelopesCursor, SearchEnvelopesOptions, SearchEnvelopesQuery, SortEnvelopes,
+            SortEnvelopesBy,
+        },
+        Envelopes,
+    },
     folder::arg::name::FolderNameOptionalArg,
     printer::{PrintTableOpts, Printer},
     ui::arg::max_width::TableMaxWidthFlag,
@@ -22,25 +24,106 @@ use crate::{
 /// List all envelopes.
 ///
 /// This command allows you to list all envelopes included in the
-/// given folder.
+/// given folder. Envelopes of several folders and accounts can be
+/// listed at once, in which case they are merged and sorted by date.
 #[derive(Debug, Parser)]
 pub struct ListEnvelopesCommand {
     #[command(flatten)]
     pub folder: FolderNameOptionalArg,
 
+    /// List envelopes from the given folders.
+    ///
+    /// This argument can be repeated in order to merge envelopes of
+    /// multiple folders. Envelope ids are relative to their folder,
+    /// which is displayed in a dedicated column.
+    #[arg(long = "folder", short = 'f', value_name = "FOLDER")]
+    #[arg(conflicts_with = "folder_name")]
+    pub folders: Vec<String>,
+
+    /// List envelopes from all accounts.
+    ///
+    /// Envelopes of all accounts are merged together. Envelope ids
+    /// are relative to their account, which is displayed in a
+    /// dedicated column.
+    #[arg(long, conflicts_with = "account_name")]
+    pub all_accounts: bool,
+
     /// The page number.
     ///
     /// The page number starts from 1 (which is the default). Giving a
-    /// page number to big will result in a out of bound error.
+    /// page past the last one fails with an out-of-bounds error,
+    /// which gives the number of the last page.
     #[arg(long, short, value_name = "NUMBER", default_value = "1")]
     pub page: usize,
 
+    /// List the envelopes following the given cursor.
+    ///
+    /// Cursors are given at the bottom of the listing (or in the
+    /// next field of the JSON output) when more envelopes follow.
+    /// Unlike page numbers, cursors are not shifted by new messages,
+    /// which makes them reliable for walking through large folders.
+    #[arg(long, value_name = "CURSOR", conflicts_with = "page")]
+    pub after: Option<EnvelopesCursor>,
+
     /// The page size.
     ///
     /// Determine the amount of envelopes a page should contain.
     #[arg(long, short = 's', value_name = "NUMBER")]
     pub page_size: Option<usize>,
 
+    /// Filter envelopes using a search query.
+    ///
+    /// A query is a list of conditions separated by spaces, joined
+    /// with "and" by default. Conditions can be combined with the
+    /// "and", "or" and "not" operators and grouped with parenthesis.
+    /// Values containing spaces need to be wrapped into double
+    /// quotes.
+    ///
+    /// The available conditions are:
+    ///
+    ///  - from:<pattern>, to:<pattern>, subject:<pattern>: the header
+    ///    contains the given pattern (case-insensitive)
+    ///
+    ///  - before:<yyyy-mm-dd>, after:<yyyy-mm-dd>, date:<yyyy-mm-dd>:
+    ///    the message was sent before, after or at the given date
+    ///
+    ///  - flag:<flag>: the envelope contains the given flag
+    ///
+    ///  - <pattern>: shortcut for subject:<pattern>
+    ///
+    /// Example: from:alice subject:invoice after:2024-01-01 not flag:seen
+    #[arg(long, short, value_name = "QUERY")]
+    pub query: Option<String>,
+
+    /// Sort envelopes by the given property.
+    ///
+    /// Envelopes are sorted by date (most recent first), sender,
+    /// subject (ignoring reply and forward prefixes) or size (biggest
+    /// first). The pagination applies after sorting. Senders are
+    /// compared by address, not by display name.
+    ///
+    /// Backends unable to sort by themselves (Maildir, or IMAP
+    /// servers without the SORT extension) need to load all the
+    /// envelopes matching the query before paginating them.
+    #[arg(long, value_name = "PROPERTY", value_enum)]
+    pub sort: Option<SortEnvelopesBy>,
+
+    /// Reverse the sort order.
+    ///
+    /// When no sort property is given, envelopes are sorted by date,
+    /// oldest first.
+    #[arg(long, short)]
+    pub reverse: bool,
+
+    /// The columns of the table, in order.
+    ///
+    /// Available columns are id, flags, subject, from, to, date,
+    /// size, account and folder. Any other name is considered as a
+    /// header name (for example list-id or x-mailer). This argument
+    /// overrides the envelope.list.columns account option.
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    pub columns: Vec<String>,
+
     #[command(flatten)]
     pub table: TableMaxWidthFlag,
 
@@ -56,8 +139,15 @@ impl Default for ListEnvelopesCommand {
     fn default() -> Self {
         Self {
             folder: Default::default(),
+            folders: Default::default(),
+            all_accounts: Default::default(),
             page: 1,
+            after: Default::default(),
             page_size: Default::default(),
+            query: Default::default(),
+            sort: Default::default(),
+            reverse: Default::default(),
+            columns: Default::default(),
             table: Default::default(),
             #[cfg(feature = "account-sync")]
             cache: Default::default(),
@@ -76,54 +166,189 @@ impl ListEnvelopesCommand {
             self.cache.disable,
         )?;
 
-        let folder = &self.folder.name;
         let page = 1.max(self.page) - 1;
         let page_size = self
             .page_size
             .unwrap_or_else(|| account_config.get_envelope_list_page_size());
 
-        let list_envelopes_kind = toml_account_config.list_envelopes_kind();
-
-        let backend = Backend::new(
-            &toml_account_config,
-            &account_config,
-            list_envelopes_kind,
-            |builder| match list_envelopes_kind {
-                #[cfg(feature = "imap")]
-                Some(BackendKind::Imap) => {
-                    builder.set_list_envelopes(|ctx| {
-                        ctx.imap.as_ref().and_then(ListEnvelopesImap::new)
-                    });
-                }
-                #[cfg(feature = "maildir")]
-                Some(BackendKind::Maildir) => {
-                    builder.set_list_envelopes(|ctx| {
-                        ctx.maildir.as_ref().and_then(ListEnvelopesMaildir::new)
-                    });
-                }
-                #[cfg(feature = "account-sync")]
-                Some(BackendKind::MaildirForSync) => {
-                    builder.set_list_envelopes(|ctx| {
-                        ctx.maildir_for_sync
-                            .as_ref()
-                            .and_then(ListEnvelopesMaildir::new)
-                    });
-                }
-                _ => (),
+        let opts = SearchEnvelopesOptions {
+            query: self
+                .query
+                .as_deref()
+                .map(str::parse::<SearchEnvelopesQuery>)
+                .transpose()?,
+            sort: if self.sort.is_some() || self.reverse {
+                Some(SortEnvelopes::new(
+                    self.sort.unwrap_or_default(),
+                    self.reverse,
+                ))
+            } else {
+                None
             },
-        )
-        .await?;
+        };
+
+        let colors = toml_account_config
+            .list_envelopes_colors()
+            .cloned()
+            .unwrap_or_default();
+        let mut columns = if self.columns.is_empty() {
+            let columns = toml_account_config
+                .list_envelopes_columns()
+                .cloned()
+                .unwrap_or_default();
+            EnvelopeColumns::new(columns, colors)?
+        } else {
+            EnvelopeColumns::new(&self.columns, colors)?
+        };
+        let headers = columns.headers();
+
+        let folders = if self.folders.is_empty() {
+            vec![self.folder.name.clone()]
+        } else {
+            self.folders.clone()
+        };
+
+        // sources that cannot be listed are reported once the
+        // envelopes of the other ones are printed
+        let mut errors = Vec::new();
+
+        let envelopes = if !self.all_accounts && folders.len() == 1 {
+            let list_envelopes_kind = toml_account_config.list_envelopes_kind();
+            let backend = Backend::new(
+                &toml_account_config,
+                &account_config,
+                list_envelopes_kind,
+                |_| (),
+            )
+            .await?;
+
+            backend
+                .list_envelopes(
+                    &folders[0],
+                    &opts,
+                    page_size,
+                    page,
+                    self.after.as_ref(),
+                    &headers,
+                )
+                .await?
+        } else {
+            if self.after.is_some() {
+                bail!("cannot list envelopes: cursors require a single folder and account");
+            }
+
+            let account_names = if self.all_accounts {
+                let mut names: Vec<_> = config.accounts.keys().cloned().map(Some).collect();
+                names.sort();
+                names
+            } else {
+                vec![self.account.name.clone()]
+            };
+
+            // every source needs to provide enough envelopes to fill
+            // the requested page once merged
+            let limit = if page_size == 0 {
+                0
+            } else {
+                (page + 1) * page_size
+            };
+
+            let mut envelopes = Vec::new();
+            let mut total = Some(0);
+
+            for account_name in account_names {
+                let account_configs = config.clone().into_account_configs(
+                    account_name.as_deref(),
+                    #[cfg(feature = "account-sync")]
+                    self.cache.disable,
+                );
 
-        let envelopes = backend.list_envelopes(folder, page_size, page).await?;
+                let (toml_account_config, account_config) = match account_configs {
+                    Ok(account_configs) => account_configs,
+                    Err(err) => {
+                        let account = account_name.as_deref().unwrap_or("default account");
+                        debug!("cannot configure account {account}: {err:?}");
+                        errors.push(format!("{account}: {err}"));
+                        total = None;
+                        continue;
+                    }
+                };
+
+                let list_envelopes_kind = toml_account_config.list_envelopes_kind();
+                let backend = Backend::new(
+                    &toml_account_config,
+                    &account_config,
+                    list_envelopes_kind,
+                    |_| (),
+                )
+                .await;
+
+                let backend = match backend {
+                    Ok(backend) => backend,
+                    Err(err) => {
+                        let account = &account_config.name;
+                        debug!("cannot list envelopes of {account}: {err:?}");
+                        errors.push(format!("{account}: {err}"));
+                        total = None;
+                        continue;
+                    }
+                };
+
+                for folder in &folders {
+                    let account = &account_config.name;
+                    let items = backend
+                        .list_envelopes(folder, &opts, limit, 0, None, &headers)
+                        .await;
+
+                    match items {
+                        Ok(items) => {
+                            total = total.zip(items.total()).map(|(a, b)| a + b);
+                            envelopes.extend(items);
+                        }
+                        Err(err) => {
+                            debug!("cannot list envelopes of {account} from {folder}: {err:?}");
+                            errors.push(format!("{account} ({folder}): {err}"));
+                            total = None;
+                        }
+                    }
+                }
+            }
+
+            opts.sort
+                .unwrap_or_default()
+                .sort_by(envelopes.as_mut_slice(), |envelope| envelope.size);
+
+            if self.all_accounts {
+                columns.insert(EnvelopeColumn::Account);
+            }
+
+            if folders.len() > 1 {
+                columns.insert(EnvelopeColumn::Folder);
+            }
+
+            paginate(&envelopes, page_size, page)?
+                .iter()
+                .cloned()
+                .collect::<Envelopes>()
+                .with_pagination(Some(page), page_size, total, None)
+        };
 
         printer.print_table(
-            Box::new(envelopes),
+            Box::new(envelopes.with_columns(columns)),
             PrintTableOpts {
                 format: &account_config.get_message_read_format(),
                 max_width: self.table.max_width,
             },
         )?;
 
+        if !errors.is_empty() {
+            bail!(
+                "cannot list envelopes of {} source(s):\n{}",
+                errors.len(),
+                errors.join("\n")
+            );
+        }
+
         Ok(())
     }
 }
@@ -1,5 +1,9 @@
+#[cfg(feature = "envelope-get")]
+pub mod get;
 #[cfg(feature = "envelope-list")]
 pub mod list;
+#[cfg(feature = "envelope-thread")]
+pub mod thread;
 #[cfg(feature = "envelope-watch")]
 pub mod watch;
 
@@ -8,8 +12,12 @@ use clap::Subcommand;
 
 use crate::{config::TomlConfig, printer::Printer};
 
+#[cfg(feature = "envelope-get")]
+use self::get::GetEnvelopeCommand;
 #[cfg(feature = "envelope-list")]
 use self::list::ListEnvelopesCommand;
+#[cfg(feature = "envelope-thread")]
+use self::thread::ThreadEnvelopesCommand;
 #[cfg(feature = "envelope-watch")]
 use self::watch::WatchEnvelopesCommand;
 
@@ -25,6 +33,14 @@ pub enum EnvelopeSubcommand {
     #[command(alias = "lst")]
     List(ListEnvelopesCommand),
 
+    #[cfg(feature = "envelope-thread")]
+    #[command()]
+    Thread(ThreadEnvelopesCommand),
+
+    #[cfg(feature = "envelope-get")]
+    #[command()]
+    Get(GetEnvelopeCommand),
+
     #[cfg(feature = "envelope-watch")]
     #[command()]
     Watch(WatchEnvelopesCommand),
@@ -36,6 +52,10 @@ impl EnvelopeSubcommand {
         match self {
             #[cfg(feature = "envelope-list")]
             Self::List(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "envelope-thread")]
+            Self::Thread(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "envelope-get")]
+            Self::Get(cmd) => cmd.execute(printer, config).await,
             #[cfg(feature = "envelope-watch")]
             Self::Watch(cmd) => cmd.execute(printer, config).await,
         }
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use clap::Parser;
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::Backend,
+    config::TomlConfig,
+    folder::arg::name::FolderNameOptionalArg,
+    printer::{PrintTableOpts, Printer},
+    ui::arg::max_width::TableMaxWidthFlag,
+};
+
+/// Thread all envelopes.
+///
+/// This command allows you to list all envelopes included in the
+/// given folder, grouped by conversation. Replies are displayed
+/// below the message they answer to, and the most recently active
+/// conversation comes first.
+#[derive(Debug, Parser)]
+pub struct ThreadEnvelopesCommand {
+    #[command(flatten)]
+    pub folder: FolderNameOptionalArg,
+
+    #[command(flatten)]
+    pub table: TableMaxWidthFlag,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl ThreadEnvelopesCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing thread envelopes command");
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let folder = &self.folder.name;
+        let list_envelopes_kind = toml_account_config.list_envelopes_kind();
+
+        let backend = Backend::new(
+            &toml_account_config,
+            &account_config,
+            list_envelopes_kind,
+            |_| (),
+        )
+        .await?;
+
+        let threads = backend.thread_envelopes(folder).await?;
+
+        printer.print_table(
+            Box::new(threads),
+            PrintTableOpts {
+                format: &account_config.get_message_read_format(),
+                max_width: self.table.max_width,
+            },
+        )?;
+
+        Ok(())
+    }
+}
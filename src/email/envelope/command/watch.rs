@@ -4,12 +4,12 @@ use clap::Parser;
 use email::envelope::watch::imap::WatchImapEnvelopes;
 #[cfg(feature = "maildir")]
 use email::envelope::watch::maildir::WatchMaildirEnvelopes;
-#[cfg(feature = "notmuch")]
-use email::envelope::watch::notmuch::WatchNotmuchEnvelopes;
 use log::info;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::WatchNotmuchEnvelopes;
 use crate::{
     account::arg::name::AccountNameFlag,
     backend::{Backend, BackendKind},
@@ -73,6 +73,12 @@ impl WatchEnvelopesCommand {
                             .and_then(WatchMaildirEnvelopes::new)
                     });
                 }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_watch_envelopes(|ctx| {
+                        ctx.notmuch.as_ref().and_then(WatchNotmuchEnvelopes::new)
+                    });
+                }
                 _ => (),
             },
         )
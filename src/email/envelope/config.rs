@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "envelope-list")]
+use std::collections::HashMap;
 use std::collections::HashSet;
 
 use crate::backend::BackendKind;
@@ -42,6 +44,13 @@ impl EnvelopeConfig {
 pub struct ListEnvelopesConfig {
     pub backend: Option<BackendKind>,
 
+    /// The columns of the envelope listing table, in order.
+    pub columns: Option<Vec<String>>,
+
+    /// The colour of the envelope listing table columns, indexed by
+    /// column name.
+    pub colors: Option<HashMap<String, String>>,
+
     #[serde(flatten)]
     pub remote: email::envelope::list::config::EnvelopeListConfig,
 }
@@ -0,0 +1,73 @@
+use anyhow::{anyhow, Result};
+use email::{imap::ImapSessionSync, message::Message};
+use log::{debug, info};
+use std::collections::HashMap;
+use utf7_imap::encode_utf7_imap as encode_utf7;
+
+use super::EnvelopeDetails;
+
+/// The headers needed to retrieve envelope details, which are not
+/// covered by the lib envelope.
+const ENVELOPE_DETAILS_HEADERS: [&str; 4] = ["TO", "CC", "IN-REPLY-TO", "CONTENT-TYPE"];
+
+/// Build the IMAP query needed to retrieve envelope details: UID,
+/// size, details headers and the given additional headers.
+fn envelope_details_query(headers: &[String]) -> String {
+    let headers = ENVELOPE_DETAILS_HEADERS
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .chain(headers.iter().map(|header| header.to_uppercase()))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!("(UID RFC822.SIZE BODY.PEEK[HEADER.FIELDS ({headers})])")
+}
+
+/// Get the details of the given IMAP envelopes, indexed by UID.
+pub async fn get_envelopes_details(
+    session: &ImapSessionSync,
+    folder: &str,
+    uids: &[String],
+    headers: &[String],
+) -> Result<HashMap<String, EnvelopeDetails>> {
+    info!("getting details of imap envelopes from folder {folder}");
+
+    if uids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut session = session.lock().await;
+
+    let folder = session.account_config.get_folder_alias(folder);
+    let folder_encoded = encode_utf7(folder.clone());
+    debug!("utf7 encoded folder: {folder_encoded}");
+
+    session
+        .execute(
+            |session| session.select(&folder_encoded),
+            |err| anyhow!(err).context(format!("cannot select imap folder {folder}")),
+        )
+        .await?;
+
+    let uids = uids.join(",");
+    let query = envelope_details_query(headers);
+    let fetches = session
+        .execute(
+            |session| session.uid_fetch(&uids, &query),
+            |err| anyhow!(err).context(format!("cannot fetch imap envelopes {uids}")),
+        )
+        .await?;
+
+    let details: HashMap<String, EnvelopeDetails> = fetches
+        .iter()
+        .filter_map(|fetch| {
+            let uid = fetch.uid?.to_string();
+            let msg = Message::from(fetch.header()?);
+            let size = fetch.size.unwrap_or_default() as u64;
+            Some((uid, EnvelopeDetails::from_msg(&msg, size, headers)))
+        })
+        .collect();
+    debug!("imap envelopes details: {details:#?}");
+
+    Ok(details)
+}
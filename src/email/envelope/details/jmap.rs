@@ -0,0 +1,86 @@
+use anyhow::Result;
+use log::{debug, info};
+use std::collections::HashMap;
+
+use crate::{
+    envelope::Mailbox,
+    jmap::{JmapAddress, JmapSession},
+};
+
+use super::EnvelopeDetails;
+
+/// The email properties needed to retrieve envelope details, which
+/// are not covered by the lib envelope.
+const ENVELOPE_DETAILS_PROPERTIES: [&str; 6] =
+    ["id", "to", "cc", "inReplyTo", "size", "hasAttachment"];
+
+/// Get the details of the given JMAP envelopes, indexed by id.
+///
+/// Additional headers are requested as `header:{name}:asText`
+/// properties, so they are decoded by the server.
+pub async fn get_envelopes_details(
+    session: &JmapSession,
+    ids: &[String],
+    headers: &[String],
+) -> Result<HashMap<String, EnvelopeDetails>> {
+    info!("getting details of JMAP envelopes");
+
+    let header_properties: Vec<String> = headers
+        .iter()
+        .map(|header| format!("header:{header}:asText"))
+        .collect();
+    let properties: Vec<&str> = ENVELOPE_DETAILS_PROPERTIES
+        .into_iter()
+        .chain(header_properties.iter().map(String::as_str))
+        .collect();
+
+    let emails = session
+        .get_emails(ids.iter().map(String::as_str), &properties)
+        .await?;
+
+    let mailboxes = |addrs: &Option<Vec<JmapAddress>>| -> Vec<Mailbox> {
+        addrs
+            .iter()
+            .flatten()
+            .map(|addr| {
+                let addr = addr.to_address();
+                Mailbox {
+                    name: addr.name,
+                    addr: addr.addr,
+                }
+            })
+            .collect()
+    };
+
+    let details: HashMap<String, EnvelopeDetails> = emails
+        .into_iter()
+        .map(|email| {
+            let headers = headers
+                .iter()
+                .zip(&header_properties)
+                .filter_map(|(header, property)| {
+                    let value = email.properties.get(property)?.as_str()?;
+                    Some((header.to_lowercase(), value.trim().to_owned()))
+                })
+                .collect();
+
+            let details = EnvelopeDetails {
+                to: mailboxes(&email.to),
+                cc: mailboxes(&email.cc),
+                in_reply_to: email
+                    .in_reply_to
+                    .as_ref()
+                    .and_then(|ids| ids.first())
+                    .map(|id| format!("<{id}>")),
+                size: email.size,
+                has_attachment: email.has_attachment,
+                headers,
+            };
+
+            (email.id, details)
+        })
+        .collect();
+    debug!("JMAP envelopes details: {details:#?}");
+
+    Ok(details)
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use email::{maildir::MaildirSessionSync, message::Message};
+use log::{debug, info};
+use std::{collections::HashMap, fs};
+
+use super::EnvelopeDetails;
+
+/// Get the details of the given Maildir envelopes, indexed by id.
+pub async fn get_envelopes_details(
+    session: &MaildirSessionSync,
+    folder: &str,
+    ids: &[String],
+    headers: &[String],
+) -> Result<HashMap<String, EnvelopeDetails>> {
+    info!("getting details of maildir envelopes from folder {folder}");
+
+    let session = session.lock().await;
+    let mdir = session.get_maildir_from_folder_name(folder)?;
+
+    let details: HashMap<String, EnvelopeDetails> = ids
+        .iter()
+        .filter_map(|id| {
+            let entry = mdir.find(id)?;
+            let bytes = match fs::read(entry.path()) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    debug!("cannot read maildir entry {id}, skipping it: {err}");
+                    debug!("{err:?}");
+                    return None;
+                }
+            };
+            let size = bytes.len() as u64;
+            let details = EnvelopeDetails::from_msg(&Message::from(bytes), size, headers);
+            Some((id.clone(), details))
+        })
+        .collect();
+    debug!("maildir envelopes details: {details:#?}");
+
+    Ok(details)
+}
@@ -0,0 +1,35 @@
+use anyhow::Result;
+use email::message::Message;
+use log::{debug, info};
+use std::collections::HashMap;
+
+use crate::mbox::MboxSession;
+
+use super::EnvelopeDetails;
+
+/// Get the details of the given mbox envelopes, indexed by id.
+pub async fn get_envelopes_details(
+    session: &MboxSession,
+    folder: &str,
+    ids: &[String],
+    headers: &[String],
+) -> Result<HashMap<String, EnvelopeDetails>> {
+    info!("getting details of mbox envelopes from folder {folder}");
+
+    let mbox = session.read_folder(folder)?;
+
+    let details: HashMap<String, EnvelopeDetails> = mbox
+        .messages
+        .iter()
+        .filter(|msg| ids.contains(&msg.id))
+        .map(|msg| {
+            let bytes = msg.to_raw_msg();
+            let size = bytes.len() as u64;
+            let details = EnvelopeDetails::from_msg(&Message::from(bytes), size, headers);
+            (msg.id.clone(), details)
+        })
+        .collect();
+    debug!("mbox envelopes details: {details:#?}");
+
+    Ok(details)
+}
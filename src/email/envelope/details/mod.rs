@@ -0,0 +1,118 @@
+//! Module dedicated to envelope details.
+//!
+//! Envelopes built by the lib only contain the first sender and
+//! recipient, the subject, the date and the Message-ID. Details
+//! contain the other headers, read directly from the backend.
+
+#[cfg(feature = "imap")]
+pub mod imap;
+#[cfg(feature = "jmap")]
+pub mod jmap;
+#[cfg(feature = "maildir")]
+pub mod maildir;
+#[cfg(feature = "mbox")]
+pub mod mbox;
+#[cfg(feature = "notmuch")]
+pub mod notmuch;
+
+use email::message::Message;
+use mail_parser::{HeaderValue, MimeHeaders};
+use std::collections::HashMap;
+
+use super::Mailbox;
+
+/// The envelope information not covered by the lib envelope.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EnvelopeDetails {
+    /// All the recipients.
+    pub to: Vec<Mailbox>,
+
+    /// All the carbon copy recipients.
+    pub cc: Vec<Mailbox>,
+
+    /// The Message-ID of the message this one replies to, with
+    /// angle brackets.
+    pub in_reply_to: Option<String>,
+
+    /// The size of the whole message, in bytes.
+    pub size: u64,
+
+    /// Whether the message has attachments.
+    ///
+    /// When only headers are available, messages with a
+    /// `multipart/mixed` content type are considered as having
+    /// attachments.
+    pub has_attachment: bool,
+
+    /// Additional headers requested by the user, indexed by their
+    /// lowercased name.
+    pub headers: HashMap<String, String>,
+}
+
+impl EnvelopeDetails {
+    /// Build details from the message headers and the size of the
+    /// whole message. The given additional headers are extracted as
+    /// well.
+    pub fn from_msg(msg: &Message, size: u64, headers: &[String]) -> Self {
+        let mut details = Self {
+            size,
+            ..Default::default()
+        };
+
+        if let Ok(msg) = msg.parsed() {
+            details.to = mailboxes(msg.to());
+            details.cc = mailboxes(msg.cc());
+            details.in_reply_to = msg.in_reply_to().as_text().map(|id| format!("<{id}>"));
+            details.has_attachment =
+                msg.attachment_count() > 0 || msg.is_content_type("multipart", "mixed");
+            details.headers = headers
+                .iter()
+                .filter_map(|name| {
+                    let value = header_value(msg, name)?;
+                    Some((name.to_lowercase(), value))
+                })
+                .collect();
+        }
+
+        details
+    }
+}
+
+/// Format the value of the given header, decoded when the parser
+/// understands it, raw otherwise.
+fn header_value(msg: &mail_parser::Message, name: &str) -> Option<String> {
+    let value = match msg.header(name)? {
+        HeaderValue::Text(text) => text.to_string(),
+        HeaderValue::TextList(texts) => texts.join(", "),
+        HeaderValue::DateTime(date) => date.to_rfc3339(),
+        HeaderValue::Address(addr) => mailboxes(Some(addr))
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => msg.header_raw(name)?.trim().to_owned(),
+    };
+
+    Some(value)
+}
+
+fn mailboxes(addr: Option<&mail_parser::Address>) -> Vec<Mailbox> {
+    let addrs: Vec<&mail_parser::Addr> = match addr {
+        Some(mail_parser::Address::List(addrs)) => addrs.iter().collect(),
+        Some(mail_parser::Address::Group(groups)) => groups
+            .iter()
+            .flat_map(|group| group.addresses.iter())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    addrs
+        .into_iter()
+        .filter_map(|addr| {
+            Some(Mailbox {
+                name: addr.name.as_ref().map(ToString::to_string),
+                addr: addr.address.as_ref()?.to_string(),
+            })
+        })
+        .collect()
+}
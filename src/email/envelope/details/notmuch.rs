@@ -0,0 +1,49 @@
+use anyhow::Result;
+use email::message::Message;
+use log::{debug, info};
+use std::{collections::HashMap, fs};
+
+use crate::notmuch::NotmuchSession;
+
+use super::EnvelopeDetails;
+
+/// Get the details of the given Notmuch envelopes, indexed by id.
+pub async fn get_envelopes_details(
+    session: &NotmuchSession,
+    ids: &[String],
+    headers: &[String],
+) -> Result<HashMap<String, EnvelopeDetails>> {
+    info!("getting details of notmuch envelopes");
+
+    let db = session.open_db()?;
+
+    let details: HashMap<String, EnvelopeDetails> = ids
+        .iter()
+        .filter_map(|id| {
+            let path = match db.find_message(id) {
+                Ok(Some(msg)) => msg.filename(),
+                Ok(None) => return None,
+                Err(err) => {
+                    debug!("cannot find notmuch message {id}, skipping it: {err}");
+                    debug!("{err:?}");
+                    return None;
+                }
+            };
+            let bytes = match fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    debug!("cannot read notmuch message {id}, skipping it: {err}");
+                    debug!("{err:?}");
+                    return None;
+                }
+            };
+            let size = bytes.len() as u64;
+            let details = EnvelopeDetails::from_msg(&Message::from(bytes), size, headers);
+            Some((id.clone(), details))
+        })
+        .collect();
+    debug!("notmuch envelopes details: {details:#?}");
+
+    db.close()?;
+    Ok(details)
+}
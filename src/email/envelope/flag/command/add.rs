@@ -4,12 +4,16 @@ use clap::Parser;
 use email::flag::add::imap::AddFlagsImap;
 #[cfg(feature = "maildir")]
 use email::flag::add::maildir::AddFlagsMaildir;
-#[cfg(feature = "notmuch")]
-use email::flag::add::notmuch::AddFlagsNotmuch;
 use log::info;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::AddFlagsJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::AddFlagsMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::AddFlagsNotmuch;
 use crate::{
     account::arg::name::AccountNameFlag,
     backend::{Backend, BackendKind},
@@ -62,6 +66,10 @@ impl FlagAddCommand {
                 Some(BackendKind::Imap) => {
                     builder.set_add_flags(|ctx| ctx.imap.as_ref().and_then(AddFlagsImap::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder.set_add_flags(|ctx| ctx.jmap.as_ref().and_then(AddFlagsJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder
@@ -73,6 +81,15 @@ impl FlagAddCommand {
                         ctx.maildir_for_sync.as_ref().and_then(AddFlagsMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder.set_add_flags(|ctx| ctx.mbox.as_ref().and_then(AddFlagsMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder
+                        .set_add_flags(|ctx| ctx.notmuch.as_ref().and_then(AddFlagsNotmuch::new));
+                }
                 _ => (),
             },
         )
@@ -4,12 +4,16 @@ use clap::Parser;
 use email::flag::remove::imap::RemoveFlagsImap;
 #[cfg(feature = "maildir")]
 use email::flag::remove::maildir::RemoveFlagsMaildir;
-#[cfg(feature = "notmuch")]
-use email::flag::remove::notmuch::RemoveFlagsNotmuch;
 use log::info;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::RemoveFlagsJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::RemoveFlagsMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::RemoveFlagsNotmuch;
 use crate::{
     account::arg::name::AccountNameFlag,
     backend::{Backend, BackendKind},
@@ -63,6 +67,11 @@ impl FlagRemoveCommand {
                     builder
                         .set_remove_flags(|ctx| ctx.imap.as_ref().and_then(RemoveFlagsImap::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_remove_flags(|ctx| ctx.jmap.as_ref().and_then(RemoveFlagsJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder.set_remove_flags(|ctx| {
@@ -77,6 +86,17 @@ impl FlagRemoveCommand {
                             .and_then(RemoveFlagsMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_remove_flags(|ctx| ctx.mbox.as_ref().and_then(RemoveFlagsMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_remove_flags(|ctx| {
+                        ctx.notmuch.as_ref().and_then(RemoveFlagsNotmuch::new)
+                    });
+                }
                 _ => (),
             },
         )
@@ -4,12 +4,16 @@ use clap::Parser;
 use email::flag::set::imap::SetFlagsImap;
 #[cfg(feature = "maildir")]
 use email::flag::set::maildir::SetFlagsMaildir;
-#[cfg(feature = "notmuch")]
-use email::flag::set::notmuch::SetFlagsNotmuch;
 use log::info;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::SetFlagsJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::SetFlagsMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::SetFlagsNotmuch;
 use crate::{
     account::arg::name::AccountNameFlag,
     backend::{Backend, BackendKind},
@@ -62,6 +66,10 @@ impl FlagSetCommand {
                 Some(BackendKind::Imap) => {
                     builder.set_set_flags(|ctx| ctx.imap.as_ref().and_then(SetFlagsImap::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder.set_set_flags(|ctx| ctx.jmap.as_ref().and_then(SetFlagsJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder
@@ -73,6 +81,15 @@ impl FlagSetCommand {
                         ctx.maildir_for_sync.as_ref().and_then(SetFlagsMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder.set_set_flags(|ctx| ctx.mbox.as_ref().and_then(SetFlagsMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder
+                        .set_set_flags(|ctx| ctx.notmuch.as_ref().and_then(SetFlagsNotmuch::new));
+                }
                 _ => (),
             },
         )
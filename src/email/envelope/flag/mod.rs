@@ -30,6 +30,20 @@ impl From<&email::flag::Flag> for Flag {
     }
 }
 
+impl From<&Flag> for email::flag::Flag {
+    fn from(flag: &Flag) -> Self {
+        use email::flag::Flag::*;
+        match flag {
+            Flag::Seen => Seen,
+            Flag::Answered => Answered,
+            Flag::Flagged => Flagged,
+            Flag::Deleted => Deleted,
+            Flag::Draft => Draft,
+            Flag::Custom(flag) => Custom(flag.clone()),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
 pub struct Flags(pub HashSet<Flag>);
 
@@ -46,3 +60,9 @@ impl From<email::flag::Flags> for Flags {
         Flags(flags.iter().map(Flag::from).collect())
     }
 }
+
+impl From<&Flags> for email::flag::Flags {
+    fn from(flags: &Flags) -> Self {
+        email::flag::Flags::from_iter(flags.iter().map(email::flag::Flag::from))
+    }
+}
@@ -1,115 +1,270 @@
 pub mod arg;
+#[cfg(feature = "envelope-list")]
+pub mod column;
 pub mod command;
 pub mod config;
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
+pub mod details;
 pub mod flag;
-
 #[cfg(feature = "envelope-list")]
+pub mod search;
+#[cfg(feature = "envelope-thread")]
+pub mod thread;
+
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
 use anyhow::Result;
-#[cfg(feature = "envelope-list")]
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
 use email::account::config::AccountConfig;
 use serde::Serialize;
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
+use std::collections::HashMap;
+use std::fmt;
 #[cfg(feature = "envelope-list")]
 use std::ops;
 
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
+use self::details::EnvelopeDetails;
+#[cfg(feature = "envelope-list")]
+use self::{column::EnvelopeColumns, search::EnvelopesCursor};
 use crate::flag::Flags;
 #[cfg(feature = "envelope-list")]
+use crate::ui;
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
 use crate::{
     cache::IdMapper,
-    flag::Flag,
     printer::{PrintTable, PrintTableOpts, WriteColor},
-    ui::{Cell, Row, Table},
+    ui::{Row, Table},
 };
+#[cfg(feature = "envelope-get")]
+use crate::{flag::Flag, ui::Cell};
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
 pub struct Mailbox {
     pub name: Option<String>,
     pub addr: String,
 }
 
+impl fmt::Display for Mailbox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name} <{}>", self.addr),
+            None => write!(f, "{}", self.addr),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize)]
 pub struct Envelope {
     pub id: String,
+    pub message_id: String,
     pub flags: Flags,
     pub subject: String,
     pub from: Mailbox,
+    pub to: Vec<Mailbox>,
+    pub cc: Vec<Mailbox>,
     pub date: String,
+    pub size: u64,
+    pub has_attachment: bool,
+    pub account: String,
+    pub folder: String,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    #[serde(skip)]
+    pub timestamp: i64,
+}
+
+/// Return the Message-ID of the given lib envelope, or `None` if the
+/// message does not have one.
+///
+/// The lib envelope always has a Message-ID: when the header is
+/// missing, it falls back to the date of the message (or to an empty
+/// string for JMAP), which cannot identify the message.
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
+pub fn message_id(envelope: &email::envelope::Envelope) -> Option<&str> {
+    let message_id = envelope.message_id.as_str();
+
+    if message_id.is_empty() || message_id == envelope.date.to_rfc3339() {
+        None
+    } else {
+        Some(message_id)
+    }
+}
+
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
+impl Envelope {
+    pub fn from_backend(
+        config: &AccountConfig,
+        id_mapper: &IdMapper,
+        folder: &str,
+        envelope: &email::envelope::Envelope,
+        details: &EnvelopeDetails,
+    ) -> Result<Envelope> {
+        let alias = id_mapper.get_or_create_message_alias(&envelope.id, message_id(envelope))?;
+        Ok(Self::from_backend_with_alias(
+            config, alias, folder, envelope, details,
+        ))
+    }
+
+    /// Same as [`Envelope::from_backend`], with the alias of the
+    /// envelope already known.
+    pub fn from_backend_with_alias(
+        config: &AccountConfig,
+        alias: String,
+        folder: &str,
+        envelope: &email::envelope::Envelope,
+        details: &EnvelopeDetails,
+    ) -> Envelope {
+        let mut to = details.to.clone();
+
+        // the lib envelope already contains the first recipient,
+        // which is used when details are not available
+        if to.is_empty() && !envelope.to.addr.is_empty() {
+            to.push(Mailbox {
+                name: envelope.to.name.clone(),
+                addr: envelope.to.addr.clone(),
+            });
+        }
+
+        Envelope {
+            id: alias,
+            message_id: envelope.message_id.clone(),
+            flags: envelope.flags.clone().into(),
+            subject: envelope.subject.clone(),
+            from: Mailbox {
+                name: envelope.from.name.clone(),
+                addr: envelope.from.addr.clone(),
+            },
+            to,
+            cc: details.cc.clone(),
+            date: envelope.format_date(config),
+            size: details.size,
+            has_attachment: details.has_attachment,
+            account: config.name.clone(),
+            folder: folder.to_owned(),
+            headers: details.headers.clone(),
+            timestamp: envelope.date.timestamp(),
+        }
+    }
 }
 
 #[cfg(feature = "envelope-list")]
 impl Table for Envelope {
     fn head() -> Row {
-        Row::new()
-            .cell(Cell::new("ID").bold().underline().white())
-            .cell(Cell::new("FLAGS").bold().underline().white())
-            .cell(Cell::new("SUBJECT").shrinkable().bold().underline().white())
-            .cell(Cell::new("FROM").bold().underline().white())
-            .cell(Cell::new("DATE").bold().underline().white())
+        EnvelopeColumns::default().head()
     }
 
     fn row(&self) -> Row {
-        let id = self.id.to_string();
-        let unseen = !self.flags.contains(&Flag::Seen);
-        let flags = {
-            let mut flags = String::new();
-            flags.push_str(if !unseen { " " } else { "✷" });
-            flags.push_str(if self.flags.contains(&Flag::Answered) {
-                "↵"
-            } else {
-                " "
-            });
-            flags.push_str(if self.flags.contains(&Flag::Flagged) {
-                "⚑"
-            } else {
-                " "
-            });
-            flags
-        };
-        let subject = &self.subject;
-        let sender = if let Some(name) = &self.from.name {
-            name
-        } else {
-            &self.from.addr
-        };
-        let date = &self.date;
-
-        Row::new()
-            .cell(Cell::new(id).bold_if(unseen).red())
-            .cell(Cell::new(flags).bold_if(unseen).white())
-            .cell(Cell::new(subject).shrinkable().bold_if(unseen).green())
-            .cell(Cell::new(sender).bold_if(unseen).blue())
-            .cell(Cell::new(date).bold_if(unseen).yellow())
+        EnvelopeColumns::default().row(self)
     }
 }
 
 #[cfg(feature = "envelope-list")]
-/// Represents the list of envelopes.
+/// Represents the list of envelopes, with pagination metadata.
 #[derive(Clone, Debug, Default, Serialize)]
-pub struct Envelopes(Vec<Envelope>);
+pub struct Envelopes {
+    envelopes: Vec<Envelope>,
+
+    /// The page number, starting from 1. Not defined when listing
+    /// envelopes after a cursor.
+    page: Option<usize>,
+    page_size: usize,
+
+    /// The amount of pages, when the total is known.
+    pages: Option<usize>,
+
+    /// The total amount of envelopes, all pages included.
+    total: Option<usize>,
+
+    /// The cursor to give to `--after` in order to get the next
+    /// page, if any.
+    next: Option<String>,
+
+    #[serde(skip)]
+    columns: EnvelopeColumns,
+}
 
 #[cfg(feature = "envelope-list")]
 impl Envelopes {
     pub fn from_backend(
         config: &AccountConfig,
         id_mapper: &IdMapper,
+        folder: &str,
         envelopes: email::envelope::Envelopes,
+        details: &HashMap<String, EnvelopeDetails>,
     ) -> Result<Envelopes> {
+        let mut aliases = id_mapper.get_or_create_message_aliases(
+            envelopes
+                .iter()
+                .map(|envelope| (envelope.id.as_str(), message_id(envelope))),
+        )?;
+
         let envelopes = envelopes
             .iter()
             .map(|envelope| {
-                Ok(Envelope {
-                    id: id_mapper.get_or_create_alias(&envelope.id)?,
-                    flags: envelope.flags.clone().into(),
-                    subject: envelope.subject.clone(),
-                    from: Mailbox {
-                        name: envelope.from.name.clone(),
-                        addr: envelope.from.addr.clone(),
-                    },
-                    date: envelope.format_date(config),
-                })
+                let alias = aliases.remove(&envelope.id).unwrap_or_default();
+                let details = details.get(&envelope.id).cloned().unwrap_or_default();
+                Envelope::from_backend_with_alias(config, alias, folder, envelope, &details)
             })
-            .collect::<Result<Vec<_>>>()?;
+            .collect();
+
+        Ok(Envelopes {
+            envelopes,
+            ..Default::default()
+        })
+    }
+
+    /// Set the pagination metadata. The page number starts from 0.
+    pub fn with_pagination(
+        mut self,
+        page: Option<usize>,
+        page_size: usize,
+        total: Option<usize>,
+        next: Option<EnvelopesCursor>,
+    ) -> Self {
+        self.page = page.map(|page| page + 1);
+        self.page_size = page_size;
+        self.pages = total.map(|total| {
+            if page_size == 0 {
+                1
+            } else {
+                total.div_ceil(page_size).max(1)
+            }
+        });
+        self.total = total;
+        self.next = next.map(|cursor| cursor.to_string());
+        self
+    }
+
+    /// Return the total amount of envelopes, all pages included.
+    pub fn total(&self) -> Option<usize> {
+        self.total
+    }
 
-        Ok(Envelopes(envelopes))
+    /// Set the columns used to print the envelopes as a table.
+    pub fn with_columns(mut self, columns: EnvelopeColumns) -> Self {
+        self.columns = columns;
+        self
+    }
+}
+
+/// Collect envelopes coming from different folders or accounts.
+#[cfg(feature = "envelope-list")]
+impl FromIterator<Envelope> for Envelopes {
+    fn from_iter<T: IntoIterator<Item = Envelope>>(iter: T) -> Self {
+        Envelopes {
+            envelopes: iter.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "envelope-list")]
+impl IntoIterator for Envelopes {
+    type Item = Envelope;
+    type IntoIter = std::vec::IntoIter<Envelope>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.envelopes.into_iter()
     }
 }
 
@@ -118,15 +273,132 @@ impl ops::Deref for Envelopes {
     type Target = Vec<Envelope>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.envelopes
     }
 }
 
 #[cfg(feature = "envelope-list")]
 impl PrintTable for Envelopes {
     fn print_table(&self, writer: &mut dyn WriteColor, opts: PrintTableOpts) -> Result<()> {
+        let head = self.columns.head();
+        let rows = self.iter().map(|envelope| self.columns.row(envelope));
+
+        writeln!(writer)?;
+        ui::print_rows(writer, head, rows, opts)?;
+        writeln!(writer)?;
+
+        let mut footer = Vec::new();
+
+        if let (Some(page), Some(pages)) = (self.page, self.pages) {
+            footer.push(format!("Page {page}/{pages}"));
+        }
+
+        if let Some(total) = self.total {
+            footer.push(format!("{total} envelope(s)"));
+        }
+
+        if let Some(next) = &self.next {
+            footer.push(format!("next page: --after {next}"));
+        }
+
+        if !footer.is_empty() {
+            writeln!(writer, "{}", footer.join(", "))?;
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents a single envelope with all its parsed headers.
+#[cfg(feature = "envelope-get")]
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct FullEnvelope {
+    #[serde(flatten)]
+    pub envelope: Envelope,
+    pub in_reply_to: Option<String>,
+}
+
+#[cfg(feature = "envelope-get")]
+impl FullEnvelope {
+    pub fn from_backend(
+        config: &AccountConfig,
+        id_mapper: &IdMapper,
+        folder: &str,
+        envelope: &email::envelope::Envelope,
+        details: EnvelopeDetails,
+    ) -> Result<FullEnvelope> {
+        Ok(FullEnvelope {
+            envelope: Envelope::from_backend(config, id_mapper, folder, envelope, &details)?,
+            in_reply_to: details.in_reply_to,
+        })
+    }
+}
+
+/// Represents a field of the [`FullEnvelope`] key/value table.
+#[cfg(feature = "envelope-get")]
+struct EnvelopeField(&'static str, String);
+
+#[cfg(feature = "envelope-get")]
+impl Table for EnvelopeField {
+    fn head() -> Row {
+        Row::new()
+            .cell(Cell::new("FIELD").bold().underline().white())
+            .cell(Cell::new("VALUE").shrinkable().bold().underline().white())
+    }
+
+    fn row(&self) -> Row {
+        Row::new()
+            .cell(Cell::new(self.0).bold().blue())
+            .cell(Cell::new(&self.1).shrinkable())
+    }
+}
+
+#[cfg(feature = "envelope-get")]
+impl PrintTable for FullEnvelope {
+    fn print_table(&self, writer: &mut dyn WriteColor, opts: PrintTableOpts) -> Result<()> {
+        let join = |mailboxes: &[Mailbox]| {
+            mailboxes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let envelope = &self.envelope;
+        let mut flags = envelope
+            .flags
+            .iter()
+            .map(|flag| match flag {
+                Flag::Seen => String::from("seen"),
+                Flag::Answered => String::from("answered"),
+                Flag::Flagged => String::from("flagged"),
+                Flag::Deleted => String::from("deleted"),
+                Flag::Draft => String::from("draft"),
+                Flag::Custom(flag) => flag.clone(),
+            })
+            .collect::<Vec<_>>();
+        flags.sort();
+
+        let fields = [
+            EnvelopeField("ID", envelope.id.clone()),
+            EnvelopeField("FLAGS", flags.join(", ")),
+            EnvelopeField("SUBJECT", envelope.subject.clone()),
+            EnvelopeField("FROM", envelope.from.to_string()),
+            EnvelopeField("TO", join(&envelope.to)),
+            EnvelopeField("CC", join(&envelope.cc)),
+            EnvelopeField("DATE", envelope.date.clone()),
+            EnvelopeField("MESSAGE-ID", envelope.message_id.clone()),
+            EnvelopeField("IN-REPLY-TO", self.in_reply_to.clone().unwrap_or_default()),
+            EnvelopeField("SIZE", envelope.size.to_string()),
+            EnvelopeField(
+                "ATTACHMENT",
+                String::from(if envelope.has_attachment { "yes" } else { "no" }),
+            ),
+        ];
+
         writeln!(writer)?;
-        Table::print(writer, self, opts)?;
+        Table::print(writer, &fields, opts)?;
         writeln!(writer)?;
         Ok(())
     }
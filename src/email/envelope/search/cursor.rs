@@ -0,0 +1,68 @@
+use anyhow::{bail, Context, Error, Result};
+use std::{fmt, str::FromStr};
+
+/// An opaque cursor pointing to an envelope.
+///
+/// Cursors are used to list the envelopes following a given one,
+/// which is more reliable than page numbers when the folder changes
+/// between two listings. A cursor wraps the backend id of the
+/// envelope, hex-encoded so that users do not rely on its content.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnvelopesCursor(String);
+
+impl EnvelopesCursor {
+    pub fn new(id: impl ToString) -> Self {
+        Self(id.to_string())
+    }
+
+    /// Return the backend id of the envelope the cursor points to.
+    pub fn id(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for EnvelopesCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0.bytes() {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for EnvelopesCursor {
+    type Err = Error;
+
+    fn from_str(cursor: &str) -> Result<Self> {
+        if cursor.is_empty() || !cursor.len().is_multiple_of(2) || !cursor.is_ascii() {
+            bail!("cannot parse envelopes cursor {cursor}: invalid length");
+        }
+
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&cursor[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("cannot parse envelopes cursor {cursor}"))?;
+
+        let id = String::from_utf8(bytes)
+            .with_context(|| format!("cannot parse envelopes cursor {cursor}"))?;
+
+        Ok(Self(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EnvelopesCursor;
+
+    #[test]
+    fn round_trip() {
+        let cursor = EnvelopesCursor::new("1706270195.R2.host:2,S");
+        let encoded = cursor.to_string();
+
+        assert!(encoded.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(encoded.parse::<EnvelopesCursor>().unwrap(), cursor);
+        assert!("zz".parse::<EnvelopesCursor>().is_err());
+        assert!("abc".parse::<EnvelopesCursor>().is_err());
+    }
+}
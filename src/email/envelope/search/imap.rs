@@ -0,0 +1,257 @@
+use anyhow::{anyhow, Context, Result};
+use email::{
+    envelope::{list::imap::LIST_ENVELOPES_QUERY, Envelopes},
+    imap::{ImapSession, ImapSessionSync},
+};
+use imap::{
+    extensions::sort::{SortCharset, SortCriterion},
+    types::Fetches,
+};
+use log::{debug, info};
+use std::collections::HashMap;
+use utf7_imap::encode_utf7_imap as encode_utf7;
+
+use super::{
+    paginate_after, EnvelopesCursor, EnvelopesPage, SearchEnvelopesOptions, SortEnvelopes,
+};
+
+/// The IMAP query used to fetch envelopes that need to be sorted
+/// locally: same as [`LIST_ENVELOPES_QUERY`] plus the message size.
+const LIST_ENVELOPES_WITH_SIZE_QUERY: &str =
+    "(UID FLAGS RFC822.SIZE BODY.PEEK[HEADER.FIELDS (MESSAGE-ID FROM TO SUBJECT DATE)])";
+
+/// List IMAP envelopes matching search options.
+///
+/// The query is turned into an IMAP SEARCH command. When a sort
+/// order is given, the IMAP SORT command is used if the server
+/// supports it, otherwise envelopes are sorted locally. In both
+/// cases, the pagination is applied after sorting.
+///
+/// Sorting locally requires to fetch the envelopes of all the
+/// messages matching the query before paginating, which can be slow
+/// on big folders: narrowing the query down helps.
+#[derive(Clone, Debug)]
+pub struct SearchEnvelopesImap {
+    session: ImapSessionSync,
+    opts: SearchEnvelopesOptions,
+}
+
+impl SearchEnvelopesImap {
+    pub fn new(session: &ImapSessionSync, opts: SearchEnvelopesOptions) -> Self {
+        let session = session.clone();
+        Self { session, opts }
+    }
+
+    pub async fn search(
+        &self,
+        folder: &str,
+        page_size: usize,
+        page: usize,
+        after: Option<&EnvelopesCursor>,
+    ) -> Result<EnvelopesPage> {
+        info!("searching imap envelopes from folder {folder}");
+
+        let mut session = self.session.lock().await;
+
+        let folder = session.account_config.get_folder_alias(folder);
+        let folder_encoded = encode_utf7(folder.clone());
+        debug!("utf7 encoded folder: {folder_encoded}");
+
+        session
+            .execute(
+                |session| session.select(&folder_encoded),
+                |err| anyhow!(err).context(format!("cannot select imap folder {folder}")),
+            )
+            .await?;
+
+        let criteria = match &self.opts.query {
+            Some(query) => query.to_imap_criteria(),
+            None => String::from("ALL"),
+        };
+        debug!("imap search criteria: {criteria}");
+
+        let server_sort = self.opts.sort.is_some() && has_sort_capability(&mut session).await?;
+
+        let found = match &self.opts.sort {
+            None => {
+                let mut uids = search_uids(&mut session, &folder, &criteria).await?;
+                let total = uids.len();
+
+                // most recent envelopes first, like the regular
+                // listing. Since UIDs are strictly ascending, the
+                // cursor still works when its envelope is deleted.
+                uids.sort_unstable_by(|a, b| b.cmp(a));
+                if let Some(cursor) = after {
+                    let uid: u32 = cursor
+                        .id()
+                        .parse()
+                        .with_context(|| format!("invalid imap envelopes cursor {cursor}"))?;
+                    uids.retain(|other| *other < uid);
+                }
+
+                let (uids, next) = paginate_after(&uids, page_size, page, None, u32::to_string)?;
+                if uids.is_empty() {
+                    return Ok(EnvelopesPage {
+                        total,
+                        ..Default::default()
+                    });
+                }
+
+                let fetches = fetch(&mut session, uids, LIST_ENVELOPES_QUERY).await?;
+                let mut envelopes = Envelopes::from_imap_fetches(fetches);
+                envelopes.sort_by(|a, b| b.date.cmp(&a.date));
+
+                EnvelopesPage {
+                    envelopes,
+                    total,
+                    next,
+                }
+            }
+            Some(sort) if server_sort => {
+                let uids = sort_uids(&mut session, &folder, &criteria, sort).await?;
+                let total = uids.len();
+
+                let (uids, next) = paginate_after(&uids, page_size, page, after, u32::to_string)?;
+                if uids.is_empty() {
+                    return Ok(EnvelopesPage {
+                        total,
+                        ..Default::default()
+                    });
+                }
+
+                let fetches = fetch(&mut session, uids, LIST_ENVELOPES_QUERY).await?;
+
+                // fetches are not guaranteed to be returned in the
+                // requested order, so the server order is restored
+                let positions: HashMap<String, usize> = uids
+                    .iter()
+                    .enumerate()
+                    .map(|(pos, uid)| (uid.to_string(), pos))
+                    .collect();
+
+                let mut envelopes = Envelopes::from_imap_fetches(fetches);
+                envelopes.sort_by_key(|envelope| positions.get(&envelope.id).copied());
+
+                EnvelopesPage {
+                    envelopes,
+                    total,
+                    next,
+                }
+            }
+            Some(sort) => {
+                debug!("imap server does not support sorting, sorting envelopes locally");
+
+                let uids = search_uids(&mut session, &folder, &criteria).await?;
+                if uids.is_empty() {
+                    return Ok(EnvelopesPage::default());
+                }
+
+                // the whole result needs to be fetched to be sorted
+                info!("fetching {} envelopes to sort them locally", uids.len());
+
+                let fetches = fetch(&mut session, &uids, LIST_ENVELOPES_WITH_SIZE_QUERY).await?;
+
+                let sizes: HashMap<String, u64> = fetches
+                    .iter()
+                    .filter_map(|fetch| Some((fetch.uid?.to_string(), fetch.size? as u64)))
+                    .collect();
+
+                let mut envelopes = Envelopes::from_imap_fetches(fetches);
+                sort.sort(&mut envelopes, &sizes);
+
+                let total = envelopes.len();
+                let (items, next) =
+                    paginate_after(&envelopes, page_size, page, after, |e| e.id.clone())?;
+                let items = items.to_vec();
+                *envelopes = items;
+
+                EnvelopesPage {
+                    envelopes,
+                    total,
+                    next,
+                }
+            }
+        };
+
+        debug!("imap envelopes: {found:#?}");
+
+        Ok(found)
+    }
+}
+
+async fn has_sort_capability(session: &mut ImapSession) -> Result<bool> {
+    let capabilities = session
+        .execute(
+            |session| session.capabilities(),
+            |err| anyhow!(err).context("cannot get imap server capabilities"),
+        )
+        .await?;
+
+    Ok(capabilities.has_str("SORT"))
+}
+
+async fn search_uids(session: &mut ImapSession, folder: &str, criteria: &str) -> Result<Vec<u32>> {
+    let criteria = if criteria.is_ascii() {
+        criteria.to_owned()
+    } else {
+        format!("CHARSET UTF-8 {criteria}")
+    };
+
+    let uids: Vec<u32> = session
+        .execute(
+            |session| session.uid_search(&criteria),
+            |err| anyhow!(err).context(format!("cannot search imap envelopes from {folder}")),
+        )
+        .await?
+        .into_iter()
+        .collect();
+    debug!("found {} matching envelopes", uids.len());
+
+    Ok(uids)
+}
+
+async fn sort_uids(
+    session: &mut ImapSession,
+    folder: &str,
+    criteria: &str,
+    sort: &SortEnvelopes,
+) -> Result<Vec<u32>> {
+    let criterion = sort.to_imap_criterion();
+    let mut sort_criteria = vec![if sort.is_descending() {
+        SortCriterion::Reverse(&criterion)
+    } else {
+        criterion
+    }];
+
+    // envelopes with the same sort key are sorted by date, most
+    // recent first, like the local sort
+    if criterion != SortCriterion::Date {
+        sort_criteria.push(SortCriterion::Reverse(&SortCriterion::Date));
+    }
+
+    let uids = session
+        .execute(
+            |session| session.uid_sort(&sort_criteria, SortCharset::Utf8, criteria),
+            |err| anyhow!(err).context(format!("cannot sort imap envelopes from {folder}")),
+        )
+        .await?;
+    debug!("found {} matching envelopes", uids.len());
+
+    Ok(uids)
+}
+
+async fn fetch(session: &mut ImapSession, uids: &[u32], query: &str) -> Result<Fetches> {
+    let uids = uids
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    debug!("fetching uids: {uids}");
+
+    session
+        .execute(
+            |session| session.uid_fetch(&uids, query),
+            |err| anyhow!(err).context(format!("cannot fetch imap envelopes {uids}")),
+        )
+        .await
+}
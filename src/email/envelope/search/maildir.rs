@@ -0,0 +1,79 @@
+use anyhow::Result;
+use email::{
+    envelope::{Envelope, Envelopes},
+    maildir::MaildirSessionSync,
+};
+use log::{debug, info};
+use std::collections::HashMap;
+
+use super::{paginate_after, EnvelopesCursor, EnvelopesPage, SearchEnvelopesOptions};
+
+/// List Maildir envelopes matching search options.
+///
+/// Maildir has no search capability, so envelopes are filtered and
+/// sorted locally before being paginated.
+#[derive(Clone)]
+pub struct SearchEnvelopesMaildir {
+    session: MaildirSessionSync,
+    opts: SearchEnvelopesOptions,
+}
+
+impl SearchEnvelopesMaildir {
+    pub fn new(session: &MaildirSessionSync, opts: SearchEnvelopesOptions) -> Self {
+        let session = session.clone();
+        Self { session, opts }
+    }
+
+    pub async fn search(
+        &self,
+        folder: &str,
+        page_size: usize,
+        page: usize,
+        after: Option<&EnvelopesCursor>,
+    ) -> Result<EnvelopesPage> {
+        info!("searching maildir envelopes from folder {folder}");
+
+        let session = self.session.lock().await;
+        let mdir = session.get_maildir_from_folder_name(folder)?;
+
+        let mut sizes = HashMap::new();
+        let mut envelopes: Envelopes = mdir
+            .list_cur()
+            .filter_map(|entry| match entry {
+                Ok(entry) => {
+                    if let Ok(metadata) = entry.path().metadata() {
+                        sizes.insert(entry.id().to_owned(), metadata.len());
+                    }
+                    Some(Envelope::from_mdir_entry(entry))
+                }
+                Err(err) => {
+                    debug!("cannot parse maildir entry, skipping it: {err}");
+                    debug!("{err:?}");
+                    None
+                }
+            })
+            .collect();
+
+        if let Some(query) = &self.opts.query {
+            envelopes.retain(|envelope| query.matches(envelope));
+        }
+        debug!("found {} matching envelopes", envelopes.len());
+
+        match &self.opts.sort {
+            Some(sort) => sort.sort(&mut envelopes, &sizes),
+            None => envelopes.sort_by(|a, b| b.date.cmp(&a.date)),
+        }
+
+        let total = envelopes.len();
+        let (items, next) = paginate_after(&envelopes, page_size, page, after, |e| e.id.clone())?;
+        let items = items.to_vec();
+        *envelopes = items;
+        debug!("maildir envelopes: {envelopes:#?}");
+
+        Ok(EnvelopesPage {
+            envelopes,
+            total,
+            next,
+        })
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{bail, Result};
+use email::envelope::Envelopes;
+
+pub mod cursor;
+#[cfg(feature = "imap")]
+pub mod imap;
+#[cfg(feature = "maildir")]
+pub mod maildir;
+pub mod query;
+pub mod sort;
+
+#[doc(inline)]
+pub use self::{
+    cursor::EnvelopesCursor,
+    query::SearchEnvelopesQuery,
+    sort::{SortEnvelopes, SortEnvelopesBy},
+};
+
+/// The options used to search envelopes.
+///
+/// When all options are empty, the regular backend listing can be
+/// used instead.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchEnvelopesOptions {
+    /// The optional search query used to filter envelopes.
+    pub query: Option<SearchEnvelopesQuery>,
+
+    /// The optional sort order of envelopes.
+    pub sort: Option<SortEnvelopes>,
+}
+
+impl SearchEnvelopesOptions {
+    /// Return `true` if no option is defined.
+    pub fn is_empty(&self) -> bool {
+        self.query.is_none() && self.sort.is_none()
+    }
+}
+
+/// A page of envelopes matching search options.
+#[derive(Clone, Debug, Default)]
+pub struct EnvelopesPage {
+    pub envelopes: Envelopes,
+
+    /// The total amount of envelopes matching search options, all
+    /// pages included.
+    pub total: usize,
+
+    /// The cursor pointing to the last envelope of the page, if
+    /// more envelopes follow.
+    pub next: Option<EnvelopesCursor>,
+}
+
+/// Return the items of the given page.
+///
+/// A page size of 0 means no pagination. Requesting a page beyond
+/// the last one results in an out of bounds error.
+pub fn paginate<T>(items: &[T], page_size: usize, page: usize) -> Result<&[T]> {
+    let page_begin = page * page_size;
+    if page_begin > 0 && page_begin >= items.len() {
+        let pages = items.len().div_ceil(page_size).max(1);
+        bail!(
+            "cannot list envelopes: page {} out of bounds, last page is {pages}",
+            page + 1
+        );
+    }
+
+    let page_end = if page_size == 0 {
+        items.len()
+    } else {
+        items.len().min(page_begin + page_size)
+    };
+
+    Ok(&items[page_begin..page_end])
+}
+
+/// Return the items of the given page, starting right after the
+/// item the cursor points to, if any. The cursor pointing to the
+/// last item of the page is returned as well when more items follow.
+pub fn paginate_after<'a, T>(
+    items: &'a [T],
+    page_size: usize,
+    page: usize,
+    after: Option<&EnvelopesCursor>,
+    id: impl Fn(&T) -> String,
+) -> Result<(&'a [T], Option<EnvelopesCursor>)> {
+    let items = match after {
+        None => items,
+        Some(cursor) => match items.iter().position(|item| id(item) == cursor.id()) {
+            Some(pos) => &items[pos + 1..],
+            None => bail!("cannot list envelopes: envelope pointed by cursor {cursor} not found"),
+        },
+    };
+
+    let page_items = paginate(items, page_size, page)?;
+    let page_end = page * page_size + page_items.len();
+    let next = page_items
+        .last()
+        .filter(|_| page_end < items.len())
+        .map(|item| EnvelopesCursor::new(id(item)));
+
+    Ok((page_items, next))
+}
@@ -0,0 +1,470 @@
+//! Module dedicated to the envelope search query language.
+//!
+//! A query is a list of conditions separated by spaces. Conditions
+//! are implicitly joined with `and`, and can be combined using the
+//! `or` and `not` operators as well as parenthesis:
+//!
+//! ```text
+//! from:alice subject:invoice after:2024-01-01 not flag:seen
+//! (from:alice or from:bob) and not subject:"weekly report"
+//! ```
+
+use anyhow::{anyhow, bail, Error, Result};
+use chrono::{Duration, NaiveDate};
+use email::{envelope::Envelope, flag::Flag};
+use std::{fmt, str::FromStr};
+
+/// The date format used by query date conditions.
+const DATE_FMT: &str = "%Y-%m-%d";
+
+/// The date format expected by the IMAP SEARCH command.
+#[cfg(feature = "imap")]
+const IMAP_DATE_FMT: &str = "%d-%b-%Y";
+
+/// The envelope search query.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SearchEnvelopesQuery {
+    /// Both conditions need to match.
+    And(Box<SearchEnvelopesQuery>, Box<SearchEnvelopesQuery>),
+    /// At least one of the conditions needs to match.
+    Or(Box<SearchEnvelopesQuery>, Box<SearchEnvelopesQuery>),
+    /// The condition must not match.
+    Not(Box<SearchEnvelopesQuery>),
+    /// The sender contains the given value.
+    From(String),
+    /// The recipient contains the given value.
+    To(String),
+    /// The subject contains the given value.
+    Subject(String),
+    /// The message has been sent before the given date.
+    Before(NaiveDate),
+    /// The message has been sent after the given date.
+    After(NaiveDate),
+    /// The message has been sent at the given date.
+    Date(NaiveDate),
+    /// The envelope contains the given flag.
+    Flag(Flag),
+}
+
+impl SearchEnvelopesQuery {
+    /// Return `true` if the given envelope matches the query.
+    ///
+    /// Used by backends that cannot search on their own, like
+    /// Maildir. Text comparisons are case-insensitive.
+    pub fn matches(&self, envelope: &Envelope) -> bool {
+        match self {
+            Self::And(left, right) => left.matches(envelope) && right.matches(envelope),
+            Self::Or(left, right) => left.matches(envelope) || right.matches(envelope),
+            Self::Not(query) => !query.matches(envelope),
+            Self::From(pattern) => {
+                contains(&envelope.from.addr, pattern)
+                    || envelope
+                        .from
+                        .name
+                        .as_ref()
+                        .map(|name| contains(name, pattern))
+                        .unwrap_or_default()
+            }
+            Self::To(pattern) => {
+                contains(&envelope.to.addr, pattern)
+                    || envelope
+                        .to
+                        .name
+                        .as_ref()
+                        .map(|name| contains(name, pattern))
+                        .unwrap_or_default()
+            }
+            Self::Subject(pattern) => contains(&envelope.subject, pattern),
+            Self::Before(date) => envelope.date.date_naive() < *date,
+            Self::After(date) => envelope.date.date_naive() > *date,
+            Self::Date(date) => envelope.date.date_naive() == *date,
+            Self::Flag(flag) => envelope.flags.contains(flag),
+        }
+    }
+
+    /// Build the IMAP SEARCH criteria matching the query.
+    ///
+    /// Dates are compared against the Date header, the same way as
+    /// [`SearchEnvelopesQuery::matches`] does.
+    #[cfg(feature = "imap")]
+    pub fn to_imap_criteria(&self) -> String {
+        match self {
+            Self::And(left, right) => {
+                format!("({} {})", left.to_imap_criteria(), right.to_imap_criteria())
+            }
+            Self::Or(left, right) => {
+                format!(
+                    "OR {} {}",
+                    left.to_imap_criteria(),
+                    right.to_imap_criteria()
+                )
+            }
+            Self::Not(query) => format!("NOT {}", query.to_imap_criteria()),
+            Self::From(pattern) => format!("FROM {}", imap_quote(pattern)),
+            Self::To(pattern) => format!("TO {}", imap_quote(pattern)),
+            Self::Subject(pattern) => format!("SUBJECT {}", imap_quote(pattern)),
+            Self::Before(date) => format!("SENTBEFORE {}", date.format(IMAP_DATE_FMT)),
+            Self::After(date) => {
+                // IMAP SENTSINCE is inclusive, whereas `after:` is not
+                let date = *date + Duration::days(1);
+                format!("SENTSINCE {}", date.format(IMAP_DATE_FMT))
+            }
+            Self::Date(date) => format!("SENTON {}", date.format(IMAP_DATE_FMT)),
+            Self::Flag(Flag::Seen) => String::from("SEEN"),
+            Self::Flag(Flag::Answered) => String::from("ANSWERED"),
+            Self::Flag(Flag::Flagged) => String::from("FLAGGED"),
+            Self::Flag(Flag::Deleted) => String::from("DELETED"),
+            Self::Flag(Flag::Draft) => String::from("DRAFT"),
+            Self::Flag(Flag::Custom(flag)) => format!("KEYWORD {flag}"),
+        }
+    }
+
+    fn from_condition(key: Option<&str>, value: String) -> Result<Self> {
+        if let (Some(key), true) = (key, value.is_empty()) {
+            bail!("cannot parse search query: missing value for condition {key}");
+        }
+
+        let query = match key {
+            None => Self::Subject(value),
+            Some(key) if key.eq_ignore_ascii_case("from") => Self::From(value),
+            Some(key) if key.eq_ignore_ascii_case("to") => Self::To(value),
+            Some(key) if key.eq_ignore_ascii_case("subject") => Self::Subject(value),
+            Some(key) if key.eq_ignore_ascii_case("before") => Self::Before(parse_date(&value)?),
+            Some(key) if key.eq_ignore_ascii_case("after") => Self::After(parse_date(&value)?),
+            Some(key) if key.eq_ignore_ascii_case("date") => Self::Date(parse_date(&value)?),
+            Some(key) if key.eq_ignore_ascii_case("flag") => Self::Flag(parse_flag(&value)?),
+            Some(key) => bail!("cannot parse search query: unknown condition {key}"),
+        };
+
+        Ok(query)
+    }
+}
+
+impl FromStr for SearchEnvelopesQuery {
+    type Err = Error;
+
+    fn from_str(query: &str) -> Result<Self> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let query = parser.parse_or()?;
+
+        match parser.tokens.get(parser.pos) {
+            None => Ok(query),
+            Some(token) => bail!("cannot parse search query: unexpected {token}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    And,
+    Or,
+    Not,
+    Condition(Option<String>, String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LeftParen => write!(f, "opening parenthesis"),
+            Self::RightParen => write!(f, "closing parenthesis"),
+            Self::And => write!(f, "operator and"),
+            Self::Or => write!(f, "operator or"),
+            Self::Not => write!(f, "operator not"),
+            Self::Condition(Some(key), val) => write!(f, "condition {key}:{val}"),
+            Self::Condition(None, val) => write!(f, "condition {val}"),
+        }
+    }
+}
+
+/// Split the given query into tokens.
+///
+/// Double quotes allow values to contain spaces, parenthesis and
+/// colons, and prevent words like `and` to be parsed as operators.
+fn tokenize(query: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.peek().copied() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LeftParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RightParen);
+            }
+            _ => {
+                let mut key = None;
+                let mut value = String::new();
+                let mut quoted = false;
+                let mut in_quotes = false;
+
+                while let Some(c) = chars.peek().copied() {
+                    match c {
+                        '"' => {
+                            quoted = true;
+                            in_quotes = !in_quotes;
+                        }
+                        '\\' if in_quotes => {
+                            chars.next();
+                            match chars.peek().copied() {
+                                Some(c) => value.push(c),
+                                None => break,
+                            }
+                        }
+                        ':' if !in_quotes && key.is_none() && !quoted && !value.is_empty() => {
+                            key = Some(value.clone());
+                            value.clear();
+                        }
+                        c if !in_quotes && (c.is_whitespace() || c == '(' || c == ')') => {
+                            break;
+                        }
+                        c => value.push(c),
+                    }
+                    chars.next();
+                }
+
+                if in_quotes {
+                    bail!("cannot parse search query: unclosed double quote");
+                }
+
+                let token = match (&key, quoted) {
+                    (None, false) if value.eq_ignore_ascii_case("and") => Token::And,
+                    (None, false) if value.eq_ignore_ascii_case("or") => Token::Or,
+                    (None, false) if value.eq_ignore_ascii_case("not") => Token::Not,
+                    _ => Token::Condition(key, value),
+                };
+
+                tokens.push(token);
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser over query tokens.
+///
+/// The `not` operator binds tighter than `and`, which binds tighter
+/// than `or`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<SearchEnvelopesQuery> {
+        let mut query = self.parse_and()?;
+
+        while let Some(Token::Or) = self.peek() {
+            self.next();
+            let right = self.parse_and()?;
+            query = SearchEnvelopesQuery::Or(Box::new(query), Box::new(right));
+        }
+
+        Ok(query)
+    }
+
+    fn parse_and(&mut self) -> Result<SearchEnvelopesQuery> {
+        let mut query = self.parse_not()?;
+
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                }
+                Some(Token::Not | Token::LeftParen | Token::Condition(..)) => (),
+                _ => break,
+            }
+
+            let right = self.parse_not()?;
+            query = SearchEnvelopesQuery::And(Box::new(query), Box::new(right));
+        }
+
+        Ok(query)
+    }
+
+    fn parse_not(&mut self) -> Result<SearchEnvelopesQuery> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.next();
+                let query = self.parse_not()?;
+                Ok(SearchEnvelopesQuery::Not(Box::new(query)))
+            }
+            _ => self.parse_atom(),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<SearchEnvelopesQuery> {
+        match self.next() {
+            Some(Token::LeftParen) => {
+                let query = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RightParen) => Ok(query),
+                    _ => bail!("cannot parse search query: missing closing parenthesis"),
+                }
+            }
+            Some(Token::Condition(key, value)) => {
+                SearchEnvelopesQuery::from_condition(key.as_deref(), value)
+            }
+            Some(token) => bail!("cannot parse search query: unexpected {token}"),
+            None => bail!("cannot parse search query: unexpected end of query"),
+        }
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, DATE_FMT)
+        .map_err(|err| anyhow!(err).context(format!("cannot parse search query date {date}")))
+}
+
+/// Parse the given flag.
+///
+/// Custom flags are sent as is to IMAP servers, so they need to be
+/// valid IMAP keywords: printable ASCII characters, without spaces
+/// nor atom specials (`(`, `)`, `{`, `%`, `*`, `"`, `\` and `]`).
+fn parse_flag(flag: &str) -> Result<Flag> {
+    let is_atom_char = |c: char| {
+        c.is_ascii_graphic() && !matches!(c, '(' | ')' | '{' | '%' | '*' | '"' | '\\' | ']')
+    };
+
+    match Flag::from(flag) {
+        Flag::Custom(keyword) if keyword.is_empty() || !keyword.chars().all(is_atom_char) => {
+            bail!("cannot parse search query: invalid flag {keyword}")
+        }
+        flag => Ok(flag),
+    }
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+#[cfg(feature = "imap")]
+fn imap_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use email::flag::Flag;
+
+    use super::SearchEnvelopesQuery as Query;
+
+    fn parse(query: &str) -> Query {
+        query.parse().unwrap()
+    }
+
+    #[test]
+    fn implicit_and() {
+        assert_eq!(
+            parse("from:alice subject:invoice"),
+            Query::And(
+                Box::new(Query::From("alice".into())),
+                Box::new(Query::Subject("invoice".into()))
+            )
+        );
+    }
+
+    #[test]
+    fn operators_precedence() {
+        assert_eq!(
+            parse("from:alice or from:bob and not flag:seen"),
+            Query::Or(
+                Box::new(Query::From("alice".into())),
+                Box::new(Query::And(
+                    Box::new(Query::From("bob".into())),
+                    Box::new(Query::Not(Box::new(Query::Flag(Flag::Seen))))
+                ))
+            )
+        );
+
+        assert_eq!(
+            parse("(from:alice or from:bob) flag:flagged"),
+            Query::And(
+                Box::new(Query::Or(
+                    Box::new(Query::From("alice".into())),
+                    Box::new(Query::From("bob".into()))
+                )),
+                Box::new(Query::Flag(Flag::Flagged))
+            )
+        );
+    }
+
+    #[test]
+    fn quoted_values() {
+        assert_eq!(
+            parse(r#"subject:"weekly report (v2)""#),
+            Query::Subject("weekly report (v2)".into())
+        );
+        assert_eq!(parse(r#""or""#), Query::Subject("or".into()));
+        assert_eq!(parse(r#""re: hi""#), Query::Subject("re: hi".into()));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(
+            parse("after:2024-01-01"),
+            Query::After(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+        );
+        assert!("after:yesterday".parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn invalid_queries() {
+        assert!("".parse::<Query>().is_err());
+        assert!("unknown:value".parse::<Query>().is_err());
+        assert!("(from:alice".parse::<Query>().is_err());
+        assert!("from:alice)".parse::<Query>().is_err());
+        assert!("from:alice or".parse::<Query>().is_err());
+        assert!(r#"subject:"unclosed"#.parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn flags() {
+        assert_eq!(parse("flag:seen"), Query::Flag(Flag::Seen));
+        assert_eq!(parse("flag:$Junk"), Query::Flag(Flag::custom("$Junk")));
+
+        assert!(r#"flag:" ""#.parse::<Query>().is_err());
+        assert!(r#"flag:"a b""#.parse::<Query>().is_err());
+        assert!(r#"flag:"a) OR (ALL""#.parse::<Query>().is_err());
+        assert!(r#"flag:"a\\b""#.parse::<Query>().is_err());
+        assert!(r#"flag:"a]""#.parse::<Query>().is_err());
+        assert!("flag:caf\u{e9}".parse::<Query>().is_err());
+    }
+
+    #[cfg(feature = "imap")]
+    #[test]
+    fn imap_criteria() {
+        assert_eq!(
+            parse("from:alice subject:invoice after:2024-01-01 not flag:seen").to_imap_criteria(),
+            r#"(((FROM "alice" SUBJECT "invoice") SENTSINCE 02-Jan-2024) NOT SEEN)"#
+        );
+        assert_eq!(
+            parse(r#"from:alice or subject:"say \"hi\"""#).to_imap_criteria(),
+            r#"OR FROM "alice" SUBJECT "say \"hi\"""#
+        );
+        assert_eq!(
+            parse("not flag:$Junk").to_imap_criteria(),
+            "NOT KEYWORD $Junk"
+        );
+    }
+}
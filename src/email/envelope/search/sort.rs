@@ -0,0 +1,222 @@
+use clap::ValueEnum;
+use email::envelope::{Envelope, Envelopes};
+#[cfg(feature = "imap")]
+use imap::extensions::sort::SortCriterion;
+use std::{cmp::Ordering, collections::HashMap};
+
+/// The envelope property used to sort envelopes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum SortEnvelopesBy {
+    /// Sort by date, most recent first.
+    #[default]
+    Date,
+
+    /// Sort by sender address, alphabetically.
+    From,
+
+    /// Sort by subject, alphabetically, ignoring reply and forward
+    /// prefixes.
+    Subject,
+
+    /// Sort by size, biggest first.
+    Size,
+}
+
+/// The sort order of envelopes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SortEnvelopes {
+    pub by: SortEnvelopesBy,
+    pub reverse: bool,
+}
+
+impl SortEnvelopes {
+    pub fn new(by: SortEnvelopesBy, reverse: bool) -> Self {
+        Self { by, reverse }
+    }
+
+    /// Return `true` if the sort order is descending, taking into
+    /// account the natural order of the sort property.
+    pub fn is_descending(&self) -> bool {
+        let descending = matches!(self.by, SortEnvelopesBy::Date | SortEnvelopesBy::Size);
+        descending != self.reverse
+    }
+
+    /// Return the IMAP SORT criterion matching the sort property,
+    /// ignoring the order.
+    #[cfg(feature = "imap")]
+    pub fn to_imap_criterion(&self) -> SortCriterion<'static> {
+        match self.by {
+            SortEnvelopesBy::Date => SortCriterion::Date,
+            SortEnvelopesBy::From => SortCriterion::From,
+            SortEnvelopesBy::Subject => SortCriterion::Subject,
+            SortEnvelopesBy::Size => SortCriterion::Size,
+        }
+    }
+
+    /// Sort the given envelopes locally.
+    ///
+    /// Sizes are not part of envelopes, they need to be given
+    /// separately, indexed by envelope id. Envelopes with the same
+    /// sort key are sorted by date, most recent first.
+    pub fn sort(&self, envelopes: &mut Envelopes, sizes: &HashMap<String, u64>) {
+        self.sort_by::<Envelope>(envelopes, |envelope| {
+            sizes.get(&envelope.id).copied().unwrap_or_default()
+        });
+    }
+
+    /// Sort the given envelopes locally, using the given function to
+    /// get their size.
+    pub fn sort_by<T: SortableEnvelope>(&self, envelopes: &mut [T], size: impl Fn(&T) -> u64) {
+        envelopes.sort_by(|a, b| {
+            let ord = match self.by {
+                SortEnvelopesBy::Date => a.timestamp().cmp(&b.timestamp()),
+                SortEnvelopesBy::From => sender_key(a.sender()).cmp(&sender_key(b.sender())),
+                SortEnvelopesBy::Subject => {
+                    base_subject(a.subject()).cmp(&base_subject(b.subject()))
+                }
+                SortEnvelopesBy::Size => size(a).cmp(&size(b)),
+            };
+
+            let ord = if self.is_descending() {
+                ord.reverse()
+            } else {
+                ord
+            };
+
+            match ord {
+                Ordering::Equal => b.timestamp().cmp(&a.timestamp()),
+                ord => ord,
+            }
+        });
+    }
+}
+
+/// The envelope properties needed to sort envelopes.
+pub trait SortableEnvelope {
+    fn timestamp(&self) -> i64;
+
+    /// Return the address of the envelope sender.
+    fn sender(&self) -> &str;
+
+    fn subject(&self) -> &str;
+}
+
+impl SortableEnvelope for Envelope {
+    fn timestamp(&self) -> i64 {
+        self.date.timestamp()
+    }
+
+    fn sender(&self) -> &str {
+        &self.from.addr
+    }
+
+    fn subject(&self) -> &str {
+        &self.subject
+    }
+}
+
+impl SortableEnvelope for crate::envelope::Envelope {
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    fn sender(&self) -> &str {
+        &self.from.addr
+    }
+
+    fn subject(&self) -> &str {
+        &self.subject
+    }
+}
+
+/// Return the sorting key of the given sender address.
+///
+/// Like the IMAP SORT command (see RFC 5256, section 3), senders are
+/// compared by the mailbox part of their address, so that the order
+/// does not depend on the backend. The domain only breaks ties.
+fn sender_key(addr: &str) -> (String, String) {
+    let addr = addr.trim().to_lowercase();
+
+    match addr.rsplit_once('@') {
+        Some((mailbox, domain)) => (mailbox.to_owned(), domain.to_owned()),
+        None => (addr, String::new()),
+    }
+}
+
+/// Return the base subject, stripped from reply and forward prefixes
+/// (see RFC 5256, section 2.1).
+pub fn base_subject(subject: &str) -> String {
+    let mut subject = subject.trim().to_lowercase();
+
+    loop {
+        let stripped = ["re:", "fwd:", "fw:"]
+            .iter()
+            .find_map(|prefix| subject.strip_prefix(prefix))
+            .map(|s| s.trim_start().to_owned());
+
+        match stripped {
+            Some(stripped) => subject = stripped,
+            None => break subject,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SortEnvelopes, SortEnvelopesBy, SortableEnvelope};
+
+    struct TestEnvelope(&'static str, &'static str);
+
+    impl SortableEnvelope for TestEnvelope {
+        fn timestamp(&self) -> i64 {
+            0
+        }
+
+        fn sender(&self) -> &str {
+            self.0
+        }
+
+        fn subject(&self) -> &str {
+            self.1
+        }
+    }
+
+    #[test]
+    fn sort_by_sender_address() {
+        // mailboxes are compared first, domains only break ties,
+        // as with IMAP SORT FROM
+        let mut envelopes = [
+            TestEnvelope("bob@example.org", ""),
+            TestEnvelope("Alice@example.com", ""),
+            TestEnvelope("alice@example.org", ""),
+            TestEnvelope("bob-1@example.org", ""),
+        ];
+
+        SortEnvelopes::new(SortEnvelopesBy::From, false).sort_by(&mut envelopes, |_| 0);
+
+        let senders: Vec<_> = envelopes.iter().map(|e| e.0).collect();
+        assert_eq!(
+            senders,
+            [
+                "Alice@example.com",
+                "alice@example.org",
+                "bob@example.org",
+                "bob-1@example.org",
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_by_base_subject() {
+        let mut envelopes = [
+            TestEnvelope("", "Re: Fwd: b"),
+            TestEnvelope("", "a"),
+            TestEnvelope("", "RE: c"),
+        ];
+
+        SortEnvelopes::new(SortEnvelopesBy::Subject, true).sort_by(&mut envelopes, |_| 0);
+
+        let subjects: Vec<_> = envelopes.iter().map(|e| e.1).collect();
+        assert_eq!(subjects, ["RE: c", "Re: Fwd: b", "a"]);
+    }
+}
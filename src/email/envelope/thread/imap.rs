@@ -0,0 +1,242 @@
+use anyhow::{anyhow, bail, Result};
+use email::{
+    envelope::{list::imap::LIST_ENVELOPES_QUERY, Envelope, Envelopes},
+    imap::ImapSessionSync,
+    message::Message,
+};
+use log::{debug, info};
+use std::collections::HashMap;
+use utf7_imap::encode_utf7_imap as encode_utf7;
+
+use super::{jwz, sort_threads, ThreadHeaders, ThreadNode};
+
+/// The IMAP query used to thread envelopes locally: same as
+/// [`LIST_ENVELOPES_QUERY`] plus the threading headers.
+const THREAD_ENVELOPES_QUERY: &str =
+    "(UID FLAGS BODY.PEEK[HEADER.FIELDS (MESSAGE-ID IN-REPLY-TO REFERENCES FROM TO SUBJECT DATE)])";
+
+/// Thread all envelopes of the given IMAP folder.
+///
+/// The IMAP THREAD command is used when the server supports the
+/// REFERENCES algorithm, otherwise envelopes are threaded locally.
+pub async fn thread_envelopes(
+    session: &ImapSessionSync,
+    folder: &str,
+) -> Result<Vec<ThreadNode<Envelope>>> {
+    info!("threading imap envelopes from folder {folder}");
+
+    let mut session = session.lock().await;
+
+    let folder = session.account_config.get_folder_alias(folder);
+    let folder_encoded = encode_utf7(folder.clone());
+    debug!("utf7 encoded folder: {folder_encoded}");
+
+    let folder_size = session
+        .execute(
+            |session| session.select(&folder_encoded),
+            |err| anyhow!(err).context(format!("cannot select imap folder {folder}")),
+        )
+        .await?
+        .exists;
+    debug!("folder size: {folder_size}");
+
+    if folder_size == 0 {
+        return Ok(Vec::new());
+    }
+
+    let capabilities = session
+        .execute(
+            |session| session.capabilities(),
+            |err| anyhow!(err).context("cannot get imap server capabilities"),
+        )
+        .await?;
+
+    if !capabilities.has_str("THREAD=REFERENCES") {
+        debug!("imap server does not support threading, threading envelopes locally");
+
+        let fetches = session
+            .execute(
+                |session| session.uid_fetch("1:*", THREAD_ENVELOPES_QUERY),
+                |err| anyhow!(err).context(format!("cannot fetch imap envelopes from {folder}")),
+            )
+            .await?;
+
+        let envelopes = fetches.iter().filter_map(|fetch| {
+            let envelope = Envelope::from_imap_fetch(fetch).ok()?;
+            let headers = ThreadHeaders::from_msg(&Message::from(fetch.header()?));
+            Some((envelope, headers))
+        });
+
+        return Ok(jwz::thread(envelopes));
+    }
+
+    let res = session
+        .execute(
+            |session| session.run_command_and_read_response("UID THREAD REFERENCES UTF-8 ALL"),
+            |err| anyhow!(err).context(format!("cannot thread imap envelopes from {folder}")),
+        )
+        .await?;
+
+    let threads = parse_thread_response(&String::from_utf8_lossy(&res))?;
+
+    let mut uids = Vec::new();
+    threads
+        .iter()
+        .for_each(|thread| collect_uids(thread, &mut uids));
+
+    if uids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let uids = uids
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let fetches = session
+        .execute(
+            |session| session.uid_fetch(&uids, LIST_ENVELOPES_QUERY),
+            |err| anyhow!(err).context(format!("cannot fetch imap envelopes {uids}")),
+        )
+        .await?;
+
+    let mut envelopes: HashMap<String, Envelope> = Envelopes::from_imap_fetches(fetches)
+        .into_iter()
+        .map(|envelope| (envelope.id.clone(), envelope))
+        .collect();
+
+    let mut threads: Vec<_> = threads
+        .into_iter()
+        .flat_map(|thread| attach_envelopes(thread, &mut envelopes))
+        .collect();
+
+    sort_threads(&mut threads);
+    debug!("imap threads: {threads:#?}");
+
+    Ok(threads)
+}
+
+fn collect_uids(node: &ThreadNode<u32>, uids: &mut Vec<u32>) {
+    uids.push(node.envelope);
+    node.children
+        .iter()
+        .for_each(|child| collect_uids(child, uids));
+}
+
+/// Replace UIDs by their envelopes. Nodes whose envelope could not
+/// be fetched are replaced by their children.
+fn attach_envelopes(
+    node: ThreadNode<u32>,
+    envelopes: &mut HashMap<String, Envelope>,
+) -> Vec<ThreadNode<Envelope>> {
+    let children = node
+        .children
+        .into_iter()
+        .flat_map(|child| attach_envelopes(child, envelopes))
+        .collect();
+
+    match envelopes.remove(&node.envelope.to_string()) {
+        Some(envelope) => vec![ThreadNode { envelope, children }],
+        None => children,
+    }
+}
+
+/// Parse the untagged THREAD response (see RFC 5256, section 4).
+///
+/// Threads are given as parenthesized lists of UIDs, where each UID
+/// is the parent of the next one. A nested list represents a branch:
+/// `(3 6 (4 23)(44 7 96))` means that 3 is the parent of 6, which
+/// has two children 4 and 44.
+fn parse_thread_response(res: &str) -> Result<Vec<ThreadNode<u32>>> {
+    let mut threads = Vec::new();
+
+    for line in res.lines() {
+        let Some(line) = line.strip_prefix("* THREAD") else {
+            continue;
+        };
+
+        let mut chars = line.trim().chars().peekable();
+        while chars.peek().is_some() {
+            threads.extend(parse_thread_list(&mut chars)?);
+        }
+    }
+
+    Ok(threads)
+}
+
+fn parse_thread_list(
+    chars: &mut std::iter::Peekable<impl Iterator<Item = char>>,
+) -> Result<Vec<ThreadNode<u32>>> {
+    if chars.next() != Some('(') {
+        bail!("cannot parse imap thread response: expected opening parenthesis");
+    }
+
+    let mut uids = Vec::new();
+    let mut branches = Vec::new();
+
+    loop {
+        match chars.peek() {
+            Some(' ') => {
+                chars.next();
+            }
+            Some('(') => {
+                branches.extend(parse_thread_list(chars)?);
+            }
+            Some(')') => {
+                chars.next();
+                break;
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let mut uid = String::new();
+                while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                    uid.push(*c);
+                    chars.next();
+                }
+                uids.push(uid.parse::<u32>()?);
+            }
+            Some(c) => bail!("cannot parse imap thread response: unexpected character {c}"),
+            None => bail!("cannot parse imap thread response: unexpected end of response"),
+        }
+    }
+
+    // a list without UID groups threads whose root is missing
+    let Some(last) = uids.pop() else {
+        return Ok(branches);
+    };
+
+    let mut node = ThreadNode {
+        envelope: last,
+        children: branches,
+    };
+
+    while let Some(uid) = uids.pop() {
+        node = ThreadNode {
+            envelope: uid,
+            children: vec![node],
+        };
+    }
+
+    Ok(vec![node])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_thread_response, ThreadNode};
+
+    fn format(nodes: &[ThreadNode<u32>]) -> String {
+        nodes
+            .iter()
+            .map(|node| format!("{}[{}]", node.envelope, format(&node.children)))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    #[test]
+    fn thread_response() {
+        let res = "* THREAD (2)(3 6 (4 23)(44 7 96))((5)(8))\r\n";
+        let threads = parse_thread_response(res).unwrap();
+
+        assert_eq!(format(&threads), "2[],3[6[4[23[]],44[7[96[]]]]],5[],8[]");
+    }
+}
@@ -0,0 +1,306 @@
+//! Module dedicated to the JWZ threading algorithm.
+//!
+//! This is the algorithm used by most mail readers to build
+//! conversation trees from the Message-ID, In-Reply-To and
+//! References headers. See <https://www.jwz.org/doc/threading.html>.
+
+use email::envelope::Envelope;
+use std::collections::HashMap;
+
+use crate::envelope::search::sort::base_subject;
+
+use super::{sort_threads, ThreadHeaders, ThreadNode};
+
+/// A node of the threading graph. Containers without envelope
+/// represent messages that are referenced but not available.
+#[derive(Default)]
+struct Container {
+    envelope: Option<Envelope>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+#[derive(Default)]
+struct Threader {
+    containers: Vec<Container>,
+    ids: HashMap<String, usize>,
+}
+
+impl Threader {
+    /// Return the container of the given message id, create it if
+    /// needed.
+    fn container(&mut self, id: &str) -> usize {
+        match self.ids.get(id) {
+            Some(idx) => *idx,
+            None => {
+                let idx = self.new_container();
+                self.ids.insert(id.to_owned(), idx);
+                idx
+            }
+        }
+    }
+
+    fn new_container(&mut self) -> usize {
+        self.containers.push(Container::default());
+        self.containers.len() - 1
+    }
+
+    /// Return `true` if `ancestor` is `idx` or one of its ancestors.
+    fn is_ancestor(&self, ancestor: usize, mut idx: usize) -> bool {
+        loop {
+            if idx == ancestor {
+                return true;
+            }
+
+            match self.containers[idx].parent {
+                Some(parent) => idx = parent,
+                None => return false,
+            }
+        }
+    }
+
+    fn unlink(&mut self, child: usize) {
+        if let Some(parent) = self.containers[child].parent.take() {
+            self.containers[parent].children.retain(|idx| *idx != child);
+        }
+    }
+
+    /// Make `child` a child of `parent`, unless it introduces a loop.
+    fn link(&mut self, parent: usize, child: usize) {
+        if self.is_ancestor(child, parent) {
+            return;
+        }
+
+        self.unlink(child);
+        self.containers[child].parent = Some(parent);
+        self.containers[parent].children.push(child);
+    }
+
+    /// Remove empty containers from the given tree, and return the
+    /// containers replacing it.
+    fn prune(&mut self, idx: usize) -> Vec<usize> {
+        let mut children = Vec::new();
+        for child in std::mem::take(&mut self.containers[idx].children) {
+            children.extend(self.prune(child));
+        }
+
+        if self.containers[idx].envelope.is_some() {
+            self.containers[idx].children = children;
+            vec![idx]
+        } else {
+            // the children of an empty container are promoted to its
+            // parent, or become roots
+            children
+        }
+    }
+
+    fn build(&mut self, idx: usize) -> Option<ThreadNode<Envelope>> {
+        let mut node = ThreadNode::new(self.containers[idx].envelope.take()?);
+
+        for child in std::mem::take(&mut self.containers[idx].children) {
+            node.children.extend(self.build(child));
+        }
+
+        Some(node)
+    }
+}
+
+/// Build conversation trees from the given envelopes and their
+/// threading headers.
+pub fn thread(
+    envelopes: impl IntoIterator<Item = (Envelope, ThreadHeaders)>,
+) -> Vec<ThreadNode<Envelope>> {
+    let mut threader = Threader::default();
+
+    for (envelope, headers) in envelopes {
+        let idx = match headers.message_id.as_deref() {
+            Some(id) => {
+                let idx = threader.container(id);
+                // duplicated message ids are threaded separately
+                if threader.containers[idx].envelope.is_some() {
+                    threader.new_container()
+                } else {
+                    idx
+                }
+            }
+            None => threader.new_container(),
+        };
+
+        // link the references together, without overriding
+        // existing links
+        let mut parent = None;
+        for id in headers.ancestors() {
+            let ref_idx = threader.container(id);
+            if let Some(parent) = parent {
+                if threader.containers[ref_idx].parent.is_none() {
+                    threader.link(parent, ref_idx);
+                }
+            }
+            parent = Some(ref_idx);
+        }
+
+        // the message headers are authoritative regarding its own
+        // parent
+        match parent {
+            Some(parent) => threader.link(parent, idx),
+            None => threader.unlink(idx),
+        }
+
+        threader.containers[idx].envelope = Some(envelope);
+    }
+
+    let mut roots = Vec::new();
+    for idx in 0..threader.containers.len() {
+        if threader.containers[idx].parent.is_none() {
+            roots.extend(threader.prune(idx));
+        }
+    }
+
+    // group remaining replies with the root sharing the same subject,
+    // for messages missing threading headers
+    let is_reply = |envelope: &Envelope| {
+        base_subject(&envelope.subject) != envelope.subject.trim().to_lowercase()
+    };
+
+    let mut subjects: HashMap<String, usize> = HashMap::new();
+    for idx in &roots {
+        let envelope = threader.containers[*idx].envelope.as_ref().unwrap();
+        let subject = base_subject(&envelope.subject);
+        if subject.is_empty() {
+            continue;
+        }
+
+        match subjects.get(&subject) {
+            Some(other) => {
+                let other = threader.containers[*other].envelope.as_ref().unwrap();
+                if is_reply(other) && !is_reply(envelope) {
+                    subjects.insert(subject, *idx);
+                }
+            }
+            None => {
+                subjects.insert(subject, *idx);
+            }
+        }
+    }
+
+    roots.retain(|idx| {
+        let envelope = threader.containers[*idx].envelope.as_ref().unwrap();
+        if !is_reply(envelope) {
+            return true;
+        }
+
+        match subjects.get(&base_subject(&envelope.subject)) {
+            Some(root) if root != idx => {
+                threader.containers[*root].children.push(*idx);
+                false
+            }
+            _ => true,
+        }
+    });
+
+    let mut threads: Vec<_> = roots
+        .into_iter()
+        .filter_map(|idx| threader.build(idx))
+        .collect();
+    sort_threads(&mut threads);
+    threads
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use email::envelope::Envelope;
+
+    use super::{thread, ThreadHeaders, ThreadNode};
+
+    fn envelope(id: &str, subject: &str, date: &str) -> Envelope {
+        Envelope {
+            id: id.into(),
+            subject: subject.into(),
+            date: DateTime::parse_from_rfc3339(date).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn headers(id: &str, refs: &[&str]) -> ThreadHeaders {
+        ThreadHeaders {
+            message_id: Some(id.into()),
+            in_reply_to: refs.last().map(|id| id.to_string()).into_iter().collect(),
+            references: refs.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    fn ids(threads: &[ThreadNode<Envelope>]) -> Vec<(String, Vec<String>)> {
+        threads
+            .iter()
+            .map(|t| {
+                let children = t.children.iter().map(|c| c.envelope.id.clone()).collect();
+                (t.envelope.id.clone(), children)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn references() {
+        let threads = thread([
+            (
+                envelope("1", "hello", "2024-01-01T10:00:00Z"),
+                headers("a@x", &[]),
+            ),
+            (
+                envelope("2", "Re: hello", "2024-01-01T11:00:00Z"),
+                headers("b@x", &["a@x"]),
+            ),
+            (
+                envelope("3", "Re: hello", "2024-01-01T12:00:00Z"),
+                headers("c@x", &["a@x", "b@x"]),
+            ),
+            (
+                envelope("4", "other", "2024-01-01T09:00:00Z"),
+                headers("d@x", &[]),
+            ),
+        ]);
+
+        assert_eq!(
+            ids(&threads),
+            vec![("1".into(), vec!["2".into()]), ("4".into(), vec![])]
+        );
+        assert_eq!(threads[0].children[0].children[0].envelope.id, "3");
+    }
+
+    #[test]
+    fn missing_parent() {
+        // both replies reference a message that is not available:
+        // they become roots, then get grouped by subject
+        let threads = thread([
+            (
+                envelope("1", "Re: hello", "2024-01-01T10:00:00Z"),
+                headers("b@x", &["a@x"]),
+            ),
+            (
+                envelope("2", "Re: hello", "2024-01-01T11:00:00Z"),
+                headers("c@x", &["a@x"]),
+            ),
+        ]);
+
+        assert_eq!(ids(&threads), vec![("1".into(), vec!["2".into()])]);
+    }
+
+    #[test]
+    fn loops() {
+        let threads = thread([
+            (
+                envelope("1", "a", "2024-01-01T10:00:00Z"),
+                headers("a@x", &["b@x"]),
+            ),
+            (
+                envelope("2", "b", "2024-01-01T11:00:00Z"),
+                headers("b@x", &["a@x"]),
+            ),
+        ]);
+
+        // the second message cannot become the parent of its own
+        // parent, so the first link is kept
+        assert_eq!(ids(&threads), vec![("2".into(), vec!["1".into()])]);
+    }
+}
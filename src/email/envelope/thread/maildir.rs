@@ -0,0 +1,36 @@
+use anyhow::Result;
+use email::{envelope::Envelope, maildir::MaildirSessionSync, message::Message};
+use log::{debug, info};
+
+use super::{jwz, ThreadHeaders, ThreadNode};
+
+/// Thread all envelopes of the given Maildir folder.
+///
+/// Maildir has no threading capability, so envelopes are threaded
+/// locally.
+pub async fn thread_envelopes(
+    session: &MaildirSessionSync,
+    folder: &str,
+) -> Result<Vec<ThreadNode<Envelope>>> {
+    info!("threading maildir envelopes from folder {folder}");
+
+    let session = session.lock().await;
+    let mdir = session.get_maildir_from_folder_name(folder)?;
+
+    let envelopes = mdir.list_cur().filter_map(|entry| match entry {
+        Ok(entry) => {
+            let headers = ThreadHeaders::from_msg(&Message::from(entry.headers()));
+            Some((Envelope::from_mdir_entry(entry), headers))
+        }
+        Err(err) => {
+            debug!("cannot parse maildir entry, skipping it: {err}");
+            debug!("{err:?}");
+            None
+        }
+    });
+
+    let threads = jwz::thread(envelopes);
+    debug!("maildir threads: {threads:#?}");
+
+    Ok(threads)
+}
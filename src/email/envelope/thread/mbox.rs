@@ -0,0 +1,31 @@
+use anyhow::Result;
+use email::{envelope::Envelope, message::Message};
+use log::{debug, info};
+
+use crate::mbox::{envelope::envelope_from_msg, MboxSession};
+
+use super::{jwz, ThreadHeaders, ThreadNode};
+
+/// Thread all envelopes of the given mbox folder.
+pub async fn thread_envelopes(
+    session: &MboxSession,
+    folder: &str,
+) -> Result<Vec<ThreadNode<Envelope>>> {
+    info!("threading mbox envelopes from folder {folder}");
+
+    let mbox = session.read_folder(folder)?;
+
+    let envelopes: Vec<_> = mbox
+        .messages
+        .iter()
+        .map(|msg| {
+            let headers = ThreadHeaders::from_msg(&Message::from(msg.to_raw_msg()));
+            (envelope_from_msg(msg), headers)
+        })
+        .collect();
+
+    let threads = jwz::thread(envelopes);
+    debug!("mbox threads: {threads:#?}");
+
+    Ok(threads)
+}
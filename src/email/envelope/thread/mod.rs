@@ -0,0 +1,213 @@
+//! Module dedicated to envelope threading.
+//!
+//! Envelopes are grouped into conversation trees, either by the IMAP
+//! server when it supports the THREAD extension, or locally using
+//! the JWZ algorithm.
+
+#[cfg(feature = "imap")]
+pub mod imap;
+pub mod jwz;
+#[cfg(feature = "maildir")]
+pub mod maildir;
+#[cfg(feature = "mbox")]
+pub mod mbox;
+#[cfg(feature = "notmuch")]
+pub mod notmuch;
+
+use anyhow::Result;
+use email::{account::config::AccountConfig, message::Message};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::{
+    cache::IdMapper,
+    envelope::{details::EnvelopeDetails, message_id, Envelope},
+    printer::{PrintTable, PrintTableOpts, WriteColor},
+    ui::Table,
+};
+
+/// The headers needed to thread a message.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ThreadHeaders {
+    /// The Message-ID, without angle brackets.
+    pub message_id: Option<String>,
+
+    /// The In-Reply-To message ids, without angle brackets.
+    pub in_reply_to: Vec<String>,
+
+    /// The References message ids, without angle brackets, from the
+    /// oldest to the most recent one.
+    pub references: Vec<String>,
+}
+
+impl ThreadHeaders {
+    pub fn from_msg(msg: &Message) -> Self {
+        let Ok(msg) = msg.parsed() else {
+            return Self::default();
+        };
+
+        let ids = |ids: Option<Vec<&str>>| {
+            ids.unwrap_or_default()
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect()
+        };
+
+        Self {
+            message_id: msg.message_id().map(ToOwned::to_owned),
+            in_reply_to: ids(msg.in_reply_to().as_text_list()),
+            references: ids(msg.references().as_text_list()),
+        }
+    }
+
+    /// Return the ancestors of the message, from the oldest to the
+    /// parent.
+    ///
+    /// The References header is completed by the In-Reply-To one,
+    /// which is sometimes the only one available.
+    pub fn ancestors(&self) -> Vec<&str> {
+        let mut ancestors: Vec<&str> = self.references.iter().map(String::as_str).collect();
+
+        if let Some(parent) = self.in_reply_to.first() {
+            if ancestors.last() != Some(&parent.as_str()) {
+                ancestors.push(parent);
+            }
+        }
+
+        ancestors
+    }
+}
+
+/// A node of a conversation tree.
+#[derive(Clone, Debug, Serialize)]
+pub struct ThreadNode<T> {
+    #[serde(flatten)]
+    pub envelope: T,
+    pub children: Vec<ThreadNode<T>>,
+}
+
+impl<T> ThreadNode<T> {
+    pub fn new(envelope: T) -> Self {
+        Self {
+            envelope,
+            children: Vec::new(),
+        }
+    }
+
+    /// Transform the envelopes of the whole tree.
+    pub fn try_map<U>(self, f: &mut impl FnMut(T) -> Result<U>) -> Result<ThreadNode<U>> {
+        Ok(ThreadNode {
+            envelope: f(self.envelope)?,
+            children: self
+                .children
+                .into_iter()
+                .map(|child| child.try_map(f))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl ThreadNode<email::envelope::Envelope> {
+    /// Return the date of the most recent envelope of the tree.
+    fn latest_date(&self) -> chrono::DateTime<chrono::FixedOffset> {
+        self.children
+            .iter()
+            .map(Self::latest_date)
+            .fold(self.envelope.date, |a, b| a.max(b))
+    }
+
+    /// Collect all the envelopes of the tree.
+    pub fn collect_envelopes<'a>(&'a self, envelopes: &mut Vec<&'a email::envelope::Envelope>) {
+        envelopes.push(&self.envelope);
+        self.children
+            .iter()
+            .for_each(|child| child.collect_envelopes(envelopes));
+    }
+
+    /// Collect the ids of all the envelopes of the tree.
+    pub fn collect_ids(&self, ids: &mut Vec<String>) {
+        ids.push(self.envelope.id.clone());
+        self.children
+            .iter()
+            .for_each(|child| child.collect_ids(ids));
+    }
+
+    fn sort_children(&mut self) {
+        self.children
+            .sort_by(|a, b| a.envelope.date.cmp(&b.envelope.date));
+        self.children.iter_mut().for_each(Self::sort_children);
+    }
+}
+
+/// Sort conversations: the most recently active conversation comes
+/// first, and replies are sorted from the oldest to the most recent.
+pub fn sort_threads(threads: &mut [ThreadNode<email::envelope::Envelope>]) {
+    threads.iter_mut().for_each(ThreadNode::sort_children);
+    threads.sort_by_cached_key(|thread| std::cmp::Reverse(thread.latest_date()));
+}
+
+/// Represents the list of conversations.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ThreadedEnvelopes(Vec<ThreadNode<Envelope>>);
+
+impl ThreadedEnvelopes {
+    pub fn from_backend(
+        config: &AccountConfig,
+        id_mapper: &IdMapper,
+        folder: &str,
+        threads: Vec<ThreadNode<email::envelope::Envelope>>,
+        details: &HashMap<String, EnvelopeDetails>,
+    ) -> Result<ThreadedEnvelopes> {
+        let mut envelopes = Vec::new();
+        threads
+            .iter()
+            .for_each(|thread| thread.collect_envelopes(&mut envelopes));
+
+        let mut aliases = id_mapper.get_or_create_message_aliases(
+            envelopes
+                .into_iter()
+                .map(|envelope| (envelope.id.as_str(), message_id(envelope))),
+        )?;
+
+        let threads = threads
+            .into_iter()
+            .map(|thread| {
+                thread.try_map(&mut |envelope| {
+                    let alias = aliases.remove(&envelope.id).unwrap_or_default();
+                    let details = details.get(&envelope.id).cloned().unwrap_or_default();
+                    Ok(Envelope::from_backend_with_alias(
+                        config, alias, folder, &envelope, &details,
+                    ))
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(ThreadedEnvelopes(threads))
+    }
+}
+
+impl PrintTable for ThreadedEnvelopes {
+    fn print_table(&self, writer: &mut dyn WriteColor, opts: PrintTableOpts) -> Result<()> {
+        fn flatten(node: &ThreadNode<Envelope>, depth: usize, envelopes: &mut Vec<Envelope>) {
+            let mut envelope = node.envelope.clone();
+            if depth > 0 {
+                envelope.subject = format!("{}└─ {}", "   ".repeat(depth - 1), envelope.subject);
+            }
+            envelopes.push(envelope);
+
+            for child in &node.children {
+                flatten(child, depth + 1, envelopes);
+            }
+        }
+
+        let mut envelopes = Vec::new();
+        for thread in &self.0 {
+            flatten(thread, 0, &mut envelopes);
+        }
+
+        writeln!(writer)?;
+        Table::print(writer, &envelopes, opts)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use email::{envelope::Envelope, message::Message};
+use log::{debug, info};
+use std::fs;
+
+use crate::notmuch::{envelope::envelope_from_msg, NotmuchSession};
+
+use super::{jwz, ThreadHeaders, ThreadNode};
+
+/// Thread all envelopes of the given Notmuch folder.
+///
+/// Notmuch threads are not exposed by the notmuch crate, so
+/// envelopes are threaded locally.
+pub async fn thread_envelopes(
+    session: &NotmuchSession,
+    folder: &str,
+) -> Result<Vec<ThreadNode<Envelope>>> {
+    info!("threading notmuch envelopes from folder {folder}");
+
+    let db = session.open_db()?;
+    let query = db.create_query(&session.folder(folder).query())?;
+
+    let envelopes: Vec<_> = query
+        .search_messages()?
+        .filter_map(|msg| {
+            let path = msg.filename();
+            let headers = match fs::read(&path) {
+                Ok(bytes) => ThreadHeaders::from_msg(&Message::from(bytes)),
+                Err(err) => {
+                    debug!("cannot read notmuch message at {path:?}, skipping it: {err}");
+                    debug!("{err:?}");
+                    return None;
+                }
+            };
+            Some((envelope_from_msg(msg), headers))
+        })
+        .collect();
+
+    db.close()?;
+
+    let threads = jwz::thread(envelopes);
+    debug!("notmuch threads: {threads:#?}");
+
+    Ok(threads)
+}
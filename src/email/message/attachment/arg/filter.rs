@@ -0,0 +1,73 @@
+use clap::Parser;
+use email::message::attachment::Attachment;
+use glob::{MatchOptions, Pattern};
+
+/// The attachment filters arguments parser.
+#[derive(Debug, Parser)]
+pub struct AttachmentFilterArgs {
+    /// Only keep attachments whose filename matches the given glob.
+    ///
+    /// The match is case insensitive: `*.PDF` matches `report.pdf`.
+    /// Attachments without filename never match.
+    #[arg(long, short = 'n', value_name = "GLOB")]
+    pub name: Option<Pattern>,
+
+    /// Only keep attachments of the given MIME type.
+    ///
+    /// The type can be complete (`image/png`) or can omit the subtype
+    /// (`image` or `image/*`).
+    #[arg(long, short = 'm', value_name = "TYPE")]
+    pub mime: Option<String>,
+}
+
+impl AttachmentFilterArgs {
+    /// Return `true` if the given attachment matches the filters.
+    pub fn matches(&self, attachment: &Attachment) -> bool {
+        let name_matches = match &self.name {
+            None => true,
+            Some(pattern) => attachment.filename.as_deref().is_some_and(|name| {
+                let opts = MatchOptions {
+                    case_sensitive: false,
+                    ..Default::default()
+                };
+                pattern.matches_with(name, opts)
+            }),
+        };
+
+        let mime_matches = match &self.mime {
+            None => true,
+            Some(mime) => mime_matches(mime, &attachment.mime),
+        };
+
+        name_matches && mime_matches
+    }
+}
+
+fn mime_matches(filter: &str, mime: &str) -> bool {
+    let filter = filter.trim().to_lowercase();
+    let mime = mime.to_lowercase();
+
+    match filter.split_once('/') {
+        None | Some((_, "*")) => {
+            let ctype = filter.split('/').next().unwrap_or_default();
+            mime.split('/').next() == Some(ctype)
+        }
+        Some(_) => mime == filter,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mime_matches;
+
+    #[test]
+    fn mime_filters() {
+        assert!(mime_matches("image/png", "image/png"));
+        assert!(mime_matches("IMAGE/PNG", "image/png"));
+        assert!(!mime_matches("image/png", "image/jpeg"));
+        assert!(mime_matches("image", "image/jpeg"));
+        assert!(mime_matches("image/*", "image/jpeg"));
+        assert!(!mime_matches("image/*", "application/pdf"));
+        assert!(!mime_matches("application/pdf", "application/pdfx"));
+    }
+}
@@ -6,23 +6,34 @@ use email::message::get::imap::GetMessagesImap;
 use email::{flag::add::maildir::AddFlagsMaildir, message::peek::maildir::PeekMessagesMaildir};
 use log::info;
 use std::{fs, path::PathBuf};
-use uuid::Uuid;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::{AddFlagsJmap, PeekMessagesJmap};
+#[cfg(feature = "mbox")]
+use crate::mbox::{AddFlagsMbox, PeekMessagesMbox};
+#[cfg(feature = "notmuch")]
+use crate::notmuch::{AddFlagsNotmuch, PeekMessagesNotmuch};
+#[cfg(feature = "pop")]
+use crate::pop::GetMessagesPop;
 use crate::{
     account::arg::name::AccountNameFlag,
     backend::{Backend, BackendKind},
     config::TomlConfig,
     envelope::arg::ids::EnvelopeIdsArgs,
     folder::arg::name::FolderNameOptionalFlag,
+    message::attachment::{self, arg::filter::AttachmentFilterArgs},
     printer::Printer,
 };
 
-/// Download all attachments for the given message.
+/// Download attachments for the given message.
 ///
 /// This command allows you to download all attachments found for the
-/// given message to your downloads directory.
+/// given message to your downloads directory. Attachments can be
+/// filtered by filename or by MIME type. Existing files are never
+/// overwritten, unless asked: a counter suffix is added to the name
+/// of the downloaded file instead.
 #[derive(Debug, Parser)]
 pub struct AttachmentDownloadCommand {
     #[command(flatten)]
@@ -31,6 +42,26 @@ pub struct AttachmentDownloadCommand {
     #[command(flatten)]
     pub envelopes: EnvelopeIdsArgs,
 
+    #[command(flatten)]
+    pub filter: AttachmentFilterArgs,
+
+    /// Download attachments to the given directory.
+    ///
+    /// The directory is created if it does not exist. Defaults to the
+    /// downloads directory of the account.
+    #[arg(long, short, value_name = "PATH")]
+    pub dir: Option<PathBuf>,
+
+    /// Overwrite existing files.
+    #[arg(long, conflicts_with = "rename")]
+    pub overwrite: bool,
+
+    /// Add a counter suffix to the name of files that already exist.
+    ///
+    /// This is the default behaviour, unless --overwrite is given.
+    #[arg(long, conflicts_with = "overwrite")]
+    pub rename: bool,
+
     #[cfg(feature = "account-sync")]
     #[command(flatten)]
     pub cache: CacheDisableFlag,
@@ -64,6 +95,12 @@ impl AttachmentDownloadCommand {
                     builder
                         .set_get_messages(|ctx| ctx.imap.as_ref().and_then(GetMessagesImap::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.jmap.as_ref().and_then(PeekMessagesJmap::new));
+                    builder.set_add_flags(|ctx| ctx.jmap.as_ref().and_then(AddFlagsJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder.set_peek_messages(|ctx| {
@@ -83,11 +120,36 @@ impl AttachmentDownloadCommand {
                         ctx.maildir_for_sync.as_ref().and_then(AddFlagsMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.mbox.as_ref().and_then(PeekMessagesMbox::new));
+                    builder.set_add_flags(|ctx| ctx.mbox.as_ref().and_then(AddFlagsMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.notmuch.as_ref().and_then(PeekMessagesNotmuch::new)
+                    });
+                    builder
+                        .set_add_flags(|ctx| ctx.notmuch.as_ref().and_then(AddFlagsNotmuch::new));
+                }
+                #[cfg(feature = "pop")]
+                Some(BackendKind::Pop) => {
+                    builder.set_get_messages(|ctx| ctx.pop.as_ref().and_then(GetMessagesPop::new));
+                }
                 _ => (),
             },
         )
         .await?;
 
+        let dir = match self.dir {
+            Some(dir) => dir,
+            None => account_config.get_downloads_dir(),
+        };
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("cannot create downloads directory at {dir:?}"))?;
+
         let emails = backend.get_messages(folder, ids).await?;
 
         let mut emails_count = 0;
@@ -96,7 +158,11 @@ impl AttachmentDownloadCommand {
         let mut ids = ids.iter();
         for email in emails.to_vec() {
             let id = ids.next().unwrap();
-            let attachments = email.attachments()?;
+            let attachments: Vec<_> = email
+                .attachments()?
+                .into_iter()
+                .filter(|attachment| self.filter.matches(attachment))
+                .collect();
 
             if attachments.is_empty() {
                 printer.print_log(format!("No attachment found for message {id}!"))?;
@@ -111,11 +177,13 @@ impl AttachmentDownloadCommand {
             ))?;
 
             for attachment in attachments {
-                let filename: PathBuf = attachment
-                    .filename
-                    .unwrap_or_else(|| Uuid::new_v4().to_string())
-                    .into();
-                let filepath = account_config.get_download_file_path(&filename)?;
+                let filename = attachment.filename.unwrap_or_default();
+                let mut filepath = attachment::download_path(&dir, &filename);
+                // renaming is the default policy, --rename only makes
+                // it explicit
+                if self.rename || !self.overwrite {
+                    filepath = attachment::rename_if_exists(filepath, |path| path.exists());
+                }
                 printer.print_log(format!("Downloading {:?}…", filepath))?;
                 fs::write(&filepath, &attachment.body)
                     .with_context(|| format!("cannot save attachment at {filepath:?}"))?;
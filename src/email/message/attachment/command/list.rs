@@ -0,0 +1,150 @@
+use anyhow::Result;
+use clap::Parser;
+#[cfg(feature = "imap")]
+use email::message::peek::imap::PeekMessagesImap;
+#[cfg(feature = "maildir")]
+use email::message::peek::maildir::PeekMessagesMaildir;
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::PeekMessagesJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::PeekMessagesMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::PeekMessagesNotmuch;
+#[cfg(feature = "pop")]
+use crate::pop::PeekMessagesPop;
+#[allow(unused)]
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{Backend, BackendKind},
+    config::TomlConfig,
+    envelope::arg::ids::EnvelopeIdsArgs,
+    folder::arg::name::FolderNameOptionalFlag,
+    message::attachment::{arg::filter::AttachmentFilterArgs, AttachmentEntries, AttachmentEntry},
+    printer::{PrintTableOpts, Printer},
+    ui::arg::max_width::TableMaxWidthFlag,
+};
+
+/// List attachments of the given messages.
+///
+/// This command lists the attachments found for the given messages,
+/// with their filename, MIME type and size, so that they can be
+/// selected before being downloaded. The messages are not marked as
+/// seen.
+#[derive(Debug, Parser)]
+pub struct AttachmentListCommand {
+    #[command(flatten)]
+    pub folder: FolderNameOptionalFlag,
+
+    #[command(flatten)]
+    pub envelopes: EnvelopeIdsArgs,
+
+    #[command(flatten)]
+    pub filter: AttachmentFilterArgs,
+
+    #[command(flatten)]
+    pub table: TableMaxWidthFlag,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl AttachmentListCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing list attachments command");
+
+        let folder = &self.folder.name;
+        let ids = &self.envelopes.ids;
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let get_messages_kind = toml_account_config.get_messages_kind();
+
+        let backend = Backend::new(
+            &toml_account_config,
+            &account_config,
+            get_messages_kind,
+            |#[allow(unused)] builder| match get_messages_kind {
+                #[cfg(feature = "imap")]
+                Some(BackendKind::Imap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.imap.as_ref().and_then(PeekMessagesImap::new));
+                }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.jmap.as_ref().and_then(PeekMessagesJmap::new));
+                }
+                #[cfg(feature = "maildir")]
+                Some(BackendKind::Maildir) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.maildir.as_ref().and_then(PeekMessagesMaildir::new)
+                    });
+                }
+                #[cfg(feature = "account-sync")]
+                Some(BackendKind::MaildirForSync) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.maildir_for_sync
+                            .as_ref()
+                            .and_then(PeekMessagesMaildir::new)
+                    });
+                }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.mbox.as_ref().and_then(PeekMessagesMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.notmuch.as_ref().and_then(PeekMessagesNotmuch::new)
+                    });
+                }
+                #[cfg(feature = "pop")]
+                Some(BackendKind::Pop) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.pop.as_ref().and_then(PeekMessagesPop::new));
+                }
+                _ => (),
+            },
+        )
+        .await?;
+
+        let emails = backend.peek_messages(folder, ids).await?;
+
+        let mut attachments = Vec::new();
+        for (id, email) in ids.iter().zip(emails.to_vec()) {
+            for attachment in email.attachments()? {
+                if !self.filter.matches(&attachment) {
+                    continue;
+                }
+
+                attachments.push(AttachmentEntry {
+                    id: *id,
+                    filename: attachment.filename,
+                    mime: attachment.mime,
+                    size: attachment.body.len(),
+                });
+            }
+        }
+
+        printer.print_table(
+            Box::new(attachments.into_iter().collect::<AttachmentEntries>()),
+            PrintTableOpts {
+                format: &account_config.get_message_read_format(),
+                max_width: self.table.max_width,
+            },
+        )
+    }
+}
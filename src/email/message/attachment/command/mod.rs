@@ -1,5 +1,7 @@
 #[cfg(feature = "attachment-download")]
 pub mod download;
+#[cfg(feature = "attachment-list")]
+pub mod list;
 
 use anyhow::Result;
 use clap::Subcommand;
@@ -8,6 +10,8 @@ use crate::{config::TomlConfig, printer::Printer};
 
 #[cfg(feature = "attachment-download")]
 use self::download::AttachmentDownloadCommand;
+#[cfg(feature = "attachment-list")]
+use self::list::AttachmentListCommand;
 
 /// Manage attachments.
 ///
@@ -16,6 +20,11 @@ use self::download::AttachmentDownloadCommand;
 /// body.
 #[derive(Debug, Subcommand)]
 pub enum AttachmentSubcommand {
+    #[cfg(feature = "attachment-list")]
+    #[command(arg_required_else_help = true)]
+    #[command(alias = "lst")]
+    List(AttachmentListCommand),
+
     #[cfg(feature = "attachment-download")]
     #[command(arg_required_else_help = true)]
     Download(AttachmentDownloadCommand),
@@ -25,6 +34,8 @@ impl AttachmentSubcommand {
     #[allow(unused)]
     pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
         match self {
+            #[cfg(feature = "attachment-list")]
+            Self::List(cmd) => cmd.execute(printer, config).await,
             #[cfg(feature = "attachment-download")]
             Self::Download(cmd) => cmd.execute(printer, config).await,
         }
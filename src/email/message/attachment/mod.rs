@@ -1 +1,176 @@
+#[cfg(any(feature = "attachment-list", feature = "attachment-download"))]
+pub mod arg;
 pub mod command;
+
+#[cfg(feature = "attachment-list")]
+use anyhow::Result;
+#[cfg(feature = "attachment-list")]
+use serde::Serialize;
+#[cfg(feature = "attachment-list")]
+use std::ops;
+#[cfg(feature = "attachment-download")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "attachment-download")]
+use uuid::Uuid;
+
+#[cfg(feature = "attachment-list")]
+use crate::{
+    printer::{PrintTable, PrintTableOpts, WriteColor},
+    ui::{human_size, Cell, Row, Table},
+};
+
+/// An attachment of a message, without its content.
+#[cfg(feature = "attachment-list")]
+#[derive(Clone, Debug, Serialize)]
+pub struct AttachmentEntry {
+    /// The identifier of the message the attachment belongs to.
+    pub id: usize,
+    pub filename: Option<String>,
+    pub mime: String,
+    pub size: usize,
+}
+
+#[cfg(feature = "attachment-list")]
+impl Table for AttachmentEntry {
+    fn head() -> Row {
+        Row::new()
+            .cell(Cell::new("ID").bold().underline().white())
+            .cell(
+                Cell::new("FILENAME")
+                    .shrinkable()
+                    .bold()
+                    .underline()
+                    .white(),
+            )
+            .cell(Cell::new("MIME").bold().underline().white())
+            .cell(Cell::new("SIZE").bold().underline().white())
+    }
+
+    fn row(&self) -> Row {
+        Row::new()
+            .cell(Cell::new(self.id.to_string()).red())
+            .cell(
+                Cell::new(self.filename.as_deref().unwrap_or_default())
+                    .shrinkable()
+                    .blue(),
+            )
+            .cell(Cell::new(&self.mime).green())
+            .cell(Cell::new(human_size(self.size as u64)).yellow())
+    }
+}
+
+/// The list of attachments of one or more messages.
+#[cfg(feature = "attachment-list")]
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct AttachmentEntries(Vec<AttachmentEntry>);
+
+#[cfg(feature = "attachment-list")]
+impl ops::Deref for AttachmentEntries {
+    type Target = Vec<AttachmentEntry>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "attachment-list")]
+impl FromIterator<AttachmentEntry> for AttachmentEntries {
+    fn from_iter<T: IntoIterator<Item = AttachmentEntry>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "attachment-list")]
+impl PrintTable for AttachmentEntries {
+    fn print_table(&self, writer: &mut dyn WriteColor, opts: PrintTableOpts) -> Result<()> {
+        writeln!(writer)?;
+        Table::print(writer, self, opts)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+/// Return the path the attachment with the given filename should be
+/// downloaded to.
+///
+/// Only the file name of the given filename is kept, so that
+/// attachments cannot be written outside of the given directory. A
+/// random name is used when there is no file name to keep (an empty
+/// filename or `..` for example).
+#[cfg(feature = "attachment-download")]
+pub fn download_path(dir: &Path, filename: &str) -> PathBuf {
+    match Path::new(filename).file_name() {
+        Some(name) => dir.join(name),
+        None => dir.join(Uuid::new_v4().to_string()),
+    }
+}
+
+/// Return the given path, or a new one with an auto-incremented
+/// counter suffix if it already exists (`report_1.pdf`,
+/// `report_2.pdf` etc).
+#[cfg(feature = "attachment-download")]
+pub fn rename_if_exists(path: PathBuf, exists: impl Fn(&Path) -> bool) -> PathBuf {
+    if !exists(&path) {
+        return path;
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut count = 1;
+    loop {
+        let renamed = path.with_file_name(format!("{stem}_{count}{ext}"));
+        if !exists(&renamed) {
+            return renamed;
+        }
+        count += 1;
+    }
+}
+
+#[cfg(all(test, feature = "attachment-download"))]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{download_path, rename_if_exists};
+
+    #[test]
+    fn download_path_keeps_file_name() {
+        let dir = Path::new("/downloads");
+
+        assert_eq!(
+            download_path(dir, "../../.bashrc"),
+            PathBuf::from("/downloads/.bashrc")
+        );
+
+        for filename in ["..", ""] {
+            let path = download_path(dir, filename);
+            assert_eq!(path.parent(), Some(dir));
+            assert!(path.file_name().is_some());
+        }
+    }
+
+    #[test]
+    fn rename_duplicates() {
+        let path = PathBuf::from("/downloads/report.pdf");
+
+        assert_eq!(rename_if_exists(path.clone(), |_| false), path);
+        assert_eq!(
+            rename_if_exists(path.clone(), |p| p == Path::new("/downloads/report.pdf")),
+            PathBuf::from("/downloads/report_1.pdf")
+        );
+        assert_eq!(
+            rename_if_exists(path, |p| !p.ends_with("report_3.pdf")),
+            PathBuf::from("/downloads/report_3.pdf")
+        );
+        assert_eq!(
+            rename_if_exists(PathBuf::from("/downloads/notes"), |p| p.ends_with("notes")),
+            PathBuf::from("/downloads/notes_1")
+        );
+    }
+}
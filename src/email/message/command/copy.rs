@@ -8,6 +8,12 @@ use log::info;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::CopyMessagesJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::CopyMessagesMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::CopyMessagesNotmuch;
 #[allow(unused)]
 use crate::{
     account::arg::name::AccountNameFlag,
@@ -64,6 +70,11 @@ impl MessageCopyCommand {
                     builder
                         .set_copy_messages(|ctx| ctx.imap.as_ref().and_then(CopyMessagesImap::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_copy_messages(|ctx| ctx.jmap.as_ref().and_then(CopyMessagesJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder.set_copy_messages(|ctx| {
@@ -78,6 +89,17 @@ impl MessageCopyCommand {
                             .and_then(CopyMessagesMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_copy_messages(|ctx| ctx.mbox.as_ref().and_then(CopyMessagesMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_copy_messages(|ctx| {
+                        ctx.notmuch.as_ref().and_then(CopyMessagesNotmuch::new)
+                    });
+                }
                 _ => (),
             },
         )
@@ -8,6 +8,12 @@ use log::info;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::{AddFlagsJmap, MoveMessagesJmap};
+#[cfg(feature = "mbox")]
+use crate::mbox::{AddFlagsMbox, MoveMessagesMbox};
+#[cfg(feature = "notmuch")]
+use crate::notmuch::{AddFlagsNotmuch, MoveMessagesNotmuch};
 #[allow(unused)]
 use crate::{
     account::arg::name::AccountNameFlag,
@@ -66,6 +72,12 @@ impl MessageDeleteCommand {
                         .set_move_messages(|ctx| ctx.imap.as_ref().and_then(MoveMessagesImap::new));
                     builder.set_add_flags(|ctx| ctx.imap.as_ref().and_then(AddFlagsImap::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_move_messages(|ctx| ctx.jmap.as_ref().and_then(MoveMessagesJmap::new));
+                    builder.set_add_flags(|ctx| ctx.jmap.as_ref().and_then(AddFlagsJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder.set_move_messages(|ctx| {
@@ -85,6 +97,20 @@ impl MessageDeleteCommand {
                         ctx.maildir_for_sync.as_ref().and_then(AddFlagsMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_move_messages(|ctx| ctx.mbox.as_ref().and_then(MoveMessagesMbox::new));
+                    builder.set_add_flags(|ctx| ctx.mbox.as_ref().and_then(AddFlagsMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_move_messages(|ctx| {
+                        ctx.notmuch.as_ref().and_then(MoveMessagesNotmuch::new)
+                    });
+                    builder
+                        .set_add_flags(|ctx| ctx.notmuch.as_ref().and_then(AddFlagsNotmuch::new));
+                }
                 _ => (),
             },
         )
@@ -0,0 +1,250 @@
+use anyhow::{anyhow, Result};
+use clap::Parser;
+#[cfg(feature = "maildir")]
+use email::flag::add::maildir::AddFlagsMaildir;
+use email::folder::DRAFTS;
+#[cfg(feature = "sendmail")]
+use email::message::send::sendmail::SendMessageSendmail;
+#[cfg(feature = "smtp")]
+use email::message::send::smtp::SendMessageSmtp;
+#[cfg(feature = "imap")]
+use email::message::{add::imap::AddImapMessage, get::imap::GetMessagesImap};
+#[cfg(feature = "maildir")]
+use email::message::{add::maildir::AddMaildirMessage, peek::maildir::PeekMessagesMaildir};
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::{AddFlagsJmap, AddMessageJmap, PeekMessagesJmap, SendMessageJmap};
+#[cfg(feature = "mbox")]
+use crate::mbox::{AddFlagsMbox, AddMessageMbox, PeekMessagesMbox};
+#[cfg(feature = "notmuch")]
+use crate::notmuch::{AddFlagsNotmuch, AddMessageNotmuch, PeekMessagesNotmuch};
+#[allow(unused)]
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{Backend, BackendKind},
+    config::TomlConfig,
+    envelope::arg::ids::EnvelopeIdArg,
+    message::draft::{Drafts, RemoteDraft},
+    printer::Printer,
+    ui::editor,
+};
+
+/// Edit a draft.
+///
+/// This command loads the given message from the drafts folder into a
+/// template, then opens it with the editor defined in your
+/// environment variable $EDITOR. Attachments are extracted next to
+/// the local draft, so they are kept when the template is compiled
+/// again. Sending or saving the message, even later with the `draft`
+/// commands, removes the original draft: it is expunged when the
+/// backend can expunge a single message (IMAP with UIDPLUS),
+/// otherwise it is deleted like with `message delete`.
+#[derive(Debug, Parser)]
+pub struct MessageEditCommand {
+    /// The name of the folder containing the draft.
+    #[arg(long = "folder", short = 'f')]
+    #[arg(name = "folder_name", value_name = "NAME", default_value = DRAFTS)]
+    pub folder: String,
+
+    #[command(flatten)]
+    pub envelope: EnvelopeIdArg,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl MessageEditCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing edit message command");
+
+        let folder = &self.folder;
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let get_messages_kind = toml_account_config.get_messages_kind();
+        let add_message_kind = toml_account_config.add_message_kind();
+        let send_message_kind = toml_account_config.send_message_kind();
+
+        let backend = Backend::new(
+            &toml_account_config,
+            &account_config,
+            get_messages_kind
+                .into_iter()
+                .chain(add_message_kind)
+                .chain(send_message_kind),
+            |#[allow(unused)] builder| {
+                match get_messages_kind {
+                    #[cfg(feature = "imap")]
+                    Some(BackendKind::Imap) => {
+                        builder.set_get_messages(|ctx| {
+                            ctx.imap.as_ref().and_then(GetMessagesImap::new)
+                        });
+                    }
+                    #[cfg(feature = "jmap")]
+                    Some(BackendKind::Jmap) => {
+                        builder.set_peek_messages(|ctx| {
+                            ctx.jmap.as_ref().and_then(PeekMessagesJmap::new)
+                        });
+                        builder.set_add_flags(|ctx| ctx.jmap.as_ref().and_then(AddFlagsJmap::new));
+                    }
+                    #[cfg(feature = "maildir")]
+                    Some(BackendKind::Maildir) => {
+                        builder.set_peek_messages(|ctx| {
+                            ctx.maildir.as_ref().and_then(PeekMessagesMaildir::new)
+                        });
+                        builder.set_add_flags(|ctx| {
+                            ctx.maildir.as_ref().and_then(AddFlagsMaildir::new)
+                        });
+                    }
+                    #[cfg(feature = "account-sync")]
+                    Some(BackendKind::MaildirForSync) => {
+                        builder.set_peek_messages(|ctx| {
+                            ctx.maildir_for_sync
+                                .as_ref()
+                                .and_then(PeekMessagesMaildir::new)
+                        });
+                        builder.set_add_flags(|ctx| {
+                            ctx.maildir_for_sync.as_ref().and_then(AddFlagsMaildir::new)
+                        });
+                    }
+                    #[cfg(feature = "mbox")]
+                    Some(BackendKind::Mbox) => {
+                        builder.set_peek_messages(|ctx| {
+                            ctx.mbox.as_ref().and_then(PeekMessagesMbox::new)
+                        });
+                        builder.set_add_flags(|ctx| ctx.mbox.as_ref().and_then(AddFlagsMbox::new));
+                    }
+                    #[cfg(feature = "notmuch")]
+                    Some(BackendKind::Notmuch) => {
+                        builder.set_peek_messages(|ctx| {
+                            ctx.notmuch.as_ref().and_then(PeekMessagesNotmuch::new)
+                        });
+                        builder.set_add_flags(|ctx| {
+                            ctx.notmuch.as_ref().and_then(AddFlagsNotmuch::new)
+                        });
+                    }
+                    _ => (),
+                };
+
+                match add_message_kind {
+                    #[cfg(feature = "imap")]
+                    Some(BackendKind::Imap) => {
+                        builder
+                            .set_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new));
+                    }
+                    #[cfg(feature = "jmap")]
+                    Some(BackendKind::Jmap) => {
+                        builder
+                            .set_add_message(|ctx| ctx.jmap.as_ref().and_then(AddMessageJmap::new));
+                    }
+                    #[cfg(feature = "maildir")]
+                    Some(BackendKind::Maildir) => {
+                        builder.set_add_message(|ctx| {
+                            ctx.maildir.as_ref().and_then(AddMaildirMessage::new)
+                        });
+                    }
+                    #[cfg(feature = "account-sync")]
+                    Some(BackendKind::MaildirForSync) => {
+                        builder.set_add_message(|ctx| {
+                            ctx.maildir_for_sync
+                                .as_ref()
+                                .and_then(AddMaildirMessage::new)
+                        });
+                    }
+                    #[cfg(feature = "mbox")]
+                    Some(BackendKind::Mbox) => {
+                        builder
+                            .set_add_message(|ctx| ctx.mbox.as_ref().and_then(AddMessageMbox::new));
+                    }
+                    #[cfg(feature = "notmuch")]
+                    Some(BackendKind::Notmuch) => {
+                        builder.set_add_message(|ctx| {
+                            ctx.notmuch.as_ref().and_then(AddMessageNotmuch::new)
+                        });
+                    }
+                    _ => (),
+                };
+
+                match send_message_kind {
+                    #[cfg(feature = "smtp")]
+                    Some(BackendKind::Smtp) => {
+                        builder.set_send_message(|ctx| {
+                            ctx.smtp.as_ref().and_then(SendMessageSmtp::new)
+                        });
+                    }
+                    #[cfg(feature = "jmap")]
+                    Some(BackendKind::Jmap) => {
+                        builder.set_send_message(|ctx| {
+                            ctx.jmap.as_ref().and_then(SendMessageJmap::new)
+                        });
+                    }
+                    #[cfg(feature = "sendmail")]
+                    Some(BackendKind::Sendmail) => {
+                        builder.set_send_message(|ctx| {
+                            ctx.sendmail.as_ref().and_then(SendMessageSendmail::new)
+                        });
+                    }
+                    _ => (),
+                };
+            },
+        )
+        .await?;
+
+        let id = self.envelope.id;
+        let msgs = backend.get_messages(folder, &[id]).await?;
+        let msg = msgs.first().ok_or(anyhow!("cannot find message {id}"))?;
+
+        // the local draft is only created once the message has been
+        // fetched, so that no empty draft is left behind
+        let drafts = Drafts::open(&account_config)?;
+        let draft_id = drafts.create("")?;
+
+        let tpl = async {
+            let remote_draft = RemoteDraft {
+                folder: folder.clone(),
+                id,
+            };
+            drafts.set_remote(&draft_id, &remote_draft)?;
+
+            let tpl = msg
+                .to_read_tpl(&account_config, |tpl| {
+                    tpl.with_show_only_headers([
+                        "From",
+                        "To",
+                        "Cc",
+                        "Bcc",
+                        "Reply-To",
+                        "Subject",
+                        "In-Reply-To",
+                        "References",
+                    ])
+                    .with_show_multiparts(true)
+                    .with_show_attachments(true)
+                    .with_show_inline_attachments(true)
+                    .with_save_attachments(true)
+                    .with_save_attachments_dir(drafts.attachments_dir(&draft_id))
+                })
+                .await?;
+
+            drafts.save(&draft_id, &tpl)
+        }
+        .await;
+
+        if let Err(err) = tpl {
+            drafts.remove(&draft_id)?;
+            return Err(err);
+        }
+
+        editor::edit_draft_with_editor(&account_config, printer, &backend, &drafts, &draft_id).await
+    }
+}
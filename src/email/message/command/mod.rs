@@ -2,12 +2,16 @@
 pub mod copy;
 #[cfg(feature = "message-delete")]
 pub mod delete;
+#[cfg(feature = "message-edit")]
+pub mod edit;
 #[cfg(feature = "message-forward")]
 pub mod forward;
 #[cfg(feature = "message-mailto")]
 pub mod mailto;
 #[cfg(feature = "message-move")]
 pub mod move_;
+#[cfg(feature = "message-part")]
+pub mod part;
 #[cfg(feature = "message-read")]
 pub mod read;
 #[cfg(feature = "message-reply")]
@@ -16,6 +20,8 @@ pub mod reply;
 pub mod save;
 #[cfg(feature = "message-send")]
 pub mod send;
+#[cfg(feature = "message-structure")]
+pub mod structure;
 #[cfg(feature = "message-write")]
 pub mod write;
 
@@ -28,12 +34,16 @@ use crate::{config::TomlConfig, printer::Printer};
 use self::copy::MessageCopyCommand;
 #[cfg(feature = "message-delete")]
 use self::delete::MessageDeleteCommand;
+#[cfg(feature = "message-edit")]
+use self::edit::MessageEditCommand;
 #[cfg(feature = "message-forward")]
 use self::forward::MessageForwardCommand;
 #[cfg(feature = "message-mailto")]
 use self::mailto::MessageMailtoCommand;
 #[cfg(feature = "message-move")]
 use self::move_::MessageMoveCommand;
+#[cfg(feature = "message-part")]
+use self::part::MessagePartCommand;
 #[cfg(feature = "message-read")]
 use self::read::MessageReadCommand;
 #[cfg(feature = "message-reply")]
@@ -42,6 +52,8 @@ use self::reply::MessageReplyCommand;
 use self::save::MessageSaveCommand;
 #[cfg(feature = "message-send")]
 use self::send::MessageSendCommand;
+#[cfg(feature = "message-structure")]
+use self::structure::MessageStructureCommand;
 #[cfg(feature = "message-write")]
 use self::write::MessageWriteCommand;
 
@@ -57,10 +69,23 @@ pub enum MessageSubcommand {
     #[command(arg_required_else_help = true)]
     Read(MessageReadCommand),
 
+    #[cfg(feature = "message-structure")]
+    #[command(arg_required_else_help = true)]
+    #[command(alias = "mime")]
+    Structure(MessageStructureCommand),
+
+    #[cfg(feature = "message-part")]
+    #[command(arg_required_else_help = true)]
+    Part(MessagePartCommand),
+
     #[cfg(feature = "message-write")]
     #[command(aliases = ["add", "create", "new", "compose"])]
     Write(MessageWriteCommand),
 
+    #[cfg(feature = "message-edit")]
+    #[command(arg_required_else_help = true)]
+    Edit(MessageEditCommand),
+
     #[cfg(feature = "message-reply")]
     #[command()]
     Reply(MessageReplyCommand),
@@ -103,8 +128,14 @@ impl MessageSubcommand {
         match self {
             #[cfg(feature = "message-read")]
             Self::Read(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "message-structure")]
+            Self::Structure(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "message-part")]
+            Self::Part(cmd) => cmd.execute(printer, config).await,
             #[cfg(feature = "message-write")]
             Self::Write(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "message-edit")]
+            Self::Edit(cmd) => cmd.execute(printer, config).await,
             #[cfg(feature = "message-reply")]
             Self::Reply(cmd) => cmd.execute(printer, config).await,
             #[cfg(feature = "message-forward")]
@@ -8,6 +8,12 @@ use log::info;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::MoveMessagesJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::MoveMessagesMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::MoveMessagesNotmuch;
 #[allow(unused)]
 use crate::{
     account::arg::name::AccountNameFlag,
@@ -64,6 +70,11 @@ impl MessageMoveCommand {
                     builder
                         .set_move_messages(|ctx| ctx.imap.as_ref().and_then(MoveMessagesImap::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_move_messages(|ctx| ctx.jmap.as_ref().and_then(MoveMessagesJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder.set_move_messages(|ctx| {
@@ -78,6 +89,17 @@ impl MessageMoveCommand {
                             .and_then(MoveMessagesMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_move_messages(|ctx| ctx.mbox.as_ref().and_then(MoveMessagesMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_move_messages(|ctx| {
+                        ctx.notmuch.as_ref().and_then(MoveMessagesNotmuch::new)
+                    });
+                }
                 _ => (),
             },
         )
@@ -0,0 +1,169 @@
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+#[cfg(feature = "imap")]
+use email::message::peek::imap::PeekMessagesImap;
+#[cfg(feature = "maildir")]
+use email::message::peek::maildir::PeekMessagesMaildir;
+use log::info;
+use std::{
+    fs,
+    io::{self, IsTerminal, Write},
+    path::PathBuf,
+};
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::PeekMessagesJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::PeekMessagesMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::PeekMessagesNotmuch;
+#[cfg(feature = "pop")]
+use crate::pop::PeekMessagesPop;
+#[allow(unused)]
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{Backend, BackendKind},
+    config::TomlConfig,
+    envelope::arg::ids::EnvelopeIdArg,
+    folder::arg::name::FolderNameOptionalFlag,
+    message::structure,
+    printer::Printer,
+};
+
+/// Extract a MIME part of a message.
+///
+/// This command extracts the part of the given message at the given
+/// index, as displayed by the message structure command. The content
+/// is decoded from its transfer encoding (base64, quoted-printable).
+/// Unless an output path is given, raw bytes are written to the
+/// standard output when it is not a terminal, which allows piping. On
+/// a terminal, only textual parts are displayed. The message is not
+/// marked as seen.
+#[derive(Debug, Parser)]
+pub struct MessagePartCommand {
+    #[command(flatten)]
+    pub folder: FolderNameOptionalFlag,
+
+    #[command(flatten)]
+    pub envelope: EnvelopeIdArg,
+
+    /// The index of the part, as displayed by the message structure
+    /// command.
+    #[arg(value_name = "PART-INDEX")]
+    pub index: usize,
+
+    // NOTE: `--output` is already taken by the global output format
+    /// Write the part to the given file path instead.
+    #[arg(long = "output-file", short = 'O', value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl MessagePartCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing message part command");
+
+        let folder = &self.folder.name;
+        let id = self.envelope.id;
+        let index = self.index;
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let get_messages_kind = toml_account_config.get_messages_kind();
+
+        let backend = Backend::new(
+            &toml_account_config,
+            &account_config,
+            get_messages_kind,
+            |#[allow(unused)] builder| match get_messages_kind {
+                #[cfg(feature = "imap")]
+                Some(BackendKind::Imap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.imap.as_ref().and_then(PeekMessagesImap::new));
+                }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.jmap.as_ref().and_then(PeekMessagesJmap::new));
+                }
+                #[cfg(feature = "maildir")]
+                Some(BackendKind::Maildir) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.maildir.as_ref().and_then(PeekMessagesMaildir::new)
+                    });
+                }
+                #[cfg(feature = "account-sync")]
+                Some(BackendKind::MaildirForSync) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.maildir_for_sync
+                            .as_ref()
+                            .and_then(PeekMessagesMaildir::new)
+                    });
+                }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.mbox.as_ref().and_then(PeekMessagesMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.notmuch.as_ref().and_then(PeekMessagesNotmuch::new)
+                    });
+                }
+                #[cfg(feature = "pop")]
+                Some(BackendKind::Pop) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.pop.as_ref().and_then(PeekMessagesPop::new));
+                }
+                _ => (),
+            },
+        )
+        .await?;
+
+        let emails = backend.peek_messages(folder, &[id]).await?;
+        let email = emails.first().ok_or(anyhow!("cannot find message {id}"))?;
+        let msg = email.parsed()?;
+        let part = structure::part(msg, index)
+            .ok_or(anyhow!("cannot find part {index} of message {id}"))?;
+
+        if part.is_multipart() {
+            bail!("cannot extract part {index} of message {id}: multipart has no content");
+        }
+
+        let contents = part.contents();
+
+        if let Some(path) = self.output {
+            fs::write(&path, contents)
+                .with_context(|| format!("cannot save part {index} at {path:?}"))?;
+            return printer.print(format!(
+                "Part {index} of message {id} successfully saved to {path:?}!"
+            ));
+        }
+
+        if io::stdout().is_terminal() {
+            match part.text_contents() {
+                Some(text) => printer.print(text.to_owned()),
+                None => bail!("cannot display binary part {index} of message {id} in a terminal"),
+            }
+        } else {
+            let mut stdout = io::stdout().lock();
+            stdout
+                .write_all(contents)
+                .and_then(|()| stdout.flush())
+                .context("cannot write part to standard output")
+        }
+    }
+}
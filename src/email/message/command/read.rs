@@ -9,6 +9,14 @@ use mml::message::FilterParts;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::{AddFlagsJmap, PeekMessagesJmap};
+#[cfg(feature = "mbox")]
+use crate::mbox::{AddFlagsMbox, PeekMessagesMbox};
+#[cfg(feature = "notmuch")]
+use crate::notmuch::{AddFlagsNotmuch, PeekMessagesNotmuch};
+#[cfg(feature = "pop")]
+use crate::pop::{GetMessagesPop, PeekMessagesPop};
 #[allow(unused)]
 use crate::{
     account::arg::name::AccountNameFlag,
@@ -16,7 +24,8 @@ use crate::{
     config::TomlConfig,
     envelope::arg::ids::EnvelopeIdsArgs,
     folder::arg::name::FolderNameOptionalFlag,
-    printer::Printer,
+    message::html,
+    printer::{pager, Printer},
 };
 
 /// Read a message.
@@ -76,6 +85,14 @@ pub struct MessageReadCommand {
     #[arg(conflicts_with = "no_headers")]
     pub headers: Vec<String>,
 
+    /// Do not page the output.
+    ///
+    /// When the output is a terminal, messages are paged with the
+    /// pager set up in your TOML configuration file, or the one
+    /// defined in your environment variable $PAGER, or `less -R`.
+    #[arg(long)]
+    pub no_pager: bool,
+
     #[cfg(feature = "account-sync")]
     #[command(flatten)]
     pub cache: CacheDisableFlag,
@@ -111,6 +128,12 @@ impl MessageReadCommand {
                     builder
                         .set_get_messages(|ctx| ctx.imap.as_ref().and_then(GetMessagesImap::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.jmap.as_ref().and_then(PeekMessagesJmap::new));
+                    builder.set_add_flags(|ctx| ctx.jmap.as_ref().and_then(AddFlagsJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder.set_peek_messages(|ctx| {
@@ -130,6 +153,26 @@ impl MessageReadCommand {
                         ctx.maildir_for_sync.as_ref().and_then(AddFlagsMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.mbox.as_ref().and_then(PeekMessagesMbox::new));
+                    builder.set_add_flags(|ctx| ctx.mbox.as_ref().and_then(AddFlagsMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.notmuch.as_ref().and_then(PeekMessagesNotmuch::new)
+                    });
+                    builder
+                        .set_add_flags(|ctx| ctx.notmuch.as_ref().and_then(AddFlagsNotmuch::new));
+                }
+                #[cfg(feature = "pop")]
+                Some(BackendKind::Pop) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.pop.as_ref().and_then(PeekMessagesPop::new));
+                    builder.set_get_messages(|ctx| ctx.pop.as_ref().and_then(GetMessagesPop::new));
+                }
                 _ => (),
             },
         )
@@ -141,6 +184,12 @@ impl MessageReadCommand {
             backend.get_messages(folder, ids).await
         }?;
 
+        let html_renderer = toml_account_config
+            .read_messages_html_renderer()
+            .cloned()
+            .unwrap_or_default();
+        let html_width = html::width(&account_config);
+
         let mut glue = "";
         let mut bodies = String::default();
 
@@ -152,6 +201,10 @@ impl MessageReadCommand {
                 // display what can be displayed
                 bodies.push_str(&String::from_utf8_lossy(email.raw()?));
             } else {
+                // HTML-only messages are rendered by the configured
+                // HTML renderer, the template only holds their headers
+                let html_only = !self.html && html::is_html_only(email.parsed()?);
+
                 let tpl = email
                     .to_read_tpl(&account_config, |mut tpl| {
                         if self.no_headers {
@@ -162,17 +215,44 @@ impl MessageReadCommand {
 
                         if self.html {
                             tpl = tpl.with_filter_parts(FilterParts::Only("text/html".into()));
+                        } else if html_only {
+                            tpl = tpl.with_filter_parts(FilterParts::Only("text/plain".into()));
                         }
 
                         tpl
                     })
                     .await?;
-                bodies.push_str(&tpl);
+
+                if html_only {
+                    let headers = tpl.trim_end();
+                    if !headers.is_empty() {
+                        bodies.push_str(headers);
+                        bodies.push_str("\n\n");
+                    }
+
+                    for html in html::bodies(email.parsed()?) {
+                        bodies.push_str(&html_renderer.render(&html, html_width).await?);
+                    }
+                } else {
+                    bodies.push_str(&tpl);
+                }
             }
 
             glue = "\n\n";
         }
 
-        printer.print(bodies)
+        let pager = if self.no_pager {
+            None
+        } else {
+            pager::cmd(toml_account_config.read_messages_pager()).and_then(|cmd| printer.pager(cmd))
+        };
+
+        match pager {
+            Some(mut pager) => {
+                pager.print(bodies)?;
+                pager.page().await
+            }
+            None => printer.print(bodies),
+        }
     }
 }
@@ -12,6 +12,12 @@ use log::info;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::{AddMessageJmap, SendMessageJmap};
+#[cfg(feature = "mbox")]
+use crate::mbox::AddMessageMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::AddMessageNotmuch;
 #[allow(unused)]
 use crate::{
     account::arg::name::AccountNameFlag,
@@ -80,6 +86,11 @@ impl MessageReplyCommand {
                         builder
                             .set_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new));
                     }
+                    #[cfg(feature = "jmap")]
+                    Some(BackendKind::Jmap) => {
+                        builder
+                            .set_add_message(|ctx| ctx.jmap.as_ref().and_then(AddMessageJmap::new));
+                    }
                     #[cfg(feature = "maildir")]
                     Some(BackendKind::Maildir) => {
                         builder.set_add_message(|ctx| {
@@ -94,6 +105,17 @@ impl MessageReplyCommand {
                                 .and_then(AddMaildirMessage::new)
                         });
                     }
+                    #[cfg(feature = "mbox")]
+                    Some(BackendKind::Mbox) => {
+                        builder
+                            .set_add_message(|ctx| ctx.mbox.as_ref().and_then(AddMessageMbox::new));
+                    }
+                    #[cfg(feature = "notmuch")]
+                    Some(BackendKind::Notmuch) => {
+                        builder.set_add_message(|ctx| {
+                            ctx.notmuch.as_ref().and_then(AddMessageNotmuch::new)
+                        });
+                    }
                     _ => (),
                 };
 
@@ -104,6 +126,12 @@ impl MessageReplyCommand {
                             ctx.smtp.as_ref().and_then(SendMessageSmtp::new)
                         });
                     }
+                    #[cfg(feature = "jmap")]
+                    Some(BackendKind::Jmap) => {
+                        builder.set_send_message(|ctx| {
+                            ctx.jmap.as_ref().and_then(SendMessageJmap::new)
+                        });
+                    }
                     #[cfg(feature = "sendmail")]
                     Some(BackendKind::Sendmail) => {
                         builder.set_send_message(|ctx| {
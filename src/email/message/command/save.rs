@@ -9,6 +9,12 @@ use std::io::{self, BufRead, IsTerminal};
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::AddMessageJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::AddMessageMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::AddMessageNotmuch;
 #[allow(unused)]
 use crate::{
     account::arg::name::AccountNameFlag,
@@ -61,6 +67,10 @@ impl MessageSaveCommand {
                 Some(BackendKind::Imap) => {
                     builder.set_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder.set_add_message(|ctx| ctx.jmap.as_ref().and_then(AddMessageJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder.set_add_message(|ctx| {
@@ -75,6 +85,16 @@ impl MessageSaveCommand {
                             .and_then(AddMaildirMessage::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder.set_add_message(|ctx| ctx.mbox.as_ref().and_then(AddMessageMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_add_message(|ctx| {
+                        ctx.notmuch.as_ref().and_then(AddMessageNotmuch::new)
+                    });
+                }
                 _ => (),
             },
         )
@@ -1,18 +1,32 @@
 use anyhow::Result;
+#[cfg(feature = "outbox-subcmd")]
+use chrono::{DateTime, Local};
 use clap::Parser;
-#[cfg(feature = "imap")]
+#[cfg(all(feature = "imap", not(feature = "outbox-subcmd")))]
 use email::message::add::imap::AddImapMessage;
-#[cfg(feature = "maildir")]
+#[cfg(all(feature = "maildir", not(feature = "outbox-subcmd")))]
 use email::message::add::maildir::AddMaildirMessage;
-#[cfg(feature = "sendmail")]
+#[cfg(all(feature = "sendmail", not(feature = "outbox-subcmd")))]
 use email::message::send::sendmail::SendMessageSendmail;
-#[cfg(feature = "smtp")]
+#[cfg(all(feature = "smtp", not(feature = "outbox-subcmd")))]
 use email::message::send::smtp::SendMessageSmtp;
 use log::info;
 use std::io::{self, BufRead, IsTerminal};
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(all(feature = "jmap", not(feature = "outbox-subcmd")))]
+use crate::jmap::{AddMessageJmap, SendMessageJmap};
+#[cfg(all(feature = "mbox", not(feature = "outbox-subcmd")))]
+use crate::mbox::AddMessageMbox;
+#[cfg(all(feature = "notmuch", not(feature = "outbox-subcmd")))]
+use crate::notmuch::AddMessageNotmuch;
+#[cfg(feature = "outbox-subcmd")]
+use crate::outbox::{
+    self,
+    schedule::{self, Schedule},
+    Outbox,
+};
 #[allow(unused)]
 use crate::{
     account::arg::name::AccountNameFlag,
@@ -25,12 +39,32 @@ use crate::{
 /// Send a message.
 ///
 /// This command allows you to send a raw message and to save a copy
-/// to your send folder.
+/// to your send folder. If the message cannot be delivered for now
+/// (the send backend cannot be reached for example), it is queued in
+/// the outbox instead, and the command fails.
 #[derive(Debug, Parser)]
 pub struct MessageSendCommand {
     #[command(flatten)]
     pub message: MessageRawArg,
 
+    /// Queue the message in the outbox instead of sending it.
+    ///
+    /// Queued messages can be sent later with the `outbox flush`
+    /// command.
+    #[cfg(feature = "outbox-subcmd")]
+    #[arg(long, short, conflicts_with = "send_at")]
+    pub queue: bool,
+
+    /// Schedule the message instead of sending it now.
+    ///
+    /// The date is expressed in local time (YYYY-MM-DD HH:MM) or in
+    /// RFC 3339. Scheduled messages are sent by the `outbox run`
+    /// command once the date has passed.
+    #[cfg(feature = "outbox-subcmd")]
+    #[arg(long = "at", name = "send_at", value_name = "DATE")]
+    #[arg(value_parser = schedule::parse_send_at)]
+    pub send_at: Option<DateTime<Local>>,
+
     #[cfg(feature = "account-sync")]
     #[command(flatten)]
     pub cache: CacheDisableFlag,
@@ -49,61 +83,6 @@ impl MessageSendCommand {
             self.cache.disable,
         )?;
 
-        let send_message_kind = toml_account_config.send_message_kind();
-
-        #[cfg(feature = "message-add")]
-        let add_message_kind = toml_account_config
-            .add_message_kind()
-            .filter(|_| account_config.should_save_copy_sent_message());
-        #[cfg(not(feature = "message-add"))]
-        let add_message_kind = None;
-
-        let backend = Backend::new(
-            &toml_account_config,
-            &account_config,
-            send_message_kind.into_iter().chain(add_message_kind),
-            |#[allow(unused)] builder| {
-                match add_message_kind {
-                    #[cfg(feature = "imap")]
-                    Some(BackendKind::Imap) => {
-                        builder
-                            .set_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new));
-                    }
-                    #[cfg(feature = "maildir")]
-                    Some(BackendKind::Maildir) => {
-                        builder.set_add_message(|ctx| {
-                            ctx.maildir.as_ref().and_then(AddMaildirMessage::new)
-                        });
-                    }
-                    #[cfg(feature = "account-sync")]
-                    Some(BackendKind::MaildirForSync) => {
-                        builder.set_add_message(|ctx| {
-                            ctx.maildir_for_sync
-                                .as_ref()
-                                .and_then(AddMaildirMessage::new)
-                        });
-                    }
-                    _ => (),
-                };
-                match send_message_kind {
-                    #[cfg(feature = "smtp")]
-                    Some(BackendKind::Smtp) => {
-                        builder.set_send_message(|ctx| {
-                            ctx.smtp.as_ref().and_then(SendMessageSmtp::new)
-                        });
-                    }
-                    #[cfg(feature = "sendmail")]
-                    Some(BackendKind::Sendmail) => {
-                        builder.set_send_message(|ctx| {
-                            ctx.sendmail.as_ref().and_then(SendMessageSendmail::new)
-                        });
-                    }
-                    _ => (),
-                };
-            },
-        )
-        .await?;
-
         let msg = if io::stdin().is_terminal() {
             self.message.raw()
         } else {
@@ -115,8 +94,115 @@ impl MessageSendCommand {
                 .join("\r\n")
         };
 
-        backend.send_message(msg.as_bytes()).await?;
+        #[cfg(feature = "outbox-subcmd")]
+        if let Some(send_at) = self.send_at {
+            let id = Schedule::open(&account_config)?.add(msg.as_bytes(), &send_at)?;
+            return printer.print(format!(
+                "Message successfully scheduled at {send_at} as {id}!"
+            ));
+        }
+
+        #[cfg(feature = "outbox-subcmd")]
+        if self.queue {
+            let id = Outbox::open(&account_config)
+                .await?
+                .add(msg.as_bytes())
+                .await?;
+            return printer.print(format!(
+                "Message successfully queued to the outbox as {id}!"
+            ));
+        }
+
+        #[cfg(feature = "outbox-subcmd")]
+        {
+            outbox::send_or_queue(&toml_account_config, &account_config, msg.as_bytes()).await?;
+            printer.print("Message successfully sent!")
+        }
+
+        #[cfg(not(feature = "outbox-subcmd"))]
+        {
+            let send_message_kind = toml_account_config.send_message_kind();
+
+            #[cfg(feature = "message-add")]
+            let add_message_kind = toml_account_config
+                .add_message_kind()
+                .filter(|_| account_config.should_save_copy_sent_message());
+            #[cfg(not(feature = "message-add"))]
+            let add_message_kind = None;
+
+            let backend = Backend::new(
+                &toml_account_config,
+                &account_config,
+                send_message_kind.into_iter().chain(add_message_kind),
+                |#[allow(unused)] builder| {
+                    match add_message_kind {
+                        #[cfg(feature = "imap")]
+                        Some(BackendKind::Imap) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.imap.as_ref().and_then(AddImapMessage::new)
+                            });
+                        }
+                        #[cfg(feature = "jmap")]
+                        Some(BackendKind::Jmap) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.jmap.as_ref().and_then(AddMessageJmap::new)
+                            });
+                        }
+                        #[cfg(feature = "maildir")]
+                        Some(BackendKind::Maildir) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.maildir.as_ref().and_then(AddMaildirMessage::new)
+                            });
+                        }
+                        #[cfg(feature = "account-sync")]
+                        Some(BackendKind::MaildirForSync) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.maildir_for_sync
+                                    .as_ref()
+                                    .and_then(AddMaildirMessage::new)
+                            });
+                        }
+                        #[cfg(feature = "mbox")]
+                        Some(BackendKind::Mbox) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.mbox.as_ref().and_then(AddMessageMbox::new)
+                            });
+                        }
+                        #[cfg(feature = "notmuch")]
+                        Some(BackendKind::Notmuch) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.notmuch.as_ref().and_then(AddMessageNotmuch::new)
+                            });
+                        }
+                        _ => (),
+                    };
+                    match send_message_kind {
+                        #[cfg(feature = "smtp")]
+                        Some(BackendKind::Smtp) => {
+                            builder.set_send_message(|ctx| {
+                                ctx.smtp.as_ref().and_then(SendMessageSmtp::new)
+                            });
+                        }
+                        #[cfg(feature = "jmap")]
+                        Some(BackendKind::Jmap) => {
+                            builder.set_send_message(|ctx| {
+                                ctx.jmap.as_ref().and_then(SendMessageJmap::new)
+                            });
+                        }
+                        #[cfg(feature = "sendmail")]
+                        Some(BackendKind::Sendmail) => {
+                            builder.set_send_message(|ctx| {
+                                ctx.sendmail.as_ref().and_then(SendMessageSendmail::new)
+                            });
+                        }
+                        _ => (),
+                    };
+                },
+            )
+            .await?;
 
-        printer.print("Message successfully sent!")
+            backend.send_message(msg.as_bytes()).await?;
+            printer.print("Message successfully sent!")
+        }
     }
 }
@@ -0,0 +1,135 @@
+use anyhow::{anyhow, Result};
+use clap::Parser;
+#[cfg(feature = "imap")]
+use email::message::peek::imap::PeekMessagesImap;
+#[cfg(feature = "maildir")]
+use email::message::peek::maildir::PeekMessagesMaildir;
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::PeekMessagesJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::PeekMessagesMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::PeekMessagesNotmuch;
+#[cfg(feature = "pop")]
+use crate::pop::PeekMessagesPop;
+#[allow(unused)]
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{Backend, BackendKind},
+    config::TomlConfig,
+    envelope::arg::ids::EnvelopeIdArg,
+    folder::arg::name::FolderNameOptionalFlag,
+    message::structure::MessageStructure,
+    printer::{PrintTableOpts, Printer},
+    ui::arg::max_width::TableMaxWidthFlag,
+};
+
+/// Display the MIME structure of a message.
+///
+/// This command displays the tree of the MIME parts of the given
+/// message, with their content type, charset, transfer encoding,
+/// size, filename, Content-ID and whether they are signed or
+/// encrypted. It is useful for debugging faulty messages. The
+/// message is not marked as seen.
+#[derive(Debug, Parser)]
+pub struct MessageStructureCommand {
+    #[command(flatten)]
+    pub folder: FolderNameOptionalFlag,
+
+    #[command(flatten)]
+    pub envelope: EnvelopeIdArg,
+
+    #[command(flatten)]
+    pub table: TableMaxWidthFlag,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl MessageStructureCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing message structure command");
+
+        let folder = &self.folder.name;
+        let id = self.envelope.id;
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let get_messages_kind = toml_account_config.get_messages_kind();
+
+        let backend = Backend::new(
+            &toml_account_config,
+            &account_config,
+            get_messages_kind,
+            |#[allow(unused)] builder| match get_messages_kind {
+                #[cfg(feature = "imap")]
+                Some(BackendKind::Imap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.imap.as_ref().and_then(PeekMessagesImap::new));
+                }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.jmap.as_ref().and_then(PeekMessagesJmap::new));
+                }
+                #[cfg(feature = "maildir")]
+                Some(BackendKind::Maildir) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.maildir.as_ref().and_then(PeekMessagesMaildir::new)
+                    });
+                }
+                #[cfg(feature = "account-sync")]
+                Some(BackendKind::MaildirForSync) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.maildir_for_sync
+                            .as_ref()
+                            .and_then(PeekMessagesMaildir::new)
+                    });
+                }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.mbox.as_ref().and_then(PeekMessagesMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.notmuch.as_ref().and_then(PeekMessagesNotmuch::new)
+                    });
+                }
+                #[cfg(feature = "pop")]
+                Some(BackendKind::Pop) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.pop.as_ref().and_then(PeekMessagesPop::new));
+                }
+                _ => (),
+            },
+        )
+        .await?;
+
+        let emails = backend.peek_messages(folder, &[id]).await?;
+        let email = emails.first().ok_or(anyhow!("cannot find message {id}"))?;
+        let structure = MessageStructure::from_msg(email.parsed()?)
+            .ok_or(anyhow!("cannot find MIME parts of message {id}"))?;
+
+        printer.print_table(
+            Box::new(structure),
+            PrintTableOpts {
+                format: &account_config.get_message_read_format(),
+                max_width: self.table.max_width,
+            },
+        )
+    }
+}
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 use crate::backend::BackendKind;
+#[cfg(any(feature = "message-get", feature = "message-read"))]
+use crate::message::html::HtmlRenderer;
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
 pub struct MessageConfig {
@@ -129,6 +131,13 @@ impl MessagePeekConfig {
 pub struct MessageGetConfig {
     pub backend: Option<BackendKind>,
 
+    /// The renderer of HTML-only messages.
+    #[serde(rename = "html-renderer")]
+    pub html_renderer: Option<HtmlRenderer>,
+
+    /// The pager used when reading messages in a terminal.
+    pub pager: Option<String>,
+
     #[serde(flatten)]
     pub remote: email::message::get::config::MessageReadConfig,
 }
@@ -0,0 +1,50 @@
+use anyhow::Result;
+use clap::Parser;
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag, config::TomlConfig, message::draft::Drafts,
+    printer::Printer,
+};
+
+/// Delete local drafts.
+///
+/// The given local drafts are deleted without being sent.
+#[derive(Debug, Parser)]
+pub struct DraftDeleteCommand {
+    /// The ids of the local drafts to delete.
+    #[arg(value_name = "ID", required = true)]
+    pub ids: Vec<String>,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl DraftDeleteCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing draft delete command");
+
+        let (_, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let drafts = Drafts::open(&account_config)?;
+
+        for id in &self.ids {
+            drafts.remove(id)?;
+        }
+
+        printer.print(format!(
+            "{} local draft(s) successfully deleted!",
+            self.ids.len()
+        ))
+    }
+}
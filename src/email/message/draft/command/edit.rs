@@ -0,0 +1,139 @@
+use anyhow::Result;
+use clap::Parser;
+#[cfg(feature = "imap")]
+use email::message::add::imap::AddImapMessage;
+#[cfg(feature = "maildir")]
+use email::message::add::maildir::AddMaildirMessage;
+#[cfg(feature = "sendmail")]
+use email::message::send::sendmail::SendMessageSendmail;
+#[cfg(feature = "smtp")]
+use email::message::send::smtp::SendMessageSmtp;
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::{AddMessageJmap, SendMessageJmap};
+#[cfg(feature = "mbox")]
+use crate::mbox::AddMessageMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::AddMessageNotmuch;
+#[allow(unused)]
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{Backend, BackendKind},
+    config::TomlConfig,
+    message::draft::Drafts,
+    printer::Printer,
+    ui::editor,
+};
+
+/// Edit a local draft.
+///
+/// This command allows you to resume the composition of a local
+/// draft using the editor defined in your environment variable
+/// $EDITOR. When the edition process finishes, you can choose between
+/// saving or sending the final message.
+#[derive(Debug, Parser)]
+pub struct DraftEditCommand {
+    /// The id of the local draft to edit.
+    #[arg(value_name = "ID")]
+    pub id: String,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl DraftEditCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing draft edit command");
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let drafts = Drafts::open(&account_config)?;
+        // fails early if the draft does not exist
+        drafts.get(&self.id)?;
+
+        let add_message_kind = toml_account_config.add_message_kind();
+        let send_message_kind = toml_account_config.send_message_kind();
+
+        let backend = Backend::new(
+            &toml_account_config,
+            &account_config,
+            add_message_kind.into_iter().chain(send_message_kind),
+            |#[allow(unused)] builder| {
+                match add_message_kind {
+                    #[cfg(feature = "imap")]
+                    Some(BackendKind::Imap) => {
+                        builder
+                            .set_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new));
+                    }
+                    #[cfg(feature = "jmap")]
+                    Some(BackendKind::Jmap) => {
+                        builder
+                            .set_add_message(|ctx| ctx.jmap.as_ref().and_then(AddMessageJmap::new));
+                    }
+                    #[cfg(feature = "maildir")]
+                    Some(BackendKind::Maildir) => {
+                        builder.set_add_message(|ctx| {
+                            ctx.maildir.as_ref().and_then(AddMaildirMessage::new)
+                        });
+                    }
+                    #[cfg(feature = "account-sync")]
+                    Some(BackendKind::MaildirForSync) => {
+                        builder.set_add_message(|ctx| {
+                            ctx.maildir_for_sync
+                                .as_ref()
+                                .and_then(AddMaildirMessage::new)
+                        });
+                    }
+                    #[cfg(feature = "mbox")]
+                    Some(BackendKind::Mbox) => {
+                        builder
+                            .set_add_message(|ctx| ctx.mbox.as_ref().and_then(AddMessageMbox::new));
+                    }
+                    #[cfg(feature = "notmuch")]
+                    Some(BackendKind::Notmuch) => {
+                        builder.set_add_message(|ctx| {
+                            ctx.notmuch.as_ref().and_then(AddMessageNotmuch::new)
+                        });
+                    }
+                    _ => (),
+                };
+
+                match send_message_kind {
+                    #[cfg(feature = "smtp")]
+                    Some(BackendKind::Smtp) => {
+                        builder.set_send_message(|ctx| {
+                            ctx.smtp.as_ref().and_then(SendMessageSmtp::new)
+                        });
+                    }
+                    #[cfg(feature = "jmap")]
+                    Some(BackendKind::Jmap) => {
+                        builder.set_send_message(|ctx| {
+                            ctx.jmap.as_ref().and_then(SendMessageJmap::new)
+                        });
+                    }
+                    #[cfg(feature = "sendmail")]
+                    Some(BackendKind::Sendmail) => {
+                        builder.set_send_message(|ctx| {
+                            ctx.sendmail.as_ref().and_then(SendMessageSendmail::new)
+                        });
+                    }
+                    _ => (),
+                };
+            },
+        )
+        .await?;
+
+        editor::edit_draft_with_editor(&account_config, printer, &backend, &drafts, &self.id).await
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(feature = "draft-delete")]
+mod delete;
+#[cfg(feature = "draft-edit")]
+mod edit;
+#[cfg(feature = "draft-list")]
+mod list;
+#[cfg(feature = "draft-send")]
+mod send;
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use crate::{config::TomlConfig, printer::Printer};
+
+#[cfg(feature = "draft-delete")]
+use self::delete::DraftDeleteCommand;
+#[cfg(feature = "draft-edit")]
+use self::edit::DraftEditCommand;
+#[cfg(feature = "draft-list")]
+use self::list::DraftListCommand;
+#[cfg(feature = "draft-send")]
+use self::send::DraftSendCommand;
+
+/// Manage local drafts.
+///
+/// Every message composed with the editor is saved as a local draft
+/// until it is sent, saved to the drafts folder or discarded. This
+/// subcommand allows you to resume, send or delete the messages you
+/// are composing.
+#[derive(Debug, Subcommand)]
+pub enum DraftSubcommand {
+    #[cfg(feature = "draft-list")]
+    #[command(alias = "lst")]
+    List(DraftListCommand),
+
+    #[cfg(feature = "draft-edit")]
+    #[command(arg_required_else_help = true)]
+    #[command(alias = "resume")]
+    Edit(DraftEditCommand),
+
+    #[cfg(feature = "draft-send")]
+    #[command(arg_required_else_help = true)]
+    Send(DraftSendCommand),
+
+    #[cfg(feature = "draft-delete")]
+    #[command(arg_required_else_help = true)]
+    #[command(alias = "remove", alias = "rm")]
+    Delete(DraftDeleteCommand),
+}
+
+impl DraftSubcommand {
+    #[allow(unused)]
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        match self {
+            #[cfg(feature = "draft-list")]
+            Self::List(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "draft-edit")]
+            Self::Edit(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "draft-send")]
+            Self::Send(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "draft-delete")]
+            Self::Delete(cmd) => cmd.execute(printer, config).await,
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::Result;
+use clap::Parser;
+#[cfg(all(feature = "imap", not(feature = "outbox-subcmd")))]
+use email::message::add::imap::AddImapMessage;
+#[cfg(all(feature = "maildir", not(feature = "outbox-subcmd")))]
+use email::message::add::maildir::AddMaildirMessage;
+#[cfg(all(feature = "sendmail", not(feature = "outbox-subcmd")))]
+use email::message::send::sendmail::SendMessageSendmail;
+#[cfg(all(feature = "smtp", not(feature = "outbox-subcmd")))]
+use email::message::send::smtp::SendMessageSmtp;
+use log::info;
+use mml::MmlCompilerBuilder;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(all(feature = "jmap", not(feature = "outbox-subcmd")))]
+use crate::jmap::{AddMessageJmap, SendMessageJmap};
+#[cfg(all(feature = "mbox", not(feature = "outbox-subcmd")))]
+use crate::mbox::AddMessageMbox;
+#[cfg(feature = "message-edit")]
+use crate::message::draft;
+#[cfg(all(feature = "notmuch", not(feature = "outbox-subcmd")))]
+use crate::notmuch::AddMessageNotmuch;
+#[cfg(feature = "outbox-subcmd")]
+use crate::outbox;
+#[allow(unused)]
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{Backend, BackendKind},
+    config::TomlConfig,
+    message::draft::Drafts,
+    printer::Printer,
+};
+
+/// Send a local draft.
+///
+/// This command compiles the given local draft, sends it and saves a
+/// copy to your send folder. The local draft is deleted once the
+/// message has been sent, or queued in the outbox because it cannot
+/// be delivered for now, together with the remote draft it has been
+/// loaded from, if any.
+#[derive(Debug, Parser)]
+pub struct DraftSendCommand {
+    /// The id of the local draft to send.
+    #[arg(value_name = "ID")]
+    pub id: String,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl DraftSendCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing draft send command");
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let drafts = Drafts::open(&account_config)?;
+        let tpl = drafts.get(&self.id)?;
+        #[cfg(feature = "message-edit")]
+        let remote_draft = drafts.remote(&self.id)?;
+
+        #[allow(unused_mut)]
+        let mut compiler = MmlCompilerBuilder::new();
+
+        #[cfg(feature = "pgp")]
+        compiler.set_some_pgp(account_config.pgp.clone());
+
+        let msg = compiler.build(tpl.as_str())?.compile().await?.into_vec()?;
+
+        #[cfg(feature = "outbox-subcmd")]
+        if let Err(err) = outbox::send_or_queue(&toml_account_config, &account_config, &msg).await {
+            // the draft is not needed anymore once its message is in
+            // the outbox
+            if outbox::is_queued(&err) {
+                drafts.remove(&self.id)?;
+                #[cfg(feature = "message-edit")]
+                if let Some(remote_draft) = &remote_draft {
+                    draft::remove_remote_draft(&toml_account_config, &account_config, remote_draft)
+                        .await?;
+                }
+            }
+            return Err(err);
+        }
+
+        #[cfg(not(feature = "outbox-subcmd"))]
+        {
+            let send_message_kind = toml_account_config.send_message_kind();
+
+            #[cfg(feature = "message-add")]
+            let add_message_kind = toml_account_config
+                .add_message_kind()
+                .filter(|_| account_config.should_save_copy_sent_message());
+            #[cfg(not(feature = "message-add"))]
+            let add_message_kind = None;
+
+            let backend = Backend::new(
+                &toml_account_config,
+                &account_config,
+                send_message_kind.into_iter().chain(add_message_kind),
+                |#[allow(unused)] builder| {
+                    match add_message_kind {
+                        #[cfg(feature = "imap")]
+                        Some(BackendKind::Imap) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.imap.as_ref().and_then(AddImapMessage::new)
+                            });
+                        }
+                        #[cfg(feature = "jmap")]
+                        Some(BackendKind::Jmap) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.jmap.as_ref().and_then(AddMessageJmap::new)
+                            });
+                        }
+                        #[cfg(feature = "maildir")]
+                        Some(BackendKind::Maildir) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.maildir.as_ref().and_then(AddMaildirMessage::new)
+                            });
+                        }
+                        #[cfg(feature = "account-sync")]
+                        Some(BackendKind::MaildirForSync) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.maildir_for_sync
+                                    .as_ref()
+                                    .and_then(AddMaildirMessage::new)
+                            });
+                        }
+                        #[cfg(feature = "mbox")]
+                        Some(BackendKind::Mbox) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.mbox.as_ref().and_then(AddMessageMbox::new)
+                            });
+                        }
+                        #[cfg(feature = "notmuch")]
+                        Some(BackendKind::Notmuch) => {
+                            builder.set_add_message(|ctx| {
+                                ctx.notmuch.as_ref().and_then(AddMessageNotmuch::new)
+                            });
+                        }
+                        _ => (),
+                    };
+                    match send_message_kind {
+                        #[cfg(feature = "smtp")]
+                        Some(BackendKind::Smtp) => {
+                            builder.set_send_message(|ctx| {
+                                ctx.smtp.as_ref().and_then(SendMessageSmtp::new)
+                            });
+                        }
+                        #[cfg(feature = "jmap")]
+                        Some(BackendKind::Jmap) => {
+                            builder.set_send_message(|ctx| {
+                                ctx.jmap.as_ref().and_then(SendMessageJmap::new)
+                            });
+                        }
+                        #[cfg(feature = "sendmail")]
+                        Some(BackendKind::Sendmail) => {
+                            builder.set_send_message(|ctx| {
+                                ctx.sendmail.as_ref().and_then(SendMessageSendmail::new)
+                            });
+                        }
+                        _ => (),
+                    };
+                },
+            )
+            .await?;
+
+            backend.send_message(&msg).await?;
+        }
+
+        drafts.remove(&self.id)?;
+
+        // the remote draft the local draft has been loaded from is
+        // replaced by the sent message
+        #[cfg(feature = "message-edit")]
+        if let Some(remote_draft) = &remote_draft {
+            draft::remove_remote_draft(&toml_account_config, &account_config, remote_draft).await?;
+        }
+
+        printer.print("Message successfully sent!")
+    }
+}
@@ -0,0 +1,60 @@
+use anyhow::{anyhow, Result};
+use email::imap::ImapSessionSync;
+use log::{debug, info};
+use utf7_imap::encode_utf7_imap as encode_utf7;
+
+/// Expunge the IMAP messages matching the given UIDs, leaving the
+/// other deleted messages of the folder untouched.
+///
+/// This requires the UIDPLUS extension (RFC 4315). If the server does
+/// not support it, nothing is done and `false` is returned.
+pub async fn expunge_messages(
+    session: &ImapSessionSync,
+    folder: &str,
+    uids: &[String],
+) -> Result<bool> {
+    info!("expunging imap messages {uids:?} from folder {folder}");
+
+    let mut session = session.lock().await;
+
+    let capabilities = session
+        .execute(
+            |session| session.capabilities(),
+            |err| anyhow!(err).context("cannot get imap server capabilities"),
+        )
+        .await?;
+
+    if !capabilities.has_str("UIDPLUS") {
+        debug!("imap server does not support UIDPLUS, cannot expunge single messages");
+        return Ok(false);
+    }
+
+    let folder = session.account_config.get_folder_alias(folder);
+    let folder_encoded = encode_utf7(folder.clone());
+    debug!("utf7 encoded folder: {folder_encoded}");
+
+    session
+        .execute(
+            |session| session.select(&folder_encoded),
+            |err| anyhow!(err).context(format!("cannot select imap folder {folder}")),
+        )
+        .await?;
+
+    let uids = uids.join(",");
+
+    session
+        .execute(
+            |session| session.uid_store(&uids, "+FLAGS.SILENT (\\Deleted)"),
+            |err| anyhow!(err).context(format!("cannot flag imap messages {uids} as deleted")),
+        )
+        .await?;
+
+    session
+        .execute(
+            |session| session.uid_expunge(&uids),
+            |err| anyhow!(err).context(format!("cannot expunge imap messages {uids}")),
+        )
+        .await?;
+
+    Ok(true)
+}
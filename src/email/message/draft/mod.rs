@@ -0,0 +1,467 @@
+//! Module dedicated to local drafts.
+//!
+//! Local drafts are templates being composed. Each one is stored in
+//! its own file of a per-account directory, and is edited in place
+//! by the editor, so that several messages can be composed at once
+//! and that no work is lost between two editor sessions.
+
+#[cfg(feature = "draft-subcmd")]
+pub mod command;
+#[cfg(all(feature = "message-edit", feature = "imap"))]
+pub(crate) mod imap;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local};
+use email::{account::config::AccountConfig, email::utils::local_draft_path};
+#[cfg(all(feature = "message-edit", feature = "imap"))]
+use email::{flag::add::imap::AddFlagsImap, message::move_::imap::MoveMessagesImap};
+#[cfg(all(feature = "message-edit", feature = "maildir"))]
+use email::{flag::add::maildir::AddFlagsMaildir, message::move_::maildir::MoveMessagesMaildir};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io, ops,
+    path::{Path, PathBuf},
+};
+use uuid::Uuid;
+
+#[cfg(all(feature = "message-edit", feature = "jmap"))]
+use crate::jmap::{AddFlagsJmap, MoveMessagesJmap};
+#[cfg(all(feature = "message-edit", feature = "mbox"))]
+use crate::mbox::{AddFlagsMbox, MoveMessagesMbox};
+#[cfg(all(feature = "message-edit", feature = "notmuch"))]
+use crate::notmuch::{AddFlagsNotmuch, MoveMessagesNotmuch};
+#[cfg(feature = "message-edit")]
+use crate::{
+    account::config::TomlAccountConfig,
+    backend::{Backend, BackendKind},
+};
+use crate::{
+    message::summary,
+    printer::{PrintTable, PrintTableOpts, WriteColor},
+    ui::{Cell, Row, Table},
+};
+
+const DRAFT_EXT: &str = "eml";
+const REMOTE_DRAFT_EXT: &str = "remote.json";
+
+/// Return the local drafts directory of the given account.
+pub fn dir(account_config: &AccountConfig) -> Result<PathBuf> {
+    let dir = dirs::data_dir()
+        .ok_or_else(|| anyhow!("cannot find user data directory"))?
+        .join("himalaya")
+        .join("drafts")
+        .join(&account_config.name);
+
+    Ok(dir)
+}
+
+/// The local drafts of an account.
+pub struct Drafts {
+    dir: PathBuf,
+}
+
+impl Drafts {
+    /// Open the local drafts of the given account, creating their
+    /// directory if needed.
+    ///
+    /// The legacy single local draft, if any, is imported at this
+    /// moment, see [`Drafts::import_legacy_draft`].
+    pub fn open(account_config: &AccountConfig) -> Result<Self> {
+        let drafts = Self::open_dir(dir(account_config)?)?;
+        drafts.import_legacy_draft(&local_draft_path())?;
+        Ok(drafts)
+    }
+
+    /// Open the local drafts located at the given directory, creating
+    /// it if needed.
+    pub fn open_dir(dir: PathBuf) -> Result<Self> {
+        debug!("opening local drafts at {dir:?}…");
+
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("cannot create local drafts directory at {dir:?}"))?;
+
+        Ok(Self { dir })
+    }
+
+    /// Import the legacy single local draft located at the given
+    /// path, and return its new id.
+    ///
+    /// The editor used to keep a single local draft, shared by all
+    /// accounts, that it offered to resume at the next session. It is
+    /// moved to the drafts of the first account opening them, so that
+    /// it is not lost.
+    pub fn import_legacy_draft(&self, path: &Path) -> Result<Option<String>> {
+        let tpl = match fs::read_to_string(path) {
+            Ok(tpl) => tpl,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("cannot read legacy local draft at {path:?}"))
+            }
+        };
+
+        let id = self.create(&tpl)?;
+        fs::remove_file(path)
+            .with_context(|| format!("cannot remove legacy local draft at {path:?}"))?;
+
+        info!("legacy local draft imported as {id}");
+        Ok(Some(id))
+    }
+
+    /// Create a new local draft from the given template, and return
+    /// its id.
+    pub fn create(&self, tpl: &str) -> Result<String> {
+        let id = loop {
+            let id = Uuid::new_v4().to_simple().to_string()[..8].to_owned();
+            if !self.path(&id).exists() {
+                break id;
+            }
+        };
+
+        let path = self.path(&id);
+        fs::write(&path, tpl).with_context(|| format!("cannot write local draft at {path:?}"))?;
+
+        debug!("local draft {id} created");
+        Ok(id)
+    }
+
+    /// Replace the template of the local draft matching the given id.
+    pub fn save(&self, id: &str, tpl: &str) -> Result<()> {
+        let path = self.existing_path(id)?;
+        fs::write(&path, tpl).with_context(|| format!("cannot write local draft at {path:?}"))
+    }
+
+    /// Return the path of the local draft matching the given id.
+    ///
+    /// The editor opens this path directly, which means that the
+    /// draft is saved at the end of every editor session.
+    pub fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.{DRAFT_EXT}"))
+    }
+
+    /// Return the directory where the attachments of the local draft
+    /// matching the given id are extracted.
+    ///
+    /// Attachments are extracted when a remote draft is loaded, so
+    /// that the template can reference them by path. They are
+    /// deleted together with the draft.
+    pub fn attachments_dir(&self, id: &str) -> PathBuf {
+        self.dir.join(id)
+    }
+
+    /// Attach the given remote draft to the local draft matching the
+    /// given id, see [`RemoteDraft`].
+    pub fn set_remote(&self, id: &str, remote: &RemoteDraft) -> Result<()> {
+        let path = self.remote_path(id);
+        let json = serde_json::to_vec(remote).context("cannot serialize remote draft")?;
+        fs::write(&path, json).with_context(|| format!("cannot write remote draft at {path:?}"))
+    }
+
+    /// Return the remote draft the local draft matching the given id
+    /// has been loaded from, if any.
+    pub fn remote(&self, id: &str) -> Result<Option<RemoteDraft>> {
+        let path = self.remote_path(id);
+
+        let json = match fs::read(&path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("cannot read remote draft at {path:?}"))
+            }
+        };
+
+        let remote = serde_json::from_slice(&json)
+            .with_context(|| format!("cannot parse remote draft at {path:?}"))?;
+        Ok(Some(remote))
+    }
+
+    fn remote_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.{REMOTE_DRAFT_EXT}"))
+    }
+
+    /// Return the template of the local draft matching the given id.
+    pub fn get(&self, id: &str) -> Result<String> {
+        let path = self.existing_path(id)?;
+        fs::read_to_string(&path).with_context(|| format!("cannot read local draft at {path:?}"))
+    }
+
+    /// List the local drafts, most recently edited first.
+    pub fn list(&self) -> Result<LocalDrafts> {
+        let entries = fs::read_dir(&self.dir)
+            .with_context(|| format!("cannot read local drafts directory at {:?}", self.dir))?;
+
+        let mut drafts = Vec::new();
+
+        for entry in entries {
+            let path = entry.context("cannot read local drafts entry")?.path();
+            if path.extension().filter(|ext| *ext == DRAFT_EXT).is_none() {
+                debug!("skipping invalid local drafts entry {path:?}");
+                continue;
+            }
+
+            let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                debug!("skipping invalid local drafts entry {path:?}");
+                continue;
+            };
+
+            let tpl =
+                fs::read(&path).with_context(|| format!("cannot read local draft at {path:?}"))?;
+            let updated_at: DateTime<Local> = fs::metadata(&path)
+                .and_then(|meta| meta.modified())
+                .with_context(|| format!("cannot get edition date of local draft {id}"))?
+                .into();
+            let (to, subject) = summary(&tpl);
+
+            drafts.push(LocalDraft {
+                id: id.to_owned(),
+                updated_at,
+                date: updated_at.format("%Y-%m-%d %H:%M").to_string(),
+                to,
+                subject,
+            });
+        }
+
+        drafts.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+
+        Ok(LocalDrafts(drafts))
+    }
+
+    /// Delete the local draft matching the given id.
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let path = self.existing_path(id)?;
+        fs::remove_file(&path).with_context(|| format!("cannot delete local draft at {path:?}"))?;
+
+        let path = self.remote_path(id);
+        if path.is_file() {
+            fs::remove_file(&path)
+                .with_context(|| format!("cannot delete remote draft at {path:?}"))?;
+        }
+
+        let dir = self.attachments_dir(id);
+        if dir.is_dir() {
+            fs::remove_dir_all(&dir)
+                .with_context(|| format!("cannot delete local draft attachments at {dir:?}"))?;
+        }
+
+        debug!("local draft {id} deleted");
+        Ok(())
+    }
+
+    fn existing_path(&self, id: &str) -> Result<PathBuf> {
+        let path = self.path(id);
+
+        if !path.is_file() {
+            bail!("cannot find local draft {id}");
+        }
+
+        Ok(path)
+    }
+}
+
+/// The remote draft a local draft has been loaded from.
+///
+/// It is stored next to the local draft, so that the remote draft is
+/// replaced rather than duplicated once the local draft is sent or
+/// saved remotely, even from a later session.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct RemoteDraft {
+    /// The folder containing the remote draft.
+    pub folder: String,
+
+    /// The id of the remote draft.
+    pub id: usize,
+}
+
+/// Remove the given remote draft, since it has been replaced by a
+/// newer version.
+///
+/// Only the remote draft is expunged when the backend supports it,
+/// otherwise it is deleted like any other message.
+#[cfg(feature = "message-edit")]
+pub async fn remove_remote_draft(
+    toml_account_config: &TomlAccountConfig,
+    account_config: &AccountConfig,
+    remote: &RemoteDraft,
+) -> Result<()> {
+    let delete_messages_kind = toml_account_config.delete_messages_kind();
+
+    let backend = Backend::new(
+        toml_account_config,
+        account_config,
+        delete_messages_kind,
+        |#[allow(unused)] builder| match delete_messages_kind {
+            #[cfg(feature = "imap")]
+            Some(BackendKind::Imap) => {
+                builder.set_move_messages(|ctx| ctx.imap.as_ref().and_then(MoveMessagesImap::new));
+                builder.set_add_flags(|ctx| ctx.imap.as_ref().and_then(AddFlagsImap::new));
+            }
+            #[cfg(feature = "jmap")]
+            Some(BackendKind::Jmap) => {
+                builder.set_move_messages(|ctx| ctx.jmap.as_ref().and_then(MoveMessagesJmap::new));
+                builder.set_add_flags(|ctx| ctx.jmap.as_ref().and_then(AddFlagsJmap::new));
+            }
+            #[cfg(feature = "maildir")]
+            Some(BackendKind::Maildir) => {
+                builder.set_move_messages(|ctx| {
+                    ctx.maildir.as_ref().and_then(MoveMessagesMaildir::new)
+                });
+                builder.set_add_flags(|ctx| ctx.maildir.as_ref().and_then(AddFlagsMaildir::new));
+            }
+            #[cfg(feature = "account-sync")]
+            Some(BackendKind::MaildirForSync) => {
+                builder.set_move_messages(|ctx| {
+                    ctx.maildir_for_sync
+                        .as_ref()
+                        .and_then(MoveMessagesMaildir::new)
+                });
+                builder.set_add_flags(|ctx| {
+                    ctx.maildir_for_sync.as_ref().and_then(AddFlagsMaildir::new)
+                });
+            }
+            #[cfg(feature = "mbox")]
+            Some(BackendKind::Mbox) => {
+                builder.set_move_messages(|ctx| ctx.mbox.as_ref().and_then(MoveMessagesMbox::new));
+                builder.set_add_flags(|ctx| ctx.mbox.as_ref().and_then(AddFlagsMbox::new));
+            }
+            #[cfg(feature = "notmuch")]
+            Some(BackendKind::Notmuch) => {
+                builder.set_move_messages(|ctx| {
+                    ctx.notmuch.as_ref().and_then(MoveMessagesNotmuch::new)
+                });
+                builder.set_add_flags(|ctx| ctx.notmuch.as_ref().and_then(AddFlagsNotmuch::new));
+            }
+            _ => (),
+        },
+    )
+    .await?;
+
+    let RemoteDraft { folder, id } = remote;
+    backend.remove_messages(folder, &[*id]).await?;
+
+    debug!("remote draft {id} removed from {folder}");
+    Ok(())
+}
+
+/// A local draft.
+#[derive(Clone, Debug, Serialize)]
+pub struct LocalDraft {
+    pub id: String,
+    #[serde(skip)]
+    pub updated_at: DateTime<Local>,
+    pub date: String,
+    pub to: String,
+    pub subject: String,
+}
+
+impl Table for LocalDraft {
+    fn head() -> Row {
+        Row::new()
+            .cell(Cell::new("ID").bold().underline().white())
+            .cell(Cell::new("EDITED").bold().underline().white())
+            .cell(Cell::new("TO").bold().underline().white())
+            .cell(Cell::new("SUBJECT").shrinkable().bold().underline().white())
+    }
+
+    fn row(&self) -> Row {
+        Row::new()
+            .cell(Cell::new(&self.id).red())
+            .cell(Cell::new(&self.date).yellow())
+            .cell(Cell::new(&self.to).blue())
+            .cell(Cell::new(&self.subject).shrinkable().green())
+    }
+}
+
+/// The list of local drafts.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LocalDrafts(Vec<LocalDraft>);
+
+impl ops::Deref for LocalDrafts {
+    type Target = Vec<LocalDraft>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl PrintTable for LocalDrafts {
+    fn print_table(&self, writer: &mut dyn WriteColor, opts: PrintTableOpts) -> Result<()> {
+        writeln!(writer)?;
+        Table::print(writer, self, opts)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{Drafts, RemoteDraft};
+
+    const TPL: &str = "To: alice@localhost\nSubject: draft\n\nHello!\n";
+
+    #[test]
+    fn create_list_remove_drafts() {
+        let dir = tempfile::tempdir().unwrap();
+        let drafts = Drafts::open_dir(dir.path().to_owned()).unwrap();
+        assert!(drafts.list().unwrap().is_empty());
+
+        let id = drafts.create(TPL).unwrap();
+        assert_eq!(drafts.get(&id).unwrap(), TPL);
+
+        let list = drafts.list().unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].id, id);
+        assert_eq!(list[0].to, "alice@localhost");
+        assert_eq!(list[0].subject, "draft");
+
+        fs::create_dir(drafts.attachments_dir(&id)).unwrap();
+        fs::write(drafts.attachments_dir(&id).join("file.txt"), "file").unwrap();
+
+        drafts.remove(&id).unwrap();
+        assert!(drafts.list().unwrap().is_empty());
+        assert!(!drafts.attachments_dir(&id).exists());
+        assert!(drafts.get(&id).is_err());
+        assert!(drafts.remove(&id).is_err());
+    }
+
+    #[test]
+    fn import_legacy_draft() {
+        let dir = tempfile::tempdir().unwrap();
+        let drafts = Drafts::open_dir(dir.path().join("drafts")).unwrap();
+        let legacy_path = dir.path().join("himalaya-draft.eml");
+
+        assert_eq!(drafts.import_legacy_draft(&legacy_path).unwrap(), None);
+
+        fs::write(&legacy_path, TPL).unwrap();
+        let id = drafts.import_legacy_draft(&legacy_path).unwrap().unwrap();
+        assert_eq!(drafts.get(&id).unwrap(), TPL);
+        assert!(!legacy_path.exists());
+
+        assert_eq!(drafts.import_legacy_draft(&legacy_path).unwrap(), None);
+        assert_eq!(drafts.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn attach_remote_draft() {
+        let dir = tempfile::tempdir().unwrap();
+        let drafts = Drafts::open_dir(dir.path().to_owned()).unwrap();
+        let id = drafts.create(TPL).unwrap();
+        assert_eq!(drafts.remote(&id).unwrap(), None);
+
+        let remote = RemoteDraft {
+            folder: "Drafts".into(),
+            id: 42,
+        };
+        drafts.set_remote(&id, &remote).unwrap();
+        assert_eq!(drafts.remote(&id).unwrap(), Some(remote));
+
+        // the remote draft is not listed as a local draft
+        assert_eq!(drafts.list().unwrap().len(), 1);
+
+        drafts.remove(&id).unwrap();
+        assert_eq!(drafts.remote(&id).unwrap(), None);
+        assert!(fs::read_dir(dir.path()).unwrap().next().is_none());
+    }
+}
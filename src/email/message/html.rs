@@ -0,0 +1,175 @@
+//! Module dedicated to HTML rendering.
+//!
+//! HTML-only messages are rendered as plain text when they are read,
+//! either by the builtin renderer or by an external command.
+
+#[cfg(feature = "message-read")]
+use anyhow::{Context, Result};
+use email::{account::config::AccountConfig, email::config::EmailTextPlainFormat};
+#[cfg(feature = "message-read")]
+use log::debug;
+use mail_parser::{Message, PartType};
+use process::SingleCmd;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use terminal_size::terminal_size;
+
+use crate::ui::table::DEFAULT_TERM_WIDTH;
+
+const BUILTIN: &str = "builtin";
+
+/// The renderer used to turn HTML parts into plain text.
+///
+/// The renderer is configured with `message.read.html-renderer`,
+/// either as `builtin` or as a shell command reading HTML from its
+/// standard input (`w3m -dump -T text/html` for example).
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum HtmlRenderer {
+    /// Render HTML with the builtin renderer: links are kept as
+    /// numbered footnotes, tables and lists are drawn, and the text
+    /// is wrapped to the given width.
+    #[default]
+    Builtin,
+
+    /// Render HTML with the given external command.
+    Cmd(SingleCmd),
+}
+
+#[cfg(feature = "message-read")]
+impl HtmlRenderer {
+    /// Render the given HTML as plain text, wrapped to the given
+    /// width.
+    pub async fn render(&self, html: &str, width: usize) -> Result<String> {
+        match self {
+            Self::Builtin => Ok(render_builtin(html, width)),
+            Self::Cmd(cmd) => {
+                debug!("rendering html with command {}", &**cmd);
+                let output = cmd
+                    .clone()
+                    .run_with(html.as_bytes())
+                    .await
+                    .context("cannot render html with external command")?;
+                let output: Vec<u8> = output.into();
+                Ok(String::from_utf8_lossy(&output).into_owned())
+            }
+        }
+    }
+}
+
+impl From<String> for HtmlRenderer {
+    fn from(renderer: String) -> Self {
+        if renderer.trim().eq_ignore_ascii_case(BUILTIN) {
+            Self::Builtin
+        } else {
+            Self::Cmd(SingleCmd::from(renderer))
+        }
+    }
+}
+
+impl From<HtmlRenderer> for String {
+    fn from(renderer: HtmlRenderer) -> Self {
+        match renderer {
+            HtmlRenderer::Builtin => BUILTIN.to_owned(),
+            HtmlRenderer::Cmd(cmd) => cmd.to_string(),
+        }
+    }
+}
+
+/// Return the width HTML should be wrapped to.
+///
+/// A fixed text/plain format takes precedence, otherwise the width of
+/// the terminal is used.
+pub fn width(account_config: &AccountConfig) -> usize {
+    match account_config.get_message_read_format() {
+        EmailTextPlainFormat::Fixed(width) => width,
+        _ => terminal_size()
+            .map(|(w, _)| w.0 as usize)
+            .unwrap_or(DEFAULT_TERM_WIDTH),
+    }
+}
+
+/// Return `true` if the given message has HTML bodies but no text
+/// one.
+pub fn is_html_only(msg: &Message) -> bool {
+    !msg.html_body.is_empty()
+        && msg.text_body.iter().all(|id| {
+            matches!(
+                msg.part(*id).map(|part| &part.body),
+                Some(PartType::Html(_))
+            )
+        })
+}
+
+/// Return the HTML bodies of the given message.
+pub fn bodies<'a>(msg: &'a Message) -> impl Iterator<Item = Cow<'a, str>> {
+    (0..msg.html_body.len()).filter_map(|pos| msg.body_html(pos))
+}
+
+#[cfg(feature = "message-read")]
+fn render_builtin(html: &str, width: usize) -> String {
+    html2text::from_read(html.as_bytes(), width.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use mail_parser::MessageParser;
+
+    #[cfg(feature = "message-read")]
+    use super::render_builtin;
+    use super::{is_html_only, HtmlRenderer};
+
+    #[test]
+    fn renderer_from_string() {
+        assert_eq!(
+            HtmlRenderer::from(String::from("builtin")),
+            HtmlRenderer::Builtin
+        );
+        assert_eq!(
+            String::from(HtmlRenderer::from(String::from("w3m -dump -T text/html"))),
+            "w3m -dump -T text/html"
+        );
+    }
+
+    #[test]
+    fn html_only() {
+        let html_only = MessageParser::new()
+            .parse("Content-Type: text/html\r\n\r\n<p>Hello</p>\r\n".as_bytes())
+            .unwrap();
+        let alternative = MessageParser::new()
+            .parse(
+                concat!(
+                    "Content-Type: multipart/alternative; boundary=b\r\n\r\n",
+                    "--b\r\nContent-Type: text/plain\r\n\r\nHello\r\n",
+                    "--b\r\nContent-Type: text/html\r\n\r\n<p>Hello</p>\r\n",
+                    "--b--\r\n",
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+
+        assert!(is_html_only(&html_only));
+        assert!(!is_html_only(&alternative));
+    }
+
+    #[cfg(feature = "message-read")]
+    #[test]
+    fn builtin_links_as_footnotes() {
+        let html = r#"<p>See <a href="https://example.com">our site</a>.</p>"#;
+        let text = render_builtin(html, 80);
+
+        assert!(text.contains("See [our site][1]."));
+        assert!(text.contains("[1]: https://example.com"));
+    }
+
+    #[cfg(feature = "message-read")]
+    #[test]
+    fn builtin_lists_and_tables() {
+        let html = "<ul><li>one</li><li>two</li></ul>\
+                    <table><tr><td>a</td><td>b</td></tr></table>";
+        let text = render_builtin(html, 80);
+
+        assert!(text.contains("* one\n* two"));
+        assert!(text.contains("a│b"));
+    }
+}
@@ -2,4 +2,43 @@ pub mod arg;
 pub mod attachment;
 pub mod command;
 pub mod config;
+pub mod draft;
+#[cfg(any(feature = "message-get", feature = "message-read"))]
+pub mod html;
+#[cfg(any(feature = "message-structure", feature = "message-part"))]
+pub mod structure;
 pub mod template;
+
+/// Return the recipients and the subject of the given raw message.
+pub fn summary(raw_msg: &[u8]) -> (String, String) {
+    let headers = mail_parser::MessageParser::default().parse_headers(raw_msg);
+    let to = headers
+        .as_ref()
+        .and_then(|msg| msg.to())
+        .map(addresses)
+        .unwrap_or_default();
+    let subject = headers
+        .as_ref()
+        .and_then(|msg| msg.subject())
+        .unwrap_or_default()
+        .to_owned();
+
+    (to, subject)
+}
+
+/// Join the email addresses of the given address header.
+fn addresses(addr: &mail_parser::Address) -> String {
+    let addrs: Vec<&mail_parser::Addr> = match addr {
+        mail_parser::Address::List(addrs) => addrs.iter().collect(),
+        mail_parser::Address::Group(groups) => groups
+            .iter()
+            .flat_map(|group| group.addresses.iter())
+            .collect(),
+    };
+
+    addrs
+        .into_iter()
+        .filter_map(|addr| addr.address())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,311 @@
+//! Module dedicated to the MIME structure of messages.
+//!
+//! The MIME structure of a message is the tree of its parts. Parts
+//! are indexed in depth-first order, parts of nested messages
+//! included, so that any part can be designated by its index.
+
+use anyhow::Result;
+use mail_parser::{Message, MessagePart, MimeHeaders, PartType};
+use serde::Serialize;
+use termcolor::Color;
+
+use crate::{
+    printer::{PrintTable, PrintTableOpts, WriteColor},
+    ui::{human_size, Cell, Row, Table},
+};
+
+/// Return the parts of the given message along with their depth, in
+/// depth-first order: the position of a part is its index.
+pub fn parts<'a, 'x>(msg: &'a Message<'x>) -> Vec<(usize, &'a MessagePart<'x>)> {
+    let mut parts = Vec::new();
+    walk(msg, 0, 0, &mut parts);
+    parts
+}
+
+fn walk<'a, 'x>(
+    msg: &'a Message<'x>,
+    id: usize,
+    depth: usize,
+    parts: &mut Vec<(usize, &'a MessagePart<'x>)>,
+) {
+    let Some(part) = msg.parts.get(id) else {
+        return;
+    };
+
+    parts.push((depth, part));
+
+    match &part.body {
+        PartType::Multipart(ids) => {
+            for id in ids {
+                walk(msg, *id, depth + 1, parts);
+            }
+        }
+        PartType::Message(msg) => walk(msg, 0, depth + 1, parts),
+        _ => (),
+    }
+}
+
+/// Return the part of the given message at the given index, as
+/// displayed by its structure.
+pub fn part<'a, 'x>(msg: &'a Message<'x>, index: usize) -> Option<&'a MessagePart<'x>> {
+    parts(msg).into_iter().nth(index).map(|(_, part)| part)
+}
+
+/// Return the content type of the given part, in lower case.
+///
+/// Parts without content type are considered as plain text, as
+/// defined in the RFC 2045.
+pub fn content_type(part: &MessagePart) -> String {
+    match part.content_type() {
+        Some(ctype) => match ctype.subtype() {
+            Some(subtype) => format!("{}/{subtype}", ctype.ctype()),
+            None => ctype.ctype().to_owned(),
+        }
+        .to_lowercase(),
+        None => String::from("text/plain"),
+    }
+}
+
+/// A MIME part and its sub-parts.
+#[derive(Clone, Debug, Serialize)]
+pub struct MimePart {
+    pub index: usize,
+    #[serde(skip)]
+    pub depth: usize,
+    pub content_type: String,
+    pub charset: Option<String>,
+    pub encoding: Option<String>,
+    pub disposition: Option<String>,
+    /// The size of the decoded content, or the raw size for
+    /// multiparts and nested messages.
+    pub size: usize,
+    pub filename: Option<String>,
+    pub content_id: Option<String>,
+    pub signed: bool,
+    pub encrypted: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<MimePart>,
+}
+
+impl MimePart {
+    fn new(index: usize, depth: usize, part: &MessagePart, signed: bool, encrypted: bool) -> Self {
+        let content_type = content_type(part);
+        let smime_type = part
+            .content_type()
+            .and_then(|ctype| ctype.attribute("smime-type"))
+            .map(str::to_lowercase);
+        let is_pkcs7_mime =
+            content_type.ends_with("/pkcs7-mime") || content_type.ends_with("/x-pkcs7-mime");
+
+        let signed = signed
+            || content_type == "multipart/signed"
+            || is_pkcs7_mime && smime_type.as_deref() == Some("signed-data");
+        let encrypted = encrypted
+            || content_type == "multipart/encrypted"
+            || content_type == "application/pgp-encrypted"
+            || is_pkcs7_mime && smime_type.as_deref() != Some("signed-data");
+
+        let size = match part.body {
+            PartType::Multipart(_) | PartType::Message(_) => part.raw_len(),
+            _ => part.len(),
+        };
+
+        Self {
+            index,
+            depth,
+            charset: part
+                .content_type()
+                .and_then(|ctype| ctype.attribute("charset"))
+                .map(ToOwned::to_owned),
+            encoding: part.content_transfer_encoding().map(str::to_lowercase),
+            disposition: part
+                .content_disposition()
+                .map(|disposition| disposition.ctype().to_lowercase()),
+            size,
+            filename: part.attachment_name().map(ToOwned::to_owned),
+            content_id: part.content_id().map(ToOwned::to_owned),
+            content_type,
+            signed,
+            encrypted,
+            parts: Vec::new(),
+        }
+    }
+
+    fn build(
+        parts: &[(usize, &MessagePart)],
+        pos: &mut usize,
+        signed: bool,
+        encrypted: bool,
+    ) -> Self {
+        let (depth, part) = parts[*pos];
+        let mut node = Self::new(*pos, depth, part, signed, encrypted);
+        *pos += 1;
+
+        while *pos < parts.len() && parts[*pos].0 > depth {
+            let child = Self::build(parts, pos, node.signed, node.encrypted);
+            node.parts.push(child);
+        }
+
+        node
+    }
+
+    fn flatten<'a>(&'a self, parts: &mut Vec<&'a MimePart>) {
+        parts.push(self);
+        for part in &self.parts {
+            part.flatten(parts);
+        }
+    }
+
+    fn security(&self) -> &'static str {
+        match (self.signed, self.encrypted) {
+            (true, true) => "signed, encrypted",
+            (true, false) => "signed",
+            (false, true) => "encrypted",
+            (false, false) => "",
+        }
+    }
+}
+
+impl Table for &MimePart {
+    fn head() -> Row {
+        Row::new()
+            .cell(Cell::new("INDEX").bold().underline().white())
+            .cell(Cell::new("TYPE").bold().underline().white())
+            .cell(Cell::new("CHARSET").bold().underline().white())
+            .cell(Cell::new("ENCODING").bold().underline().white())
+            .cell(Cell::new("SIZE").bold().underline().white())
+            .cell(
+                Cell::new("FILENAME")
+                    .shrinkable()
+                    .bold()
+                    .underline()
+                    .white(),
+            )
+            .cell(
+                Cell::new("CONTENT-ID")
+                    .shrinkable()
+                    .bold()
+                    .underline()
+                    .white(),
+            )
+            .cell(Cell::new("SECURITY").bold().underline().white())
+    }
+
+    fn row(&self) -> Row {
+        let indent = "  ".repeat(self.depth);
+
+        Row::new()
+            .cell(Cell::new(self.index.to_string()).red())
+            .cell(Cell::new(format!("{indent}{}", self.content_type)).green())
+            .cell(Cell::new(self.charset.as_deref().unwrap_or_default()))
+            .cell(Cell::new(self.encoding.as_deref().unwrap_or_default()))
+            .cell(Cell::new(human_size(self.size as u64)).yellow())
+            .cell(
+                Cell::new(self.filename.as_deref().unwrap_or_default())
+                    .shrinkable()
+                    .blue(),
+            )
+            .cell(Cell::new(self.content_id.as_deref().unwrap_or_default()).shrinkable())
+            .cell(Cell::new(self.security()).fg(Color::Magenta))
+    }
+}
+
+/// The MIME structure of a message.
+///
+/// It is displayed as an indented table, and serialized as nested
+/// parts.
+#[derive(Clone, Debug, Serialize)]
+#[serde(transparent)]
+pub struct MessageStructure(MimePart);
+
+impl MessageStructure {
+    /// Return the MIME structure of the given message.
+    pub fn from_msg(msg: &Message) -> Option<Self> {
+        let parts = parts(msg);
+
+        if parts.is_empty() {
+            return None;
+        }
+
+        Some(Self(MimePart::build(&parts, &mut 0, false, false)))
+    }
+}
+
+impl PrintTable for MessageStructure {
+    fn print_table(&self, writer: &mut dyn WriteColor, opts: PrintTableOpts) -> Result<()> {
+        let mut parts = Vec::new();
+        self.0.flatten(&mut parts);
+
+        writeln!(writer)?;
+        Table::print(writer, &parts, opts)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mail_parser::MessageParser;
+
+    use super::{part, MessageStructure};
+
+    #[test]
+    fn nested_parts() {
+        let raw = concat!(
+            "Content-Type: multipart/signed; boundary=s; protocol=\"application/pgp-signature\"\r\n",
+            "\r\n",
+            "--s\r\n",
+            "Content-Type: multipart/mixed; boundary=m\r\n",
+            "\r\n",
+            "--m\r\n",
+            "Content-Type: text/plain; charset=utf-8\r\n",
+            "Content-Transfer-Encoding: quoted-printable\r\n",
+            "\r\n",
+            "Hello=21\r\n",
+            "--m\r\n",
+            "Content-Type: application/pdf\r\n",
+            "Content-Disposition: attachment; filename=doc.pdf\r\n",
+            "Content-Transfer-Encoding: base64\r\n",
+            "\r\n",
+            "SGVsbG8=\r\n",
+            "--m--\r\n",
+            "--s\r\n",
+            "Content-Type: application/pgp-signature\r\n",
+            "\r\n",
+            "sig\r\n",
+            "--s--\r\n",
+        );
+
+        let msg = MessageParser::new().parse(raw.as_bytes()).unwrap();
+        let root = MessageStructure::from_msg(&msg).unwrap().0;
+
+        assert_eq!(root.content_type, "multipart/signed");
+        assert!(root.signed);
+        assert_eq!(root.parts.len(), 2);
+
+        let mixed = &root.parts[0];
+        assert_eq!(mixed.index, 1);
+        assert_eq!(mixed.content_type, "multipart/mixed");
+
+        let text = &mixed.parts[0];
+        assert_eq!(text.index, 2);
+        assert_eq!(text.depth, 2);
+        assert_eq!(text.charset.as_deref(), Some("utf-8"));
+        assert_eq!(text.encoding.as_deref(), Some("quoted-printable"));
+        assert!(text.signed);
+        assert!(!text.encrypted);
+
+        let pdf = &mixed.parts[1];
+        assert_eq!(pdf.index, 3);
+        assert_eq!(pdf.filename.as_deref(), Some("doc.pdf"));
+        assert_eq!(pdf.size, 5);
+
+        let sig = &root.parts[1];
+        assert_eq!(sig.index, 4);
+        assert_eq!(sig.content_type, "application/pgp-signature");
+
+        assert_eq!(part(&msg, 2).unwrap().contents(), b"Hello!");
+        assert_eq!(part(&msg, 3).unwrap().contents(), b"Hello");
+        assert!(part(&msg, 5).is_none());
+    }
+}
@@ -8,6 +8,12 @@ use log::info;
 
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::{AddFlagsJmap, PeekMessagesJmap};
+#[cfg(feature = "mbox")]
+use crate::mbox::{AddFlagsMbox, PeekMessagesMbox};
+#[cfg(feature = "notmuch")]
+use crate::notmuch::{AddFlagsNotmuch, PeekMessagesNotmuch};
 #[allow(unused)]
 use crate::{
     account::arg::name::AccountNameFlag,
@@ -70,6 +76,12 @@ impl TemplateForwardCommand {
                     builder
                         .set_get_messages(|ctx| ctx.imap.as_ref().and_then(GetMessagesImap::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.jmap.as_ref().and_then(PeekMessagesJmap::new));
+                    builder.set_add_flags(|ctx| ctx.jmap.as_ref().and_then(AddFlagsJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder.set_peek_messages(|ctx| {
@@ -89,6 +101,20 @@ impl TemplateForwardCommand {
                         ctx.maildir_for_sync.as_ref().and_then(AddFlagsMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.mbox.as_ref().and_then(PeekMessagesMbox::new));
+                    builder.set_add_flags(|ctx| ctx.mbox.as_ref().and_then(AddFlagsMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.notmuch.as_ref().and_then(PeekMessagesNotmuch::new)
+                    });
+                    builder
+                        .set_add_flags(|ctx| ctx.notmuch.as_ref().and_then(AddFlagsNotmuch::new));
+                }
                 _ => (),
             },
         )
@@ -6,10 +6,20 @@ use email::folder::add::imap::AddFolderImap;
 use email::folder::add::maildir::AddFolderMaildir;
 use log::info;
 
-#[cfg(any(feature = "imap", feature = "maildir", feature = "account-sync"))]
+#[cfg(any(
+    feature = "imap",
+    feature = "maildir",
+    feature = "account-sync",
+    feature = "mbox",
+    feature = "notmuch"
+))]
 use crate::backend::BackendKind;
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "mbox")]
+use crate::mbox::AddFolderMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::AddFolderNotmuch;
 use crate::{
     account::arg::name::AccountNameFlag, backend::Backend, config::TomlConfig,
     folder::arg::name::FolderNameArg, printer::Printer,
@@ -67,6 +77,15 @@ impl AddFolderCommand {
                             .and_then(AddFolderMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder.set_add_folder(|ctx| ctx.mbox.as_ref().and_then(AddFolderMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder
+                        .set_add_folder(|ctx| ctx.notmuch.as_ref().and_then(AddFolderNotmuch::new));
+                }
                 _ => (),
             },
         )
@@ -8,10 +8,20 @@ use email::folder::delete::maildir::DeleteFolderMaildir;
 use log::info;
 use std::process;
 
-#[cfg(any(feature = "imap", feature = "maildir", feature = "account-sync"))]
+#[cfg(any(
+    feature = "imap",
+    feature = "maildir",
+    feature = "account-sync",
+    feature = "mbox",
+    feature = "notmuch"
+))]
 use crate::backend::BackendKind;
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "mbox")]
+use crate::mbox::DeleteFolderMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::DeleteFolderNotmuch;
 use crate::{
     account::arg::name::AccountNameFlag, backend::Backend, config::TomlConfig,
     folder::arg::name::FolderNameArg, printer::Printer,
@@ -82,6 +92,17 @@ impl FolderDeleteCommand {
                             .and_then(DeleteFolderMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_delete_folder(|ctx| ctx.mbox.as_ref().and_then(DeleteFolderMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_delete_folder(|ctx| {
+                        ctx.notmuch.as_ref().and_then(DeleteFolderNotmuch::new)
+                    });
+                }
                 _ => (),
             },
         )
@@ -0,0 +1,235 @@
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use email::envelope::Id;
+#[cfg(feature = "imap")]
+use email::message::peek::imap::PeekMessagesImap;
+#[cfg(feature = "maildir")]
+use email::message::peek::maildir::PeekMessagesMaildir;
+use indicatif::ProgressBar;
+use log::info;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::PeekMessagesJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::PeekMessagesMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::PeekMessagesNotmuch;
+#[allow(unused)]
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{Backend, BackendKind},
+    config::TomlConfig,
+    folder::arg::name::FolderNameArg,
+    mbox::{Mbox, MboxMessage},
+    printer::Printer,
+};
+
+use super::TRANSFER_PROGRESS_STYLE;
+
+/// The archive format of an exported folder.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    /// A single mbox file, using the mboxrd format.
+    Mbox,
+
+    /// A directory containing one .eml file per message.
+    EmlDir,
+}
+
+/// Export a folder.
+///
+/// All messages of the given folder are exported to the given path,
+/// either as an mbox file or as a directory of .eml files. Flags are
+/// kept in the Status, X-Status and X-Keywords headers, so that the
+/// archive can be imported back with the folder import command.
+#[derive(Debug, Parser)]
+pub struct FolderExportCommand {
+    #[command(flatten)]
+    pub folder: FolderNameArg,
+
+    /// The format of the archive.
+    #[arg(long, short = 'F', value_enum, default_value_t = ExportFormat::Mbox)]
+    pub format: ExportFormat,
+
+    /// The path of the archive.
+    ///
+    /// The archive must not exist yet. For the eml-dir format, the
+    /// directory is created if needed but must not contain already
+    /// exported messages.
+    #[arg(value_name = "PATH")]
+    pub path: PathBuf,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl FolderExportCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing export folder command");
+
+        let folder = &self.folder.name;
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let list_envelopes_kind = toml_account_config.list_envelopes_kind();
+        let get_messages_kind = toml_account_config.get_messages_kind();
+
+        let backend = Backend::new(
+            &toml_account_config,
+            &account_config,
+            list_envelopes_kind.into_iter().chain(get_messages_kind),
+            |#[allow(unused)] builder| match get_messages_kind {
+                #[cfg(feature = "imap")]
+                Some(BackendKind::Imap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.imap.as_ref().and_then(PeekMessagesImap::new));
+                }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.jmap.as_ref().and_then(PeekMessagesJmap::new));
+                }
+                #[cfg(feature = "maildir")]
+                Some(BackendKind::Maildir) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.maildir.as_ref().and_then(PeekMessagesMaildir::new)
+                    });
+                }
+                #[cfg(feature = "account-sync")]
+                Some(BackendKind::MaildirForSync) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.maildir_for_sync
+                            .as_ref()
+                            .and_then(PeekMessagesMaildir::new)
+                    });
+                }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_peek_messages(|ctx| ctx.mbox.as_ref().and_then(PeekMessagesMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_peek_messages(|ctx| {
+                        ctx.notmuch.as_ref().and_then(PeekMessagesNotmuch::new)
+                    });
+                }
+                _ => (),
+            },
+        )
+        .await?;
+
+        // the lib listing is used directly, so that messages can be
+        // peeked using their backend id instead of their alias
+        let envelopes = (*backend)
+            .list_envelopes(folder, 0, 0)
+            .await
+            .with_context(|| format!("cannot list envelopes of folder {folder}"))?;
+
+        // envelopes are listed from the most recent one, archives
+        // are usually written the other way around
+        let envelopes: Vec<_> = envelopes.iter().rev().collect();
+
+        let mut archive = Archive::create(self.format, &self.path)?;
+
+        let progress = if printer.is_json() {
+            ProgressBar::hidden()
+        } else {
+            ProgressBar::new(envelopes.len() as u64)
+                .with_style(TRANSFER_PROGRESS_STYLE.clone())
+                .with_prefix(folder.clone())
+        };
+
+        for envelope in &envelopes {
+            progress.set_message(envelope.subject.clone());
+
+            let id = Id::single(envelope.id.clone());
+            let msgs = (*backend).peek_messages(folder, &id).await?;
+            let msg = msgs
+                .first()
+                .with_context(|| format!("cannot find message {id} in folder {folder}"))?;
+
+            archive.write(MboxMessage::from_raw_msg(msg.raw()?, &envelope.flags))?;
+
+            progress.inc(1);
+        }
+
+        progress.finish_and_clear();
+
+        let path = self.path.display();
+        let count = envelopes.len();
+        printer.print(format!(
+            "{count} message(s) successfully exported from folder {folder} to {path}!"
+        ))
+    }
+}
+
+/// The archive messages are written to, one by one.
+pub(super) enum Archive {
+    Mbox(File),
+    EmlDir { dir: PathBuf, next_index: usize },
+}
+
+impl Archive {
+    pub(super) fn create(format: ExportFormat, path: &Path) -> Result<Self> {
+        match format {
+            ExportFormat::Mbox => {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(path)
+                    .with_context(|| format!("cannot create mbox archive at {path:?}"))?;
+                Ok(Self::Mbox(file))
+            }
+            ExportFormat::EmlDir => {
+                fs::create_dir_all(path)
+                    .with_context(|| format!("cannot create eml directory at {path:?}"))?;
+                Ok(Self::EmlDir {
+                    dir: path.to_owned(),
+                    next_index: 1,
+                })
+            }
+        }
+    }
+
+    pub(super) fn write(&mut self, msg: MboxMessage) -> Result<()> {
+        match self {
+            Self::Mbox(file) => {
+                let bytes = Mbox {
+                    messages: vec![msg],
+                }
+                .to_bytes();
+                file.write_all(&bytes)
+                    .context("cannot write message to mbox archive")
+            }
+            Self::EmlDir { dir, next_index } => {
+                // zero-padded indexes keep the messages order when
+                // sorting file names
+                let path = dir.join(format!("{next_index:06}.eml"));
+                *next_index += 1;
+
+                OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&path)
+                    .and_then(|mut file| file.write_all(&msg.raw))
+                    .with_context(|| format!("cannot write message to {path:?}"))
+            }
+        }
+    }
+}
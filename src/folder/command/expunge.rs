@@ -6,10 +6,22 @@ use email::folder::expunge::imap::ExpungeFolderImap;
 use email::folder::expunge::maildir::ExpungeFolderMaildir;
 use log::info;
 
-#[cfg(any(feature = "imap", feature = "maildir", feature = "account-sync"))]
+#[cfg(any(
+    feature = "imap",
+    feature = "maildir",
+    feature = "account-sync",
+    feature = "mbox",
+    feature = "notmuch"
+))]
 use crate::backend::BackendKind;
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::ExpungeFolderJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::ExpungeFolderMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::ExpungeFolderNotmuch;
 use crate::{
     account::arg::name::AccountNameFlag, backend::Backend, config::TomlConfig,
     folder::arg::name::FolderNameArg, printer::Printer,
@@ -57,6 +69,12 @@ impl FolderExpungeCommand {
                         ctx.imap.as_ref().and_then(ExpungeFolderImap::new)
                     });
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder.set_expunge_folder(|ctx| {
+                        ctx.jmap.as_ref().and_then(ExpungeFolderJmap::new)
+                    });
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder.set_expunge_folder(|ctx| {
@@ -71,6 +89,18 @@ impl FolderExpungeCommand {
                             .and_then(ExpungeFolderMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder.set_expunge_folder(|ctx| {
+                        ctx.mbox.as_ref().and_then(ExpungeFolderMbox::new)
+                    });
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_expunge_folder(|ctx| {
+                        ctx.notmuch.as_ref().and_then(ExpungeFolderNotmuch::new)
+                    });
+                }
                 _ => (),
             },
         )
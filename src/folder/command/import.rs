@@ -0,0 +1,328 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+#[cfg(feature = "imap")]
+use email::message::add::imap::AddImapMessage;
+#[cfg(feature = "maildir")]
+use email::message::add::maildir::AddMaildirMessage;
+use indicatif::ProgressBar;
+use log::{debug, info};
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    vec,
+};
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::AddMessageJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::AddMessageMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::AddMessageNotmuch;
+#[allow(unused)]
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{Backend, BackendKind},
+    config::TomlConfig,
+    envelope::message_id,
+    folder::arg::name::FolderNameArg,
+    mbox::{MboxMessage, MboxReader},
+    printer::Printer,
+};
+
+use super::TRANSFER_PROGRESS_STYLE;
+
+/// Import messages into a folder.
+///
+/// Messages are read from the given path, which can be either an mbox
+/// file or a directory of .eml files (like the ones produced by the
+/// folder export command). Flags found in the Status, X-Status and
+/// X-Keywords headers are preserved. Messages whose Message-ID
+/// already exists in the folder are skipped, so the command can be
+/// run again safely.
+#[derive(Debug, Parser)]
+pub struct FolderImportCommand {
+    /// The path of the mbox file or of the .eml directory.
+    #[arg(value_name = "PATH")]
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub folder: FolderNameArg,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl FolderImportCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing import folder command");
+
+        let folder = &self.folder.name;
+        let archive = ArchiveReader::open(&self.path)?;
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let list_envelopes_kind = toml_account_config.list_envelopes_kind();
+        let add_message_kind = toml_account_config.add_message_kind();
+
+        let backend = Backend::new(
+            &toml_account_config,
+            &account_config,
+            list_envelopes_kind.into_iter().chain(add_message_kind),
+            |#[allow(unused)] builder| match add_message_kind {
+                #[cfg(feature = "imap")]
+                Some(BackendKind::Imap) => {
+                    builder.set_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new));
+                }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder.set_add_message(|ctx| ctx.jmap.as_ref().and_then(AddMessageJmap::new));
+                }
+                #[cfg(feature = "maildir")]
+                Some(BackendKind::Maildir) => {
+                    builder.set_add_message(|ctx| {
+                        ctx.maildir.as_ref().and_then(AddMaildirMessage::new)
+                    });
+                }
+                #[cfg(feature = "account-sync")]
+                Some(BackendKind::MaildirForSync) => {
+                    builder.set_add_message(|ctx| {
+                        ctx.maildir_for_sync
+                            .as_ref()
+                            .and_then(AddMaildirMessage::new)
+                    });
+                }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder.set_add_message(|ctx| ctx.mbox.as_ref().and_then(AddMessageMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_add_message(|ctx| {
+                        ctx.notmuch.as_ref().and_then(AddMessageNotmuch::new)
+                    });
+                }
+                _ => (),
+            },
+        )
+        .await?;
+
+        // the lib listing is used directly, since aliases and details
+        // are not needed
+        let mut message_ids: HashSet<String> = (*backend)
+            .list_envelopes(folder, 0, 0)
+            .await
+            .with_context(|| format!("cannot list envelopes of folder {folder}"))?
+            .iter()
+            .filter_map(message_id)
+            .map(normalize_message_id)
+            .collect();
+
+        let progress = if printer.is_json() {
+            ProgressBar::hidden()
+        } else {
+            match archive.len() {
+                Some(len) => ProgressBar::new(len as u64),
+                None => ProgressBar::no_length(),
+            }
+            .with_style(TRANSFER_PROGRESS_STYLE.clone())
+            .with_prefix(folder.clone())
+        };
+
+        let mut imported = 0;
+        let mut skipped = 0;
+
+        for msg in archive {
+            let msg = msg?;
+            let message_id = msg.message_id();
+
+            if !insert_message_id(&mut message_ids, message_id.as_deref()) {
+                debug!("message {message_id:?} already exists in folder {folder}, skipping it");
+                skipped += 1;
+                progress.inc(1);
+                continue;
+            }
+
+            progress.set_message(message_id.unwrap_or_default());
+
+            backend
+                .add_message_with_flags(folder, &msg.to_raw_msg(), &msg.flags())
+                .await?;
+
+            imported += 1;
+            progress.inc(1);
+        }
+
+        progress.finish_and_clear();
+
+        printer.print(format!(
+            "{imported} message(s) successfully imported to folder {folder}, {skipped} skipped!"
+        ))
+    }
+}
+
+/// The messages of an mbox file or of an .eml directory, read one by
+/// one.
+pub(super) enum ArchiveReader {
+    Mbox(MboxReader<BufReader<File>>),
+    EmlDir(vec::IntoIter<PathBuf>),
+}
+
+impl ArchiveReader {
+    pub(super) fn open(path: &Path) -> Result<Self> {
+        if !path.is_dir() {
+            let file =
+                File::open(path).with_context(|| format!("cannot open mbox file {path:?}"))?;
+            return Ok(Self::Mbox(MboxReader::new(BufReader::new(file))));
+        }
+
+        let mut paths = Vec::new();
+
+        for entry in
+            fs::read_dir(path).with_context(|| format!("cannot read directory {path:?}"))?
+        {
+            let entry_path = entry?.path();
+            let is_eml = entry_path
+                .extension()
+                .map_or(false, |ext| ext.eq_ignore_ascii_case("eml"));
+
+            if is_eml && entry_path.is_file() {
+                paths.push(entry_path);
+            }
+        }
+
+        paths.sort();
+
+        Ok(Self::EmlDir(paths.into_iter()))
+    }
+
+    /// Return the amount of messages, when it is known without
+    /// reading the whole archive.
+    fn len(&self) -> Option<usize> {
+        match self {
+            Self::Mbox(_) => None,
+            Self::EmlDir(paths) => Some(paths.len()),
+        }
+    }
+}
+
+impl Iterator for ArchiveReader {
+    type Item = Result<MboxMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Mbox(reader) => {
+                let msg = reader.next()?;
+                Some(msg.context("cannot read message from mbox file"))
+            }
+            Self::EmlDir(paths) => {
+                let path = paths.next()?;
+                let raw = fs::read(&path).with_context(|| format!("cannot read eml file {path:?}"));
+                Some(raw.map(|raw| MboxMessage {
+                    id: String::new(),
+                    from_line: String::new(),
+                    raw,
+                }))
+            }
+        }
+    }
+}
+
+/// Add the given Message-ID to the given ones, and return `false` if
+/// it was already there.
+///
+/// Messages without Message-ID cannot be deduplicated, so they are
+/// always imported.
+fn insert_message_id(message_ids: &mut HashSet<String>, message_id: Option<&str>) -> bool {
+    match message_id {
+        Some(id) => message_ids.insert(normalize_message_id(id)),
+        None => true,
+    }
+}
+
+/// Normalize the given Message-ID by removing its angle brackets, so
+/// that ids coming from envelopes and from raw headers can be
+/// compared.
+fn normalize_message_id(id: &str) -> String {
+    id.trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .trim()
+        .to_owned()
+}
+
+#[cfg(all(test, feature = "folder-export"))]
+mod tests {
+    use email::flag::{Flag, Flags};
+    use std::collections::HashSet;
+
+    use super::{insert_message_id, normalize_message_id, ArchiveReader};
+    use crate::{
+        folder::command::export::{Archive, ExportFormat},
+        mbox::MboxMessage,
+    };
+
+    fn messages() -> Vec<(Vec<u8>, Flags)> {
+        vec![
+            (
+                b"Message-ID: <1@localhost>\r\nFrom: alice@localhost\r\nSubject: first\r\n\r\nFrom the start\r\n".to_vec(),
+                Flags::from_iter([Flag::Seen, Flag::Flagged, Flag::Custom("work".into())]),
+            ),
+            (
+                b"From: bob@localhost\r\nSubject: second\r\n\r\nbody\r\n".to_vec(),
+                Flags::default(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn export_then_import() {
+        for format in [ExportFormat::Mbox, ExportFormat::EmlDir] {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("archive");
+
+            let mut archive = Archive::create(format, &path).unwrap();
+            for (raw, flags) in messages() {
+                archive
+                    .write(MboxMessage::from_raw_msg(&raw, &flags))
+                    .unwrap();
+            }
+            drop(archive);
+
+            let imported: Vec<(Vec<u8>, Flags)> = ArchiveReader::open(&path)
+                .unwrap()
+                .map(|msg| {
+                    let msg = msg.unwrap();
+                    (msg.to_raw_msg(), msg.flags())
+                })
+                .collect();
+
+            assert_eq!(imported, messages(), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn skip_known_message_ids() {
+        let mut message_ids = HashSet::from_iter([normalize_message_id("<1@localhost>")]);
+
+        assert!(!insert_message_id(&mut message_ids, Some("<1@localhost>")));
+        assert!(!insert_message_id(&mut message_ids, Some("1@localhost")));
+        assert!(insert_message_id(&mut message_ids, Some("<2@localhost>")));
+        assert!(!insert_message_id(&mut message_ids, Some("<2@localhost>")));
+
+        // messages without Message-ID are always imported
+        assert!(insert_message_id(&mut message_ids, None));
+        assert!(insert_message_id(&mut message_ids, None));
+    }
+}
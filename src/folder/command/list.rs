@@ -6,10 +6,22 @@ use email::folder::list::imap::ListFoldersImap;
 use email::folder::list::maildir::ListFoldersMaildir;
 use log::info;
 
-#[cfg(any(feature = "imap", feature = "maildir", feature = "account-sync"))]
+#[cfg(any(
+    feature = "imap",
+    feature = "maildir",
+    feature = "account-sync",
+    feature = "mbox",
+    feature = "notmuch"
+))]
 use crate::backend::BackendKind;
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "jmap")]
+use crate::jmap::ListFoldersJmap;
+#[cfg(feature = "mbox")]
+use crate::mbox::ListFoldersMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::ListFoldersNotmuch;
 use crate::{
     account::arg::name::AccountNameFlag,
     backend::Backend,
@@ -57,6 +69,11 @@ impl FolderListCommand {
                     builder
                         .set_list_folders(|ctx| ctx.imap.as_ref().and_then(ListFoldersImap::new));
                 }
+                #[cfg(feature = "jmap")]
+                Some(BackendKind::Jmap) => {
+                    builder
+                        .set_list_folders(|ctx| ctx.jmap.as_ref().and_then(ListFoldersJmap::new));
+                }
                 #[cfg(feature = "maildir")]
                 Some(BackendKind::Maildir) => {
                     builder.set_list_folders(|ctx| {
@@ -71,6 +88,17 @@ impl FolderListCommand {
                             .and_then(ListFoldersMaildir::new)
                     });
                 }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_list_folders(|ctx| ctx.mbox.as_ref().and_then(ListFoldersMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_list_folders(|ctx| {
+                        ctx.notmuch.as_ref().and_then(ListFoldersNotmuch::new)
+                    });
+                }
                 _ => (),
             },
         )
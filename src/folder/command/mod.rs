@@ -2,8 +2,12 @@
 mod create;
 #[cfg(feature = "folder-delete")]
 mod delete;
+#[cfg(feature = "folder-export")]
+mod export;
 #[cfg(feature = "folder-expunge")]
 mod expunge;
+#[cfg(feature = "folder-import")]
+mod import;
 #[cfg(feature = "folder-list")]
 mod list;
 #[cfg(feature = "folder-purge")]
@@ -11,6 +15,10 @@ mod purge;
 
 use anyhow::Result;
 use clap::Subcommand;
+#[cfg(any(feature = "folder-export", feature = "folder-import"))]
+use indicatif::ProgressStyle;
+#[cfg(any(feature = "folder-export", feature = "folder-import"))]
+use once_cell::sync::Lazy;
 
 use crate::{config::TomlConfig, printer::Printer};
 
@@ -18,13 +26,27 @@ use crate::{config::TomlConfig, printer::Printer};
 use self::create::AddFolderCommand;
 #[cfg(feature = "folder-delete")]
 use self::delete::FolderDeleteCommand;
+#[cfg(feature = "folder-export")]
+use self::export::FolderExportCommand;
 #[cfg(feature = "folder-expunge")]
 use self::expunge::FolderExpungeCommand;
+#[cfg(feature = "folder-import")]
+use self::import::FolderImportCommand;
 #[cfg(feature = "folder-list")]
 use self::list::FolderListCommand;
 #[cfg(feature = "folder-purge")]
 use self::purge::FolderPurgeCommand;
 
+/// The progress bar style shared by the export and import commands,
+/// matching the folder progress bars of the account synchronization.
+#[cfg(any(feature = "folder-export", feature = "folder-import"))]
+static TRANSFER_PROGRESS_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
+    ProgressStyle::with_template(
+        "   {prefix:.bold} — {wide_msg:.dim} \n   {wide_bar:.cyan/blue} {pos}/{len} ",
+    )
+    .unwrap()
+});
+
 /// Manage folders.
 ///
 /// A folder (as known as mailbox, or directory) contains one or more
@@ -50,6 +72,14 @@ pub enum FolderSubcommand {
     #[cfg(feature = "folder-delete")]
     #[command(alias = "remove", alias = "rm")]
     Delete(FolderDeleteCommand),
+
+    #[cfg(feature = "folder-export")]
+    #[command()]
+    Export(FolderExportCommand),
+
+    #[cfg(feature = "folder-import")]
+    #[command()]
+    Import(FolderImportCommand),
 }
 
 impl FolderSubcommand {
@@ -66,6 +96,10 @@ impl FolderSubcommand {
             Self::Purge(cmd) => cmd.execute(printer, config).await,
             #[cfg(feature = "folder-delete")]
             Self::Delete(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "folder-export")]
+            Self::Export(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "folder-import")]
+            Self::Import(cmd) => cmd.execute(printer, config).await,
         }
     }
 }
@@ -6,10 +6,20 @@ use email::folder::purge::imap::PurgeFolderImap;
 use log::info;
 use std::process;
 
-#[cfg(any(feature = "imap", feature = "maildir", feature = "account-sync"))]
+#[cfg(any(
+    feature = "imap",
+    feature = "maildir",
+    feature = "account-sync",
+    feature = "mbox",
+    feature = "notmuch"
+))]
 use crate::backend::BackendKind;
 #[cfg(feature = "account-sync")]
 use crate::cache::arg::disable::CacheDisableFlag;
+#[cfg(feature = "mbox")]
+use crate::mbox::PurgeFolderMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::PurgeFolderNotmuch;
 use crate::{
     account::arg::name::AccountNameFlag, backend::Backend, config::TomlConfig,
     folder::arg::name::FolderNameArg, printer::Printer,
@@ -81,6 +91,17 @@ impl FolderPurgeCommand {
                 //             .and_then(PurgeFolderMaildir::new)
                 //     });
                 // }
+                #[cfg(feature = "mbox")]
+                Some(BackendKind::Mbox) => {
+                    builder
+                        .set_purge_folder(|ctx| ctx.mbox.as_ref().and_then(PurgeFolderMbox::new));
+                }
+                #[cfg(feature = "notmuch")]
+                Some(BackendKind::Notmuch) => {
+                    builder.set_purge_folder(|ctx| {
+                        ctx.notmuch.as_ref().and_then(PurgeFolderNotmuch::new)
+                    });
+                }
                 _ => (),
             },
         )
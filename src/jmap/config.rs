@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use email::account::config::passwd::PasswdConfig;
+use log::debug;
+use secret::Secret;
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// The JMAP backend configuration.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct JmapConfig {
+    /// The URL of the JMAP session resource.
+    ///
+    /// When the URL has no path, the session resource is discovered
+    /// at `/.well-known/jmap`.
+    pub url: String,
+
+    /// The login used for the basic authentication, usually the
+    /// email address.
+    pub login: String,
+
+    /// The authentication configuration.
+    #[serde(flatten)]
+    pub auth: JmapAuthConfig,
+}
+
+/// The JMAP authentication configuration.
+///
+/// Authentication can be done using the login and a password (basic
+/// authentication), or using an API token (bearer authentication).
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JmapAuthConfig {
+    /// The password configuration.
+    #[serde(alias = "password")]
+    Passwd(PasswdConfig),
+
+    /// The API token configuration.
+    Bearer(Secret),
+}
+
+impl Default for JmapAuthConfig {
+    fn default() -> Self {
+        Self::Passwd(Default::default())
+    }
+}
+
+impl JmapAuthConfig {
+    /// Reset the secret, if it is a keyring entry.
+    pub async fn reset(&self) -> Result<()> {
+        match self {
+            Self::Passwd(passwd) => passwd.reset().await,
+            Self::Bearer(token) => token
+                .delete_keyring_entry_secret()
+                .await
+                .context("cannot delete JMAP token from global keyring"),
+        }
+    }
+
+    /// Define the secret only if it does not exist in the keyring.
+    pub async fn configure(&self, get_secret: impl Fn() -> io::Result<String>) -> Result<()> {
+        match self {
+            Self::Passwd(passwd) => passwd.configure(get_secret).await,
+            Self::Bearer(token) => match token.find().await {
+                Ok(None) => {
+                    debug!("cannot find JMAP token from keyring, setting it");
+                    let token_value = get_secret().context("cannot get JMAP token from user")?;
+                    token
+                        .set_keyring_entry_secret(token_value)
+                        .await
+                        .context("cannot save JMAP token into global keyring")?;
+                    Ok(())
+                }
+                Ok(_) => Ok(()),
+                Err(err) => Err(err).context("cannot get JMAP token from global keyring"),
+            },
+        }
+    }
+
+    /// Replace undefined secrets by keyring entries named after the
+    /// given account name.
+    pub fn replace_undefined_keyring_entries(&mut self, name: impl AsRef<str>) {
+        let name = name.as_ref();
+
+        match self {
+            Self::Passwd(passwd) => {
+                passwd.set_keyring_entry_if_undefined(format!("{name}-jmap-passwd"));
+            }
+            Self::Bearer(token) => {
+                token.set_keyring_entry_if_undefined(format!("{name}-jmap-token"));
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Module dedicated to JMAP envelopes.
+
+#[cfg(feature = "envelope-get")]
+use anyhow::anyhow;
+use anyhow::Result;
+use async_trait::async_trait;
+#[cfg(feature = "envelope-get")]
+use email::envelope::{get::GetEnvelope, Envelope, Id};
+#[cfg(feature = "envelope-list")]
+use email::envelope::{list::ListEnvelopes, Envelopes};
+use log::info;
+#[cfg(feature = "envelope-list")]
+use log::trace;
+#[cfg(feature = "envelope-list")]
+use serde_json::json;
+
+use super::{JmapSession, ENVELOPE_PROPERTIES};
+
+#[cfg(feature = "envelope-list")]
+pub struct ListEnvelopesJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "envelope-list")]
+impl ListEnvelopesJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn ListEnvelopes>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "envelope-list")]
+#[async_trait]
+impl ListEnvelopes for ListEnvelopesJmap {
+    /// List envelopes from the most recent one. Pagination is done by
+    /// the server.
+    async fn list_envelopes(
+        &self,
+        folder: &str,
+        page_size: usize,
+        page: usize,
+    ) -> Result<Envelopes> {
+        info!("listing JMAP envelopes from folder {folder}");
+
+        let mailbox_id = self.session.mailbox_id(folder).await?;
+        let ids = self
+            .session
+            .query_emails(
+                json!({ "inMailbox": mailbox_id }),
+                page * page_size,
+                page_size,
+            )
+            .await?;
+
+        let emails = self
+            .session
+            .get_emails(ids.iter().map(String::as_str), &ENVELOPE_PROPERTIES)
+            .await?;
+
+        let envelopes = Envelopes::from_iter(emails.iter().map(|email| email.to_envelope()));
+        trace!("JMAP envelopes: {envelopes:#?}");
+
+        Ok(envelopes)
+    }
+}
+
+#[cfg(feature = "envelope-get")]
+pub struct GetEnvelopeJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "envelope-get")]
+impl GetEnvelopeJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn GetEnvelope>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "envelope-get")]
+#[async_trait]
+impl GetEnvelope for GetEnvelopeJmap {
+    async fn get_envelope(&self, folder: &str, id: &Id) -> Result<Envelope> {
+        info!("getting JMAP envelope {id} from folder {folder}");
+
+        let emails = self
+            .session
+            .get_emails(id.iter().take(1), &ENVELOPE_PROPERTIES)
+            .await?;
+
+        emails
+            .first()
+            .map(|email| email.to_envelope())
+            .ok_or_else(|| anyhow!("cannot find JMAP envelope {id}"))
+    }
+}
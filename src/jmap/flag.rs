@@ -0,0 +1,113 @@
+//! Module dedicated to JMAP flags.
+//!
+//! Flags are stored as email keywords, see the module documentation
+//! of [`super`] for the mapping.
+
+use anyhow::Result;
+use async_trait::async_trait;
+#[cfg(feature = "flag-add")]
+use email::flag::add::AddFlags;
+#[cfg(feature = "flag-remove")]
+use email::flag::remove::RemoveFlags;
+#[cfg(feature = "flag-set")]
+use email::flag::set::SetFlags;
+use email::{envelope::Id, flag::Flags};
+use log::info;
+#[cfg(feature = "flag-set")]
+use serde_json::json;
+#[cfg(any(feature = "flag-add", feature = "flag-remove"))]
+use serde_json::{Map, Value};
+
+#[cfg(any(feature = "flag-add", feature = "flag-remove"))]
+use super::keyword;
+#[cfg(feature = "flag-set")]
+use super::keywords;
+use super::JmapSession;
+
+/// Build a patch setting the keywords matching the given flags to
+/// the given value. The `null` value removes the keywords.
+#[cfg(any(feature = "flag-add", feature = "flag-remove"))]
+fn keywords_patch(flags: &Flags, value: Value) -> Value {
+    let patch: Map<String, Value> = flags
+        .iter()
+        .map(|flag| {
+            // keywords are part of a JSON pointer, so they need to be
+            // escaped
+            let keyword = keyword(flag).replace('~', "~0").replace('/', "~1");
+            (format!("keywords/{keyword}"), value.clone())
+        })
+        .collect();
+    Value::Object(patch)
+}
+
+#[cfg(feature = "flag-add")]
+pub struct AddFlagsJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "flag-add")]
+impl AddFlagsJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn AddFlags>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "flag-add")]
+#[async_trait]
+impl AddFlags for AddFlagsJmap {
+    async fn add_flags(&self, folder: &str, id: &Id, flags: &Flags) -> Result<()> {
+        info!("adding JMAP flags {flags} to envelope(s) {id} from folder {folder}");
+
+        let patch = keywords_patch(flags, Value::Bool(true));
+        self.session.update_emails(id.iter(), patch).await
+    }
+}
+
+#[cfg(feature = "flag-set")]
+pub struct SetFlagsJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "flag-set")]
+impl SetFlagsJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn SetFlags>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "flag-set")]
+#[async_trait]
+impl SetFlags for SetFlagsJmap {
+    async fn set_flags(&self, folder: &str, id: &Id, flags: &Flags) -> Result<()> {
+        info!("setting JMAP flags {flags} to envelope(s) {id} from folder {folder}");
+
+        let patch = json!({ "keywords": keywords(flags) });
+        self.session.update_emails(id.iter(), patch).await
+    }
+}
+
+#[cfg(feature = "flag-remove")]
+pub struct RemoveFlagsJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "flag-remove")]
+impl RemoveFlagsJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn RemoveFlags>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "flag-remove")]
+#[async_trait]
+impl RemoveFlags for RemoveFlagsJmap {
+    async fn remove_flags(&self, folder: &str, id: &Id, flags: &Flags) -> Result<()> {
+        info!("removing JMAP flags {flags} from envelope(s) {id} from folder {folder}");
+
+        let patch = keywords_patch(flags, Value::Null);
+        self.session.update_emails(id.iter(), patch).await
+    }
+}
@@ -0,0 +1,98 @@
+//! Module dedicated to JMAP folders.
+//!
+//! Folders are the JMAP mailboxes, see the module documentation of
+//! [`super`] for the naming rules.
+
+use anyhow::Result;
+use async_trait::async_trait;
+#[cfg(feature = "folder-expunge")]
+use email::folder::expunge::ExpungeFolder;
+#[cfg(feature = "folder-list")]
+use email::folder::{list::ListFolders, Folder, FolderKind, Folders};
+use log::info;
+#[cfg(feature = "folder-expunge")]
+use serde_json::json;
+
+use super::JmapSession;
+
+#[cfg(feature = "folder-list")]
+pub struct ListFoldersJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "folder-list")]
+impl ListFoldersJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn ListFolders>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-list")]
+#[async_trait]
+impl ListFolders for ListFoldersJmap {
+    async fn list_folders(&self) -> Result<Folders> {
+        info!("listing JMAP folders");
+
+        let config = &self.session.account_config;
+        let mut mailboxes = self.session.list_mailboxes().await?;
+        mailboxes.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut folders = Folders::default();
+
+        for mailbox in mailboxes {
+            let role = mailbox.role.unwrap_or_default();
+
+            // the role takes precedence over the user aliases, since
+            // it is defined by the server
+            let kind = match role.parse::<FolderKind>() {
+                Ok(kind) => Some(kind),
+                Err(_) => config.find_folder_kind_from_alias(&mailbox.path),
+            };
+
+            folders.push(Folder {
+                kind,
+                name: mailbox.path,
+                desc: role,
+            });
+        }
+
+        Ok(folders)
+    }
+}
+
+#[cfg(feature = "folder-expunge")]
+pub struct ExpungeFolderJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "folder-expunge")]
+impl ExpungeFolderJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn ExpungeFolder>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-expunge")]
+#[async_trait]
+impl ExpungeFolder for ExpungeFolderJmap {
+    /// Destroy the folder emails having the `$deleted` keyword.
+    async fn expunge_folder(&self, folder: &str) -> Result<()> {
+        info!("expunging JMAP folder {folder}");
+
+        let mailbox_id = self.session.mailbox_id(folder).await?;
+        let ids = self
+            .session
+            .query_emails(
+                json!({ "inMailbox": mailbox_id, "hasKeyword": "$deleted" }),
+                0,
+                0,
+            )
+            .await?;
+
+        self.session
+            .destroy_emails(ids.iter().map(String::as_str))
+            .await
+    }
+}
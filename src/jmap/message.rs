@@ -0,0 +1,237 @@
+//! Module dedicated to JMAP messages.
+//!
+//! Raw messages are JMAP blobs: they are uploaded then imported when
+//! added, and downloaded when peeked. Sending a message means
+//! importing it as a draft then submitting it, the draft being
+//! destroyed by the server once the submission succeeded.
+
+use anyhow::Result;
+#[cfg(feature = "message-send")]
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+#[cfg(any(
+    feature = "message-peek",
+    feature = "message-copy",
+    feature = "message-move"
+))]
+use email::envelope::Id;
+#[cfg(feature = "message-copy")]
+use email::message::copy::CopyMessages;
+#[cfg(feature = "message-move")]
+use email::message::move_::MoveMessages;
+#[cfg(feature = "message-send")]
+use email::message::send::SendMessage;
+#[cfg(feature = "message-peek")]
+use email::message::{peek::PeekMessages, Messages};
+#[cfg(feature = "message-add")]
+use email::{envelope::SingleId, flag::Flags, message::add::AddMessage};
+#[cfg(feature = "message-send")]
+use log::debug;
+use log::info;
+#[cfg(any(
+    feature = "message-copy",
+    feature = "message-move",
+    feature = "message-send"
+))]
+use serde_json::json;
+
+use super::JmapSession;
+
+#[cfg(feature = "message-add")]
+pub struct AddMessageJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "message-add")]
+impl AddMessageJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn AddMessage>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-add")]
+#[async_trait]
+impl AddMessage for AddMessageJmap {
+    async fn add_message_with_flags(
+        &self,
+        folder: &str,
+        raw_msg: &[u8],
+        flags: &Flags,
+    ) -> Result<SingleId> {
+        info!("adding JMAP message to folder {folder} with flags {flags}");
+
+        let mailbox_id = self.session.mailbox_id(folder).await?;
+        let id = self
+            .session
+            .import_email(&mailbox_id, raw_msg, flags)
+            .await?;
+
+        Ok(SingleId::from(id))
+    }
+}
+
+#[cfg(feature = "message-peek")]
+pub struct PeekMessagesJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "message-peek")]
+impl PeekMessagesJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn PeekMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-peek")]
+#[async_trait]
+impl PeekMessages for PeekMessagesJmap {
+    async fn peek_messages(&self, folder: &str, id: &Id) -> Result<Messages> {
+        info!("peeking JMAP messages {id} from folder {folder}");
+
+        let emails = self
+            .session
+            .get_emails(id.iter(), &["id", "blobId"])
+            .await?;
+        let mut msgs = Vec::with_capacity(emails.len());
+
+        for email in emails {
+            msgs.push(self.session.download(&email.blob_id).await?);
+        }
+
+        Ok(Messages::from(msgs))
+    }
+}
+
+#[cfg(feature = "message-copy")]
+pub struct CopyMessagesJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "message-copy")]
+impl CopyMessagesJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn CopyMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-copy")]
+#[async_trait]
+impl CopyMessages for CopyMessagesJmap {
+    /// Add the target mailbox to the emails. JMAP emails can belong
+    /// to several mailboxes, so nothing is duplicated.
+    async fn copy_messages(&self, from_folder: &str, to_folder: &str, id: &Id) -> Result<()> {
+        info!("copying JMAP messages {id} from folder {from_folder} to folder {to_folder}");
+
+        let mailbox_id = self.session.mailbox_id(to_folder).await?;
+        let patch = json!({ format!("mailboxIds/{mailbox_id}"): true });
+
+        self.session.update_emails(id.iter(), patch).await
+    }
+}
+
+#[cfg(feature = "message-move")]
+pub struct MoveMessagesJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "message-move")]
+impl MoveMessagesJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn MoveMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-move")]
+#[async_trait]
+impl MoveMessages for MoveMessagesJmap {
+    async fn move_messages(&self, from_folder: &str, to_folder: &str, id: &Id) -> Result<()> {
+        info!("moving JMAP messages {id} from folder {from_folder} to folder {to_folder}");
+
+        let from_mailbox_id = self.session.mailbox_id(from_folder).await?;
+        let to_mailbox_id = self.session.mailbox_id(to_folder).await?;
+
+        // only the source mailbox is replaced, so that emails
+        // belonging to other mailboxes stay there
+        let patch = json!({
+            format!("mailboxIds/{from_mailbox_id}"): null,
+            format!("mailboxIds/{to_mailbox_id}"): true,
+        });
+
+        self.session.update_emails(id.iter(), patch).await
+    }
+}
+
+#[cfg(feature = "message-send")]
+pub struct SendMessageJmap {
+    session: JmapSession,
+}
+
+#[cfg(feature = "message-send")]
+impl SendMessageJmap {
+    pub fn new(session: &JmapSession) -> Option<Box<dyn SendMessage>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-send")]
+#[async_trait]
+impl SendMessage for SendMessageJmap {
+    async fn send_message(&self, raw_msg: &[u8]) -> Result<()> {
+        info!("sending JMAP message");
+
+        let session = &self.session;
+        let identity_id = session.identity_id().await?;
+        let drafts = session.account_config.get_drafts_folder_alias();
+        let drafts_id = session
+            .mailbox_id(&drafts)
+            .await
+            .context("cannot find JMAP drafts mailbox")?;
+        let blob_id = session.upload(raw_msg).await?;
+
+        let res = session
+            .call(vec![
+                (
+                    "Email/import",
+                    json!({
+                        "accountId": session.account_id,
+                        "emails": {
+                            "draft": {
+                                "blobId": blob_id,
+                                "mailboxIds": { drafts_id: true },
+                                "keywords": { "$seen": true, "$draft": true },
+                            },
+                        },
+                    }),
+                ),
+                (
+                    "EmailSubmission/set",
+                    json!({
+                        "accountId": session.submission_account_id,
+                        "create": {
+                            "send": {
+                                "identityId": identity_id,
+                                "emailId": "#draft",
+                            },
+                        },
+                        "onSuccessDestroyEmail": ["#send"],
+                    }),
+                ),
+            ])
+            .await?;
+
+        super::check_set_errors(&res[0], "notCreated").context("cannot import JMAP draft")?;
+        super::check_set_errors(&res[1], "notCreated").context("cannot submit JMAP email")?;
+
+        let submission_id = res[1]["created"]["send"]["id"]
+            .as_str()
+            .ok_or_else(|| anyhow!("cannot find id of JMAP email submission"))?;
+        debug!("JMAP email submission {submission_id} created");
+
+        Ok(())
+    }
+}
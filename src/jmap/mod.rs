@@ -0,0 +1,1054 @@
+//! Module dedicated to the JMAP backend.
+//!
+//! JMAP (RFC 8620 and RFC 8621) is a JSON API served over HTTP. The
+//! session resource is fetched once, in order to discover the API,
+//! upload and download URLs, the capabilities supported by the server
+//! as well as the mail account id. Every backend feature is then made
+//! of one or few method calls.
+//!
+//! Folders are JMAP mailboxes, named after their path (parent names
+//! joined with `/`). Mailboxes having a role can also be reached by
+//! the name of their role (`inbox`, `sent`, `drafts`, `trash` etc),
+//! so that default folder aliases work out of the box.
+//!
+//! Flags are mapped to the standard `$seen`, `$answered`, `$flagged`
+//! and `$draft` keywords. JMAP has no deleted keyword, so the
+//! `$deleted` keyword is used instead, and expunging a folder
+//! destroys the emails having it.
+
+pub mod config;
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
+pub mod envelope;
+#[cfg(any(feature = "flag-add", feature = "flag-set", feature = "flag-remove"))]
+pub mod flag;
+#[cfg(any(feature = "folder-list", feature = "folder-expunge"))]
+pub mod folder;
+#[cfg(any(
+    feature = "message-add",
+    feature = "message-peek",
+    feature = "message-copy",
+    feature = "message-move",
+    feature = "message-send"
+))]
+pub mod message;
+#[cfg(feature = "wizard")]
+pub(crate) mod wizard;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, FixedOffset};
+use email::{
+    account::config::AccountConfig,
+    envelope::{Address, Envelope},
+    flag::{Flag, Flags},
+};
+use log::{debug, trace};
+use reqwest::{header::CONTENT_TYPE, Client, Method, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::PathBuf,
+};
+use url::Url;
+
+pub use self::config::{JmapAuthConfig, JmapConfig};
+#[cfg(feature = "envelope-get")]
+pub use self::envelope::GetEnvelopeJmap;
+#[cfg(feature = "envelope-list")]
+pub use self::envelope::ListEnvelopesJmap;
+#[cfg(feature = "flag-add")]
+pub use self::flag::AddFlagsJmap;
+#[cfg(feature = "flag-remove")]
+pub use self::flag::RemoveFlagsJmap;
+#[cfg(feature = "flag-set")]
+pub use self::flag::SetFlagsJmap;
+#[cfg(feature = "folder-expunge")]
+pub use self::folder::ExpungeFolderJmap;
+#[cfg(feature = "folder-list")]
+pub use self::folder::ListFoldersJmap;
+#[cfg(feature = "message-add")]
+pub use self::message::AddMessageJmap;
+#[cfg(feature = "message-copy")]
+pub use self::message::CopyMessagesJmap;
+#[cfg(feature = "message-move")]
+pub use self::message::MoveMessagesJmap;
+#[cfg(feature = "message-peek")]
+pub use self::message::PeekMessagesJmap;
+#[cfg(feature = "message-send")]
+pub use self::message::SendMessageJmap;
+
+const CORE_CAPABILITY: &str = "urn:ietf:params:jmap:core";
+const MAIL_CAPABILITY: &str = "urn:ietf:params:jmap:mail";
+const SUBMISSION_CAPABILITY: &str = "urn:ietf:params:jmap:submission";
+
+/// The keywords matching the flags, see the module documentation.
+const KEYWORDS: [(Flag, &str); 5] = [
+    (Flag::Seen, "$seen"),
+    (Flag::Answered, "$answered"),
+    (Flag::Flagged, "$flagged"),
+    (Flag::Draft, "$draft"),
+    (Flag::Deleted, "$deleted"),
+];
+
+/// The email properties needed to build envelopes.
+const ENVELOPE_PROPERTIES: [&str; 8] = [
+    "id",
+    "messageId",
+    "keywords",
+    "from",
+    "to",
+    "subject",
+    "sentAt",
+    "receivedAt",
+];
+
+/// The JMAP session builder.
+#[derive(Clone, Debug)]
+pub struct JmapSessionBuilder {
+    account_config: AccountConfig,
+    jmap_config: JmapConfig,
+}
+
+impl JmapSessionBuilder {
+    pub fn new(account_config: AccountConfig, jmap_config: JmapConfig) -> Self {
+        Self {
+            account_config,
+            jmap_config,
+        }
+    }
+
+    /// Build the session by fetching the session resource.
+    pub async fn build(self) -> Result<JmapSession> {
+        let auth = match &self.jmap_config.auth {
+            JmapAuthConfig::Passwd(passwd) => JmapAuth::Basic {
+                login: self.jmap_config.login.clone(),
+                passwd: passwd.get().await.context("cannot get JMAP password")?,
+            },
+            JmapAuthConfig::Bearer(token) => {
+                JmapAuth::Bearer(token.get().await.context("cannot get JMAP token")?)
+            }
+        };
+
+        let url = session_url(&self.jmap_config.url)?;
+        debug!("fetching JMAP session resource at {url}");
+
+        let client = Client::new();
+        let resource: SessionResource = auth
+            .apply(client.get(url.clone()))
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .with_context(|| format!("cannot fetch JMAP session resource at {url}"))?
+            .json()
+            .await
+            .context("cannot parse JMAP session resource")?;
+        trace!("JMAP session resource: {resource:#?}");
+
+        let account_id = resource
+            .primary_accounts
+            .get(MAIL_CAPABILITY)
+            .cloned()
+            .ok_or_else(|| anyhow!("cannot find JMAP mail account"))?;
+        let submission_account_id = resource
+            .primary_accounts
+            .get(SUBMISSION_CAPABILITY)
+            .cloned()
+            .unwrap_or_else(|| account_id.clone());
+
+        Ok(JmapSession {
+            account_config: self.account_config,
+            jmap_config: self.jmap_config,
+            client,
+            auth,
+            account_id,
+            submission_account_id,
+            capabilities: resource.capabilities.into_keys().collect(),
+            api_url: resolve_url(&url, &resource.api_url),
+            download_url: resolve_url(&url, &resource.download_url),
+            upload_url: resolve_url(&url, &resource.upload_url),
+        })
+    }
+}
+
+/// The JMAP session.
+#[derive(Clone, Debug)]
+pub struct JmapSession {
+    pub account_config: AccountConfig,
+    pub jmap_config: JmapConfig,
+    client: Client,
+    auth: JmapAuth,
+    account_id: String,
+    submission_account_id: String,
+    capabilities: HashSet<String>,
+    api_url: String,
+    download_url: String,
+    upload_url: String,
+}
+
+impl JmapSession {
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.auth.apply(self.client.request(method, url))
+    }
+
+    /// Send the given method calls in a single request, and return
+    /// the arguments of their responses, in the same order.
+    ///
+    /// Calls can refer to the results of the previous ones using
+    /// their index as call id.
+    ///
+    /// The submission capability is only used by calls needing it,
+    /// since servers reject requests using unsupported capabilities.
+    pub async fn call(&self, calls: Vec<(&str, Value)>) -> Result<Vec<Value>> {
+        let len = calls.len();
+
+        let mut using = vec![CORE_CAPABILITY, MAIL_CAPABILITY];
+        if calls.iter().any(|(name, _)| is_submission_method(name)) {
+            if !self.capabilities.contains(SUBMISSION_CAPABILITY) {
+                bail!("JMAP server does not support email submission");
+            }
+            using.push(SUBMISSION_CAPABILITY);
+        }
+
+        let method_calls: Vec<Value> = calls
+            .into_iter()
+            .enumerate()
+            .map(|(id, (name, args))| json!([name, args, id.to_string()]))
+            .collect();
+        let body = json!({
+            "using": using,
+            "methodCalls": method_calls,
+        });
+        trace!("JMAP request: {body}");
+
+        let res: Value = self
+            .request(Method::POST, &self.api_url)
+            .json(&body)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .context("cannot send JMAP request")?
+            .json()
+            .await
+            .context("cannot parse JMAP response")?;
+        trace!("JMAP response: {res}");
+
+        let responses = res["methodResponses"]
+            .as_array()
+            .ok_or_else(|| anyhow!("cannot find JMAP method responses"))?;
+
+        // a single call can produce several responses, only the
+        // first one is the response of the call itself
+        (0..len)
+            .map(|id| {
+                let id = id.to_string();
+                let res = responses
+                    .iter()
+                    .find(|res| res[2] == id.as_str())
+                    .ok_or_else(|| anyhow!("cannot find JMAP method response {id}"))?;
+
+                if res[0] == "error" {
+                    bail!("JMAP method error: {}", describe_error(&res[1]));
+                }
+
+                Ok(res[1].clone())
+            })
+            .collect()
+    }
+
+    /// Send the given method call and return the arguments of its
+    /// response.
+    pub async fn call_one(&self, name: &str, args: Value) -> Result<Value> {
+        let mut responses = self.call(vec![(name, args)]).await?;
+        Ok(responses.remove(0))
+    }
+
+    /// List all mailboxes, with their path.
+    pub async fn list_mailboxes(&self) -> Result<Vec<JmapMailbox>> {
+        let res = self
+            .call_one(
+                "Mailbox/get",
+                json!({
+                    "accountId": self.account_id,
+                    "ids": null,
+                    "properties": ["id", "name", "parentId", "role"],
+                }),
+            )
+            .await?;
+
+        let mut mailboxes: Vec<JmapMailbox> =
+            serde_json::from_value(res["list"].clone()).context("cannot parse JMAP mailboxes")?;
+
+        let parents: HashMap<String, (String, Option<String>)> = mailboxes
+            .iter()
+            .map(|m| (m.id.clone(), (m.name.clone(), m.parent_id.clone())))
+            .collect();
+
+        for mailbox in &mut mailboxes {
+            let mut names = vec![mailbox.name.clone()];
+            let mut parent_id = mailbox.parent_id.clone();
+
+            // the length check protects against cyclic parents
+            while let Some((name, next_id)) = parent_id.and_then(|id| parents.get(&id)) {
+                if names.len() > parents.len() {
+                    break;
+                }
+                names.push(name.clone());
+                parent_id = next_id.clone();
+            }
+
+            names.reverse();
+            mailbox.path = names.join("/");
+        }
+
+        Ok(mailboxes)
+    }
+
+    /// Find the id of the mailbox matching the given folder, by path
+    /// or by role.
+    pub async fn mailbox_id(&self, folder: &str) -> Result<String> {
+        let alias = self.account_config.get_folder_alias(folder);
+        let mailboxes = self.list_mailboxes().await?;
+
+        mailboxes
+            .iter()
+            .find(|mailbox| mailbox.path == alias)
+            .or_else(|| {
+                mailboxes.iter().find(|mailbox| {
+                    let role = mailbox.role.as_deref().unwrap_or_default();
+                    !role.is_empty() && role.eq_ignore_ascii_case(&alias)
+                })
+            })
+            .map(|mailbox| mailbox.id.clone())
+            .ok_or_else(|| anyhow!("cannot find JMAP mailbox matching folder {folder}"))
+    }
+
+    /// Query the ids of the emails matching the given filter, from
+    /// the most recent one. A limit of 0 means no limit.
+    pub async fn query_emails(
+        &self,
+        filter: Value,
+        position: usize,
+        limit: usize,
+    ) -> Result<Vec<String>> {
+        let mut args = json!({
+            "accountId": self.account_id,
+            "filter": filter,
+            "sort": [{ "property": "receivedAt", "isAscending": false }],
+            "position": position,
+        });
+
+        if limit > 0 {
+            args["limit"] = json!(limit);
+        }
+
+        let res = self.call_one("Email/query", args).await?;
+
+        serde_json::from_value(res["ids"].clone()).context("cannot parse JMAP email ids")
+    }
+
+    /// Get the given properties of the given emails, in the given
+    /// order.
+    pub async fn get_emails<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a str>,
+        properties: &[&str],
+    ) -> Result<Vec<JmapEmail>> {
+        let ids: Vec<&str> = ids.into_iter().collect();
+
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let res = self
+            .call_one(
+                "Email/get",
+                json!({
+                    "accountId": self.account_id,
+                    "ids": ids,
+                    "properties": properties,
+                }),
+            )
+            .await?;
+
+        let emails: Vec<JmapEmail> =
+            serde_json::from_value(res["list"].clone()).context("cannot parse JMAP emails")?;
+
+        ids.iter()
+            .map(|id| {
+                emails
+                    .iter()
+                    .find(|email| email.id == *id)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("cannot find JMAP email {id}"))
+            })
+            .collect()
+    }
+
+    /// Apply the given patch to the given emails.
+    pub async fn update_emails<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a str>,
+        patch: Value,
+    ) -> Result<()> {
+        let update: Map<String, Value> = ids
+            .into_iter()
+            .map(|id| (id.to_owned(), patch.clone()))
+            .collect();
+
+        let res = self
+            .call_one(
+                "Email/set",
+                json!({ "accountId": self.account_id, "update": update }),
+            )
+            .await?;
+
+        check_set_errors(&res, "notUpdated").context("cannot update JMAP emails")
+    }
+
+    /// Destroy the given emails.
+    pub async fn destroy_emails<'a>(&self, ids: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        let ids: Vec<&str> = ids.into_iter().collect();
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let res = self
+            .call_one(
+                "Email/set",
+                json!({ "accountId": self.account_id, "destroy": ids }),
+            )
+            .await?;
+
+        check_set_errors(&res, "notDestroyed").context("cannot destroy JMAP emails")
+    }
+
+    /// Find the id of the identity matching the account email,
+    /// otherwise the id of the first identity.
+    pub async fn identity_id(&self) -> Result<String> {
+        let res = self
+            .call_one(
+                "Identity/get",
+                json!({ "accountId": self.submission_account_id, "ids": null }),
+            )
+            .await?;
+
+        let identities = res["list"].as_array().cloned().unwrap_or_default();
+        let email = &self.account_config.email;
+
+        identities
+            .iter()
+            .find(|identity| {
+                identity["email"]
+                    .as_str()
+                    .map_or(false, |addr| addr.eq_ignore_ascii_case(email))
+            })
+            .or_else(|| identities.first())
+            .and_then(|identity| identity["id"].as_str())
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| anyhow!("cannot find JMAP identity"))
+    }
+
+    /// Import the given raw message into the given mailbox, and
+    /// return the id of the created email.
+    pub async fn import_email(
+        &self,
+        mailbox_id: &str,
+        raw_msg: &[u8],
+        flags: &Flags,
+    ) -> Result<String> {
+        let blob_id = self.upload(raw_msg).await?;
+
+        let res = self
+            .call_one(
+                "Email/import",
+                json!({
+                    "accountId": self.account_id,
+                    "emails": {
+                        "email": {
+                            "blobId": blob_id,
+                            "mailboxIds": { mailbox_id: true },
+                            "keywords": keywords(flags),
+                        },
+                    },
+                }),
+            )
+            .await?;
+
+        check_set_errors(&res, "notCreated").context("cannot import JMAP email")?;
+
+        res["created"]["email"]["id"]
+            .as_str()
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| anyhow!("cannot find id of imported JMAP email"))
+    }
+
+    /// Upload the given raw message and return its blob id.
+    pub async fn upload(&self, raw_msg: &[u8]) -> Result<String> {
+        let url = self.upload_url.replace("{accountId}", &self.account_id);
+
+        let res: Value = self
+            .request(Method::POST, &url)
+            .header(CONTENT_TYPE, "message/rfc822")
+            .body(raw_msg.to_vec())
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .context("cannot upload JMAP blob")?
+            .json()
+            .await
+            .context("cannot parse JMAP upload response")?;
+
+        res["blobId"]
+            .as_str()
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| anyhow!("cannot find id of uploaded JMAP blob"))
+    }
+
+    /// Download the raw message of the given blob.
+    pub async fn download(&self, blob_id: &str) -> Result<Vec<u8>> {
+        let url = self
+            .download_url
+            .replace("{accountId}", &self.account_id)
+            .replace("{blobId}", blob_id)
+            .replace("{type}", "message%2Frfc822")
+            .replace("{name}", "message.eml");
+
+        let bytes = self
+            .request(Method::GET, &url)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .with_context(|| format!("cannot download JMAP blob {blob_id}"))?
+            .bytes()
+            .await
+            .with_context(|| format!("cannot read JMAP blob {blob_id}"))?;
+
+        Ok(bytes.to_vec())
+    }
+}
+
+/// The authentication applied to every request.
+#[derive(Clone)]
+enum JmapAuth {
+    Basic { login: String, passwd: String },
+    Bearer(String),
+}
+
+impl JmapAuth {
+    fn apply(&self, req: RequestBuilder) -> RequestBuilder {
+        match self {
+            Self::Basic { login, passwd } => req.basic_auth(login, Some(passwd)),
+            Self::Bearer(token) => req.bearer_auth(token),
+        }
+    }
+}
+
+/// Secrets are not exposed in logs.
+impl fmt::Debug for JmapAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Basic { login, .. } => write!(f, "Basic({login})"),
+            Self::Bearer(_) => write!(f, "Bearer"),
+        }
+    }
+}
+
+/// The parts of the session resource used by the backend.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionResource {
+    api_url: String,
+    download_url: String,
+    upload_url: String,
+    primary_accounts: HashMap<String, String>,
+    #[serde(default)]
+    capabilities: HashMap<String, Value>,
+}
+
+/// A JMAP mailbox.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JmapMailbox {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    pub role: Option<String>,
+
+    /// The names of the mailbox and of its parents, joined with `/`.
+    #[serde(skip)]
+    pub path: String,
+}
+
+/// A JMAP email, limited to the requested properties.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JmapEmail {
+    pub id: String,
+    pub blob_id: String,
+    pub message_id: Option<Vec<String>>,
+    pub in_reply_to: Option<Vec<String>>,
+    pub keywords: HashMap<String, bool>,
+    pub from: Option<Vec<JmapAddress>>,
+    pub to: Option<Vec<JmapAddress>>,
+    pub cc: Option<Vec<JmapAddress>>,
+    pub subject: Option<String>,
+    pub sent_at: Option<String>,
+    pub received_at: Option<String>,
+    pub size: u64,
+    pub has_attachment: bool,
+
+    /// The other properties, like `header:{name}:asText` ones.
+    #[serde(flatten)]
+    pub properties: HashMap<String, Value>,
+}
+
+impl JmapEmail {
+    /// Build flags from the email keywords.
+    pub fn flags(&self) -> Flags {
+        self.keywords
+            .iter()
+            .filter(|(_, set)| **set)
+            .map(|(keyword, _)| flag(keyword))
+            .collect()
+    }
+
+    /// Build an envelope from the email.
+    pub fn to_envelope(&self) -> Envelope {
+        let first_address = |addrs: &Option<Vec<JmapAddress>>| {
+            addrs
+                .as_ref()
+                .and_then(|addrs| addrs.first())
+                .map(JmapAddress::to_address)
+                .unwrap_or_default()
+        };
+
+        // the sent date comes from the Date header, which is the one
+        // used by the other backends
+        let date = self
+            .sent_at
+            .as_deref()
+            .or(self.received_at.as_deref())
+            .and_then(|date| DateTime::<FixedOffset>::parse_from_rfc3339(date).ok())
+            .unwrap_or_default();
+
+        Envelope {
+            id: self.id.clone(),
+            message_id: self
+                .message_id
+                .as_ref()
+                .and_then(|ids| ids.first())
+                .map(|id| format!("<{id}>"))
+                .unwrap_or_default(),
+            flags: self.flags(),
+            from: first_address(&self.from),
+            to: first_address(&self.to),
+            subject: self.subject.clone().unwrap_or_default(),
+            date,
+        }
+    }
+}
+
+/// A JMAP email address.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct JmapAddress {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+impl JmapAddress {
+    pub fn to_address(&self) -> Address {
+        Address {
+            name: self.name.clone().filter(|name| !name.is_empty()),
+            addr: self.email.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Return the keyword matching the given flag.
+pub fn keyword(flag: &Flag) -> String {
+    match KEYWORDS.iter().find(|(f, _)| f == flag) {
+        Some((_, keyword)) => keyword.to_string(),
+        None => match flag {
+            Flag::Custom(keyword) => keyword.to_lowercase(),
+            _ => unreachable!("all standard flags have a keyword"),
+        },
+    }
+}
+
+/// Return the flag matching the given keyword.
+pub fn flag(keyword: &str) -> Flag {
+    match KEYWORDS
+        .iter()
+        .find(|(_, k)| k.eq_ignore_ascii_case(keyword))
+    {
+        Some((flag, _)) => flag.clone(),
+        None => Flag::Custom(keyword.to_owned()),
+    }
+}
+
+/// Build the keywords object matching the given flags.
+pub fn keywords(flags: &Flags) -> Value {
+    let keywords: Map<String, Value> = flags
+        .iter()
+        .map(|flag| (keyword(flag), Value::Bool(true)))
+        .collect();
+    Value::Object(keywords)
+}
+
+/// Return the directory of the id mapper database of the given
+/// account. JMAP has no local directory, so the user cache directory
+/// is used instead.
+pub fn id_mapper_db_dir(account_config: &AccountConfig) -> Result<PathBuf> {
+    let dir = dirs::cache_dir()
+        .ok_or_else(|| anyhow!("cannot find user cache directory"))?
+        .join("himalaya")
+        .join("jmap")
+        .join(&account_config.name);
+
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("cannot create JMAP cache directory at {dir:?}"))?;
+
+    Ok(dir)
+}
+
+/// Return the URL of the session resource. URLs without path point
+/// to the well-known location.
+fn session_url(url: &str) -> Result<Url> {
+    let mut url = Url::parse(url).with_context(|| format!("cannot parse JMAP URL {url}"))?;
+
+    if url.path().is_empty() || url.path() == "/" {
+        url.set_path("/.well-known/jmap");
+    }
+
+    Ok(url)
+}
+
+/// Resolve the given URL (or URL template) against the session URL.
+/// Templates cannot be parsed as URLs, so only absolute paths are
+/// resolved.
+fn resolve_url(session_url: &Url, url: &str) -> String {
+    if url.starts_with('/') {
+        format!("{}{url}", session_url.origin().ascii_serialization())
+    } else {
+        url.to_owned()
+    }
+}
+
+/// Return an error for the first object that could not be created,
+/// updated or destroyed, depending on the given key.
+fn check_set_errors(res: &Value, key: &str) -> Result<()> {
+    match res[key].as_object().and_then(|errors| errors.iter().next()) {
+        Some((id, err)) => bail!("JMAP object {id} {key}: {}", describe_error(err)),
+        None => Ok(()),
+    }
+}
+
+/// Return `true` if the given method belongs to the submission
+/// capability (RFC 8621 section 6 and 7).
+fn is_submission_method(name: &str) -> bool {
+    name.starts_with("Identity/") || name.starts_with("EmailSubmission/")
+}
+
+fn describe_error(err: &Value) -> String {
+    let kind = err["type"].as_str().unwrap_or("unknown");
+
+    match err["description"].as_str() {
+        Some(desc) => format!("{kind} ({desc})"),
+        None => kind.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use email::account::config::{passwd::PasswdConfig, AccountConfig};
+    use secret::Secret;
+    use serde_json::{json, Map, Value};
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use super::{JmapAuthConfig, JmapConfig, JmapSession, JmapSessionBuilder};
+
+    /// The method calls received by the stand-in server.
+    type Calls = Arc<Mutex<Vec<(String, Value)>>>;
+
+    /// The capabilities supported by the stand-in server by default.
+    const CAPABILITIES: [&str; 3] = [
+        "urn:ietf:params:jmap:core",
+        "urn:ietf:params:jmap:mail",
+        "urn:ietf:params:jmap:submission",
+    ];
+
+    /// Spawn a minimal JMAP server answering method calls with the
+    /// given handler, and return a session connected to it.
+    async fn spawn_server(handler: fn(&str, &Value) -> Value) -> (JmapSession, Calls) {
+        spawn_server_with(&CAPABILITIES, handler).await
+    }
+
+    /// Same as [`spawn_server`], but supporting only the given
+    /// capabilities. Requests using other capabilities are rejected.
+    async fn spawn_server_with(
+        capabilities: &[&str],
+        handler: fn(&str, &Value) -> Value,
+    ) -> (JmapSession, Calls) {
+        let capabilities: Vec<String> = capabilities.iter().map(|c| c.to_string()).collect();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let calls = Calls::default();
+        let server_calls = calls.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_owned();
+
+                let mut len = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            len = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut status = "200 OK";
+                let res = if path == "/.well-known/jmap" {
+                    let capabilities: Map<String, Value> = capabilities
+                        .iter()
+                        .map(|capability| (capability.clone(), json!({})))
+                        .collect();
+                    json!({
+                        "capabilities": capabilities,
+                        "apiUrl": "/api",
+                        "uploadUrl": "/upload/{accountId}/",
+                        "downloadUrl": "/download/{accountId}/{blobId}/{name}?type={type}",
+                        "primaryAccounts": { "urn:ietf:params:jmap:mail": "a1" },
+                    })
+                } else if path.starts_with("/upload/") {
+                    json!({ "blobId": "blob1" })
+                } else {
+                    let req: Value = serde_json::from_slice(&body).unwrap();
+                    let using = req["using"].as_array().unwrap();
+
+                    if using
+                        .iter()
+                        .any(|c| !capabilities.contains(&c.as_str().unwrap().to_owned()))
+                    {
+                        status = "400 Bad Request";
+                        json!({ "type": "urn:ietf:params:jmap:error:unknownCapability" })
+                    } else {
+                        let responses: Vec<Value> = req["methodCalls"]
+                            .as_array()
+                            .unwrap()
+                            .iter()
+                            .map(|call| {
+                                let name = call[0].as_str().unwrap();
+                                server_calls
+                                    .lock()
+                                    .unwrap()
+                                    .push((name.to_owned(), call[1].clone()));
+                                json!([name, handler(name, &call[1]), call[2]])
+                            })
+                            .collect();
+                        json!({ "methodResponses": responses })
+                    }
+                };
+
+                let res = res.to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{res}",
+                    res.len(),
+                )
+                .unwrap();
+            }
+        });
+
+        let account_config = AccountConfig {
+            name: String::from("test"),
+            email: String::from("alice@localhost"),
+            ..Default::default()
+        };
+        let jmap_config = JmapConfig {
+            url: format!("http://{addr}"),
+            login: String::from("alice@localhost"),
+            auth: JmapAuthConfig::Passwd(PasswdConfig(Secret::new_raw(String::from("passwd")))),
+        };
+
+        let session = JmapSessionBuilder::new(account_config, jmap_config)
+            .build()
+            .await
+            .unwrap();
+
+        (session, calls)
+    }
+
+    fn handler(name: &str, _args: &Value) -> Value {
+        match name {
+            "Mailbox/get" => json!({
+                "list": [
+                    { "id": "m1", "name": "Inbox", "parentId": null, "role": "inbox" },
+                    { "id": "m2", "name": "Archive", "parentId": null, "role": null },
+                    { "id": "m3", "name": "2024", "parentId": "m2", "role": null },
+                    { "id": "m4", "name": "Drafts", "parentId": null, "role": "drafts" },
+                ],
+            }),
+            "Email/get" => json!({
+                "list": [{
+                    "id": "e1",
+                    "messageId": ["1@localhost"],
+                    "keywords": { "$seen": true, "$flagged": true, "work": true },
+                    "from": [{ "name": "Bob", "email": "bob@localhost" }],
+                    "to": [{ "name": null, "email": "alice@localhost" }],
+                    "subject": "Hello",
+                    "sentAt": "2024-01-01T10:00:00+01:00",
+                }],
+            }),
+            "Email/set" => json!({ "updated": { "e1": null } }),
+            "Email/import" => json!({ "created": { "draft": { "id": "e2" } } }),
+            "Identity/get" => json!({ "list": [{ "id": "i1", "email": "alice@localhost" }] }),
+            "EmailSubmission/set" => json!({ "created": { "send": { "id": "s1" } } }),
+            _ => json!({ "type": "unknownMethod" }),
+        }
+    }
+
+    #[tokio::test]
+    async fn mailboxes() {
+        let (session, _) = spawn_server(handler).await;
+
+        let paths: Vec<_> = session
+            .list_mailboxes()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|mailbox| mailbox.path)
+            .collect();
+        assert_eq!(paths, ["Inbox", "Archive", "Archive/2024", "Drafts"]);
+
+        assert_eq!(session.mailbox_id("INBOX").await.unwrap(), "m1");
+        assert_eq!(session.mailbox_id("Archive/2024").await.unwrap(), "m3");
+        assert!(session.mailbox_id("unknown").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn capabilities() {
+        let (session, calls) = spawn_server_with(&CAPABILITIES[..2], handler).await;
+
+        assert_eq!(session.list_mailboxes().await.unwrap().len(), 4);
+
+        let err = session.identity_id().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "JMAP server does not support email submission"
+        );
+
+        let calls = calls.lock().unwrap();
+        let names: Vec<_> = calls.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["Mailbox/get"]);
+    }
+
+    #[tokio::test]
+    async fn envelopes() {
+        let (session, _) = spawn_server(handler).await;
+
+        let emails = session
+            .get_emails(["e1"], &super::ENVELOPE_PROPERTIES)
+            .await
+            .unwrap();
+        let envelope = emails[0].to_envelope();
+
+        assert_eq!(envelope.id, "e1");
+        assert_eq!(envelope.message_id, "<1@localhost>");
+        assert_eq!(envelope.subject, "Hello");
+        assert_eq!(envelope.from.name.as_deref(), Some("Bob"));
+        assert_eq!(envelope.to.addr, "alice@localhost");
+        assert_eq!(envelope.date.to_rfc3339(), "2024-01-01T10:00:00+01:00");
+
+        let flags = envelope.flags.to_string();
+        assert!(flags.contains("seen") && flags.contains("flagged") && flags.contains("work"));
+    }
+
+    #[cfg(feature = "flag-add")]
+    #[tokio::test]
+    async fn add_flags() {
+        use email::{
+            envelope::Id,
+            flag::{Flag, Flags},
+        };
+
+        use super::AddFlagsJmap;
+
+        let (session, calls) = spawn_server(handler).await;
+
+        let flags = Flags::from_iter([Flag::Seen, Flag::custom("a/b")]);
+        AddFlagsJmap::new(&session)
+            .unwrap()
+            .add_flags("inbox", &Id::single("e1"), &flags)
+            .await
+            .unwrap();
+
+        let calls = calls.lock().unwrap();
+        let (name, args) = calls.last().unwrap();
+        assert_eq!(name, "Email/set");
+        assert_eq!(
+            args["update"]["e1"],
+            json!({ "keywords/$seen": true, "keywords/a~1b": true })
+        );
+    }
+
+    #[cfg(feature = "message-send")]
+    #[tokio::test]
+    async fn send_message() {
+        use super::SendMessageJmap;
+
+        let (session, calls) = spawn_server(handler).await;
+
+        SendMessageJmap::new(&session)
+            .unwrap()
+            .send_message(b"Subject: Hello\r\n\r\nbody")
+            .await
+            .unwrap();
+
+        let calls = calls.lock().unwrap();
+        let names: Vec<_> = calls.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "Identity/get",
+                "Mailbox/get",
+                "Email/import",
+                "EmailSubmission/set"
+            ]
+        );
+
+        let import = &calls[2].1["emails"]["draft"];
+        assert_eq!(import["blobId"], "blob1");
+        assert_eq!(import["mailboxIds"], json!({ "m4": true }));
+
+        let submission = &calls[3].1;
+        assert_eq!(submission["create"]["send"]["identityId"], "i1");
+        assert_eq!(submission["create"]["send"]["emailId"], "#draft");
+        assert_eq!(submission["onSuccessDestroyEmail"], json!(["#send"]));
+    }
+}
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use dialoguer::{Input, Select};
+use email::account::config::passwd::PasswdConfig;
+use secret::Secret;
+use std::sync::OnceLock;
+
+use crate::{
+    backend::config::BackendConfig,
+    ui::{prompt, THEME},
+};
+
+use super::{JmapAuthConfig, JmapConfig};
+
+const SECRETS: &[&str] = &[KEYRING, RAW, CMD, TOKEN];
+const KEYRING: &str = "Ask my password, then save it in my system's global keyring";
+const RAW: &str = "Ask my password, then save it in the configuration file (not safe)";
+const CMD: &str = "Ask me a shell command that exposes my password";
+const TOKEN: &str = "Ask my API token, then save it in my system's global keyring";
+
+/// The JMAP configuration of the current wizard session. The same
+/// backend is usually used for both reading and sending messages, so
+/// the configuration is only asked once.
+static CONFIG: OnceLock<JmapConfig> = OnceLock::new();
+
+pub(crate) async fn configure(account_name: &str, email: &str) -> Result<BackendConfig> {
+    if let Some(config) = CONFIG.get() {
+        return Ok(BackendConfig::Jmap(config.clone()));
+    }
+
+    let domain = email.rsplit_once('@').map_or(email, |(_, domain)| domain);
+
+    let url = Input::with_theme(&*THEME)
+        .with_prompt("JMAP session URL")
+        .default(format!("https://{domain}/.well-known/jmap"))
+        .interact()?;
+
+    let login = Input::with_theme(&*THEME)
+        .with_prompt("JMAP login")
+        .default(email.to_owned())
+        .interact()?;
+
+    let secret_idx = Select::with_theme(&*THEME)
+        .with_prompt("JMAP authentication strategy")
+        .items(SECRETS)
+        .default(0)
+        .interact_opt()?;
+
+    let auth = match secret_idx {
+        Some(idx) if SECRETS[idx] == KEYRING => {
+            let secret = Secret::new_keyring_entry(format!("{account_name}-jmap-passwd"));
+            secret
+                .set_keyring_entry_secret(prompt::passwd("JMAP password")?)
+                .await?;
+            JmapAuthConfig::Passwd(PasswdConfig(secret))
+        }
+        Some(idx) if SECRETS[idx] == RAW => {
+            let secret = Secret::new_raw(prompt::passwd("JMAP password")?);
+            JmapAuthConfig::Passwd(PasswdConfig(secret))
+        }
+        Some(idx) if SECRETS[idx] == CMD => {
+            let secret = Secret::new_cmd(
+                Input::with_theme(&*THEME)
+                    .with_prompt("Shell command")
+                    .default(format!("pass show {account_name}-jmap-passwd"))
+                    .interact()?,
+            );
+            JmapAuthConfig::Passwd(PasswdConfig(secret))
+        }
+        Some(idx) if SECRETS[idx] == TOKEN => {
+            let secret = Secret::new_keyring_entry(format!("{account_name}-jmap-token"));
+            secret
+                .set_keyring_entry_secret(prompt::passwd("JMAP API token")?)
+                .await?;
+            JmapAuthConfig::Bearer(secret)
+        }
+        _ => Default::default(),
+    };
+
+    let config = CONFIG.get_or_init(|| JmapConfig { url, login, auth });
+
+    Ok(BackendConfig::Jmap(config.clone()))
+}
@@ -8,13 +8,23 @@ pub mod email;
 pub mod folder;
 #[cfg(feature = "imap")]
 pub mod imap;
+#[cfg(feature = "jmap")]
+pub mod jmap;
 #[cfg(feature = "maildir")]
 pub mod maildir;
 pub mod manual;
+#[cfg(feature = "mbox")]
+pub mod mbox;
 #[cfg(feature = "notmuch")]
 pub mod notmuch;
+#[cfg(feature = "outbox-subcmd")]
+pub mod outbox;
 pub mod output;
+#[cfg(feature = "pop")]
+pub mod pop;
 pub mod printer;
+#[cfg(feature = "search")]
+pub mod search;
 #[cfg(feature = "sendmail")]
 pub mod sendmail;
 #[cfg(feature = "smtp")]
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The mbox backend configuration.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MboxConfig {
+    /// The directory containing the mbox files. Each file is a
+    /// folder, named after its path relative to this directory.
+    pub root_dir: PathBuf,
+}
@@ -0,0 +1,83 @@
+//! Module dedicated to mbox envelopes.
+
+use anyhow::Result;
+use async_trait::async_trait;
+#[cfg(feature = "envelope-get")]
+use email::envelope::{get::GetEnvelope, Id};
+#[cfg(feature = "envelope-list")]
+use email::envelope::{list::ListEnvelopes, Envelopes};
+use email::{envelope::Envelope, message::Message};
+use log::info;
+#[cfg(feature = "envelope-list")]
+use log::trace;
+
+#[cfg(feature = "envelope-list")]
+use crate::envelope::search::paginate;
+
+use super::{MboxMessage, MboxSession};
+
+/// Build an envelope from an mbox message.
+pub fn envelope_from_msg(msg: &MboxMessage) -> Envelope {
+    Envelope::from_msg(&msg.id, msg.flags(), Message::from(msg.to_raw_msg()))
+}
+
+#[cfg(feature = "envelope-list")]
+pub struct ListEnvelopesMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "envelope-list")]
+impl ListEnvelopesMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn ListEnvelopes>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "envelope-list")]
+#[async_trait]
+impl ListEnvelopes for ListEnvelopesMbox {
+    async fn list_envelopes(
+        &self,
+        folder: &str,
+        page_size: usize,
+        page: usize,
+    ) -> Result<Envelopes> {
+        info!("listing mbox envelopes from folder {folder}");
+
+        let mbox = self.session.read_folder(folder)?;
+        let mut envelopes: Vec<_> = mbox.messages.iter().map(envelope_from_msg).collect();
+        envelopes.sort_by(|a, b| b.date.cmp(&a.date));
+        trace!("mbox envelopes: {envelopes:#?}");
+
+        let envelopes = paginate(&envelopes, page_size, page)?;
+
+        Ok(Envelopes::from_iter(envelopes.iter().cloned()))
+    }
+}
+
+#[cfg(feature = "envelope-get")]
+pub struct GetEnvelopeMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "envelope-get")]
+impl GetEnvelopeMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn GetEnvelope>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "envelope-get")]
+#[async_trait]
+impl GetEnvelope for GetEnvelopeMbox {
+    async fn get_envelope(&self, folder: &str, id: &Id) -> Result<Envelope> {
+        info!("getting mbox envelope {id} from folder {folder}");
+
+        let mbox = self.session.read_folder(folder)?;
+        let msg = mbox.find(id)?[0];
+
+        Ok(envelope_from_msg(msg))
+    }
+}
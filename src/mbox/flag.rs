@@ -0,0 +1,100 @@
+//! Module dedicated to mbox flags.
+//!
+//! Flags are stored in the status headers of the messages, see
+//! [`super::MboxMessage::flags`] for the mapping.
+
+use anyhow::Result;
+use async_trait::async_trait;
+#[cfg(feature = "flag-add")]
+use email::flag::add::AddFlags;
+#[cfg(feature = "flag-remove")]
+use email::flag::remove::RemoveFlags;
+#[cfg(feature = "flag-set")]
+use email::flag::set::SetFlags;
+use email::{envelope::Id, flag::Flags};
+use log::info;
+
+use super::{MboxMessage, MboxSession};
+
+/// Apply the given flags function to the messages matching the
+/// given ids.
+fn update_flags(
+    session: &MboxSession,
+    folder: &str,
+    id: &Id,
+    flags: &Flags,
+    f: impl Fn(&mut MboxMessage, &Flags),
+) -> Result<()> {
+    session.update_folder(folder, |mbox| {
+        for msg in mbox.find_mut(id)? {
+            f(msg, flags);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(feature = "flag-add")]
+pub struct AddFlagsMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "flag-add")]
+impl AddFlagsMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn AddFlags>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "flag-add")]
+#[async_trait]
+impl AddFlags for AddFlagsMbox {
+    async fn add_flags(&self, folder: &str, id: &Id, flags: &Flags) -> Result<()> {
+        info!("adding mbox flags {flags} to envelope(s) {id} from folder {folder}");
+        update_flags(&self.session, folder, id, flags, MboxMessage::add_flags)
+    }
+}
+
+#[cfg(feature = "flag-set")]
+pub struct SetFlagsMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "flag-set")]
+impl SetFlagsMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn SetFlags>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "flag-set")]
+#[async_trait]
+impl SetFlags for SetFlagsMbox {
+    async fn set_flags(&self, folder: &str, id: &Id, flags: &Flags) -> Result<()> {
+        info!("setting mbox flags {flags} to envelope(s) {id} from folder {folder}");
+        update_flags(&self.session, folder, id, flags, MboxMessage::set_flags)
+    }
+}
+
+#[cfg(feature = "flag-remove")]
+pub struct RemoveFlagsMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "flag-remove")]
+impl RemoveFlagsMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn RemoveFlags>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "flag-remove")]
+#[async_trait]
+impl RemoveFlags for RemoveFlagsMbox {
+    async fn remove_flags(&self, folder: &str, id: &Id, flags: &Flags) -> Result<()> {
+        info!("removing mbox flags {flags} from envelope(s) {id} from folder {folder}");
+        update_flags(&self.session, folder, id, flags, MboxMessage::remove_flags)
+    }
+}
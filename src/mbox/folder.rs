@@ -0,0 +1,215 @@
+//! Module dedicated to mbox folders.
+//!
+//! Folders are the mbox files of the root directory, including the
+//! ones located in sub-directories.
+
+#[cfg(any(feature = "folder-list", feature = "folder-delete"))]
+use anyhow::Context;
+use anyhow::Result;
+use async_trait::async_trait;
+#[cfg(feature = "folder-add")]
+use email::folder::add::AddFolder;
+#[cfg(feature = "folder-delete")]
+use email::folder::delete::DeleteFolder;
+#[cfg(feature = "folder-list")]
+use email::folder::list::ListFolders;
+#[cfg(feature = "folder-purge")]
+use email::folder::purge::PurgeFolder;
+#[cfg(feature = "folder-list")]
+use email::folder::{Folder, Folders};
+#[cfg(feature = "folder-expunge")]
+use email::{flag::Flag, folder::expunge::ExpungeFolder};
+#[cfg(feature = "folder-list")]
+use log::debug;
+use log::info;
+#[cfg(any(feature = "folder-list", feature = "folder-delete"))]
+use std::fs;
+#[cfg(feature = "folder-list")]
+use std::path::{Path, PathBuf};
+
+use super::MboxSession;
+
+#[cfg(feature = "folder-add")]
+pub struct AddFolderMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "folder-add")]
+impl AddFolderMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn AddFolder>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-add")]
+#[async_trait]
+impl AddFolder for AddFolderMbox {
+    async fn add_folder(&self, folder: &str) -> Result<()> {
+        info!("creating mbox folder {folder}");
+        self.session.append_to_folder(folder, Vec::new())?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "folder-list")]
+pub struct ListFoldersMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "folder-list")]
+impl ListFoldersMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn ListFolders>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-list")]
+#[async_trait]
+impl ListFolders for ListFoldersMbox {
+    async fn list_folders(&self) -> Result<Folders> {
+        info!("listing mbox folders");
+
+        let config = &self.session.account_config;
+        let root = self.session.path();
+
+        let mut paths = Vec::new();
+        list_files(&root, &mut paths)?;
+        paths.sort();
+
+        let mut folders = Folders::default();
+
+        for path in paths {
+            let name = match path.strip_prefix(&root).ok().and_then(Path::to_str) {
+                Some(name) => name.replace(std::path::MAIN_SEPARATOR, "/"),
+                None => {
+                    debug!("cannot get mbox folder name of {path:?}, skipping it");
+                    continue;
+                }
+            };
+
+            folders.push(Folder {
+                kind: config.find_folder_kind_from_alias(&name),
+                name,
+                desc: path.display().to_string(),
+            });
+        }
+
+        Ok(folders)
+    }
+}
+
+/// Collect the mbox files of the given directory and its
+/// sub-directories. Hidden files are ignored.
+#[cfg(feature = "folder-list")]
+fn list_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("cannot read mbox directory {dir:?}"))?;
+
+    for entry in entries {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(err) => {
+                debug!("cannot read mbox directory entry, skipping it: {err}");
+                continue;
+            }
+        };
+
+        let is_hidden = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(true, |name| name.starts_with('.'));
+
+        if is_hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            list_files(&path, paths)?;
+        } else if path.is_file() {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "folder-expunge")]
+pub struct ExpungeFolderMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "folder-expunge")]
+impl ExpungeFolderMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn ExpungeFolder>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-expunge")]
+#[async_trait]
+impl ExpungeFolder for ExpungeFolderMbox {
+    /// Remove the folder messages flagged as deleted.
+    async fn expunge_folder(&self, folder: &str) -> Result<()> {
+        info!("expunging mbox folder {folder}");
+
+        self.session.update_folder(folder, |mbox| {
+            mbox.remove_where(|msg| msg.flags().contains(&Flag::Deleted));
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "folder-purge")]
+pub struct PurgeFolderMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "folder-purge")]
+impl PurgeFolderMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn PurgeFolder>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-purge")]
+#[async_trait]
+impl PurgeFolder for PurgeFolderMbox {
+    /// Remove all the folder messages.
+    async fn purge_folder(&self, folder: &str) -> Result<()> {
+        info!("purging mbox folder {folder}");
+
+        self.session.update_folder(folder, |mbox| {
+            mbox.messages.clear();
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "folder-delete")]
+pub struct DeleteFolderMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "folder-delete")]
+impl DeleteFolderMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn DeleteFolder>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-delete")]
+#[async_trait]
+impl DeleteFolder for DeleteFolderMbox {
+    /// Delete the mbox file of the folder, with all its messages.
+    async fn delete_folder(&self, folder: &str) -> Result<()> {
+        info!("deleting mbox folder {folder}");
+
+        let path = self.session.folder_path(folder);
+        fs::remove_file(&path).with_context(|| format!("cannot delete mbox folder at {path:?}"))
+    }
+}
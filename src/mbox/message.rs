@@ -0,0 +1,146 @@
+//! Module dedicated to mbox messages.
+//!
+//! Messages returned by the backend do not contain the mbox status
+//! headers, which are only used to store flags.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use email::envelope::Id;
+#[cfg(feature = "message-copy")]
+use email::message::copy::CopyMessages;
+#[cfg(feature = "message-move")]
+use email::message::move_::MoveMessages;
+#[cfg(feature = "message-peek")]
+use email::message::{peek::PeekMessages, Messages};
+#[cfg(feature = "message-add")]
+use email::{envelope::SingleId, flag::Flags, message::add::AddMessage};
+use log::info;
+
+#[cfg(feature = "message-add")]
+use super::MboxMessage;
+use super::MboxSession;
+
+#[cfg(feature = "message-add")]
+pub struct AddMessageMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "message-add")]
+impl AddMessageMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn AddMessage>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-add")]
+#[async_trait]
+impl AddMessage for AddMessageMbox {
+    async fn add_message_with_flags(
+        &self,
+        folder: &str,
+        raw_msg: &[u8],
+        flags: &Flags,
+    ) -> Result<SingleId> {
+        info!("adding mbox message to folder {folder} with flags {flags}");
+
+        let msg = MboxMessage::from_raw_msg(raw_msg, flags);
+        let ids = self.session.append_to_folder(folder, [msg])?;
+
+        Ok(SingleId::from(&ids[0]))
+    }
+}
+
+#[cfg(feature = "message-peek")]
+pub struct PeekMessagesMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "message-peek")]
+impl PeekMessagesMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn PeekMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-peek")]
+#[async_trait]
+impl PeekMessages for PeekMessagesMbox {
+    async fn peek_messages(&self, folder: &str, id: &Id) -> Result<Messages> {
+        info!("peeking mbox messages {id} from folder {folder}");
+
+        let mbox = self.session.read_folder(folder)?;
+        let msgs = mbox
+            .find(id)?
+            .into_iter()
+            .map(|msg| msg.to_raw_msg())
+            .collect::<Vec<_>>();
+
+        Ok(Messages::from(msgs))
+    }
+}
+
+#[cfg(feature = "message-copy")]
+pub struct CopyMessagesMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "message-copy")]
+impl CopyMessagesMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn CopyMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-copy")]
+#[async_trait]
+impl CopyMessages for CopyMessagesMbox {
+    async fn copy_messages(&self, from_folder: &str, to_folder: &str, id: &Id) -> Result<()> {
+        info!("copying mbox messages {id} from folder {from_folder} to folder {to_folder}");
+
+        let mbox = self.session.read_folder(from_folder)?;
+        let msgs: Vec<_> = mbox.find(id)?.into_iter().cloned().collect();
+        self.session.append_to_folder(to_folder, msgs)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "message-move")]
+pub struct MoveMessagesMbox {
+    session: MboxSession,
+}
+
+#[cfg(feature = "message-move")]
+impl MoveMessagesMbox {
+    pub fn new(session: &MboxSession) -> Option<Box<dyn MoveMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-move")]
+#[async_trait]
+impl MoveMessages for MoveMessagesMbox {
+    /// Move messages by appending them to the target folder first,
+    /// so that they cannot be lost if the source folder cannot be
+    /// written.
+    async fn move_messages(&self, from_folder: &str, to_folder: &str, id: &Id) -> Result<()> {
+        info!("moving mbox messages {id} from folder {from_folder} to folder {to_folder}");
+
+        if self.session.folder_path(from_folder) == self.session.folder_path(to_folder) {
+            return Ok(());
+        }
+
+        let mbox = self.session.read_folder(from_folder)?;
+        let msgs: Vec<_> = mbox.find(id)?.into_iter().cloned().collect();
+        self.session.append_to_folder(to_folder, msgs)?;
+
+        self.session.update_folder(from_folder, |mbox| {
+            mbox.remove(id)?;
+            Ok(())
+        })
+    }
+}
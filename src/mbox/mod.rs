@@ -0,0 +1,774 @@
+//! Module dedicated to the mbox backend.
+//!
+//! Each folder is an mbox file located in the root directory. Files
+//! use the mboxrd format: messages are separated by `From ` lines,
+//! and body lines starting with `From ` (possibly quoted with `>`)
+//! are escaped with an additional `>`.
+//!
+//! Flags are stored in the `Status` (seen), `X-Status` (answered,
+//! flagged, draft, deleted) and `X-Keywords` (custom flags) headers,
+//! like most mbox clients do. These headers are hidden from the
+//! messages returned by the backend.
+//!
+//! Mbox messages have no identifier, so ids are derived from the
+//! Message-ID header, or from the content of the message when the
+//! header is missing.
+
+pub mod config;
+#[cfg(any(feature = "envelope-list", feature = "envelope-get"))]
+pub mod envelope;
+#[cfg(any(feature = "flag-add", feature = "flag-set", feature = "flag-remove"))]
+pub mod flag;
+#[cfg(any(
+    feature = "folder-add",
+    feature = "folder-list",
+    feature = "folder-expunge",
+    feature = "folder-purge",
+    feature = "folder-delete"
+))]
+pub mod folder;
+#[cfg(any(
+    feature = "message-add",
+    feature = "message-peek",
+    feature = "message-copy",
+    feature = "message-move"
+))]
+pub mod message;
+#[cfg(feature = "wizard")]
+pub(crate) mod wizard;
+
+use anyhow::{anyhow, Context, Result};
+use email::{
+    account::config::AccountConfig,
+    envelope::Id,
+    flag::{Flag, Flags},
+};
+use log::{debug, trace};
+use shellexpand_utils::shellexpand_path;
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+pub use self::config::MboxConfig;
+#[cfg(feature = "envelope-get")]
+pub use self::envelope::GetEnvelopeMbox;
+#[cfg(feature = "envelope-list")]
+pub use self::envelope::ListEnvelopesMbox;
+#[cfg(feature = "flag-add")]
+pub use self::flag::AddFlagsMbox;
+#[cfg(feature = "flag-remove")]
+pub use self::flag::RemoveFlagsMbox;
+#[cfg(feature = "flag-set")]
+pub use self::flag::SetFlagsMbox;
+#[cfg(feature = "folder-add")]
+pub use self::folder::AddFolderMbox;
+#[cfg(feature = "folder-delete")]
+pub use self::folder::DeleteFolderMbox;
+#[cfg(feature = "folder-expunge")]
+pub use self::folder::ExpungeFolderMbox;
+#[cfg(feature = "folder-list")]
+pub use self::folder::ListFoldersMbox;
+#[cfg(feature = "folder-purge")]
+pub use self::folder::PurgeFolderMbox;
+#[cfg(feature = "message-add")]
+pub use self::message::AddMessageMbox;
+#[cfg(feature = "message-copy")]
+pub use self::message::CopyMessagesMbox;
+#[cfg(feature = "message-move")]
+pub use self::message::MoveMessagesMbox;
+#[cfg(feature = "message-peek")]
+pub use self::message::PeekMessagesMbox;
+
+/// The headers holding the flags of a message.
+const STATUS_HEADERS: [&str; 3] = ["Status", "X-Status", "X-Keywords"];
+
+/// The flags stored in the `X-Status` header, with their letter.
+const X_STATUS_FLAGS: [(Flag, char); 4] = [
+    (Flag::Answered, 'A'),
+    (Flag::Flagged, 'F'),
+    (Flag::Draft, 'T'),
+    (Flag::Deleted, 'D'),
+];
+
+/// The mbox session builder.
+#[derive(Clone, Debug)]
+pub struct MboxSessionBuilder {
+    account_config: AccountConfig,
+    mbox_config: MboxConfig,
+}
+
+impl MboxSessionBuilder {
+    pub fn new(account_config: AccountConfig, mbox_config: MboxConfig) -> Self {
+        Self {
+            account_config,
+            mbox_config,
+        }
+    }
+
+    /// Build the session, creating the root directory if needed.
+    pub async fn build(self) -> Result<MboxSession> {
+        let session = MboxSession {
+            account_config: self.account_config,
+            mbox_config: self.mbox_config,
+            lock: Default::default(),
+        };
+
+        let path = session.path();
+        fs::create_dir_all(&path)
+            .with_context(|| format!("cannot create mbox root directory at {path:?}"))?;
+
+        Ok(session)
+    }
+}
+
+/// The mbox session.
+///
+/// Mbox files are entirely rewritten on every change, so changes
+/// made by the session are serialized using a lock.
+#[derive(Clone, Debug)]
+pub struct MboxSession {
+    pub account_config: AccountConfig,
+    pub mbox_config: MboxConfig,
+    lock: Arc<Mutex<()>>,
+}
+
+impl MboxSession {
+    /// Return the shell-expanded path of the root directory.
+    pub fn path(&self) -> PathBuf {
+        shellexpand_path(&self.mbox_config.root_dir)
+    }
+
+    /// Return the path of the mbox file of the given folder.
+    pub fn folder_path(&self, folder: &str) -> PathBuf {
+        self.path()
+            .join(self.account_config.get_folder_alias(folder))
+    }
+
+    /// Read and parse the mbox file of the given folder.
+    pub fn read_folder(&self, folder: &str) -> Result<Mbox> {
+        let path = self.folder_path(folder);
+        debug!("reading mbox folder {folder} at {path:?}");
+
+        let bytes =
+            fs::read(&path).with_context(|| format!("cannot read mbox folder at {path:?}"))?;
+
+        Ok(Mbox::parse(&bytes))
+    }
+
+    /// Write the given mbox in the file of the given folder.
+    ///
+    /// The mbox is written in a temporary file first, then renamed,
+    /// so that the folder is never left half-written.
+    pub fn write_folder(&self, folder: &str, mbox: &Mbox) -> Result<()> {
+        let path = self.folder_path(folder);
+        debug!("writing mbox folder {folder} at {path:?}");
+
+        let dir = path
+            .parent()
+            .ok_or_else(|| anyhow!("cannot find parent directory of mbox folder {path:?}"))?;
+        fs::create_dir_all(dir)
+            .with_context(|| format!("cannot create mbox directory at {dir:?}"))?;
+
+        let name = path
+            .file_name()
+            .ok_or_else(|| anyhow!("cannot find file name of mbox folder {path:?}"))?;
+        let tmp_path = dir.join(format!(".{}.tmp", name.to_string_lossy()));
+
+        fs::write(&tmp_path, mbox.to_bytes())
+            .with_context(|| format!("cannot write mbox folder at {tmp_path:?}"))?;
+        fs::rename(&tmp_path, &path)
+            .with_context(|| format!("cannot move mbox folder to {path:?}"))?;
+
+        Ok(())
+    }
+
+    /// Apply the given change to the mbox of the given folder, then
+    /// write it back.
+    pub fn update_folder<T>(
+        &self,
+        folder: &str,
+        f: impl FnOnce(&mut Mbox) -> Result<T>,
+    ) -> Result<T> {
+        let _lock = self
+            .lock
+            .lock()
+            .map_err(|_| anyhow!("cannot lock mbox session"))?;
+
+        let mut mbox = self.read_folder(folder)?;
+        let output = f(&mut mbox)?;
+        self.write_folder(folder, &mbox)?;
+
+        Ok(output)
+    }
+
+    /// Append the given messages to the mbox of the given folder,
+    /// creating it if needed, and return their ids.
+    pub fn append_to_folder(
+        &self,
+        folder: &str,
+        msgs: impl IntoIterator<Item = MboxMessage>,
+    ) -> Result<Vec<String>> {
+        let _lock = self
+            .lock
+            .lock()
+            .map_err(|_| anyhow!("cannot lock mbox session"))?;
+
+        let mut mbox = match fs::read(self.folder_path(folder)) {
+            Ok(bytes) => Mbox::parse(&bytes),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Mbox::default(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("cannot read mbox folder {folder}"))
+            }
+        };
+
+        let prev_len = mbox.messages.len();
+        mbox.messages.extend(msgs);
+        mbox.assign_ids();
+        self.write_folder(folder, &mbox)?;
+
+        Ok(mbox.messages[prev_len..]
+            .iter()
+            .map(|msg| msg.id.clone())
+            .collect())
+    }
+}
+
+/// An mbox file, parsed into messages.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Mbox {
+    pub messages: Vec<MboxMessage>,
+}
+
+impl Mbox {
+    /// Parse the given mboxrd content.
+    ///
+    /// A `From ` line starts a new message when it is the first line
+    /// of the file or when it follows an empty line. Content before
+    /// the first message is ignored.
+    pub fn parse(bytes: &[u8]) -> Self {
+        // reading from a slice cannot fail
+        let messages = MboxReader::new(bytes).flatten().collect();
+
+        let mut mbox = Self { messages };
+        mbox.assign_ids();
+        trace!("mbox messages: {:?}", mbox.ids().collect::<Vec<_>>());
+        mbox
+    }
+
+    /// Serialize the mbox using the mboxrd format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for msg in &self.messages {
+            bytes.extend_from_slice(msg.from_line.as_bytes());
+            bytes.push(b'\n');
+
+            for line in msg.raw.split_inclusive(|b| *b == b'\n') {
+                if is_from_line(line) {
+                    bytes.push(b'>');
+                }
+                bytes.extend_from_slice(line);
+            }
+
+            if !msg.raw.ends_with(b"\n") {
+                bytes.push(b'\n');
+            }
+            bytes.push(b'\n');
+        }
+
+        bytes
+    }
+
+    /// Return the ids of the messages, in the file order.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.messages.iter().map(|msg| msg.id.as_str())
+    }
+
+    /// Find the messages matching the given ids.
+    pub fn find(&self, id: &Id) -> Result<Vec<&MboxMessage>> {
+        id.iter()
+            .map(|id| {
+                self.messages
+                    .iter()
+                    .find(|msg| msg.id == id)
+                    .ok_or_else(|| anyhow!("cannot find mbox message {id}"))
+            })
+            .collect()
+    }
+
+    /// Find the messages matching the given ids, mutably.
+    pub fn find_mut(&mut self, id: &Id) -> Result<Vec<&mut MboxMessage>> {
+        let ids: Vec<&str> = id.iter().collect();
+
+        for id in &ids {
+            if !self.messages.iter().any(|msg| msg.id == *id) {
+                return Err(anyhow!("cannot find mbox message {id}"));
+            }
+        }
+
+        Ok(self
+            .messages
+            .iter_mut()
+            .filter(|msg| ids.contains(&msg.id.as_str()))
+            .collect())
+    }
+
+    /// Remove the messages matching the given ids and return them.
+    pub fn remove(&mut self, id: &Id) -> Result<Vec<MboxMessage>> {
+        let ids: Vec<String> = self.find(id)?.iter().map(|msg| msg.id.clone()).collect();
+        let (removed, kept): (Vec<_>, Vec<_>) = self
+            .messages
+            .drain(..)
+            .partition(|msg| ids.contains(&msg.id));
+
+        self.messages = kept;
+        self.assign_ids();
+
+        Ok(removed)
+    }
+
+    /// Remove the messages matching the given predicate.
+    pub fn remove_where(&mut self, f: impl Fn(&MboxMessage) -> bool) {
+        self.messages.retain(|msg| !f(msg));
+        self.assign_ids();
+    }
+
+    /// Compute the id of each message. Messages sharing the same
+    /// hash get a numeric suffix, in the file order.
+    fn assign_ids(&mut self) {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+
+        for msg in &mut self.messages {
+            let hash = msg.hash();
+            let count = counts.entry(hash.clone()).or_default();
+
+            msg.id = match *count {
+                0 => hash,
+                n => format!("{hash}-{n}"),
+            };
+
+            *count += 1;
+        }
+    }
+}
+
+/// A streaming mboxrd reader, yielding messages one by one without
+/// loading the whole file in memory.
+///
+/// Messages follow the same rules as [`Mbox::parse`], but do not
+/// have ids, since ids depend on the other messages of the file.
+pub struct MboxReader<R> {
+    reader: R,
+    line: Vec<u8>,
+    current: Option<(String, Vec<u8>)>,
+    prev_line_empty: bool,
+}
+
+impl<R: io::BufRead> MboxReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            current: None,
+            prev_line_empty: true,
+        }
+    }
+}
+
+impl<R: io::BufRead> Iterator for MboxReader<R> {
+    type Item = io::Result<MboxMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => {
+                    let (from_line, raw) = self.current.take()?;
+                    return Some(Ok(MboxMessage::new(from_line, raw)));
+                }
+                Ok(_) => (),
+                Err(err) => return Some(Err(err)),
+            }
+
+            let line = &self.line;
+
+            if self.prev_line_empty && line.starts_with(b"From ") {
+                self.prev_line_empty = false;
+
+                let from_line = String::from_utf8_lossy(line).trim_end().to_owned();
+                if let Some((from_line, raw)) = self.current.replace((from_line, Vec::new())) {
+                    return Some(Ok(MboxMessage::new(from_line, raw)));
+                }
+
+                continue;
+            }
+
+            self.prev_line_empty = is_empty_line(line);
+
+            if let Some((_, raw)) = self.current.as_mut() {
+                raw.extend_from_slice(unescape_line(line));
+            }
+        }
+    }
+}
+
+/// A message of an mbox file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MboxMessage {
+    /// The id of the message, see [`Mbox::assign_ids`].
+    pub id: String,
+
+    /// The `From ` line preceding the message, without line ending.
+    pub from_line: String,
+
+    /// The unescaped raw message, including status headers.
+    pub raw: Vec<u8>,
+}
+
+impl MboxMessage {
+    fn new(from_line: String, mut raw: Vec<u8>) -> Self {
+        // the empty line preceding the next `From ` line belongs to
+        // the separator, not to the message
+        if raw.ends_with(b"\r\n\r\n") {
+            raw.truncate(raw.len() - 2);
+        } else if raw.ends_with(b"\n\n") {
+            raw.truncate(raw.len() - 1);
+        }
+
+        Self {
+            id: String::new(),
+            from_line,
+            raw,
+        }
+    }
+
+    /// Build a message from the given raw message and flags, using
+    /// the sender of the message in the `From ` line.
+    pub fn from_raw_msg(raw_msg: &[u8], flags: &Flags) -> Self {
+        let sender = mail_parser::MessageParser::default()
+            .parse_headers(raw_msg)
+            .and_then(|msg| Some(msg.from()?.first()?.address()?.to_owned()))
+            .filter(|sender| !sender.contains(char::is_whitespace))
+            .unwrap_or_else(|| String::from("MAILER-DAEMON"));
+        let date = chrono::Utc::now().format("%a %b %e %H:%M:%S %Y");
+
+        let mut msg = Self::new(format!("From {sender} {date}"), raw_msg.to_vec());
+        msg.set_flags(flags);
+        msg
+    }
+
+    /// Return the raw message, without status headers.
+    pub fn to_raw_msg(&self) -> Vec<u8> {
+        self.rebuild(Vec::new())
+    }
+
+    /// Return the Message-ID header of the message, if any.
+    pub fn message_id(&self) -> Option<String> {
+        let (fields, _) = split_headers(&self.raw);
+        header(&fields, "Message-ID").filter(|id| !id.is_empty())
+    }
+
+    /// Build flags from the status headers.
+    pub fn flags(&self) -> Flags {
+        let (fields, _) = split_headers(&self.raw);
+        let mut flags = Flags::default();
+
+        if let Some(status) = header(&fields, "Status") {
+            if status.contains('R') {
+                flags.insert(Flag::Seen);
+            }
+        }
+
+        if let Some(status) = header(&fields, "X-Status") {
+            for (flag, letter) in X_STATUS_FLAGS {
+                if status.contains(letter) {
+                    flags.insert(flag);
+                }
+            }
+        }
+
+        if let Some(keywords) = header(&fields, "X-Keywords") {
+            for keyword in keywords
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|k| !k.is_empty())
+            {
+                flags.insert(Flag::Custom(keyword.to_owned()));
+            }
+        }
+
+        flags
+    }
+
+    /// Replace the flags of the message by rewriting its status
+    /// headers.
+    pub fn set_flags(&mut self, flags: &Flags) {
+        let eol = self.eol();
+        let mut headers = Vec::new();
+
+        let status = if flags.contains(&Flag::Seen) {
+            "RO"
+        } else {
+            "O"
+        };
+        headers.push(format!("Status: {status}{eol}"));
+
+        let x_status: String = X_STATUS_FLAGS
+            .iter()
+            .filter(|(flag, _)| flags.contains(flag))
+            .map(|(_, letter)| *letter)
+            .collect();
+        if !x_status.is_empty() {
+            headers.push(format!("X-Status: {x_status}{eol}"));
+        }
+
+        let mut keywords: Vec<&str> = flags
+            .iter()
+            .filter_map(|flag| match flag {
+                Flag::Custom(keyword) => Some(keyword.as_str()),
+                _ => None,
+            })
+            .collect();
+        keywords.sort_unstable();
+        if !keywords.is_empty() {
+            headers.push(format!("X-Keywords: {}{eol}", keywords.join(" ")));
+        }
+
+        self.raw = self.rebuild(headers);
+    }
+
+    /// Add the given flags to the message.
+    pub fn add_flags(&mut self, flags: &Flags) {
+        let mut next = self.flags();
+        next.extend(flags.iter().cloned());
+        self.set_flags(&next);
+    }
+
+    /// Remove the given flags from the message.
+    pub fn remove_flags(&mut self, flags: &Flags) {
+        let next = Flags::from_iter(self.flags().iter().filter(|f| !flags.contains(f)).cloned());
+        self.set_flags(&next);
+    }
+
+    /// Compute the hash of the message, from its Message-ID or from
+    /// its content when the header is missing.
+    fn hash(&self) -> String {
+        let digest = match self.message_id() {
+            Some(id) => md5::compute(id),
+            None => md5::compute(self.to_raw_msg()),
+        };
+
+        format!("{digest:x}")
+    }
+
+    /// Return the line ending used by the message.
+    fn eol(&self) -> &'static str {
+        match self.raw.split(|b| *b == b'\n').next() {
+            Some(line) if line.ends_with(b"\r") => "\r\n",
+            _ => "\n",
+        }
+    }
+
+    /// Rebuild the raw message, replacing its status headers with the
+    /// given ones.
+    fn rebuild(&self, status_headers: Vec<String>) -> Vec<u8> {
+        let eol = self.eol().as_bytes();
+        let (fields, body) = split_headers(&self.raw);
+        let mut raw = Vec::with_capacity(self.raw.len());
+
+        for field in fields.iter().filter(|field| !is_status_field(field)) {
+            raw.extend_from_slice(field);
+            if !field.ends_with(b"\n") {
+                raw.extend_from_slice(eol);
+            }
+        }
+
+        for header in status_headers {
+            raw.extend_from_slice(header.as_bytes());
+        }
+
+        if body.is_empty() {
+            raw.extend_from_slice(eol);
+        }
+
+        raw.extend_from_slice(body);
+        raw
+    }
+}
+
+fn is_empty_line(line: &[u8]) -> bool {
+    line == b"\n" || line == b"\r\n"
+}
+
+/// Return `true` if the given line is a `From ` line, possibly
+/// quoted with `>`.
+fn is_from_line(line: &[u8]) -> bool {
+    let quotes = line.iter().take_while(|b| **b == b'>').count();
+    line[quotes..].starts_with(b"From ")
+}
+
+/// Remove one level of quoting from escaped `From ` lines.
+fn unescape_line(line: &[u8]) -> &[u8] {
+    if line.starts_with(b">") && is_from_line(line) {
+        &line[1..]
+    } else {
+        line
+    }
+}
+
+/// Split the given raw message into header fields and body.
+///
+/// Fields keep their continuation lines and line endings, and the
+/// body starts with the empty line separating it from the headers,
+/// so that concatenating both gives back the raw message.
+fn split_headers(raw: &[u8]) -> (Vec<&[u8]>, &[u8]) {
+    let mut fields = Vec::new();
+    let mut field_start = None;
+    let mut pos = 0;
+
+    for line in raw.split_inclusive(|b| *b == b'\n') {
+        if is_empty_line(line) {
+            break;
+        }
+
+        let is_continuation = line.starts_with(b" ") || line.starts_with(b"\t");
+
+        if !is_continuation || field_start.is_none() {
+            if let Some(start) = field_start {
+                fields.push(&raw[start..pos]);
+            }
+            field_start = Some(pos);
+        }
+
+        pos += line.len();
+    }
+
+    if let Some(start) = field_start {
+        fields.push(&raw[start..pos]);
+    }
+
+    (fields, &raw[pos..])
+}
+
+fn field_name(field: &[u8]) -> &[u8] {
+    let end = field.iter().position(|b| *b == b':').unwrap_or(field.len());
+    let name = &field[..end];
+    let start = name
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(end);
+    let end = name
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(start, |i| i + 1);
+    &name[start..end]
+}
+
+fn is_status_field(field: &&[u8]) -> bool {
+    let name = field_name(field);
+    STATUS_HEADERS
+        .iter()
+        .any(|header| name.eq_ignore_ascii_case(header.as_bytes()))
+}
+
+/// Return the unfolded value of the first header field matching the
+/// given name.
+fn header(fields: &[&[u8]], name: &str) -> Option<String> {
+    let field = fields
+        .iter()
+        .find(|field| field_name(field).eq_ignore_ascii_case(name.as_bytes()))?;
+    let start = field.iter().position(|b| *b == b':')? + 1;
+    let value = String::from_utf8_lossy(&field[start..]);
+
+    Some(value.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use email::flag::{Flag, Flags};
+
+    use super::{Mbox, MboxMessage};
+
+    const MBOX: &[u8] = b"From alice@localhost Mon Jan  1 00:00:00 2024
+Message-ID: <1@localhost>
+Subject: first
+
+>From the start
+>>From the quote
+
+From bob@localhost Mon Jan  1 00:00:00 2024
+Subject: second
+Status: RO
+X-Status: AF
+X-Keywords: work
+
+body
+
+";
+
+    #[test]
+    fn parse_and_serialize() {
+        let mbox = Mbox::parse(MBOX);
+
+        assert_eq!(mbox.messages.len(), 2);
+        assert_eq!(
+            mbox.messages[0].from_line,
+            "From alice@localhost Mon Jan  1 00:00:00 2024"
+        );
+        assert_eq!(
+            mbox.messages[0].raw,
+            b"Message-ID: <1@localhost>\nSubject: first\n\nFrom the start\n>From the quote\n"
+        );
+        assert_eq!(
+            mbox.messages[0].id,
+            format!("{:x}", md5::compute("<1@localhost>"))
+        );
+        assert_eq!(mbox.to_bytes(), MBOX);
+    }
+
+    #[test]
+    fn flags() {
+        let mbox = Mbox::parse(MBOX);
+
+        assert_eq!(mbox.messages[0].flags(), Flags::default());
+        assert_eq!(
+            mbox.messages[1].flags(),
+            Flags::from_iter([
+                Flag::Seen,
+                Flag::Answered,
+                Flag::Flagged,
+                Flag::Custom("work".into())
+            ])
+        );
+        assert_eq!(mbox.messages[1].to_raw_msg(), b"Subject: second\n\nbody\n");
+    }
+
+    #[test]
+    fn set_flags_keeps_id() {
+        let mut mbox = Mbox::parse(MBOX);
+        let id = mbox.messages[1].id.clone();
+
+        mbox.messages[1].set_flags(&Flags::from_iter([Flag::Deleted]));
+        mbox.assign_ids();
+
+        assert_eq!(mbox.messages[1].id, id);
+        assert_eq!(
+            mbox.messages[1].raw,
+            b"Subject: second\nStatus: O\nX-Status: D\n\nbody\n"
+        );
+    }
+
+    #[test]
+    fn duplicate_ids() {
+        let msg =
+            MboxMessage::from_raw_msg(b"Message-ID: <1@localhost>\n\nbody\n", &Flags::default());
+        let mut mbox = Mbox::parse(MBOX);
+        mbox.messages.push(msg);
+        mbox.assign_ids();
+
+        assert_eq!(mbox.messages[2].id, format!("{}-1", mbox.messages[0].id));
+    }
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use dialoguer::Input;
+use dirs::home_dir;
+
+use crate::{backend::config::BackendConfig, ui::THEME};
+
+use super::MboxConfig;
+
+pub(crate) fn configure() -> Result<BackendConfig> {
+    let mut config = MboxConfig::default();
+
+    let mut input = Input::with_theme(&*THEME);
+
+    if let Some(home) = home_dir() {
+        input.default(home.join("Mail").display().to_string());
+    };
+
+    config.root_dir = input.with_prompt("Mbox directory").interact_text()?.into();
+
+    Ok(BackendConfig::Mbox(config))
+}
@@ -0,0 +1,172 @@
+//! Module dedicated to Notmuch envelopes.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+#[cfg(feature = "envelope-get")]
+use email::envelope::{get::GetEnvelope, Id};
+#[cfg(feature = "envelope-list")]
+use email::envelope::{list::ListEnvelopes, Envelopes};
+#[cfg(feature = "envelope-watch")]
+use email::envelope::{watch::WatchEnvelopes, Envelope};
+use log::{debug, info, trace};
+#[cfg(feature = "envelope-watch")]
+use std::{collections::HashMap, time::Duration};
+
+#[cfg(feature = "envelope-list")]
+use crate::envelope::search::paginate;
+
+#[cfg(feature = "envelope-get")]
+use super::find_messages;
+use super::{flags_from_tags, NotmuchSession};
+
+/// The delay between two database checks when watching a folder.
+#[cfg(feature = "envelope-watch")]
+const WATCH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Build an envelope from a Notmuch message, using the CLI flags
+/// mapping rather than the lib one.
+pub fn envelope_from_msg(msg: notmuch::Message) -> email::envelope::Envelope {
+    let flags = flags_from_tags(&msg);
+    let mut envelope = email::envelope::Envelope::from_notmuch_msg(msg);
+    envelope.flags = flags;
+    envelope
+}
+
+/// Search the envelopes matching the given query, from the most
+/// recent to the oldest.
+fn search_envelopes(
+    session: &NotmuchSession,
+    query: &str,
+) -> Result<Vec<email::envelope::Envelope>> {
+    debug!("searching notmuch envelopes matching {query}");
+
+    let db = session.open_db()?;
+    let query_builder = db
+        .create_query(query)
+        .with_context(|| format!("cannot build notmuch query {query}"))?;
+    query_builder.set_sort(notmuch::Sort::NewestFirst);
+
+    let envelopes: Vec<_> = query_builder
+        .search_messages()
+        .with_context(|| format!("cannot search notmuch messages matching {query}"))?
+        .map(envelope_from_msg)
+        .collect();
+    trace!("notmuch envelopes: {envelopes:#?}");
+
+    db.close()?;
+    Ok(envelopes)
+}
+
+#[cfg(feature = "envelope-list")]
+pub struct ListEnvelopesNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "envelope-list")]
+impl ListEnvelopesNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn ListEnvelopes>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "envelope-list")]
+#[async_trait]
+impl ListEnvelopes for ListEnvelopesNotmuch {
+    async fn list_envelopes(
+        &self,
+        folder: &str,
+        page_size: usize,
+        page: usize,
+    ) -> Result<Envelopes> {
+        info!("listing notmuch envelopes from folder {folder}");
+
+        let query = self.session.folder(folder).query();
+        let envelopes = search_envelopes(&self.session, &query)?;
+        let envelopes = paginate(&envelopes, page_size, page)?;
+
+        Ok(Envelopes::from_iter(envelopes.iter().cloned()))
+    }
+}
+
+#[cfg(feature = "envelope-get")]
+pub struct GetEnvelopeNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "envelope-get")]
+impl GetEnvelopeNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn GetEnvelope>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "envelope-get")]
+#[async_trait]
+impl GetEnvelope for GetEnvelopeNotmuch {
+    async fn get_envelope(&self, folder: &str, id: &Id) -> Result<email::envelope::Envelope> {
+        info!("getting notmuch envelope {id} from folder {folder}");
+
+        let db = self.session.open_db()?;
+        let msg = find_messages(&db, id)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("cannot find notmuch message {id}"))?;
+        let envelope = envelope_from_msg(msg);
+        db.close()?;
+
+        Ok(envelope)
+    }
+}
+
+#[cfg(feature = "envelope-watch")]
+pub struct WatchNotmuchEnvelopes {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "envelope-watch")]
+impl WatchNotmuchEnvelopes {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn WatchEnvelopes>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+
+    fn envelopes(&self, query: &str) -> Result<HashMap<String, Envelope>> {
+        let envelopes = search_envelopes(&self.session, query)?;
+        Ok(HashMap::from_iter(
+            envelopes.into_iter().map(|e| (e.id.clone(), e)),
+        ))
+    }
+}
+
+#[cfg(feature = "envelope-watch")]
+#[async_trait]
+impl WatchEnvelopes for WatchNotmuchEnvelopes {
+    /// Watch the given folder by checking the database regularly,
+    /// since Notmuch has no change notification.
+    async fn watch_envelopes(&self, folder: &str) -> Result<()> {
+        info!("notmuch: watching folder {folder} for email changes");
+
+        let config = &self.session.account_config;
+        let query = self.session.folder(folder).query();
+        let mut envelopes = self.envelopes(&query)?;
+
+        loop {
+            tokio::time::sleep(WATCH_INTERVAL).await;
+
+            let next_envelopes = match self.envelopes(&query) {
+                Ok(envelopes) => envelopes,
+                Err(err) => {
+                    debug!("cannot check notmuch folder {folder}: {err}");
+                    debug!("{err:?}");
+                    continue;
+                }
+            };
+
+            self.exec_hooks(config, &envelopes, &next_envelopes).await;
+
+            envelopes = next_envelopes;
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Module dedicated to Notmuch flags.
+//!
+//! Flags are stored as Notmuch tags, see [`super::flags_from_tags`]
+//! for the mapping.
+
+use anyhow::Result;
+use async_trait::async_trait;
+#[cfg(feature = "flag-add")]
+use email::flag::add::AddFlags;
+#[cfg(feature = "flag-remove")]
+use email::flag::remove::RemoveFlags;
+#[cfg(feature = "flag-set")]
+use email::flag::set::SetFlags;
+use email::{envelope::Id, flag::Flags};
+use log::info;
+
+use super::{find_messages, NotmuchSession};
+
+/// Apply the given tagging function to the messages matching the
+/// given ids.
+fn update_tags(
+    session: &NotmuchSession,
+    id: &Id,
+    flags: &Flags,
+    f: impl Fn(&notmuch::Message, &Flags) -> Result<()>,
+) -> Result<()> {
+    let db = session.open_db()?;
+
+    for msg in find_messages(&db, id)? {
+        f(&msg, flags)?;
+    }
+
+    db.close()?;
+    Ok(())
+}
+
+#[cfg(feature = "flag-add")]
+pub struct AddFlagsNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "flag-add")]
+impl AddFlagsNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn AddFlags>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "flag-add")]
+#[async_trait]
+impl AddFlags for AddFlagsNotmuch {
+    async fn add_flags(&self, folder: &str, id: &Id, flags: &Flags) -> Result<()> {
+        info!("adding notmuch flags {flags} to envelope(s) {id} from folder {folder}");
+        update_tags(&self.session, id, flags, super::add_flags)
+    }
+}
+
+#[cfg(feature = "flag-set")]
+pub struct SetFlagsNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "flag-set")]
+impl SetFlagsNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn SetFlags>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "flag-set")]
+#[async_trait]
+impl SetFlags for SetFlagsNotmuch {
+    async fn set_flags(&self, folder: &str, id: &Id, flags: &Flags) -> Result<()> {
+        info!("setting notmuch flags {flags} to envelope(s) {id} from folder {folder}");
+        update_tags(&self.session, id, flags, super::set_flags)
+    }
+}
+
+#[cfg(feature = "flag-remove")]
+pub struct RemoveFlagsNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "flag-remove")]
+impl RemoveFlagsNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn RemoveFlags>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "flag-remove")]
+#[async_trait]
+impl RemoveFlags for RemoveFlagsNotmuch {
+    async fn remove_flags(&self, folder: &str, id: &Id, flags: &Flags) -> Result<()> {
+        info!("removing notmuch flags {flags} from envelope(s) {id} from folder {folder}");
+        update_tags(&self.session, id, flags, super::remove_flags)
+    }
+}
@@ -0,0 +1,280 @@
+//! Module dedicated to Notmuch folders.
+//!
+//! Maildir directories of the database can be created and deleted
+//! like any other folder. Tags and queries cannot be created, they
+//! are defined by folder aliases.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+#[cfg(feature = "folder-add")]
+use email::folder::add::AddFolder;
+#[cfg(feature = "folder-delete")]
+use email::folder::delete::DeleteFolder;
+#[cfg(feature = "folder-expunge")]
+use email::folder::expunge::ExpungeFolder;
+#[cfg(feature = "folder-list")]
+use email::folder::list::ListFolders;
+#[cfg(feature = "folder-purge")]
+use email::folder::purge::PurgeFolder;
+#[cfg(feature = "folder-list")]
+use email::folder::{Folder, FolderKind, Folders};
+use log::{debug, info};
+use std::fs;
+
+use super::{NotmuchFolder, NotmuchSession};
+
+#[cfg(feature = "folder-add")]
+pub struct AddFolderNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "folder-add")]
+impl AddFolderNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn AddFolder>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-add")]
+#[async_trait]
+impl AddFolder for AddFolderNotmuch {
+    async fn add_folder(&self, folder: &str) -> Result<()> {
+        info!("creating notmuch folder {folder}");
+
+        let mdir = self.session.folder(folder);
+        if !matches!(mdir, NotmuchFolder::Maildir(_)) {
+            bail!("cannot create notmuch folder {folder}: tags and queries are defined by aliases");
+        }
+
+        let path = self.session.maildir_path(&mdir);
+
+        for dir in ["cur", "new", "tmp"] {
+            let dir = path.join(dir);
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("cannot create notmuch folder directory {dir:?}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "folder-list")]
+pub struct ListFoldersNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "folder-list")]
+impl ListFoldersNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn ListFolders>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-list")]
+#[async_trait]
+impl ListFolders for ListFoldersNotmuch {
+    /// List the folder aliases, then the maildir directories of the
+    /// database that are not covered by an alias.
+    async fn list_folders(&self) -> Result<Folders> {
+        info!("listing notmuch folders");
+
+        let config = &self.session.account_config;
+        let mut folders = Folders::default();
+
+        let mut aliases: Vec<(String, String)> = config
+            .folder
+            .as_ref()
+            .and_then(|config| config.aliases.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        aliases.sort();
+
+        if !aliases
+            .iter()
+            .any(|(name, _)| FolderKind::matches_inbox(name))
+        {
+            folders.push(Folder {
+                kind: Some(FolderKind::Inbox),
+                name: config.get_inbox_folder_alias(),
+                desc: self
+                    .session
+                    .folder(&config.get_inbox_folder_alias())
+                    .query(),
+            });
+        }
+
+        for (name, _) in &aliases {
+            folders.push(Folder {
+                kind: name.parse().ok(),
+                name: name.clone(),
+                desc: self.session.folder(name).query(),
+            });
+        }
+
+        let root = self.session.path();
+        let entries =
+            fs::read_dir(&root).with_context(|| format!("cannot read notmuch root {root:?}"))?;
+
+        let mut dirs: Vec<String> = entries
+            .filter_map(|entry| match entry {
+                Ok(entry) => Some(entry.path()),
+                Err(err) => {
+                    debug!("cannot read notmuch root entry, skipping it: {err}");
+                    None
+                }
+            })
+            .filter(|path| path.join("cur").is_dir())
+            .filter_map(|path| Some(path.file_name()?.to_str()?.to_owned()))
+            .filter(|name| !name.starts_with(".notmuch"))
+            .collect();
+        dirs.sort();
+
+        for dir in dirs {
+            let folder = NotmuchFolder::Maildir(dir.clone());
+            let is_aliased = aliases
+                .iter()
+                .any(|(name, _)| self.session.folder(name) == folder);
+
+            if !is_aliased {
+                folders.push(Folder {
+                    kind: config.find_folder_kind_from_alias(&dir),
+                    name: dir,
+                    desc: folder.query(),
+                });
+            }
+        }
+
+        Ok(folders)
+    }
+}
+
+#[cfg(feature = "folder-expunge")]
+pub struct ExpungeFolderNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "folder-expunge")]
+impl ExpungeFolderNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn ExpungeFolder>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-expunge")]
+#[async_trait]
+impl ExpungeFolder for ExpungeFolderNotmuch {
+    /// Remove the files of the folder messages tagged as deleted.
+    async fn expunge_folder(&self, folder: &str) -> Result<()> {
+        info!("expunging notmuch folder {folder}");
+
+        let query = format!("({}) and tag:deleted", self.session.folder(folder).query());
+        remove_messages(&self.session, &query)
+    }
+}
+
+#[cfg(feature = "folder-purge")]
+pub struct PurgeFolderNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "folder-purge")]
+impl PurgeFolderNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn PurgeFolder>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-purge")]
+#[async_trait]
+impl PurgeFolder for PurgeFolderNotmuch {
+    /// Remove the files of all the folder messages.
+    async fn purge_folder(&self, folder: &str) -> Result<()> {
+        info!("purging notmuch folder {folder}");
+
+        let query = self.session.folder(folder).query();
+        remove_messages(&self.session, &query)
+    }
+}
+
+#[cfg(feature = "folder-delete")]
+pub struct DeleteFolderNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "folder-delete")]
+impl DeleteFolderNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn DeleteFolder>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "folder-delete")]
+#[async_trait]
+impl DeleteFolder for DeleteFolderNotmuch {
+    /// Delete a maildir directory with all its messages, or untag
+    /// all the messages of a tag.
+    async fn delete_folder(&self, folder: &str) -> Result<()> {
+        info!("deleting notmuch folder {folder}");
+
+        match self.session.folder(folder) {
+            NotmuchFolder::Maildir(dir) if dir.is_empty() => {
+                bail!("cannot delete notmuch folder {folder}: root folder cannot be deleted")
+            }
+            NotmuchFolder::Maildir(dir) => {
+                let folder = NotmuchFolder::Maildir(dir);
+                remove_messages(&self.session, &folder.query())?;
+
+                let path = self.session.maildir_path(&folder);
+                fs::remove_dir_all(&path)
+                    .with_context(|| format!("cannot delete notmuch folder at {path:?}"))?;
+            }
+            NotmuchFolder::Tag(tag) => {
+                let db = self.session.open_db()?;
+                let query = db.create_query(&format!("tag:\"{tag}\""))?;
+                for msg in query.search_messages()? {
+                    msg.remove_tag(&tag)?;
+                }
+                db.close()?;
+            }
+            NotmuchFolder::Query(_) => {
+                bail!("cannot delete notmuch folder {folder}: queries are defined by aliases")
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Remove the files of the messages matching the given query from
+/// the filesystem and from the database.
+#[cfg(any(
+    feature = "folder-expunge",
+    feature = "folder-purge",
+    feature = "folder-delete"
+))]
+fn remove_messages(session: &NotmuchSession, query: &str) -> Result<()> {
+    debug!("removing notmuch messages matching {query}");
+
+    let db = session.open_db()?;
+    let paths: Vec<_> = db
+        .create_query(query)?
+        .search_messages()?
+        .flat_map(|msg| msg.filenames().collect::<Vec<_>>())
+        .collect();
+
+    for path in paths {
+        db.remove_message(&path)
+            .with_context(|| format!("cannot remove notmuch message {path:?}"))?;
+        fs::remove_file(&path)
+            .with_context(|| format!("cannot delete notmuch message at {path:?}"))?;
+    }
+
+    db.close()?;
+    Ok(())
+}
@@ -0,0 +1,221 @@
+//! Module dedicated to Notmuch messages.
+//!
+//! Messages added, copied or moved to a maildir directory are stored
+//! in this directory then indexed. Messages added, copied or moved
+//! to a tag are tagged.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use email::envelope::Id;
+#[cfg(feature = "message-copy")]
+use email::message::copy::CopyMessages;
+#[cfg(feature = "message-move")]
+use email::message::move_::MoveMessages;
+#[cfg(feature = "message-peek")]
+use email::message::{peek::PeekMessages, Messages};
+#[cfg(feature = "message-add")]
+use email::{envelope::SingleId, flag::Flags, message::add::AddMessage};
+use log::info;
+use std::fs;
+#[cfg(any(feature = "message-copy", feature = "message-move"))]
+use std::path::{Path, PathBuf};
+
+use super::{find_messages, NotmuchFolder, NotmuchSession};
+
+#[cfg(feature = "message-add")]
+pub struct AddMessageNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "message-add")]
+impl AddMessageNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn AddMessage>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-add")]
+#[async_trait]
+impl AddMessage for AddMessageNotmuch {
+    async fn add_message_with_flags(
+        &self,
+        folder: &str,
+        raw_msg: &[u8],
+        flags: &Flags,
+    ) -> Result<SingleId> {
+        info!("adding notmuch message to folder {folder} with flags {flags}");
+
+        let target = self.session.folder(folder);
+        if let NotmuchFolder::Query(query) = &target {
+            bail!("cannot add message to notmuch folder {folder}: query {query} is read-only");
+        }
+
+        let db = self.session.open_db()?;
+        let msg = super::store_message(&db, &self.session.maildir_path(&target), raw_msg, flags)?;
+
+        if let NotmuchFolder::Tag(tag) = &target {
+            msg.add_tag(tag)?;
+        }
+
+        let id = SingleId::from(msg.id());
+        db.close()?;
+
+        Ok(id)
+    }
+}
+
+#[cfg(feature = "message-peek")]
+pub struct PeekMessagesNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "message-peek")]
+impl PeekMessagesNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn PeekMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-peek")]
+#[async_trait]
+impl PeekMessages for PeekMessagesNotmuch {
+    async fn peek_messages(&self, folder: &str, id: &Id) -> Result<Messages> {
+        info!("peeking notmuch messages {id} from folder {folder}");
+
+        let db = self.session.open_db()?;
+        let msgs = find_messages(&db, id)?
+            .into_iter()
+            .map(|msg| {
+                let path = msg.filename();
+                fs::read(&path).with_context(|| format!("cannot read notmuch message at {path:?}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        db.close()?;
+
+        Ok(Messages::from(msgs))
+    }
+}
+
+#[cfg(feature = "message-copy")]
+pub struct CopyMessagesNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "message-copy")]
+impl CopyMessagesNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn CopyMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-copy")]
+#[async_trait]
+impl CopyMessages for CopyMessagesNotmuch {
+    async fn copy_messages(&self, from_folder: &str, to_folder: &str, id: &Id) -> Result<()> {
+        info!("copying notmuch messages {id} from folder {from_folder} to folder {to_folder}");
+        transfer_messages(&self.session, from_folder, to_folder, id, false)
+    }
+}
+
+#[cfg(feature = "message-move")]
+pub struct MoveMessagesNotmuch {
+    session: NotmuchSession,
+}
+
+#[cfg(feature = "message-move")]
+impl MoveMessagesNotmuch {
+    pub fn new(session: &NotmuchSession) -> Option<Box<dyn MoveMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-move")]
+#[async_trait]
+impl MoveMessages for MoveMessagesNotmuch {
+    async fn move_messages(&self, from_folder: &str, to_folder: &str, id: &Id) -> Result<()> {
+        info!("moving notmuch messages {id} from folder {from_folder} to folder {to_folder}");
+        transfer_messages(&self.session, from_folder, to_folder, id, true)
+    }
+}
+
+/// Copy or move messages between folders.
+///
+/// Moving a message out of a tag removes the tag, moving a message
+/// out of a maildir directory moves its file.
+#[cfg(any(feature = "message-copy", feature = "message-move"))]
+fn transfer_messages(
+    session: &NotmuchSession,
+    from_folder: &str,
+    to_folder: &str,
+    id: &Id,
+    remove_source: bool,
+) -> Result<()> {
+    let source = session.folder(from_folder);
+    let target = session.folder(to_folder);
+
+    if let NotmuchFolder::Query(query) = &target {
+        bail!("cannot transfer messages to notmuch folder {to_folder}: query {query} is read-only");
+    }
+
+    if remove_source && source == target {
+        return Ok(());
+    }
+
+    let db = session.open_db()?;
+
+    for msg in find_messages(&db, id)? {
+        let path = source_file(session, &source, &msg);
+
+        match &target {
+            NotmuchFolder::Tag(tag) => msg.add_tag(tag)?,
+            NotmuchFolder::Maildir(_) | NotmuchFolder::Query(_) => {
+                let raw_msg = fs::read(&path)
+                    .with_context(|| format!("cannot read notmuch message at {path:?}"))?;
+                let flags = super::flags_from_tags(&msg);
+                let mdir = session.maildir_path(&target);
+                let new_path = super::store_file(&mdir, &raw_msg, &flags)?;
+                super::index_file(&db, &new_path)?;
+
+                if remove_source {
+                    db.remove_message(&path)
+                        .with_context(|| format!("cannot remove notmuch message {path:?}"))?;
+                    fs::remove_file(&path)
+                        .with_context(|| format!("cannot delete notmuch message at {path:?}"))?;
+                }
+            }
+        }
+
+        if let (true, NotmuchFolder::Tag(tag)) = (remove_source, &source) {
+            msg.remove_tag(tag)?;
+        }
+    }
+
+    db.close()?;
+    Ok(())
+}
+
+/// Return the file of the given message belonging to the given
+/// folder, or its first file if none belongs to the folder.
+#[cfg(any(feature = "message-copy", feature = "message-move"))]
+fn source_file(
+    session: &NotmuchSession,
+    folder: &NotmuchFolder,
+    msg: &notmuch::Message,
+) -> PathBuf {
+    if let NotmuchFolder::Maildir(_) = folder {
+        let mdir = session.maildir_path(folder);
+        let file = msg
+            .filenames()
+            .find(|path| path.parent().and_then(Path::parent) == Some(mdir.as_path()));
+
+        if let Some(file) = file {
+            return file;
+        }
+    }
+
+    msg.filename()
+}
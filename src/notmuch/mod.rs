@@ -1,2 +1,500 @@
+//! Module dedicated to the Notmuch backend.
+//!
+//! The lib only provides the Notmuch configuration and envelope
+//! mapping functions, so the backend features are implemented here,
+//! on top of the [notmuch] crate.
+//!
+//! Notmuch has no folder: folders are resolved to Notmuch queries.
+//! A folder alias can be a maildir directory of the database (`Sent`
+//! or `folder:Sent`), a single tag (`tag:sent`) or any other Notmuch
+//! query (`tag:work and not tag:archived`). Messages can be added to,
+//! moved or copied to maildir directories and tags, while other
+//! queries are read-only.
+
+#[cfg(any(
+    feature = "envelope-list",
+    feature = "envelope-watch",
+    feature = "envelope-get"
+))]
+pub mod envelope;
+#[cfg(any(feature = "flag-add", feature = "flag-set", feature = "flag-remove"))]
+pub mod flag;
+#[cfg(any(
+    feature = "folder-add",
+    feature = "folder-list",
+    feature = "folder-expunge",
+    feature = "folder-purge",
+    feature = "folder-delete"
+))]
+pub mod folder;
+#[cfg(any(
+    feature = "message-add",
+    feature = "message-peek",
+    feature = "message-copy",
+    feature = "message-move"
+))]
+pub mod message;
 #[cfg(feature = "wizard")]
 pub(crate) mod wizard;
+
+use anyhow::{anyhow, Context, Result};
+use email::{
+    account::config::AccountConfig,
+    envelope::Id,
+    flag::{Flag, Flags},
+    folder::FolderKind,
+    notmuch::NotmuchConfig,
+};
+use log::{debug, trace};
+use notmuch::{Database, DatabaseMode};
+use shellexpand_utils::shellexpand_path;
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "envelope-get")]
+pub use self::envelope::GetEnvelopeNotmuch;
+#[cfg(any(feature = "envelope-list", feature = "envelope-watch"))]
+pub use self::envelope::{ListEnvelopesNotmuch, WatchNotmuchEnvelopes};
+#[cfg(feature = "flag-add")]
+pub use self::flag::AddFlagsNotmuch;
+#[cfg(feature = "flag-remove")]
+pub use self::flag::RemoveFlagsNotmuch;
+#[cfg(feature = "flag-set")]
+pub use self::flag::SetFlagsNotmuch;
+#[cfg(feature = "folder-add")]
+pub use self::folder::AddFolderNotmuch;
+#[cfg(feature = "folder-delete")]
+pub use self::folder::DeleteFolderNotmuch;
+#[cfg(feature = "folder-expunge")]
+pub use self::folder::ExpungeFolderNotmuch;
+#[cfg(feature = "folder-list")]
+pub use self::folder::ListFoldersNotmuch;
+#[cfg(feature = "folder-purge")]
+pub use self::folder::PurgeFolderNotmuch;
+#[cfg(feature = "message-add")]
+pub use self::message::AddMessageNotmuch;
+#[cfg(feature = "message-copy")]
+pub use self::message::CopyMessagesNotmuch;
+#[cfg(feature = "message-move")]
+pub use self::message::MoveMessagesNotmuch;
+#[cfg(feature = "message-peek")]
+pub use self::message::PeekMessagesNotmuch;
+
+/// The tag marking unread messages. Notmuch has no seen tag: a
+/// message is seen when it is not tagged as unread.
+const UNREAD_TAG: &str = "unread";
+
+/// The tag of the inbox folder, when no inbox alias is defined.
+const INBOX_TAG: &str = "inbox";
+
+/// The Notmuch session builder.
+#[derive(Clone, Debug)]
+pub struct NotmuchSessionBuilder {
+    account_config: AccountConfig,
+    notmuch_config: NotmuchConfig,
+}
+
+impl NotmuchSessionBuilder {
+    pub fn new(account_config: AccountConfig, notmuch_config: NotmuchConfig) -> Self {
+        Self {
+            account_config,
+            notmuch_config,
+        }
+    }
+
+    /// Build the session, making sure the database can be opened.
+    pub async fn build(self) -> Result<NotmuchSession> {
+        let session = NotmuchSession {
+            account_config: self.account_config,
+            notmuch_config: self.notmuch_config,
+        };
+
+        session.open_db()?.close()?;
+
+        Ok(session)
+    }
+}
+
+/// The Notmuch session.
+///
+/// Notmuch databases cannot be shared between threads, so the
+/// session only holds the configuration and the database is opened
+/// by each backend feature.
+#[derive(Clone, Debug)]
+pub struct NotmuchSession {
+    pub account_config: AccountConfig,
+    pub notmuch_config: NotmuchConfig,
+}
+
+impl NotmuchSession {
+    /// Return the shell-expanded path of the database.
+    pub fn path(&self) -> PathBuf {
+        shellexpand_path(&self.notmuch_config.db_path)
+    }
+
+    pub fn open_db(&self) -> Result<Database> {
+        let path = self.path();
+        debug!("opening notmuch database at {path:?}");
+
+        Database::open_with_config(
+            Some(&path),
+            DatabaseMode::ReadWrite,
+            None as Option<PathBuf>,
+            None,
+        )
+        .with_context(|| format!("cannot open notmuch database at {path:?}"))
+    }
+
+    /// Resolve the given folder name to its Notmuch representation.
+    pub fn folder(&self, name: &str) -> NotmuchFolder {
+        match self.account_config.find_folder_alias(name) {
+            Some(alias) => NotmuchFolder::parse(&alias),
+            None if FolderKind::matches_inbox(name) => NotmuchFolder::Tag(INBOX_TAG.into()),
+            None => NotmuchFolder::parse(name),
+        }
+    }
+
+    /// Return the maildir directory messages added to the given
+    /// folder are stored in. Messages added to tags and queries are
+    /// stored in the root maildir of the database.
+    pub fn maildir_path(&self, folder: &NotmuchFolder) -> PathBuf {
+        match folder {
+            NotmuchFolder::Maildir(dir) => self.path().join(dir),
+            NotmuchFolder::Tag(_) | NotmuchFolder::Query(_) => self.path(),
+        }
+    }
+}
+
+/// The Notmuch representation of a folder.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NotmuchFolder {
+    /// A maildir directory, relative to the database root.
+    Maildir(String),
+
+    /// A tag: messages belong to the folder when they are tagged.
+    Tag(String),
+
+    /// Any other Notmuch query.
+    Query(String),
+}
+
+impl NotmuchFolder {
+    /// Parse a folder from a folder name or alias.
+    ///
+    /// Names containing a colon or a whitespace are considered as
+    /// Notmuch queries, `folder:` and `tag:` queries are recognized
+    /// when they target a single directory or tag.
+    pub fn parse(name: &str) -> Self {
+        let name = name.trim();
+        let is_simple = |term: &str| !term.is_empty() && !term.contains(char::is_whitespace);
+
+        if let Some(tag) = name.strip_prefix("tag:").filter(|tag| is_simple(tag)) {
+            return Self::Tag(tag.trim_matches('"').to_owned());
+        }
+
+        if let Some(dir) = name.strip_prefix("folder:") {
+            let dir = dir.trim_matches('"');
+            if !dir.contains(char::is_whitespace) && !dir.contains('"') {
+                return Self::Maildir(dir.to_owned());
+            }
+        }
+
+        if name.contains(':') || name.contains(char::is_whitespace) {
+            Self::Query(name.to_owned())
+        } else {
+            Self::Maildir(name.to_owned())
+        }
+    }
+
+    /// Return the Notmuch query matching messages of the folder.
+    pub fn query(&self) -> String {
+        match self {
+            Self::Maildir(dir) => format!("folder:\"{dir}\""),
+            Self::Tag(tag) => format!("tag:\"{tag}\""),
+            Self::Query(query) => query.clone(),
+        }
+    }
+}
+
+/// Find the messages matching the given ids.
+pub fn find_messages(db: &Database, id: &Id) -> Result<Vec<notmuch::Message>> {
+    id.iter()
+        .map(|id| {
+            db.find_message(id)
+                .with_context(|| format!("cannot find notmuch message {id}"))?
+                .ok_or_else(|| anyhow!("cannot find notmuch message {id}"))
+        })
+        .collect()
+}
+
+/// Build flags from the tags of the given message.
+pub fn flags_from_tags(msg: &notmuch::Message) -> Flags {
+    let flags = flags_from_tag_names(msg.tags());
+    trace!("notmuch flags of message {}: {flags:?}", msg.id());
+    flags
+}
+
+/// Build flags from the given tags. Messages without the
+/// [`UNREAD_TAG`] are seen.
+fn flags_from_tag_names<T: AsRef<str>>(tags: impl IntoIterator<Item = T>) -> Flags {
+    let mut flags = Flags::default();
+    let mut unread = false;
+
+    for tag in tags {
+        match tag.as_ref() {
+            UNREAD_TAG => unread = true,
+            tag => {
+                flags.insert(tag_to_flag(tag));
+            }
+        }
+    }
+
+    if !unread {
+        flags.insert(Flag::Seen);
+    }
+
+    flags
+}
+
+fn tag_to_flag(tag: &str) -> Flag {
+    match tag {
+        "replied" => Flag::Answered,
+        "flagged" => Flag::Flagged,
+        "draft" => Flag::Draft,
+        "deleted" => Flag::Deleted,
+        tag => Flag::Custom(tag.to_owned()),
+    }
+}
+
+/// Return the tag matching the given flag. The seen flag has no
+/// tag, see [`UNREAD_TAG`].
+fn flag_to_tag(flag: &Flag) -> Option<&str> {
+    match flag {
+        Flag::Seen => None,
+        Flag::Answered => Some("replied"),
+        Flag::Flagged => Some("flagged"),
+        Flag::Draft => Some("draft"),
+        Flag::Deleted => Some("deleted"),
+        Flag::Custom(tag) => Some(tag.as_str()),
+    }
+}
+
+/// Tag the given message with the given flags.
+pub fn add_flags(msg: &notmuch::Message, flags: &Flags) -> Result<()> {
+    for flag in flags.iter() {
+        match flag_to_tag(flag) {
+            None => msg.remove_tag(UNREAD_TAG)?,
+            Some(tag) => msg.add_tag(tag)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Untag the given message from the given flags.
+pub fn remove_flags(msg: &notmuch::Message, flags: &Flags) -> Result<()> {
+    for flag in flags.iter() {
+        match flag_to_tag(flag) {
+            None => msg.add_tag(UNREAD_TAG)?,
+            Some(tag) => msg.remove_tag(tag)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace the flags of the given message.
+///
+/// Only tags matching flags are replaced: other tags (like `inbox`
+/// or `attachment`) are kept as they also represent folders.
+pub fn set_flags(msg: &notmuch::Message, flags: &Flags) -> Result<()> {
+    let prev: BTreeSet<String> = msg.tags().collect();
+    let next = replace_flag_tags(&prev, flags);
+
+    for tag in prev.difference(&next) {
+        msg.remove_tag(tag)?;
+    }
+
+    for tag in next.difference(&prev) {
+        msg.add_tag(tag)?;
+    }
+
+    Ok(())
+}
+
+/// Return the given tags once the flags they represent are replaced
+/// by the given flags. Custom tags are kept.
+fn replace_flag_tags<'a>(
+    tags: impl IntoIterator<Item = &'a str>,
+    flags: &Flags,
+) -> BTreeSet<String> {
+    let mut tags: BTreeSet<String> = tags
+        .into_iter()
+        .filter(|tag| *tag != UNREAD_TAG && matches!(tag_to_flag(tag), Flag::Custom(_)))
+        .map(ToOwned::to_owned)
+        .collect();
+
+    if !flags.contains(&Flag::Seen) {
+        tags.insert(UNREAD_TAG.to_owned());
+    }
+
+    tags.extend(flags.iter().filter_map(flag_to_tag).map(ToOwned::to_owned));
+    tags
+}
+
+/// Store the given raw message in the given maildir directory, then
+/// index it.
+pub fn store_message(
+    db: &Database,
+    mdir: &Path,
+    raw_msg: &[u8],
+    flags: &Flags,
+) -> Result<notmuch::Message> {
+    let path = store_file(mdir, raw_msg, flags)?;
+    let msg = index_file(db, &path)?;
+
+    if !flags.contains(&Flag::Seen) {
+        msg.add_tag(UNREAD_TAG)?;
+    }
+    add_flags(&msg, flags)?;
+
+    Ok(msg)
+}
+
+/// Index the given message file.
+///
+/// Indexing a file whose Message-ID is already known adds the file
+/// to the existing message, but is reported as an error by Notmuch.
+/// The message is therefore searched by file name afterwards.
+pub fn index_file(db: &Database, path: &Path) -> Result<notmuch::Message> {
+    debug!("indexing notmuch message at {path:?}");
+
+    if let Err(err) = db.index_file(&path, None) {
+        debug!("notmuch reported an error while indexing {path:?}: {err}");
+    }
+
+    db.find_message_by_filename(&path)
+        .with_context(|| format!("cannot find notmuch message at {path:?}"))?
+        .ok_or_else(|| anyhow!("cannot index notmuch message at {path:?}"))
+}
+
+/// Write the given raw message in the `cur` directory of the given
+/// maildir, using a unique file name.
+pub fn store_file(mdir: &Path, raw_msg: &[u8], flags: &Flags) -> Result<PathBuf> {
+    for dir in ["cur", "new", "tmp"] {
+        let dir = mdir.join(dir);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("cannot create maildir directory {dir:?}"))?;
+    }
+
+    let mut mdir_flags: Vec<char> = flags
+        .iter()
+        .filter_map(|flag| match flag {
+            Flag::Seen => Some('S'),
+            Flag::Answered => Some('R'),
+            Flag::Flagged => Some('F'),
+            Flag::Draft => Some('D'),
+            Flag::Deleted => Some('T'),
+            Flag::Custom(_) => None,
+        })
+        .collect();
+    mdir_flags.sort_unstable();
+
+    let name = format!(
+        "{}.{}.himalaya:2,{}",
+        chrono::Utc::now().timestamp(),
+        uuid::Uuid::new_v4(),
+        mdir_flags.into_iter().collect::<String>(),
+    );
+
+    let tmp_path = mdir.join("tmp").join(&name);
+    let path = mdir.join("cur").join(&name);
+
+    fs::write(&tmp_path, raw_msg)
+        .with_context(|| format!("cannot write notmuch message at {tmp_path:?}"))?;
+    fs::rename(&tmp_path, &path)
+        .with_context(|| format!("cannot move notmuch message to {path:?}"))?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use email::flag::{Flag, Flags};
+    use std::collections::BTreeSet;
+
+    use super::{flag_to_tag, flags_from_tag_names, replace_flag_tags, tag_to_flag, NotmuchFolder};
+
+    fn tags(tags: &[&str]) -> BTreeSet<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_folder() {
+        assert_eq!(
+            NotmuchFolder::parse("Sent"),
+            NotmuchFolder::Maildir("Sent".into())
+        );
+        assert_eq!(
+            NotmuchFolder::parse("folder:\"Archives\""),
+            NotmuchFolder::Maildir("Archives".into())
+        );
+        assert_eq!(
+            NotmuchFolder::parse("tag:sent"),
+            NotmuchFolder::Tag("sent".into())
+        );
+        assert_eq!(
+            NotmuchFolder::parse("tag:work and not tag:archived"),
+            NotmuchFolder::Query("tag:work and not tag:archived".into())
+        );
+        assert_eq!(
+            NotmuchFolder::parse("from:alice@localhost"),
+            NotmuchFolder::Query("from:alice@localhost".into())
+        );
+    }
+
+    #[test]
+    fn map_tags_to_flags() {
+        for flag in [Flag::Answered, Flag::Flagged, Flag::Draft, Flag::Deleted] {
+            let tag = flag_to_tag(&flag).unwrap();
+            assert_eq!(tag_to_flag(tag), flag);
+        }
+        assert_eq!(flag_to_tag(&Flag::Seen), None);
+        assert_eq!(tag_to_flag("inbox"), Flag::Custom("inbox".into()));
+
+        assert_eq!(
+            flags_from_tag_names(["replied", "flagged", "inbox"]),
+            Flags::from_iter([
+                Flag::Seen,
+                Flag::Answered,
+                Flag::Flagged,
+                Flag::Custom("inbox".into()),
+            ])
+        );
+        assert_eq!(
+            flags_from_tag_names(["unread", "draft"]),
+            Flags::from_iter([Flag::Draft])
+        );
+    }
+
+    #[test]
+    fn set_flags_keeps_custom_tags() {
+        let prev = ["inbox", "unread", "flagged", "work"];
+
+        assert_eq!(
+            replace_flag_tags(prev, &Flags::from_iter([Flag::Seen, Flag::Answered])),
+            tags(&["inbox", "replied", "work"])
+        );
+        assert_eq!(
+            replace_flag_tags(prev, &Flags::default()),
+            tags(&["inbox", "unread", "work"])
+        );
+        assert_eq!(
+            replace_flag_tags(prev, &Flags::from_iter([Flag::Custom("todo".into())])),
+            tags(&["inbox", "todo", "unread", "work"])
+        );
+    }
+}
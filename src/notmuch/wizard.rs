@@ -1,13 +1,25 @@
 use anyhow::Result;
 use dialoguer::Input;
-use email::backend::{BackendConfig, NotmuchBackend, NotmuchConfig};
+use email::notmuch::NotmuchConfig;
+use notmuch::{Database, DatabaseMode};
+use std::path::PathBuf;
 
-use crate::config::wizard::THEME;
+use crate::{backend::config::BackendConfig, ui::THEME};
 
 pub(crate) fn configure() -> Result<BackendConfig> {
     let mut config = NotmuchConfig::default();
 
-    config.db_path = if let Ok(db_path) = NotmuchBackend::get_default_db_path() {
+    // the default database path comes from the notmuch user
+    // configuration file
+    let default_db_path = Database::open_with_config(
+        None as Option<PathBuf>,
+        DatabaseMode::ReadOnly,
+        None as Option<PathBuf>,
+        None,
+    )
+    .map(|db| db.path().to_owned());
+
+    config.db_path = if let Ok(db_path) = default_db_path {
         db_path
     } else {
         let db_path: String = Input::with_theme(&*THEME)
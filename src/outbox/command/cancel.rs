@@ -0,0 +1,51 @@
+use anyhow::Result;
+use clap::Parser;
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag, config::TomlConfig, outbox::schedule::Schedule,
+    printer::Printer,
+};
+
+/// Cancel scheduled messages.
+///
+/// The given scheduled messages are removed from the schedule without
+/// being sent.
+#[derive(Debug, Parser)]
+pub struct OutboxCancelCommand {
+    /// The ids of the scheduled messages to cancel.
+    #[arg(value_name = "ID", required = true)]
+    pub ids: Vec<String>,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl OutboxCancelCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing outbox cancel command");
+
+        let (_, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let schedule = Schedule::open(&account_config)?;
+
+        for id in &self.ids {
+            schedule.remove(id)?;
+        }
+
+        printer.print(format!(
+            "{} scheduled message(s) successfully cancelled!",
+            self.ids.len()
+        ))
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::Result;
+use clap::Parser;
+use dialoguer::Confirm;
+use log::info;
+use std::process;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag, config::TomlConfig, outbox::Outbox, printer::Printer,
+};
+
+/// Drop messages from the outbox.
+///
+/// The given messages are removed from the outbox without being
+/// sent, or all the messages of the outbox if no id is given.
+#[derive(Debug, Parser)]
+pub struct OutboxDropCommand {
+    /// The outbox ids of the messages to drop.
+    #[arg(value_name = "ID")]
+    pub ids: Vec<String>,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl OutboxDropCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing outbox drop command");
+
+        let (_, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let outbox = Outbox::open(&account_config).await?;
+
+        let ids = if self.ids.is_empty() {
+            let account = &account_config.name;
+            let confirm = Confirm::new()
+                .with_prompt(format!("Do you really want to drop all the messages of the outbox of the account {account}? They will never be sent."))
+                .default(false)
+                .report(false)
+                .interact_opt()?;
+            if let Some(false) | None = confirm {
+                process::exit(0);
+            };
+
+            outbox
+                .list()
+                .await?
+                .iter()
+                .map(|msg| msg.id.clone())
+                .collect()
+        } else {
+            self.ids
+        };
+
+        for id in &ids {
+            outbox.remove(id).await?;
+        }
+
+        printer.print(format!("{} message(s) successfully dropped!", ids.len()))
+    }
+}
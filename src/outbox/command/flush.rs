@@ -0,0 +1,95 @@
+use anyhow::Result;
+use clap::Parser;
+use log::{debug, info, warn};
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag,
+    config::TomlConfig,
+    outbox::{self, Outbox},
+    printer::Printer,
+};
+
+/// Send the messages of the outbox.
+///
+/// This command tries to send again the given messages of the outbox,
+/// or all of them if no id is given, using the configured send
+/// backend. A copy of each message is saved to the sent folder once
+/// it has been delivered. Messages that still cannot be delivered are
+/// kept in the outbox.
+#[derive(Debug, Parser)]
+pub struct OutboxFlushCommand {
+    /// The outbox ids of the messages to send.
+    #[arg(value_name = "ID")]
+    pub ids: Vec<String>,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl OutboxFlushCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing outbox flush command");
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let outbox = Outbox::open(&account_config).await?;
+
+        let ids: Vec<String> = if self.ids.is_empty() {
+            outbox
+                .list()
+                .await?
+                .iter()
+                .map(|msg| msg.id.clone())
+                .collect()
+        } else {
+            self.ids
+        };
+
+        if ids.is_empty() {
+            return printer.print("Outbox is empty, nothing to send!");
+        }
+
+        let backend = outbox::build_send_backend(&toml_account_config, &account_config).await?;
+        let backend = &backend;
+        let delivered = outbox
+            .flush(
+                &ids,
+                |msg| async move { backend.deliver_message(&msg).await },
+            )
+            .await?;
+
+        if !delivered.is_empty() {
+            match outbox::build_add_backend(&toml_account_config, &account_config).await {
+                Ok(Some(backend)) => {
+                    for msg in &delivered {
+                        if let Err(err) = backend.save_copy_sent_message(msg).await {
+                            warn!("cannot save copy of sent outbox message: {err}");
+                            debug!("{err:?}");
+                        }
+                    }
+                }
+                Ok(None) => (),
+                Err(err) => {
+                    warn!("cannot save copies of sent outbox messages: {err}");
+                    debug!("{err:?}");
+                }
+            }
+        }
+
+        let sent = delivered.len();
+        let queued = ids.len() - sent;
+        printer.print(format!(
+            "{sent} message(s) successfully sent, {queued} message(s) still queued!"
+        ))
+    }
+}
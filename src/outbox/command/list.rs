@@ -0,0 +1,59 @@
+use anyhow::Result;
+use clap::Parser;
+use log::info;
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag,
+    config::TomlConfig,
+    outbox::{schedule::Schedule, Outbox},
+    printer::{PrintTableOpts, Printer},
+    ui::arg::max_width::TableMaxWidthFlag,
+};
+
+/// List all messages of the outbox.
+///
+/// This command allows you to list the messages waiting in the
+/// outbox, oldest first, or the scheduled messages by due date.
+#[derive(Debug, Parser)]
+pub struct OutboxListCommand {
+    /// List scheduled messages instead of queued ones.
+    #[arg(long, short)]
+    pub scheduled: bool,
+
+    #[command(flatten)]
+    pub table: TableMaxWidthFlag,
+
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl OutboxListCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing outbox list command");
+
+        let (_, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let opts = PrintTableOpts {
+            format: &account_config.get_message_read_format(),
+            max_width: self.table.max_width,
+        };
+
+        if self.scheduled {
+            let msgs = Schedule::open(&account_config)?.list()?;
+            printer.print_table(Box::new(msgs), opts)
+        } else {
+            let msgs = Outbox::open(&account_config).await?.list().await?;
+            printer.print_table(Box::new(msgs), opts)
+        }
+    }
+}
@@ -0,0 +1,74 @@
+#[cfg(feature = "outbox-cancel")]
+mod cancel;
+#[cfg(feature = "outbox-drop")]
+mod drop;
+#[cfg(feature = "outbox-flush")]
+mod flush;
+#[cfg(feature = "outbox-list")]
+mod list;
+#[cfg(feature = "outbox-run")]
+mod run;
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use crate::{config::TomlConfig, printer::Printer};
+
+#[cfg(feature = "outbox-cancel")]
+use self::cancel::OutboxCancelCommand;
+#[cfg(feature = "outbox-drop")]
+use self::drop::OutboxDropCommand;
+#[cfg(feature = "outbox-flush")]
+use self::flush::OutboxFlushCommand;
+#[cfg(feature = "outbox-list")]
+use self::list::OutboxListCommand;
+#[cfg(feature = "outbox-run")]
+use self::run::OutboxRunCommand;
+
+/// Manage the outbox.
+///
+/// Messages that cannot be delivered are queued in a local outbox
+/// instead of being lost, and scheduled messages wait for their due
+/// date. This subcommand allows you to inspect the outbox, to send
+/// its messages again or to drop them, as well as to send or cancel
+/// scheduled messages.
+#[derive(Debug, Subcommand)]
+pub enum OutboxSubcommand {
+    #[cfg(feature = "outbox-list")]
+    #[command(alias = "lst")]
+    List(OutboxListCommand),
+
+    #[cfg(feature = "outbox-flush")]
+    #[command(alias = "send")]
+    Flush(OutboxFlushCommand),
+
+    #[cfg(feature = "outbox-drop")]
+    #[command(alias = "delete", alias = "remove", alias = "rm")]
+    Drop(OutboxDropCommand),
+
+    #[cfg(feature = "outbox-run")]
+    #[command()]
+    Run(OutboxRunCommand),
+
+    #[cfg(feature = "outbox-cancel")]
+    #[command(alias = "unschedule")]
+    Cancel(OutboxCancelCommand),
+}
+
+impl OutboxSubcommand {
+    #[allow(unused)]
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        match self {
+            #[cfg(feature = "outbox-list")]
+            Self::List(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "outbox-flush")]
+            Self::Flush(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "outbox-drop")]
+            Self::Drop(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "outbox-run")]
+            Self::Run(cmd) => cmd.execute(printer, config).await,
+            #[cfg(feature = "outbox-cancel")]
+            Self::Cancel(cmd) => cmd.execute(printer, config).await,
+        }
+    }
+}
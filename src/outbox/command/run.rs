@@ -0,0 +1,113 @@
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use log::{debug, info, warn};
+
+#[cfg(feature = "account-sync")]
+use crate::cache::arg::disable::CacheDisableFlag;
+use crate::{
+    account::arg::name::AccountNameFlag,
+    config::TomlConfig,
+    outbox::{self, schedule::Schedule, Outbox},
+    printer::Printer,
+};
+
+/// Send the scheduled messages that are due.
+///
+/// This command sends the scheduled messages whose due date has
+/// passed, using the configured send backend. Messages that cannot
+/// be delivered are queued in the outbox. This command is meant to be
+/// run periodically, for example by a cron job or a systemd timer.
+/// Each message is claimed before being sent, so that overlapping
+/// runs never send the same message twice.
+#[derive(Debug, Parser)]
+pub struct OutboxRunCommand {
+    #[cfg(feature = "account-sync")]
+    #[command(flatten)]
+    pub cache: CacheDisableFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl OutboxRunCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing outbox run command");
+
+        let (toml_account_config, account_config) = config.clone().into_account_configs(
+            self.account.name.as_deref(),
+            #[cfg(feature = "account-sync")]
+            self.cache.disable,
+        )?;
+
+        let schedule = Schedule::open(&account_config)?;
+        let ids: Vec<String> = schedule
+            .list()?
+            .iter()
+            .filter(|msg| msg.is_due())
+            .map(|msg| msg.id.clone())
+            .collect();
+
+        if ids.is_empty() {
+            return printer.print("No scheduled message is due, nothing to send!");
+        }
+
+        let outbox = Outbox::open(&account_config).await?;
+        let send_backend = outbox::build_send_backend(&toml_account_config, &account_config).await;
+        let add_backend = outbox::build_add_backend(&toml_account_config, &account_config)
+            .await
+            .unwrap_or_else(|err| {
+                warn!("cannot save copies of sent scheduled messages: {err}");
+                debug!("{err:?}");
+                None
+            });
+
+        let mut sent = 0;
+        let mut queued = 0;
+
+        for id in &ids {
+            // the message may have been claimed by another run
+            // meanwhile, in which case it is left to it
+            let Some(msg) = schedule.claim(id)? else {
+                debug!("scheduled message {id} already claimed, skipping it");
+                continue;
+            };
+
+            let delivery = match &send_backend {
+                Ok(backend) => backend.deliver_message(&msg.raw_msg).await,
+                Err(err) => Err(anyhow!("{err:#}")),
+            };
+
+            if let Err(err) = delivery {
+                // undeliverable messages are moved to the outbox, so
+                // they are not sent again at every run
+                warn!("cannot send scheduled message {id}, queuing it to the outbox: {err}");
+                debug!("{err:?}");
+
+                if let Err(err) = outbox.add(&msg.raw_msg).await {
+                    msg.release()?;
+                    return Err(err);
+                }
+
+                msg.remove()?;
+                queued += 1;
+                continue;
+            }
+
+            // the message is removed before saving its copy, so that
+            // it cannot be sent twice
+            msg.remove()?;
+            sent += 1;
+
+            if let Some(backend) = &add_backend {
+                if let Err(err) = backend.save_copy_sent_message(&msg.raw_msg).await {
+                    warn!("cannot save copy of sent scheduled message {id}: {err}");
+                    debug!("{err:?}");
+                }
+            }
+        }
+
+        printer.print(format!(
+            "{sent} message(s) successfully sent, {queued} message(s) queued to the outbox!"
+        ))
+    }
+}
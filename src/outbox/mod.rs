@@ -0,0 +1,583 @@
+//! Module dedicated to the outbox.
+//!
+//! The outbox is a per-account Maildir where messages are queued when
+//! they cannot be delivered (or when their delivery is explicitly
+//! deferred). Queued messages are then sent again with the `outbox
+//! flush` command, using the configured send backend.
+
+pub mod command;
+pub mod schedule;
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+use chrono::{DateTime, Local};
+#[cfg(feature = "imap")]
+use email::message::add::imap::AddImapMessage;
+#[cfg(feature = "sendmail")]
+use email::message::send::sendmail::SendMessageSendmail;
+#[cfg(feature = "smtp")]
+use email::message::send::smtp::SendMessageSmtp;
+use email::{
+    account::config::AccountConfig,
+    backend::BackendContextBuilder,
+    maildir::{config::MaildirConfig, MaildirSessionBuilder, MaildirSessionSync},
+    message::add::maildir::AddMaildirMessage,
+};
+use log::{debug, warn};
+use serde::Serialize;
+use std::{
+    fmt, fs,
+    future::Future,
+    io, ops,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "jmap")]
+use crate::jmap::{AddMessageJmap, SendMessageJmap};
+#[cfg(feature = "mbox")]
+use crate::mbox::AddMessageMbox;
+#[cfg(feature = "notmuch")]
+use crate::notmuch::AddMessageNotmuch;
+use crate::{
+    account::config::TomlAccountConfig,
+    backend::{Backend, BackendKind},
+    message::summary,
+    printer::{PrintTable, PrintTableOpts, WriteColor},
+    ui::{Cell, Row, Table},
+};
+
+/// Return the outbox directory of the given account.
+///
+/// The outbox is not part of the synchronization Maildir, otherwise
+/// queued messages would be synchronized with the remote backend.
+pub fn dir(account_config: &AccountConfig) -> Result<PathBuf> {
+    let dir = dirs::data_dir()
+        .ok_or_else(|| anyhow!("cannot find user data directory"))?
+        .join("himalaya")
+        .join("outbox")
+        .join(&account_config.name);
+
+    Ok(dir)
+}
+
+/// The outbox of an account.
+pub struct Outbox {
+    session: MaildirSessionSync,
+}
+
+impl Outbox {
+    /// Open the outbox of the given account, creating its Maildir
+    /// structure if needed.
+    pub async fn open(account_config: &AccountConfig) -> Result<Self> {
+        Self::open_dir(account_config, dir(account_config)?).await
+    }
+
+    /// Open the outbox located at the given directory, creating its
+    /// Maildir structure if needed.
+    pub async fn open_dir(account_config: &AccountConfig, root_dir: PathBuf) -> Result<Self> {
+        debug!("opening outbox at {root_dir:?}…");
+
+        fs::create_dir_all(&root_dir)
+            .with_context(|| format!("cannot create outbox directory at {root_dir:?}"))?;
+
+        let mdir_config = MaildirConfig { root_dir };
+        let session = MaildirSessionBuilder::new(account_config.clone(), mdir_config)
+            .build()
+            .await?;
+
+        Ok(Self { session })
+    }
+
+    /// Queue the given raw message, and return its outbox id.
+    pub async fn add(&self, raw_msg: &[u8]) -> Result<String> {
+        let mdir = self.session.lock().await;
+        let id = mdir
+            .store_cur_with_flags(raw_msg, "")
+            .context("cannot add message to the outbox")?;
+        debug!("message queued to the outbox with id {id}");
+        Ok(id)
+    }
+
+    /// Queue the given raw message if it could not be delivered
+    /// because of a transient error, see [`is_transient`].
+    ///
+    /// The returned error is the one to report to the user: when the
+    /// message has been queued, it mentions its outbox id.
+    pub async fn queue_undelivered(&self, raw_msg: &[u8], err: Error) -> Error {
+        if !is_transient(&err) {
+            return err;
+        }
+
+        warn!("cannot send message, queuing it to the outbox: {err}");
+        debug!("{err:?}");
+
+        match self.add(raw_msg).await {
+            Ok(id) => err.context(Queued { id }),
+            Err(queue_err) => {
+                warn!("cannot queue message to the outbox: {queue_err}");
+                debug!("{queue_err:?}");
+                err
+            }
+        }
+    }
+
+    /// Send again the queued messages matching the given outbox ids
+    /// using the given delivery function, and return the raw
+    /// messages that have been delivered.
+    ///
+    /// Delivered messages are removed from the outbox before being
+    /// returned, so that they cannot be sent twice. Messages that
+    /// still cannot be delivered are kept.
+    pub async fn flush<F, Fut>(&self, ids: &[String], deliver: F) -> Result<Vec<Vec<u8>>>
+    where
+        F: Fn(Vec<u8>) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let mut delivered = Vec::new();
+
+        for id in ids {
+            let msg = self.get(id).await?;
+
+            if let Err(err) = deliver(msg.clone()).await {
+                warn!("cannot send outbox message {id}, keeping it: {err}");
+                debug!("{err:?}");
+                continue;
+            }
+
+            self.remove(id).await?;
+            delivered.push(msg);
+        }
+
+        Ok(delivered)
+    }
+
+    /// List the queued messages, oldest first.
+    pub async fn list(&self) -> Result<OutboxMessages> {
+        let mdir = self.session.lock().await;
+
+        let mut msgs: Vec<OutboxMessage> = mdir
+            .list_cur()
+            .filter_map(|entry| match entry {
+                Ok(entry) => Some(OutboxMessage::from_path(entry.id(), entry.path())),
+                Err(err) => {
+                    debug!("skipping invalid outbox entry: {err}");
+                    None
+                }
+            })
+            .collect::<Result<_>>()?;
+        msgs.sort_by_key(|msg| msg.queued_at);
+
+        Ok(OutboxMessages(msgs))
+    }
+
+    /// Return the raw message matching the given outbox id.
+    pub async fn get(&self, id: &str) -> Result<Vec<u8>> {
+        let mdir = self.session.lock().await;
+        let Some(entry) = mdir.find(id) else {
+            bail!("cannot find message {id} in the outbox");
+        };
+
+        fs::read(entry.path()).with_context(|| format!("cannot read outbox message {id}"))
+    }
+
+    /// Remove the message matching the given outbox id.
+    pub async fn remove(&self, id: &str) -> Result<()> {
+        let mdir = self.session.lock().await;
+        mdir.delete(id)
+            .with_context(|| format!("cannot remove message {id} from the outbox"))?;
+        debug!("message {id} removed from the outbox");
+        Ok(())
+    }
+}
+
+/// A message waiting in the outbox.
+#[derive(Clone, Debug, Serialize)]
+pub struct OutboxMessage {
+    pub id: String,
+    #[serde(skip)]
+    pub queued_at: DateTime<Local>,
+    pub date: String,
+    pub to: String,
+    pub subject: String,
+}
+
+impl OutboxMessage {
+    fn from_path(id: &str, path: &Path) -> Result<Self> {
+        let raw_msg = fs::read(path).with_context(|| format!("cannot read outbox message {id}"))?;
+        let queued_at: DateTime<Local> = fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .with_context(|| format!("cannot get queue date of outbox message {id}"))?
+            .into();
+
+        let (to, subject) = summary(&raw_msg);
+
+        Ok(Self {
+            id: id.to_owned(),
+            queued_at,
+            date: queued_at.format("%Y-%m-%d %H:%M").to_string(),
+            to,
+            subject,
+        })
+    }
+}
+
+impl Table for OutboxMessage {
+    fn head() -> Row {
+        Row::new()
+            .cell(Cell::new("ID").bold().underline().white())
+            .cell(Cell::new("QUEUED").bold().underline().white())
+            .cell(Cell::new("TO").bold().underline().white())
+            .cell(Cell::new("SUBJECT").shrinkable().bold().underline().white())
+    }
+
+    fn row(&self) -> Row {
+        Row::new()
+            .cell(Cell::new(&self.id).red())
+            .cell(Cell::new(&self.date).yellow())
+            .cell(Cell::new(&self.to).blue())
+            .cell(Cell::new(&self.subject).shrinkable().green())
+    }
+}
+
+/// The list of messages waiting in the outbox.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct OutboxMessages(Vec<OutboxMessage>);
+
+impl ops::Deref for OutboxMessages {
+    type Target = Vec<OutboxMessage>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl PrintTable for OutboxMessages {
+    fn print_table(&self, writer: &mut dyn WriteColor, opts: PrintTableOpts) -> Result<()> {
+        writeln!(writer)?;
+        Table::print(writer, self, opts)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+/// The context of a delivery error, when the undelivered message has
+/// been queued in the outbox.
+#[derive(Debug)]
+struct Queued {
+    id: String,
+}
+
+impl fmt::Display for Queued {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "message queued to the outbox as {}, run `outbox flush` to send it again",
+            self.id
+        )
+    }
+}
+
+/// Return `true` if the given delivery error means that the message
+/// has been queued in the outbox.
+pub fn is_queued(err: &Error) -> bool {
+    err.downcast_ref::<Queued>().is_some()
+}
+
+/// Return `true` if the given delivery error is transient, in which
+/// case the message can be queued and sent again later.
+///
+/// Connection failures, timeouts and temporary server replies are
+/// transient. Rejections, authentication and configuration errors are
+/// not: sending the message again would fail the same way.
+pub fn is_transient(err: &Error) -> bool {
+    err.chain().any(|err| {
+        if let Some(err) = err.downcast_ref::<io::Error>() {
+            return is_transient_io_error(err);
+        }
+
+        #[cfg(feature = "smtp")]
+        if let Some(err) = err.downcast_ref::<email::smtp::Error>() {
+            return is_transient_smtp_error(err);
+        }
+
+        #[cfg(feature = "jmap")]
+        if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+            return err.is_connect()
+                || err.is_timeout()
+                || err.status().is_some_and(|status| status.is_server_error());
+        }
+
+        false
+    })
+}
+
+fn is_transient_io_error(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::AddrNotAvailable
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+#[cfg(feature = "smtp")]
+fn is_transient_smtp_error(err: &email::smtp::Error) -> bool {
+    use email::smtp::Error;
+
+    let err = match err {
+        Error::ConnectTcpError(err) | Error::ConnectTlsError(err) | Error::SendEmailError(err) => {
+            // the underlying SMTP error type is not exposed by the
+            // library, so its variant is read from its debug form
+            format!("{err:?}")
+        }
+        _ => return false,
+    };
+
+    err.starts_with("Io(")
+        || err.starts_with("Timeout")
+        || err.starts_with("UnexpectedReply(Response { code: 4")
+}
+
+/// Build the backend used to deliver messages of the given account.
+///
+/// It is built on its own, so that a failure of the backend saving
+/// copies of sent messages is not taken for a delivery failure.
+pub async fn build_send_backend(
+    toml_account_config: &TomlAccountConfig,
+    account_config: &AccountConfig,
+) -> Result<Backend> {
+    let send_message_kind = toml_account_config.send_message_kind();
+
+    Backend::new(
+        toml_account_config,
+        account_config,
+        send_message_kind,
+        |#[allow(unused)] builder| match send_message_kind {
+            #[cfg(feature = "smtp")]
+            Some(BackendKind::Smtp) => {
+                builder.set_send_message(|ctx| ctx.smtp.as_ref().and_then(SendMessageSmtp::new));
+            }
+            #[cfg(feature = "jmap")]
+            Some(BackendKind::Jmap) => {
+                builder.set_send_message(|ctx| ctx.jmap.as_ref().and_then(SendMessageJmap::new));
+            }
+            #[cfg(feature = "sendmail")]
+            Some(BackendKind::Sendmail) => {
+                builder.set_send_message(|ctx| {
+                    ctx.sendmail.as_ref().and_then(SendMessageSendmail::new)
+                });
+            }
+            _ => (),
+        },
+    )
+    .await
+}
+
+/// Build the backend used to save copies of sent messages of the
+/// given account, or `None` if copies should not be saved.
+pub async fn build_add_backend(
+    toml_account_config: &TomlAccountConfig,
+    account_config: &AccountConfig,
+) -> Result<Option<Backend>> {
+    let Some(add_message_kind) = toml_account_config
+        .add_message_kind()
+        .filter(|_| account_config.should_save_copy_sent_message())
+    else {
+        return Ok(None);
+    };
+
+    let backend = Backend::new(
+        toml_account_config,
+        account_config,
+        Some(add_message_kind),
+        |builder| match add_message_kind {
+            #[cfg(feature = "imap")]
+            BackendKind::Imap => {
+                builder.set_add_message(|ctx| ctx.imap.as_ref().and_then(AddImapMessage::new));
+            }
+            #[cfg(feature = "jmap")]
+            BackendKind::Jmap => {
+                builder.set_add_message(|ctx| ctx.jmap.as_ref().and_then(AddMessageJmap::new));
+            }
+            BackendKind::Maildir => {
+                builder
+                    .set_add_message(|ctx| ctx.maildir.as_ref().and_then(AddMaildirMessage::new));
+            }
+            #[cfg(feature = "account-sync")]
+            BackendKind::MaildirForSync => {
+                builder.set_add_message(|ctx| {
+                    ctx.maildir_for_sync
+                        .as_ref()
+                        .and_then(AddMaildirMessage::new)
+                });
+            }
+            #[cfg(feature = "mbox")]
+            BackendKind::Mbox => {
+                builder.set_add_message(|ctx| ctx.mbox.as_ref().and_then(AddMessageMbox::new));
+            }
+            #[cfg(feature = "notmuch")]
+            BackendKind::Notmuch => {
+                builder
+                    .set_add_message(|ctx| ctx.notmuch.as_ref().and_then(AddMessageNotmuch::new));
+            }
+            _ => (),
+        },
+    )
+    .await?;
+
+    Ok(Some(backend))
+}
+
+/// Send the given raw message, or queue it in the outbox of the
+/// account if it cannot be delivered for now.
+///
+/// The message is queued only when its delivery failed because of a
+/// transient error (see [`is_transient`]), including when the send
+/// backend cannot be reached. An error is returned whenever the
+/// message has not been delivered, even if it has been queued, so
+/// that the command fails.
+///
+/// The copy of the message is only saved to the sent folder once it
+/// has been delivered.
+pub async fn send_or_queue(
+    toml_account_config: &TomlAccountConfig,
+    account_config: &AccountConfig,
+    raw_msg: &[u8],
+) -> Result<()> {
+    let delivery = match build_send_backend(toml_account_config, account_config).await {
+        Ok(backend) => backend.deliver_message(raw_msg).await,
+        Err(err) => Err(err),
+    };
+
+    if let Err(err) = delivery {
+        return Err(queue_undelivered(account_config, raw_msg, err).await);
+    }
+
+    if let Some(backend) = build_add_backend(toml_account_config, account_config)
+        .await
+        .context("message sent, but cannot save a copy to the sent folder")?
+    {
+        backend
+            .save_copy_sent_message(raw_msg)
+            .await
+            .context("message sent, but cannot save a copy to the sent folder")?;
+    }
+
+    Ok(())
+}
+
+/// Same as [`send_or_queue`], using the given backend to deliver the
+/// message and to save its copy.
+pub async fn send_or_queue_with(backend: &Backend, raw_msg: &[u8]) -> Result<()> {
+    if let Err(err) = backend.deliver_message(raw_msg).await {
+        return Err(queue_undelivered(&backend.account_config, raw_msg, err).await);
+    }
+
+    backend
+        .save_copy_sent_message(raw_msg)
+        .await
+        .context("message sent, but cannot save a copy to the sent folder")
+}
+
+async fn queue_undelivered(account_config: &AccountConfig, raw_msg: &[u8], err: Error) -> Error {
+    match Outbox::open(account_config).await {
+        Ok(outbox) => outbox.queue_undelivered(raw_msg, err).await,
+        Err(outbox_err) => {
+            warn!("cannot open outbox: {outbox_err}");
+            debug!("{outbox_err:?}");
+            err
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{anyhow, Error};
+    use email::account::config::AccountConfig;
+    use std::io;
+
+    use super::{is_queued, is_transient, Outbox};
+
+    const MSG_1: &[u8] = b"To: alice@localhost\r\nSubject: first\r\n\r\nHello!\r\n";
+    const MSG_2: &[u8] = b"To: bob@localhost\r\nSubject: second\r\n\r\nHello!\r\n";
+
+    async fn open_outbox(dir: &tempfile::TempDir) -> Outbox {
+        let account_config = AccountConfig {
+            name: "account".into(),
+            ..Default::default()
+        };
+
+        Outbox::open_dir(&account_config, dir.path().to_owned())
+            .await
+            .unwrap()
+    }
+
+    fn connection_refused() -> Error {
+        Error::new(io::Error::from(io::ErrorKind::ConnectionRefused))
+            .context("cannot connect to smtp server")
+    }
+
+    #[test]
+    fn transient_errors() {
+        assert!(is_transient(&connection_refused()));
+        assert!(is_transient(
+            &Error::new(io::Error::from(io::ErrorKind::TimedOut)).context("cannot send message")
+        ));
+
+        assert!(!is_transient(&anyhow!("550 mailbox unavailable")));
+        assert!(!is_transient(&Error::new(io::Error::from(
+            io::ErrorKind::PermissionDenied
+        ))));
+    }
+
+    #[tokio::test]
+    async fn queue_transient_errors_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = open_outbox(&dir).await;
+
+        let err = outbox.queue_undelivered(MSG_1, anyhow!("rejected")).await;
+        assert!(!is_queued(&err));
+        assert_eq!(err.to_string(), "rejected");
+        assert!(outbox.list().await.unwrap().is_empty());
+
+        let err = outbox.queue_undelivered(MSG_1, connection_refused()).await;
+        assert!(is_queued(&err));
+
+        let msgs = outbox.list().await.unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert!(err.to_string().contains(&msgs[0].id));
+        assert_eq!(msgs[0].to, "alice@localhost");
+        assert_eq!(msgs[0].subject, "first");
+        assert_eq!(outbox.get(&msgs[0].id).await.unwrap(), MSG_1);
+    }
+
+    #[tokio::test]
+    async fn flush_delivered_messages_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = open_outbox(&dir).await;
+
+        let id_1 = outbox.add(MSG_1).await.unwrap();
+        let id_2 = outbox.add(MSG_2).await.unwrap();
+
+        let delivered = outbox
+            .flush(&[id_1.clone(), id_2.clone()], |msg| async move {
+                if msg == MSG_1 {
+                    Ok(())
+                } else {
+                    Err(connection_refused())
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(delivered, vec![MSG_1.to_vec()]);
+
+        let msgs = outbox.list().await.unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].id, id_2);
+        assert!(outbox.get(&id_1).await.is_err());
+    }
+}
@@ -0,0 +1,374 @@
+//! Module dedicated to scheduled messages.
+//!
+//! Scheduled messages are compiled messages waiting for their due
+//! time. They are stored as files in a per-account directory, named
+//! after their due time and their id, so that the schedule survives
+//! restarts. Due messages are sent by the `outbox run` command.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use email::account::config::AccountConfig;
+use log::debug;
+use serde::Serialize;
+use std::{
+    fs, io, ops,
+    path::{Path, PathBuf},
+};
+use uuid::Uuid;
+
+use crate::{
+    message::summary,
+    printer::{PrintTable, PrintTableOpts, WriteColor},
+    ui::{Cell, Row, Table},
+};
+
+const SCHEDULED_MSG_EXT: &str = "eml";
+const CLAIMED_MSG_EXT: &str = "sending";
+
+/// The date formats accepted when scheduling a message, in local
+/// time.
+const SEND_AT_FMTS: &[&str] = &[
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+];
+
+/// Parse the given date at which a message should be sent.
+///
+/// Dates are expressed in local time (`2026-10-20 09:00`), or in
+/// RFC 3339 (`2026-10-20T09:00:00+02:00`).
+pub fn parse_send_at(date: &str) -> Result<DateTime<Local>> {
+    let date = date.trim();
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Ok(date.with_timezone(&Local));
+    }
+
+    SEND_AT_FMTS
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(date, fmt).ok())
+        .and_then(|date| Local.from_local_datetime(&date).earliest())
+        .ok_or_else(|| anyhow!("cannot parse date {date}, expected YYYY-MM-DD HH:MM"))
+}
+
+/// Return the scheduled messages directory of the given account.
+pub fn dir(account_config: &AccountConfig) -> Result<PathBuf> {
+    let dir = dirs::data_dir()
+        .ok_or_else(|| anyhow!("cannot find user data directory"))?
+        .join("himalaya")
+        .join("scheduled")
+        .join(&account_config.name);
+
+    Ok(dir)
+}
+
+/// The queue of the scheduled messages of an account.
+pub struct Schedule {
+    dir: PathBuf,
+}
+
+impl Schedule {
+    /// Open the schedule of the given account, creating its
+    /// directory if needed.
+    pub fn open(account_config: &AccountConfig) -> Result<Self> {
+        let dir = dir(account_config)?;
+        debug!("opening schedule at {dir:?}…");
+
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("cannot create schedule directory at {dir:?}"))?;
+
+        Ok(Self { dir })
+    }
+
+    /// Schedule the given raw message at the given date, and return
+    /// its id.
+    pub fn add(&self, raw_msg: &[u8], send_at: &DateTime<Local>) -> Result<String> {
+        if *send_at <= Local::now() {
+            bail!("cannot schedule message at {send_at}: date is in the past");
+        }
+
+        let id = loop {
+            let id = Uuid::new_v4().to_simple().to_string()[..8].to_owned();
+            if self.find(&id)?.is_none() {
+                break id;
+            }
+        };
+
+        let name = file_name(&id, send_at);
+        let tmp_path = self.dir.join(format!(".{name}.tmp"));
+
+        // the message is written to a temporary file first, so that
+        // an interrupted write does not leave a partial message
+        fs::write(&tmp_path, raw_msg)
+            .with_context(|| format!("cannot write scheduled message at {tmp_path:?}"))?;
+        fs::rename(&tmp_path, self.dir.join(&name))
+            .with_context(|| format!("cannot save scheduled message {id}"))?;
+
+        debug!("message {id} scheduled at {send_at}");
+        Ok(id)
+    }
+
+    /// List the scheduled messages, by due date.
+    pub fn list(&self) -> Result<ScheduledMessages> {
+        let entries = fs::read_dir(&self.dir)
+            .with_context(|| format!("cannot read schedule directory at {:?}", self.dir))?;
+
+        let mut msgs = Vec::new();
+
+        for entry in entries {
+            let path = entry.context("cannot read schedule entry")?.path();
+            let Some((id, send_at)) = parse_file_name(&path) else {
+                debug!("skipping invalid schedule entry {path:?}");
+                continue;
+            };
+
+            let raw_msg =
+                fs::read(&path).with_context(|| format!("cannot read scheduled message {id}"))?;
+            let (to, subject) = summary(&raw_msg);
+
+            msgs.push(ScheduledMessage {
+                id,
+                send_at,
+                date: send_at.format("%Y-%m-%d %H:%M").to_string(),
+                to,
+                subject,
+            });
+        }
+
+        msgs.sort_by_key(|msg| msg.send_at);
+
+        Ok(ScheduledMessages(msgs))
+    }
+
+    /// Return the raw message matching the given id.
+    pub fn get(&self, id: &str) -> Result<Vec<u8>> {
+        let path = self.path(id)?;
+        fs::read(path).with_context(|| format!("cannot read scheduled message {id}"))
+    }
+
+    /// Cancel the message matching the given id.
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let path = self.path(id)?;
+        fs::remove_file(path).with_context(|| format!("cannot cancel scheduled message {id}"))?;
+        debug!("scheduled message {id} removed");
+        Ok(())
+    }
+
+    /// Claim the message matching the given id before sending it, and
+    /// return it.
+    ///
+    /// The message is claimed by renaming it atomically, so that it
+    /// cannot be sent twice by concurrent runs: `None` is returned if
+    /// it has already been claimed (or cancelled) meanwhile. A claimed
+    /// message is hidden from the schedule.
+    pub fn claim(&self, id: &str) -> Result<Option<ClaimedMessage>> {
+        let Some(path) = self.find(id)? else {
+            return Ok(None);
+        };
+
+        let claimed_path = path.with_extension(CLAIMED_MSG_EXT);
+        match fs::rename(&path, &claimed_path) {
+            Ok(()) => (),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("cannot claim scheduled message {id}"))
+            }
+        }
+        debug!("scheduled message {id} claimed");
+
+        let mut claimed = ClaimedMessage {
+            id: id.to_owned(),
+            raw_msg: Vec::new(),
+            path,
+            claimed_path,
+        };
+
+        match fs::read(&claimed.claimed_path) {
+            Ok(raw_msg) => {
+                claimed.raw_msg = raw_msg;
+                Ok(Some(claimed))
+            }
+            Err(err) => {
+                claimed.release()?;
+                Err(err).with_context(|| format!("cannot read scheduled message {id}"))
+            }
+        }
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        match self.find(id)? {
+            Some(path) => Ok(path),
+            None => bail!("cannot find scheduled message {id}"),
+        }
+    }
+
+    fn find(&self, id: &str) -> Result<Option<PathBuf>> {
+        let entries = fs::read_dir(&self.dir)
+            .with_context(|| format!("cannot read schedule directory at {:?}", self.dir))?;
+
+        for entry in entries {
+            let path = entry.context("cannot read schedule entry")?.path();
+            if matches!(parse_file_name(&path), Some((entry_id, _)) if entry_id == id) {
+                return Ok(Some(path));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+fn file_name(id: &str, send_at: &DateTime<Local>) -> String {
+    format!("{}.{id}.{SCHEDULED_MSG_EXT}", send_at.timestamp())
+}
+
+fn parse_file_name(path: &Path) -> Option<(String, DateTime<Local>)> {
+    if path.extension()? != SCHEDULED_MSG_EXT {
+        return None;
+    }
+
+    let (timestamp, id) = path.file_stem()?.to_str()?.split_once('.')?;
+    let send_at = Local.timestamp_opt(timestamp.parse().ok()?, 0).single()?;
+
+    Some((id.to_owned(), send_at))
+}
+
+/// A scheduled message claimed by [`Schedule::claim`].
+pub struct ClaimedMessage {
+    pub id: String,
+    pub raw_msg: Vec<u8>,
+    path: PathBuf,
+    claimed_path: PathBuf,
+}
+
+impl ClaimedMessage {
+    /// Remove the claimed message from the schedule, once it has been
+    /// sent or moved to the outbox.
+    pub fn remove(&self) -> Result<()> {
+        fs::remove_file(&self.claimed_path)
+            .with_context(|| format!("cannot remove scheduled message {}", self.id))?;
+        debug!("scheduled message {} removed", self.id);
+        Ok(())
+    }
+
+    /// Put the claimed message back in the schedule.
+    pub fn release(&self) -> Result<()> {
+        fs::rename(&self.claimed_path, &self.path)
+            .with_context(|| format!("cannot release scheduled message {}", self.id))?;
+        debug!("scheduled message {} released", self.id);
+        Ok(())
+    }
+}
+
+/// A message waiting for its due date.
+#[derive(Clone, Debug, Serialize)]
+pub struct ScheduledMessage {
+    pub id: String,
+    #[serde(skip)]
+    pub send_at: DateTime<Local>,
+    pub date: String,
+    pub to: String,
+    pub subject: String,
+}
+
+impl ScheduledMessage {
+    /// Return `true` if the message should be sent.
+    pub fn is_due(&self) -> bool {
+        self.send_at <= Local::now()
+    }
+}
+
+impl Table for ScheduledMessage {
+    fn head() -> Row {
+        Row::new()
+            .cell(Cell::new("ID").bold().underline().white())
+            .cell(Cell::new("SEND AT").bold().underline().white())
+            .cell(Cell::new("TO").bold().underline().white())
+            .cell(Cell::new("SUBJECT").shrinkable().bold().underline().white())
+    }
+
+    fn row(&self) -> Row {
+        Row::new()
+            .cell(Cell::new(&self.id).red())
+            .cell(Cell::new(&self.date).yellow())
+            .cell(Cell::new(&self.to).blue())
+            .cell(Cell::new(&self.subject).shrinkable().green())
+    }
+}
+
+/// The list of scheduled messages.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ScheduledMessages(Vec<ScheduledMessage>);
+
+impl ops::Deref for ScheduledMessages {
+    type Target = Vec<ScheduledMessage>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl PrintTable for ScheduledMessages {
+    fn print_table(&self, writer: &mut dyn WriteColor, opts: PrintTableOpts) -> Result<()> {
+        writeln!(writer)?;
+        Table::print(writer, self, opts)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local, TimeZone};
+    use std::{fs, path::Path};
+
+    use super::{file_name, parse_file_name, parse_send_at, Schedule};
+
+    #[test]
+    fn send_at() {
+        let expected = Local.with_ymd_and_hms(2026, 10, 20, 9, 0, 0).unwrap();
+
+        assert_eq!(parse_send_at("2026-10-20 09:00").unwrap(), expected);
+        assert_eq!(parse_send_at(" 2026-10-20T09:00:00 ").unwrap(), expected);
+        assert_eq!(parse_send_at(&expected.to_rfc3339()).unwrap(), expected);
+        assert!(parse_send_at("20/10/2026").is_err());
+    }
+
+    #[test]
+    fn file_names() {
+        let send_at = Local.with_ymd_and_hms(2026, 10, 20, 9, 0, 0).unwrap();
+        let name = file_name("0a1b2c3d", &send_at);
+
+        assert_eq!(
+            parse_file_name(Path::new(&name)),
+            Some((String::from("0a1b2c3d"), send_at))
+        );
+        assert_eq!(parse_file_name(Path::new(&format!(".{name}.tmp"))), None);
+        assert_eq!(parse_file_name(Path::new("notes.txt")), None);
+    }
+
+    #[test]
+    fn claim_messages_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let schedule = Schedule {
+            dir: dir.path().to_owned(),
+        };
+
+        let raw_msg = b"Subject: test\r\n\r\nHello!\r\n";
+        let id = schedule
+            .add(raw_msg, &(Local::now() + Duration::hours(1)))
+            .unwrap();
+
+        let msg = schedule.claim(&id).unwrap().unwrap();
+        assert_eq!(msg.raw_msg, raw_msg);
+        assert!(schedule.claim(&id).unwrap().is_none());
+        assert!(schedule.list().unwrap().is_empty());
+
+        msg.release().unwrap();
+        assert_eq!(schedule.list().unwrap().len(), 1);
+
+        schedule.claim(&id).unwrap().unwrap().remove().unwrap();
+        assert!(schedule.claim(&id).unwrap().is_none());
+        assert!(fs::read_dir(dir.path()).unwrap().next().is_none());
+    }
+}
@@ -0,0 +1,72 @@
+use email::account::config::passwd::PasswdConfig;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The POP backend configuration.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PopConfig {
+    /// The POP server host name.
+    pub host: String,
+
+    /// The POP server host port.
+    ///
+    /// Defaults to 995 for SSL/TLS, 110 otherwise.
+    pub port: Option<u16>,
+
+    /// The POP server encryption protocol.
+    ///
+    /// Supported encryption: SSL/TLS, STARTTLS (STLS) or none.
+    /// Defaults to SSL/TLS.
+    pub encryption: Option<PopEncryptionKind>,
+
+    /// The POP server login, usually the email address.
+    pub login: String,
+
+    /// The POP server password configuration.
+    pub passwd: PasswdConfig,
+
+    /// Keep messages on the server after they have been synchronized.
+    ///
+    /// By default, messages are deleted from the server once they
+    /// have been saved in the local Maildir.
+    pub leave_on_server: Option<bool>,
+}
+
+impl PopConfig {
+    pub fn encryption(&self) -> PopEncryptionKind {
+        self.encryption.clone().unwrap_or_default()
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(match self.encryption() {
+            PopEncryptionKind::Tls => 995,
+            PopEncryptionKind::StartTls | PopEncryptionKind::None => 110,
+        })
+    }
+
+    pub fn leave_on_server(&self) -> bool {
+        self.leave_on_server.unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PopEncryptionKind {
+    #[default]
+    #[serde(alias = "ssl")]
+    Tls,
+    #[serde(alias = "starttls", alias = "stls")]
+    StartTls,
+    None,
+}
+
+impl fmt::Display for PopEncryptionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tls => write!(f, "SSL/TLS"),
+            Self::StartTls => write!(f, "StartTLS"),
+            Self::None => write!(f, "None"),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Module dedicated to POP envelopes.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use email::{
+    envelope::{list::ListEnvelopes, Envelope, Envelopes},
+    flag::Flags,
+    message::Message,
+};
+use log::{info, trace};
+
+use crate::envelope::search::paginate;
+
+use super::PopSession;
+
+pub struct ListEnvelopesPop {
+    session: PopSession,
+}
+
+impl ListEnvelopesPop {
+    pub fn new(session: &PopSession) -> Option<Box<dyn ListEnvelopes>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[async_trait]
+impl ListEnvelopes for ListEnvelopesPop {
+    /// List envelopes from the most recent one. Message numbers
+    /// follow the arrival order, so pagination is done on them and
+    /// headers are only fetched for the requested page.
+    async fn list_envelopes(
+        &self,
+        folder: &str,
+        page_size: usize,
+        page: usize,
+    ) -> Result<Envelopes> {
+        info!("listing POP envelopes from folder {folder}");
+
+        self.session.check_folder(folder)?;
+
+        let mut uidls = self.session.uidls().await?;
+        uidls.reverse();
+
+        let mut envelopes = Vec::with_capacity(page_size);
+
+        for (num, uidl) in paginate(&uidls, page_size, page)? {
+            let headers = self.session.top(*num).await?;
+            let envelope = Envelope::from_msg(uidl, Flags::default(), Message::from(headers));
+            envelopes.push(envelope);
+        }
+
+        envelopes.sort_by(|a, b| b.date.cmp(&a.date));
+        trace!("POP envelopes: {envelopes:#?}");
+
+        Ok(Envelopes::from_iter(envelopes))
+    }
+}
@@ -0,0 +1,73 @@
+//! Module dedicated to POP messages.
+//!
+//! POP has no seen flag, so getting messages is the same as peeking
+//! them.
+
+use anyhow::Result;
+use async_trait::async_trait;
+#[cfg(feature = "message-get")]
+use email::message::get::GetMessages;
+#[cfg(feature = "message-peek")]
+use email::message::peek::PeekMessages;
+use email::{envelope::Id, message::Messages};
+use log::info;
+
+use super::PopSession;
+
+/// Retrieve the messages matching the given unique ids.
+async fn retr_messages(session: &PopSession, folder: &str, id: &Id) -> Result<Messages> {
+    session.check_folder(folder)?;
+
+    let nums = session.numbers(id.iter()).await?;
+    let mut msgs = Vec::with_capacity(nums.len());
+
+    for num in nums {
+        msgs.push(session.retr(num).await?);
+    }
+
+    Ok(Messages::from(msgs))
+}
+
+#[cfg(feature = "message-peek")]
+pub struct PeekMessagesPop {
+    session: PopSession,
+}
+
+#[cfg(feature = "message-peek")]
+impl PeekMessagesPop {
+    pub fn new(session: &PopSession) -> Option<Box<dyn PeekMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-peek")]
+#[async_trait]
+impl PeekMessages for PeekMessagesPop {
+    async fn peek_messages(&self, folder: &str, id: &Id) -> Result<Messages> {
+        info!("peeking POP messages {id} from folder {folder}");
+        retr_messages(&self.session, folder, id).await
+    }
+}
+
+#[cfg(feature = "message-get")]
+pub struct GetMessagesPop {
+    session: PopSession,
+}
+
+#[cfg(feature = "message-get")]
+impl GetMessagesPop {
+    pub fn new(session: &PopSession) -> Option<Box<dyn GetMessages>> {
+        let session = session.clone();
+        Some(Box::new(Self { session }))
+    }
+}
+
+#[cfg(feature = "message-get")]
+#[async_trait]
+impl GetMessages for GetMessagesPop {
+    async fn get_messages(&self, folder: &str, id: &Id) -> Result<Messages> {
+        info!("getting POP messages {id} from folder {folder}");
+        retr_messages(&self.session, folder, id).await
+    }
+}
@@ -0,0 +1,545 @@
+//! Module dedicated to the POP backend.
+//!
+//! POP3 (RFC 1939) only gives access to the inbox of the mailbox,
+//! and has no notion of flags. Messages are identified by their
+//! unique id (as returned by `UIDL`), which is stable across
+//! sessions, unlike message numbers. Envelopes are built from the
+//! headers returned by `TOP`.
+//!
+//! The backend is receive-only: messages can be listed and read, and
+//! the account synchronization downloads new messages into the local
+//! Maildir. Unique ids of downloaded messages are kept in the id
+//! mapper database, so that messages are never downloaded twice.
+
+pub mod config;
+#[cfg(feature = "envelope-list")]
+pub mod envelope;
+#[cfg(any(feature = "message-peek", feature = "message-get"))]
+pub mod message;
+#[cfg(feature = "account-sync")]
+pub mod sync;
+#[cfg(feature = "account-sync")]
+pub mod uidl;
+#[cfg(feature = "wizard")]
+pub(crate) mod wizard;
+
+use anyhow::{anyhow, bail, Context, Result};
+use email::{account::config::AccountConfig, folder::FolderKind};
+use log::{debug, trace};
+use std::{fs, path::PathBuf, sync::Arc};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    sync::Mutex,
+};
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+
+pub use self::config::{PopConfig, PopEncryptionKind};
+#[cfg(feature = "envelope-list")]
+pub use self::envelope::ListEnvelopesPop;
+#[cfg(feature = "message-get")]
+pub use self::message::GetMessagesPop;
+#[cfg(feature = "message-peek")]
+pub use self::message::PeekMessagesPop;
+
+/// The POP session builder.
+#[derive(Clone, Debug)]
+pub struct PopSessionBuilder {
+    account_config: AccountConfig,
+    pop_config: PopConfig,
+}
+
+impl PopSessionBuilder {
+    pub fn new(account_config: AccountConfig, pop_config: PopConfig) -> Self {
+        Self {
+            account_config,
+            pop_config,
+        }
+    }
+
+    /// Build the session by connecting and authenticating to the
+    /// server.
+    pub async fn build(self) -> Result<PopSession> {
+        let mut client = PopClient::connect(&self.pop_config).await?;
+
+        let passwd = self
+            .pop_config
+            .passwd
+            .get()
+            .await
+            .context("cannot get POP password")?;
+        let passwd = passwd.lines().next().unwrap_or_default();
+        client.login(&self.pop_config.login, passwd).await?;
+
+        Ok(PopSession {
+            account_config: self.account_config,
+            pop_config: self.pop_config,
+            client: Arc::new(Mutex::new(client)),
+        })
+    }
+}
+
+/// The POP session.
+///
+/// Commands cannot be pipelined, so the client is shared behind a
+/// lock.
+#[derive(Clone)]
+pub struct PopSession {
+    pub account_config: AccountConfig,
+    pub pop_config: PopConfig,
+    client: Arc<Mutex<PopClient>>,
+}
+
+impl PopSession {
+    /// Return an error when the given folder is not the inbox, the
+    /// only folder POP gives access to.
+    pub fn check_folder(&self, folder: &str) -> Result<()> {
+        let alias = self.account_config.get_folder_alias(folder);
+
+        if FolderKind::matches_inbox(&alias)
+            || alias == self.account_config.get_inbox_folder_alias()
+        {
+            Ok(())
+        } else {
+            bail!("cannot access folder {folder}: POP only gives access to the inbox")
+        }
+    }
+
+    /// List the message numbers and the unique ids of all messages,
+    /// from the oldest one.
+    pub async fn uidls(&self) -> Result<Vec<(usize, String)>> {
+        self.client.lock().await.uidl().await
+    }
+
+    /// Return the message numbers matching the given unique ids, in
+    /// the same order.
+    pub async fn numbers<'a>(&self, ids: impl IntoIterator<Item = &'a str>) -> Result<Vec<usize>> {
+        let uidls = self.uidls().await?;
+
+        ids.into_iter()
+            .map(|id| {
+                uidls
+                    .iter()
+                    .find(|(_, uidl)| uidl == id)
+                    .map(|(num, _)| *num)
+                    .ok_or_else(|| anyhow!("cannot find POP message {id}"))
+            })
+            .collect()
+    }
+
+    /// Return the headers of the given message.
+    pub async fn top(&self, num: usize) -> Result<Vec<u8>> {
+        self.client.lock().await.top(num).await
+    }
+
+    /// Return the raw content of the given message.
+    pub async fn retr(&self, num: usize) -> Result<Vec<u8>> {
+        self.client.lock().await.retr(num).await
+    }
+
+    /// Mark the given message as deleted. Messages are only deleted
+    /// once the session is closed using [`PopSession::quit`].
+    pub async fn dele(&self, num: usize) -> Result<()> {
+        self.client.lock().await.dele(num).await
+    }
+
+    /// Close the session, which commits deletions.
+    pub async fn quit(&self) -> Result<()> {
+        self.client.lock().await.quit().await
+    }
+}
+
+/// The transport of the client, either plain or encrypted.
+trait PopStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> PopStream for T {}
+
+/// A minimal POP3 client, covering the commands needed by the
+/// backend.
+pub struct PopClient {
+    stream: BufReader<Box<dyn PopStream>>,
+}
+
+impl PopClient {
+    fn new(stream: impl PopStream + 'static) -> Self {
+        Self {
+            stream: BufReader::new(Box::new(stream)),
+        }
+    }
+
+    /// Connect to the server, upgrade the connection if needed and
+    /// read the greeting.
+    pub async fn connect(config: &PopConfig) -> Result<Self> {
+        let host = config.host.as_str();
+        let port = config.port();
+        debug!("connecting to POP server {host}:{port}");
+
+        let tcp = TcpStream::connect((host, port))
+            .await
+            .with_context(|| format!("cannot connect to POP server {host}:{port}"))?;
+
+        let client = match config.encryption() {
+            PopEncryptionKind::Tls => {
+                let mut client = Self::new(tls_connect(host, tcp).await?);
+                client.read_response().await?;
+                client
+            }
+            PopEncryptionKind::StartTls => {
+                let mut client = Self::new(tcp);
+                client.read_response().await?;
+                client
+                    .command("STLS")
+                    .await
+                    .context("cannot start POP TLS session")?;
+                Self::new(tls_connect(host, client.stream.into_inner()).await?)
+            }
+            PopEncryptionKind::None => {
+                let mut client = Self::new(tcp);
+                client.read_response().await?;
+                client
+            }
+        };
+
+        Ok(client)
+    }
+
+    pub async fn login(&mut self, login: &str, passwd: &str) -> Result<()> {
+        self.command(&format!("USER {login}"))
+            .await
+            .context("cannot authenticate to POP server")?;
+        self.command(&format!("PASS {passwd}"))
+            .await
+            .context("cannot authenticate to POP server")?;
+        Ok(())
+    }
+
+    pub async fn uidl(&mut self) -> Result<Vec<(usize, String)>> {
+        self.command("UIDL")
+            .await
+            .context("cannot list POP unique ids")?;
+
+        self.read_multiline()
+            .await?
+            .iter()
+            .map(|line| {
+                let line = String::from_utf8_lossy(line);
+                let (num, uidl) = line
+                    .trim()
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow!("cannot parse POP unique id line {line}"))?;
+                let num = num
+                    .parse()
+                    .with_context(|| format!("cannot parse POP message number {num}"))?;
+                Ok((num, uidl.trim().to_owned()))
+            })
+            .collect()
+    }
+
+    pub async fn top(&mut self, num: usize) -> Result<Vec<u8>> {
+        self.command(&format!("TOP {num} 0"))
+            .await
+            .with_context(|| format!("cannot get headers of POP message {num}"))?;
+        Ok(to_raw(self.read_multiline().await?))
+    }
+
+    pub async fn retr(&mut self, num: usize) -> Result<Vec<u8>> {
+        self.command(&format!("RETR {num}"))
+            .await
+            .with_context(|| format!("cannot get POP message {num}"))?;
+        Ok(to_raw(self.read_multiline().await?))
+    }
+
+    pub async fn dele(&mut self, num: usize) -> Result<()> {
+        self.command(&format!("DELE {num}"))
+            .await
+            .with_context(|| format!("cannot delete POP message {num}"))?;
+        Ok(())
+    }
+
+    pub async fn quit(&mut self) -> Result<()> {
+        self.command("QUIT")
+            .await
+            .context("cannot close POP session")?;
+        Ok(())
+    }
+
+    /// Send the given command and read its status line.
+    async fn command(&mut self, cmd: &str) -> Result<String> {
+        // the password should not end up in logs
+        match cmd.strip_prefix("PASS ") {
+            Some(_) => trace!("POP command: PASS *****"),
+            None => trace!("POP command: {cmd}"),
+        }
+
+        self.stream
+            .write_all(format!("{cmd}\r\n").as_bytes())
+            .await
+            .context("cannot send POP command")?;
+        self.stream
+            .flush()
+            .await
+            .context("cannot send POP command")?;
+
+        self.read_response().await
+    }
+
+    /// Read a status line, and return its text when it is positive.
+    async fn read_response(&mut self) -> Result<String> {
+        let line = self.read_line().await?;
+        let line = String::from_utf8_lossy(&line);
+        trace!("POP response: {line}");
+
+        match line.strip_prefix("+OK") {
+            Some(text) => Ok(text.trim().to_owned()),
+            None => {
+                let err = line.strip_prefix("-ERR").unwrap_or(&line).trim();
+                bail!("POP server error: {err}")
+            }
+        }
+    }
+
+    /// Read the lines of a multi-line response, up to the terminating
+    /// dot. Lines starting with a dot are unstuffed.
+    async fn read_multiline(&mut self) -> Result<Vec<Vec<u8>>> {
+        let mut lines = Vec::new();
+
+        loop {
+            let mut line = self.read_line().await?;
+
+            if line == b"." {
+                break;
+            }
+
+            if line.starts_with(b".") {
+                line.remove(0);
+            }
+
+            lines.push(line);
+        }
+
+        Ok(lines)
+    }
+
+    /// Read a line, without its line ending.
+    async fn read_line(&mut self) -> Result<Vec<u8>> {
+        let mut line = Vec::new();
+
+        let len = self
+            .stream
+            .read_until(b'\n', &mut line)
+            .await
+            .context("cannot read POP response")?;
+
+        if len == 0 {
+            bail!("cannot read POP response: connection closed by server");
+        }
+
+        if line.ends_with(b"\n") {
+            line.pop();
+        }
+
+        if line.ends_with(b"\r") {
+            line.pop();
+        }
+
+        Ok(line)
+    }
+}
+
+/// Join the given lines back into a raw message.
+fn to_raw(lines: Vec<Vec<u8>>) -> Vec<u8> {
+    lines
+        .into_iter()
+        .flat_map(|mut line| {
+            line.extend_from_slice(b"\r\n");
+            line
+        })
+        .collect()
+}
+
+/// Wrap the given stream into a TLS session, trusting the Mozilla
+/// root certificates.
+async fn tls_connect<S: PopStream>(host: &str, stream: S) -> Result<TlsStream<S>> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+    let config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    let server_name = ServerName::try_from(host)
+        .with_context(|| format!("cannot parse POP server name {host}"))?
+        .to_owned();
+
+    TlsConnector::from(Arc::new(config))
+        .connect(server_name, stream)
+        .await
+        .with_context(|| format!("cannot establish TLS session with POP server {host}"))
+}
+
+/// Return the directory of the id mapper database of the given
+/// account. POP has no local directory, so the user cache directory
+/// is used instead.
+pub fn id_mapper_db_dir(account_config: &AccountConfig) -> Result<PathBuf> {
+    let dir = dirs::cache_dir()
+        .ok_or_else(|| anyhow!("cannot find user cache directory"))?
+        .join("himalaya")
+        .join("pop")
+        .join(&account_config.name);
+
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("cannot create POP cache directory at {dir:?}"))?;
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use email::account::config::{passwd::PasswdConfig, AccountConfig};
+    use secret::Secret;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use super::{PopConfig, PopEncryptionKind, PopSession, PopSessionBuilder};
+
+    /// The commands received by the stand-in server.
+    type Commands = Arc<Mutex<Vec<String>>>;
+
+    const MSG_1: &str = "From: bob@localhost\r\nSubject: first\r\n\r\nHello\r\n";
+    const MSG_2: &str = "From: bob@localhost\r\nSubject: second\r\n\r\n.leading dot\r\n";
+
+    /// Spawn a minimal POP server serving two messages, and return a
+    /// session connected to it.
+    async fn spawn_server() -> (PopSession, Commands) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let commands = Commands::default();
+        let server_commands = commands.clone();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let multiline = |msg: &str| {
+                let lines: String = msg
+                    .lines()
+                    .map(|line| match line.starts_with('.') {
+                        true => format!(".{line}\r\n"),
+                        false => format!("{line}\r\n"),
+                    })
+                    .collect();
+                format!("+OK\r\n{lines}.\r\n")
+            };
+
+            write!(stream, "+OK POP3 ready\r\n").unwrap();
+
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+
+                let cmd = line.trim_end().to_owned();
+                server_commands.lock().unwrap().push(cmd.clone());
+
+                let res = match cmd.split(' ').collect::<Vec<_>>()[..] {
+                    ["USER", _] | ["DELE", _] => String::from("+OK\r\n"),
+                    ["PASS", "passwd"] => String::from("+OK logged in\r\n"),
+                    ["PASS", _] => String::from("-ERR invalid password\r\n"),
+                    ["UIDL"] => String::from("+OK\r\n1 uid-1\r\n2 uid-2\r\n.\r\n"),
+                    ["TOP", "1", "0"] => multiline(MSG_1.split("\r\n\r\n").next().unwrap()),
+                    ["TOP", "2", "0"] => multiline(MSG_2.split("\r\n\r\n").next().unwrap()),
+                    ["RETR", "1"] => multiline(MSG_1),
+                    ["RETR", "2"] => multiline(MSG_2),
+                    ["QUIT"] => String::from("+OK bye\r\n"),
+                    _ => String::from("-ERR unknown command\r\n"),
+                };
+
+                stream.write_all(res.as_bytes()).unwrap();
+
+                if cmd == "QUIT" {
+                    break;
+                }
+            }
+        });
+
+        let account_config = AccountConfig {
+            name: String::from("test"),
+            ..Default::default()
+        };
+        let pop_config = PopConfig {
+            host: String::from("127.0.0.1"),
+            port: Some(port),
+            encryption: Some(PopEncryptionKind::None),
+            login: String::from("alice"),
+            passwd: PasswdConfig(Secret::new_raw(String::from("passwd"))),
+            leave_on_server: None,
+        };
+
+        let session = PopSessionBuilder::new(account_config, pop_config)
+            .build()
+            .await
+            .unwrap();
+
+        (session, commands)
+    }
+
+    #[tokio::test]
+    async fn uidls() {
+        let (session, commands) = spawn_server().await;
+
+        let uidls = session.uidls().await.unwrap();
+        assert_eq!(
+            uidls,
+            [(1, String::from("uid-1")), (2, String::from("uid-2"))]
+        );
+
+        assert_eq!(session.numbers(["uid-2", "uid-1"]).await.unwrap(), [2, 1]);
+        assert!(session.numbers(["uid-3"]).await.is_err());
+
+        assert_eq!(commands.lock().unwrap()[..2], ["USER alice", "PASS passwd"]);
+    }
+
+    #[tokio::test]
+    async fn messages() {
+        let (session, commands) = spawn_server().await;
+
+        assert_eq!(
+            session.top(1).await.unwrap(),
+            b"From: bob@localhost\r\nSubject: first\r\n"
+        );
+
+        // the leading dot is unstuffed
+        assert_eq!(session.retr(2).await.unwrap(), MSG_2.as_bytes());
+
+        session.dele(1).await.unwrap();
+        session.quit().await.unwrap();
+        assert!(session.retr(3).await.is_err());
+
+        let commands = commands.lock().unwrap();
+        assert_eq!(commands[commands.len() - 2..], ["DELE 1", "QUIT"]);
+    }
+
+    #[test]
+    fn check_folder() {
+        let session = PopSession {
+            account_config: AccountConfig::default(),
+            pop_config: PopConfig::default(),
+            client: Arc::new(tokio::sync::Mutex::new(super::PopClient::new(
+                tokio::io::duplex(1).0,
+            ))),
+        };
+
+        assert!(session.check_folder("INBOX").is_ok());
+        assert!(session.check_folder("inbox").is_ok());
+        assert!(session.check_folder("Sent").is_err());
+    }
+}
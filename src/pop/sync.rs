@@ -0,0 +1,116 @@
+//! Module dedicated to the POP account synchronization.
+//!
+//! POP cannot be synchronized like other backends: there is only one
+//! folder and no flags. Instead, new messages are downloaded into the
+//! inbox of the synchronization Maildir, and their unique ids are
+//! saved in the [`UidlCache`]. Unless messages are left on the
+//! server, downloaded messages are then deleted from the server.
+
+use anyhow::{anyhow, Result};
+use email::{
+    account::config::AccountConfig,
+    backend::BackendContextBuilder,
+    flag::Flags,
+    folder::INBOX,
+    maildir::{config::MaildirConfig, MaildirSessionBuilder},
+    message::add::{maildir::AddMaildirMessage, AddMessage},
+};
+use log::{debug, info};
+use std::collections::HashSet;
+
+use super::{uidl::UidlCache, PopConfig, PopSessionBuilder};
+
+/// The report of a POP account synchronization.
+#[derive(Debug, Default)]
+pub struct PopSyncReport {
+    /// The unique ids of the downloaded messages (or of the messages
+    /// to download, in dry run mode).
+    pub fetched: Vec<String>,
+
+    /// The amount of messages deleted from the server (or to delete,
+    /// in dry run mode).
+    pub deleted: usize,
+}
+
+/// Download new messages of the given POP account into its
+/// synchronization Maildir.
+pub async fn sync(
+    account_config: &AccountConfig,
+    pop_config: &PopConfig,
+    dry_run: bool,
+) -> Result<PopSyncReport> {
+    info!("synchronizing POP account {}", account_config.name);
+
+    let session = PopSessionBuilder::new(account_config.clone(), pop_config.clone())
+        .build()
+        .await?;
+    let cache = UidlCache::open(account_config)?;
+    let uidls = session.uidls().await?;
+
+    if !dry_run {
+        let existing_uidls: HashSet<String> = uidls.iter().map(|(_, uidl)| uidl.clone()).collect();
+        cache.retain(&existing_uidls)?;
+    }
+
+    let mut new_uidls = Vec::new();
+    let mut fetched_nums = Vec::new();
+
+    for (num, uidl) in &uidls {
+        if cache.contains(uidl)? {
+            fetched_nums.push(*num);
+        } else {
+            new_uidls.push((*num, uidl.clone()));
+        }
+    }
+    debug!(
+        "{} new POP messages, {} already downloaded",
+        new_uidls.len(),
+        fetched_nums.len()
+    );
+
+    let mut report = PopSyncReport::default();
+    let leave_on_server = pop_config.leave_on_server();
+
+    if dry_run {
+        report.fetched = new_uidls.into_iter().map(|(_, uidl)| uidl).collect();
+        if !leave_on_server {
+            report.deleted = uidls.len();
+        }
+        return Ok(report);
+    }
+
+    let mdir_config = MaildirConfig {
+        root_dir: account_config.get_sync_dir()?,
+    };
+    let mdir = MaildirSessionBuilder::new(account_config.clone(), mdir_config)
+        .build()
+        .await?;
+    let add_message = AddMaildirMessage::new(&mdir)
+        .ok_or_else(|| anyhow!("cannot add messages to synchronization Maildir"))?;
+
+    for (num, uidl) in new_uidls {
+        let raw_msg = session.retr(num).await?;
+        add_message
+            .add_message_with_flags(INBOX, &raw_msg, &Flags::default())
+            .await?;
+
+        // the unique id is saved right after the message, so that an
+        // interrupted synchronization does not download it again
+        cache.insert(&uidl)?;
+        fetched_nums.push(num);
+        report.fetched.push(uidl);
+    }
+
+    // messages are deleted once the session is closed, so they are
+    // kept on the server if anything failed before
+    if !leave_on_server {
+        for num in fetched_nums {
+            session.dele(num).await?;
+            report.deleted += 1;
+        }
+    }
+
+    session.quit().await?;
+
+    Ok(report)
+}
@@ -0,0 +1,115 @@
+//! Module dedicated to the POP unique ids cache.
+//!
+//! The unique ids of the messages downloaded by the account
+//! synchronization are kept in the id mapper database, in a table
+//! shared by all POP accounts.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use email::account::config::AccountConfig;
+use log::{debug, trace};
+use std::collections::HashSet;
+
+use crate::cache::IdMapper;
+
+const UIDL_TABLE: &str = "pop_uidls";
+
+/// The cache of the unique ids of the messages already downloaded
+/// from a POP account.
+#[derive(Debug)]
+pub struct UidlCache {
+    account: String,
+    conn: rusqlite::Connection,
+}
+
+impl UidlCache {
+    pub fn open(account_config: &AccountConfig) -> Result<Self> {
+        let db_path = IdMapper::find_closest_db_path(super::id_mapper_db_dir(account_config)?);
+        debug!("opening POP unique ids cache at {db_path:?}…");
+
+        let conn = rusqlite::Connection::open(&db_path)
+            .with_context(|| format!("cannot open id mapper database at {db_path:?}"))?;
+
+        let query = format!(
+            "CREATE TABLE IF NOT EXISTS {UIDL_TABLE} (
+                account TEXT NOT NULL,
+                uidl TEXT NOT NULL,
+                fetched_at INTEGER NOT NULL,
+                PRIMARY KEY (account, uidl)
+            )",
+        );
+        trace!("create table query: {query:#?}");
+
+        conn.execute(&query, [])
+            .context("cannot create POP unique ids table")?;
+
+        Ok(Self {
+            account: account_config.name.clone(),
+            conn,
+        })
+    }
+
+    /// Return `true` if the message matching the given unique id has
+    /// already been downloaded.
+    pub fn contains(&self, uidl: &str) -> Result<bool> {
+        let query = format!("SELECT COUNT(*) FROM {UIDL_TABLE} WHERE account = ? AND uidl = ?");
+        trace!("select query: {query:#?}");
+
+        let count: i64 = self
+            .conn
+            .query_row(&query, [&self.account, uidl], |row| row.get(0))
+            .with_context(|| format!("cannot find POP unique id {uidl}"))?;
+
+        Ok(count > 0)
+    }
+
+    /// Mark the message matching the given unique id as downloaded.
+    pub fn insert(&self, uidl: &str) -> Result<()> {
+        let query = format!("INSERT OR REPLACE INTO {UIDL_TABLE} VALUES (?, ?, ?)");
+        trace!("insert query: {query:#?}");
+
+        self.conn
+            .execute(
+                &query,
+                rusqlite::params![self.account, uidl, Utc::now().timestamp()],
+            )
+            .with_context(|| format!("cannot insert POP unique id {uidl}"))?;
+
+        Ok(())
+    }
+
+    /// Remove the unique ids that are not part of the given existing
+    /// ones, and return the amount of removed unique ids.
+    ///
+    /// Servers may reuse the unique ids of deleted messages, so they
+    /// should not be kept longer than the messages themselves.
+    pub fn retain(&self, uidls: &HashSet<String>) -> Result<usize> {
+        let query = format!("SELECT uidl FROM {UIDL_TABLE} WHERE account = ?");
+        trace!("select query: {query:#?}");
+
+        let mut stmt = self
+            .conn
+            .prepare(&query)
+            .context("cannot list POP unique ids")?;
+        let stale_uidls: Vec<String> = stmt
+            .query_map([&self.account], |row| row.get(0))
+            .context("cannot list POP unique ids")?
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .context("cannot list POP unique ids")?
+            .into_iter()
+            .filter(|uidl| !uidls.contains(uidl))
+            .collect();
+
+        let query = format!("DELETE FROM {UIDL_TABLE} WHERE account = ? AND uidl = ?");
+        trace!("delete query: {query:#?}");
+
+        for uidl in &stale_uidls {
+            self.conn
+                .execute(&query, [&self.account, uidl])
+                .with_context(|| format!("cannot delete POP unique id {uidl}"))?;
+        }
+
+        debug!("pruned {} POP unique ids", stale_uidls.len());
+        Ok(stale_uidls.len())
+    }
+}
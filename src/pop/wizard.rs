@@ -0,0 +1,103 @@
+use anyhow::Result;
+use dialoguer::{Confirm, Input, Select};
+use email::account::config::passwd::PasswdConfig;
+use secret::Secret;
+
+use crate::{
+    backend::config::BackendConfig,
+    ui::{prompt, THEME},
+    wizard_prompt,
+};
+
+use super::{PopConfig, PopEncryptionKind};
+
+const ENCRYPTIONS: &[PopEncryptionKind] = &[
+    PopEncryptionKind::Tls,
+    PopEncryptionKind::StartTls,
+    PopEncryptionKind::None,
+];
+
+const SECRETS: &[&str] = &[KEYRING, RAW, CMD];
+const KEYRING: &str = "Ask my password, then save it in my system's global keyring";
+const RAW: &str = "Ask my password, then save it in the configuration file (not safe)";
+const CMD: &str = "Ask me a shell command that exposes my password";
+
+pub(crate) async fn configure(account_name: &str, email: &str) -> Result<BackendConfig> {
+    let domain = email.rsplit_once('@').map_or(email, |(_, domain)| domain);
+
+    let host = Input::with_theme(&*THEME)
+        .with_prompt("POP hostname")
+        .default(format!("pop.{domain}"))
+        .interact()?;
+
+    let encryption_idx = Select::with_theme(&*THEME)
+        .with_prompt("POP encryption")
+        .items(ENCRYPTIONS)
+        .default(0)
+        .interact_opt()?;
+
+    let encryption = match encryption_idx {
+        Some(idx) => ENCRYPTIONS[idx].clone(),
+        None => PopEncryptionKind::None,
+    };
+
+    let default_port = match encryption {
+        PopEncryptionKind::Tls => 995,
+        PopEncryptionKind::StartTls | PopEncryptionKind::None => 110,
+    };
+
+    let port = Input::with_theme(&*THEME)
+        .with_prompt("POP port")
+        .validate_with(|input: &String| input.parse::<u16>().map(|_| ()))
+        .default(default_port.to_string())
+        .interact()
+        .map(|input| input.parse::<u16>().unwrap())?;
+
+    let login = Input::with_theme(&*THEME)
+        .with_prompt("POP login")
+        .default(email.to_owned())
+        .interact()?;
+
+    let secret_idx = Select::with_theme(&*THEME)
+        .with_prompt("POP authentication strategy")
+        .items(SECRETS)
+        .default(0)
+        .interact_opt()?;
+
+    let secret = match secret_idx {
+        Some(idx) if SECRETS[idx] == KEYRING => {
+            let secret = Secret::new_keyring_entry(format!("{account_name}-pop-passwd"));
+            secret
+                .set_keyring_entry_secret(prompt::passwd("POP password")?)
+                .await?;
+            secret
+        }
+        Some(idx) if SECRETS[idx] == RAW => Secret::new_raw(prompt::passwd("POP password")?),
+        Some(idx) if SECRETS[idx] == CMD => Secret::new_cmd(
+            Input::with_theme(&*THEME)
+                .with_prompt("Shell command")
+                .default(format!("pass show {account_name}-pop-passwd"))
+                .interact()?,
+        ),
+        _ => Default::default(),
+    };
+
+    let leave_on_server = Confirm::new()
+        .with_prompt(wizard_prompt!(
+            "Would you like to keep messages on the server after synchronization?"
+        ))
+        .default(false)
+        .interact_opt()?
+        .unwrap_or_default();
+
+    let config = PopConfig {
+        host,
+        port: Some(port),
+        encryption: Some(encryption),
+        login,
+        passwd: PasswdConfig(secret),
+        leave_on_server: Some(leave_on_server),
+    };
+
+    Ok(BackendConfig::Pop(config))
+}
@@ -1,8 +1,10 @@
+pub mod pager;
 pub mod print;
 pub mod print_table;
 #[allow(clippy::module_inception)]
 pub mod printer;
 
+pub use pager::PagerPrinter;
 pub use print::*;
 pub use print_table::*;
 pub use printer::*;
@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use process::SingleCmd;
+use std::{
+    env,
+    fmt::Debug,
+    io::{self, Write},
+    path::Path,
+};
+use termcolor::Buffer;
+
+use crate::printer::{Print, PrintTable, PrintTableOpts, Printer, WriteColor};
+
+/// The pager used when neither the configuration nor the environment
+/// define one. The `-R` option keeps colours.
+pub const DEFAULT_PAGER: &str = "less -R";
+
+/// Return the pager command, from the given configured pager, from
+/// the `$PAGER` environment variable or the default one.
+///
+/// An empty pager or `cat` disables paging.
+pub fn cmd(pager: Option<&str>) -> Option<SingleCmd> {
+    let pager = match pager {
+        Some(pager) => pager.to_owned(),
+        None => env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_owned()),
+    };
+
+    match pager.trim() {
+        "" | "cat" => None,
+        pager => Some(SingleCmd::from(pager.to_owned())),
+    }
+}
+
+/// A printer paging its output.
+///
+/// The output is buffered, then piped to the pager command once
+/// [`PagerPrinter::page`] is called. It is only used for plain
+/// output, when the standard output is a terminal.
+pub struct PagerPrinter {
+    buffer: Buffer,
+    cmd: SingleCmd,
+}
+
+impl PagerPrinter {
+    pub fn new(cmd: SingleCmd, color: bool) -> Self {
+        let buffer = if color {
+            Buffer::ansi()
+        } else {
+            Buffer::no_color()
+        };
+
+        Self { buffer, cmd }
+    }
+
+    /// Pipe the buffered output to the pager.
+    ///
+    /// If the pager cannot be found or fails, the output is written
+    /// to the standard output instead so that it is never lost.
+    pub async fn page(self) -> Result<()> {
+        self.page_or_write(&mut io::stdout()).await
+    }
+
+    async fn page_or_write(self, out: &mut impl Write) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let output = self.buffer.into_inner();
+
+        if !is_available(&self.cmd) {
+            warn!("cannot find pager {}, printing output", &*self.cmd);
+            return write_output(out, &output);
+        }
+
+        debug!("paging output with {}", &*self.cmd);
+
+        let res = self
+            .cmd
+            .with_output_piped(false)
+            .run_with(&output)
+            .await
+            .context("cannot run pager");
+
+        match res {
+            Ok(_) => Ok(()),
+            // the pager may have been quit before reading the whole
+            // output, which is not an error for the user
+            Err(err) if is_broken_pipe(&err) => {
+                debug!("pager quit early: {err:?}");
+                Ok(())
+            }
+            Err(err) => {
+                warn!("{err}, printing output");
+                debug!("{err:?}");
+                write_output(out, &output)
+            }
+        }
+    }
+}
+
+/// Return `true` if the program of the given pager command can be
+/// found, either from its path or from the `$PATH` directories.
+///
+/// Leading environment variable assignments are skipped.
+fn is_available(cmd: &str) -> bool {
+    let program = cmd.split_whitespace().find(|word| !word.contains('='));
+
+    let Some(program) = program else {
+        return false;
+    };
+
+    if program.contains('/') || program.contains(std::path::MAIN_SEPARATOR) {
+        return Path::new(program).is_file();
+    }
+
+    let Some(paths) = env::var_os("PATH") else {
+        return false;
+    };
+
+    env::split_paths(&paths).any(|dir| {
+        dir.join(program).is_file()
+            || (cfg!(windows) && dir.join(format!("{program}.exe")).is_file())
+    })
+}
+
+fn is_broken_pipe(err: &anyhow::Error) -> bool {
+    err.chain().any(|err| {
+        err.downcast_ref::<io::Error>()
+            .map(|err| err.kind() == io::ErrorKind::BrokenPipe)
+            .unwrap_or_default()
+    })
+}
+
+fn write_output(out: &mut impl Write, output: &[u8]) -> Result<()> {
+    out.write_all(output)
+        .and_then(|()| out.flush())
+        .context("cannot write output")
+}
+
+impl Printer for PagerPrinter {
+    fn print_log<T: Debug + Print>(&mut self, data: T) -> Result<()> {
+        data.print(&mut self.buffer)
+    }
+
+    fn print<T: Debug + Print + serde::Serialize>(&mut self, data: T) -> Result<()> {
+        data.print(&mut self.buffer)
+    }
+
+    fn print_table<T: Debug + erased_serde::Serialize + PrintTable + ?Sized>(
+        &mut self,
+        data: Box<T>,
+        opts: PrintTableOpts,
+    ) -> Result<()> {
+        data.print_table(&mut self.buffer, opts)
+    }
+
+    fn is_json(&self) -> bool {
+        false
+    }
+}
+
+impl WriteColor for Buffer {}
+
+#[cfg(test)]
+mod tests {
+    use process::SingleCmd;
+
+    use crate::printer::Printer;
+
+    use super::{is_available, PagerPrinter};
+
+    #[test]
+    fn find_pager_program() {
+        assert!(!is_available(""));
+        assert!(!is_available("himalaya-missing-pager -R"));
+        assert!(!is_available("LESS=R himalaya-missing-pager"));
+        assert!(!is_available("/himalaya/missing/pager"));
+
+        #[cfg(unix)]
+        assert!(is_available("LESS=R sh -c cat"));
+    }
+
+    #[tokio::test]
+    async fn print_output_when_pager_is_missing() {
+        let cmd = SingleCmd::from(String::from("himalaya-missing-pager -R"));
+        let mut pager = PagerPrinter::new(cmd, false);
+        pager.print_log("message body").unwrap();
+
+        let mut out = Vec::new();
+        pager.page_or_write(&mut out).await.unwrap();
+
+        assert_eq!(out, b"message body\n");
+    }
+}
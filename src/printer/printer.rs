@@ -1,11 +1,15 @@
 use anyhow::{Context, Error, Result};
 use clap::ArgMatches;
-use std::fmt::{self, Debug};
-use termcolor::StandardStream;
+use process::SingleCmd;
+use std::{
+    fmt::{self, Debug},
+    io::{self, IsTerminal},
+};
+use termcolor::{StandardStream, WriteColor as _};
 
 use crate::{
     output::{args, ColorFmt, OutputFmt},
-    printer::{Print, PrintTable, PrintTableOpts, WriteColor},
+    printer::{PagerPrinter, Print, PrintTable, PrintTableOpts, WriteColor},
 };
 
 pub trait Printer {
@@ -21,6 +25,12 @@ pub trait Printer {
         opts: PrintTableOpts,
     ) -> Result<()>;
     fn is_json(&self) -> bool;
+
+    /// Return a printer paging its output through the given pager
+    /// command, if the output is meant to be read in a terminal.
+    fn pager(&self, _cmd: SingleCmd) -> Option<PagerPrinter> {
+        None
+    }
 }
 
 pub struct StdoutPrinter {
@@ -78,6 +88,14 @@ impl Printer for StdoutPrinter {
     fn is_json(&self) -> bool {
         self.fmt == OutputFmt::Json
     }
+
+    fn pager(&self, cmd: SingleCmd) -> Option<PagerPrinter> {
+        if self.fmt == OutputFmt::Plain && io::stdout().is_terminal() {
+            Some(PagerPrinter::new(cmd, self.writer.supports_color()))
+        } else {
+            None
+        }
+    }
 }
 
 impl From<OutputFmt> for StdoutPrinter {
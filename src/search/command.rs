@@ -0,0 +1,145 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use email::folder::FolderKind;
+use log::info;
+
+use crate::{
+    account::arg::name::AccountNameFlag,
+    backend::{Backend, BackendKind},
+    config::TomlConfig,
+    envelope::column::{EnvelopeColumn, EnvelopeColumns},
+    printer::{PrintTableOpts, Printer},
+    ui::arg::max_width::TableMaxWidthFlag,
+};
+
+use super::SearchIndex;
+
+/// Search messages using the full-text search index.
+///
+/// This command searches the subject, the addresses and the decoded
+/// text parts of the messages of the synchronized Maildir, which
+/// means that the synchronization needs to be enabled for the
+/// account. The index is updated before searching (only folders
+/// modified since the last update are read), and after every account
+/// synchronization. Envelopes are sorted by relevance, and
+/// their ids can be used with the --folder argument of other
+/// commands.
+#[derive(Debug, Parser)]
+pub struct SearchCommand {
+    /// The search query.
+    ///
+    /// Words are joined with "AND" by default, and can be combined
+    /// with the "OR" and "NOT" operators. Words wrapped into double
+    /// quotes are searched as a phrase, and words ending with a star
+    /// are searched as prefixes. Searches can be restricted to a
+    /// column by prefixing words with "subject:", "addresses:" or
+    /// "body:".
+    ///
+    /// Example: invoice OR receipt addresses:alice NOT body:draft*
+    #[arg(value_name = "QUERY", required = true, num_args = 1..)]
+    pub query: Vec<String>,
+
+    /// Search only messages of the given folder.
+    #[arg(long, short, value_name = "FOLDER")]
+    pub folder: Option<String>,
+
+    /// The page number.
+    ///
+    /// Results are sorted by relevance, the best matches first, then
+    /// split into pages. The page number starts from 1 (which is the
+    /// default). Giving a page number too big results in an
+    /// out-of-bounds error, which gives the number of the last page.
+    #[arg(long, short, value_name = "NUMBER", default_value = "1")]
+    pub page: usize,
+
+    /// The page size.
+    ///
+    /// Determine the amount of envelopes a page should contain.
+    #[arg(long, short = 's', value_name = "NUMBER")]
+    pub page_size: Option<usize>,
+
+    #[command(flatten)]
+    pub table: TableMaxWidthFlag,
+
+    #[command(flatten)]
+    pub account: AccountNameFlag,
+}
+
+impl SearchCommand {
+    pub async fn execute(self, printer: &mut impl Printer, config: &TomlConfig) -> Result<()> {
+        info!("executing search command");
+
+        let (toml_account_config, account_config) = config
+            .clone()
+            .into_account_configs(self.account.name.as_deref(), false)?;
+
+        let sync_enabled = toml_account_config
+            .sync
+            .as_ref()
+            .and_then(|sync| sync.enable)
+            .unwrap_or_default();
+        if !sync_enabled {
+            let account = &account_config.name;
+            bail!("cannot search messages: synchronization is not enabled for account {account}");
+        }
+
+        let query = self.query.join(" ");
+        let page = 1.max(self.page) - 1;
+        let page_size = self
+            .page_size
+            .unwrap_or_else(|| account_config.get_envelope_list_page_size());
+
+        let colors = toml_account_config
+            .list_envelopes_colors()
+            .cloned()
+            .unwrap_or_default();
+        let columns = toml_account_config
+            .list_envelopes_columns()
+            .cloned()
+            .unwrap_or_default();
+        let mut columns = EnvelopeColumns::new(columns, colors)?;
+        if self.folder.is_none() {
+            columns.insert(EnvelopeColumn::Folder);
+        }
+
+        // the index stores folder aliases
+        let folder = self.folder.as_deref().map(|folder| {
+            if FolderKind::matches_inbox(folder) {
+                account_config.get_inbox_folder_alias()
+            } else {
+                account_config.get_folder_alias(folder)
+            }
+        });
+
+        let index = SearchIndex::open(account_config.get_sync_dir()?)?;
+        let backend = Backend::new(
+            &toml_account_config,
+            &account_config,
+            Some(&BackendKind::MaildirForSync),
+            |_| (),
+        )
+        .await?;
+
+        backend.update_search_index(&index).await?;
+
+        let envelopes = backend
+            .search_envelopes(
+                &index,
+                &query,
+                folder.as_deref(),
+                page_size,
+                page,
+                &columns.headers(),
+            )
+            .await?
+            .with_columns(columns);
+
+        printer.print_table(
+            Box::new(envelopes),
+            PrintTableOpts {
+                format: &account_config.get_message_read_format(),
+                max_width: self.table.max_width,
+            },
+        )
+    }
+}
@@ -0,0 +1,590 @@
+//! Module dedicated to full-text search.
+//!
+//! Messages of the synchronized Maildir are indexed in a SQLite FTS5
+//! table containing their subject, their addresses and their decoded
+//! text parts. The index is updated incrementally: only folders
+//! modified since the last update are listed, only messages added
+//! since the last update are parsed, and messages that disappeared
+//! are removed from the index.
+
+pub mod command;
+
+use anyhow::{bail, Context, Result};
+use email::{
+    account::config::AccountConfig, flag::Flags, folder::Folders, maildir::MaildirSessionSync,
+    message::Message,
+};
+use log::{debug, info, trace};
+use rusqlite::OptionalExtension;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::{
+    account::config::TomlAccountConfig,
+    backend::{Backend, BackendKind},
+    envelope::details::EnvelopeDetails,
+};
+
+const SEARCH_INDEX_DB_FILE_NAME: &str = ".search-index.sqlite";
+
+/// A message matching a search query.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchHit {
+    pub folder: String,
+    pub id: String,
+}
+
+/// A search hit with its envelope and its details, read from the
+/// synchronized Maildir.
+#[derive(Clone, Debug)]
+pub struct SearchHitEnvelope {
+    pub folder: String,
+    pub envelope: email::envelope::Envelope,
+    pub details: EnvelopeDetails,
+}
+
+/// The changes applied to the index by an update.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchIndexReport {
+    pub indexed: usize,
+    pub removed: usize,
+}
+
+/// The full-text search index of an account.
+#[derive(Debug)]
+pub struct SearchIndex {
+    conn: rusqlite::Connection,
+}
+
+impl SearchIndex {
+    /// Open the index living in the given directory, which is
+    /// usually the sync directory of the account.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let path = dir.as_ref().join(SEARCH_INDEX_DB_FILE_NAME);
+        debug!("opening search index at {path:?}…");
+
+        let conn = rusqlite::Connection::open(&path)
+            .with_context(|| format!("cannot open search index at {path:?}"))?;
+
+        let query = "
+            CREATE TABLE IF NOT EXISTS messages (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                folder TEXT NOT NULL,
+                internal_id TEXT NOT NULL,
+                UNIQUE (folder, internal_id)
+            );
+            CREATE TABLE IF NOT EXISTS folders (
+                name TEXT PRIMARY KEY,
+                cur_mtime INTEGER NOT NULL,
+                new_mtime INTEGER NOT NULL
+            );
+            CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+                subject,
+                addresses,
+                body,
+                tokenize = 'unicode61 remove_diacritics 2'
+            );
+        ";
+        trace!("create tables query: {query:#?}");
+
+        conn.execute_batch(query)
+            .context("cannot create search index tables")?;
+
+        Ok(Self { conn })
+    }
+
+    /// Index the messages of all the folders of the given Maildir
+    /// that are not indexed yet, and remove the messages that do not
+    /// exist anymore.
+    ///
+    /// Folders whose `cur` and `new` directories did not change since
+    /// the last update are skipped: adding, removing or renaming a
+    /// message (flags are part of the file name) updates the
+    /// modification time of its directory.
+    pub async fn update(&self, session: &MaildirSessionSync) -> Result<SearchIndexReport> {
+        info!("updating search index");
+
+        let session = session.lock().await;
+        let config = &session.account_config;
+
+        let mut folders = vec![config.get_inbox_folder_alias()];
+        folders.extend(
+            Folders::from_submaildirs(config, session.list_subdirs())
+                .iter()
+                .map(|folder| folder.name.clone()),
+        );
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .context("cannot start search index update")?;
+
+        let mut report = SearchIndexReport::default();
+
+        for folder in &folders {
+            let mdir = session.get_maildir_from_folder_name(folder)?;
+
+            // mtimes are read before listing entries, so that changes
+            // happening during the update are caught by the next one
+            let mtimes = folder_mtimes(mdir.path());
+            if mtimes.is_some() && indexed_folder_mtimes(&tx, folder)? == mtimes {
+                debug!("folder {folder} did not change since last update, skipping it");
+                continue;
+            }
+
+            let mut entries: HashMap<String, PathBuf> = HashMap::new();
+            for entry in mdir.list_cur().chain(mdir.list_new()) {
+                match entry {
+                    Ok(entry) => {
+                        entries.insert(entry.id().to_owned(), entry.path().to_owned());
+                    }
+                    Err(err) => {
+                        debug!("cannot read maildir entry, skipping it: {err}");
+                        debug!("{err:?}");
+                    }
+                }
+            }
+
+            update_folder(&tx, folder, entries, &mut report)?;
+
+            if let Some((cur_mtime, new_mtime)) = mtimes {
+                tx.execute(
+                    "INSERT OR REPLACE INTO folders (name, cur_mtime, new_mtime) VALUES (?, ?, ?)",
+                    rusqlite::params![folder, cur_mtime, new_mtime],
+                )
+                .with_context(|| format!("cannot save state of folder {folder}"))?;
+            }
+        }
+
+        // messages of folders that do not exist anymore
+        let known_folders: HashSet<&String> = folders.iter().collect();
+        let mut stmt = tx.prepare("SELECT id, folder FROM messages")?;
+        let stale: Vec<i64> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)))?
+            .collect::<rusqlite::Result<Vec<(i64, String)>>>()?
+            .into_iter()
+            .filter(|(_, folder)| !known_folders.contains(folder))
+            .map(|(rowid, _)| rowid)
+            .collect();
+        drop(stmt);
+
+        for rowid in stale {
+            remove_message(&tx, rowid)?;
+            report.removed += 1;
+        }
+
+        let mut stmt = tx.prepare("SELECT name FROM folders")?;
+        let stale: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?
+            .into_iter()
+            .filter(|folder| !known_folders.contains(folder))
+            .collect();
+        drop(stmt);
+
+        for folder in stale {
+            tx.execute("DELETE FROM folders WHERE name = ?", [folder])
+                .context("cannot remove folder from search index")?;
+        }
+
+        tx.commit().context("cannot save search index")?;
+        debug!("search index report: {report:?}");
+
+        Ok(report)
+    }
+
+    /// Search messages matching the given FTS5 query, optionally
+    /// restricted to a folder. Messages are sorted by relevance.
+    ///
+    /// Return the matching messages of the requested page, as well
+    /// as the total amount of matching messages. Requesting a page
+    /// past the last one fails, the error gives the last page.
+    pub fn search(
+        &self,
+        query: &str,
+        folder: Option<&str>,
+        page_size: usize,
+        page: usize,
+    ) -> Result<(Vec<SearchHit>, usize)> {
+        info!("searching messages matching {query}");
+
+        let filter = "messages_fts MATCH ?1 AND (?2 IS NULL OR messages.folder = ?2)";
+
+        let count_query = format!(
+            "SELECT COUNT(*) FROM messages_fts
+             JOIN messages ON messages.id = messages_fts.rowid
+             WHERE {filter}",
+        );
+        trace!("count query: {count_query:#?}");
+
+        let total: i64 = self
+            .conn
+            .query_row(&count_query, rusqlite::params![query, folder], |row| {
+                row.get(0)
+            })
+            .with_context(|| format!("cannot search messages matching {query}"))?;
+        let total = total as usize;
+
+        if page_size > 0 && page > 0 && page * page_size >= total {
+            let pages = total.div_ceil(page_size).max(1);
+            bail!(
+                "cannot search messages: page {} out of bounds, last page is {pages}",
+                page + 1
+            );
+        }
+
+        let (limit, offset) = if page_size == 0 {
+            (-1, 0)
+        } else {
+            (page_size as i64, (page * page_size) as i64)
+        };
+
+        let select_query = format!(
+            "SELECT messages.folder, messages.internal_id FROM messages_fts
+             JOIN messages ON messages.id = messages_fts.rowid
+             WHERE {filter}
+             ORDER BY rank LIMIT ?3 OFFSET ?4",
+        );
+        trace!("select query: {select_query:#?}");
+
+        let mut stmt = self
+            .conn
+            .prepare(&select_query)
+            .with_context(|| format!("cannot search messages matching {query}"))?;
+        let hits = stmt
+            .query_map(rusqlite::params![query, folder, limit, offset], |row| {
+                Ok(SearchHit {
+                    folder: row.get(0)?,
+                    id: row.get(1)?,
+                })
+            })
+            .with_context(|| format!("cannot search messages matching {query}"))?
+            .collect::<rusqlite::Result<_>>()
+            .with_context(|| format!("cannot search messages matching {query}"))?;
+
+        Ok((hits, total))
+    }
+}
+
+/// Synchronize the indexed messages of the given folder with its
+/// entries, indexed by id: new entries are indexed while missing ones
+/// are removed.
+fn update_folder(
+    tx: &rusqlite::Transaction,
+    folder: &str,
+    entries: HashMap<String, PathBuf>,
+    report: &mut SearchIndexReport,
+) -> Result<()> {
+    let mut stmt = tx.prepare("SELECT id, internal_id FROM messages WHERE folder = ?")?;
+    let indexed: HashMap<String, i64> = stmt
+        .query_map([folder], |row| Ok((row.get(1)?, row.get(0)?)))?
+        .collect::<rusqlite::Result<_>>()
+        .with_context(|| format!("cannot get indexed messages of folder {folder}"))?;
+    drop(stmt);
+
+    for (id, rowid) in &indexed {
+        if !entries.contains_key(id) {
+            remove_message(tx, *rowid)?;
+            report.removed += 1;
+        }
+    }
+
+    for (id, path) in entries {
+        if indexed.contains_key(&id) {
+            continue;
+        }
+
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                debug!("cannot read message {id} at {path:?}, skipping it: {err}");
+                continue;
+            }
+        };
+
+        let doc = IndexedMessage::from_bytes(bytes);
+
+        tx.execute(
+            "INSERT INTO messages (folder, internal_id) VALUES (?, ?)",
+            [folder, &id],
+        )
+        .with_context(|| format!("cannot index message {id}"))?;
+        let rowid = tx.last_insert_rowid();
+
+        tx.execute(
+            "INSERT INTO messages_fts (rowid, subject, addresses, body) VALUES (?, ?, ?, ?)",
+            rusqlite::params![rowid, doc.subject, doc.addresses, doc.body],
+        )
+        .with_context(|| format!("cannot index message {id}"))?;
+
+        report.indexed += 1;
+    }
+
+    Ok(())
+}
+
+/// Return the modification times of the `cur` and `new` directories
+/// of the given Maildir folder, in nanoseconds.
+fn folder_mtimes(dir: &Path) -> Option<(i64, i64)> {
+    let mtime = |name: &str| -> Option<i64> {
+        let mtime = fs::metadata(dir.join(name)).ok()?.modified().ok()?;
+        let mtime = mtime.duration_since(UNIX_EPOCH).ok()?;
+        i64::try_from(mtime.as_nanos()).ok()
+    };
+
+    Some((mtime("cur")?, mtime("new")?))
+}
+
+/// Return the modification times saved by the last update of the
+/// given folder.
+fn indexed_folder_mtimes(tx: &rusqlite::Transaction, folder: &str) -> Result<Option<(i64, i64)>> {
+    tx.query_row(
+        "SELECT cur_mtime, new_mtime FROM folders WHERE name = ?",
+        [folder],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .with_context(|| format!("cannot get state of folder {folder}"))
+}
+
+fn remove_message(tx: &rusqlite::Transaction, rowid: i64) -> Result<()> {
+    tx.execute("DELETE FROM messages WHERE id = ?", [rowid])
+        .context("cannot remove message from search index")?;
+    tx.execute("DELETE FROM messages_fts WHERE rowid = ?", [rowid])
+        .context("cannot remove message from search index")?;
+    Ok(())
+}
+
+/// The searchable content of a message.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct IndexedMessage {
+    subject: String,
+    addresses: String,
+    body: String,
+}
+
+impl IndexedMessage {
+    fn from_bytes(bytes: Vec<u8>) -> Self {
+        let msg = Message::from(bytes);
+        let Ok(msg) = msg.parsed() else {
+            return Self::default();
+        };
+
+        let addresses = [msg.from(), msg.to(), msg.cc(), msg.bcc()]
+            .into_iter()
+            .flatten()
+            .flat_map(|addr| addr.iter())
+            .flat_map(|addr| [addr.name.as_deref(), addr.address.as_deref()])
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let body = (0..msg.text_body_count())
+            .filter_map(|pos| msg.body_text(pos))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Self {
+            subject: msg.subject().unwrap_or_default().to_owned(),
+            addresses,
+            body,
+        }
+    }
+}
+
+/// Read the envelopes of the given hits from the synchronized
+/// Maildir, in the same order.
+///
+/// Each folder is listed once whatever the amount of hits it
+/// contains, and each message is read once. Hits that do not exist
+/// anymore are skipped.
+pub async fn read_hits(
+    session: &MaildirSessionSync,
+    hits: Vec<SearchHit>,
+    headers: &[String],
+) -> Result<Vec<SearchHitEnvelope>> {
+    let mut entries: HashMap<(String, String), (PathBuf, Flags)> = HashMap::new();
+
+    {
+        let session = session.lock().await;
+
+        let mut ids: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for hit in &hits {
+            ids.entry(&hit.folder).or_default().insert(&hit.id);
+        }
+
+        for (folder, ids) in ids {
+            let mdir = session.get_maildir_from_folder_name(folder)?;
+            for entry in mdir.list_cur().chain(mdir.list_new()).flatten() {
+                if ids.contains(entry.id()) {
+                    let key = (folder.to_owned(), entry.id().to_owned());
+                    let flags = Flags::from_mdir_entry(&entry);
+                    entries.insert(key, (entry.path().to_owned(), flags));
+                }
+            }
+        }
+    }
+
+    let envelopes = hits
+        .into_iter()
+        .filter_map(|hit| {
+            let key = (hit.folder, hit.id);
+            let Some((path, flags)) = entries.remove(&key) else {
+                debug!(
+                    "cannot find indexed message {} in {}, skipping it",
+                    key.1, key.0
+                );
+                return None;
+            };
+
+            let (folder, id) = key;
+            let bytes = match fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    debug!("cannot read message {id} at {path:?}, skipping it: {err}");
+                    return None;
+                }
+            };
+
+            let size = bytes.len() as u64;
+            let msg = Message::from(bytes);
+            let details = EnvelopeDetails::from_msg(&msg, size, headers);
+            let envelope = email::envelope::Envelope::from_msg(id, flags, msg);
+
+            Some(SearchHitEnvelope {
+                folder,
+                envelope,
+                details,
+            })
+        })
+        .collect();
+
+    Ok(envelopes)
+}
+
+/// Update the search index of the given account from its
+/// synchronized Maildir.
+pub async fn update_index(
+    toml_account_config: &TomlAccountConfig,
+    account_config: &AccountConfig,
+) -> Result<SearchIndexReport> {
+    let index = SearchIndex::open(account_config.get_sync_dir()?)?;
+    let backend = Backend::new(
+        toml_account_config,
+        account_config,
+        Some(&BackendKind::MaildirForSync),
+        |_| (),
+    )
+    .await?;
+
+    backend.update_search_index(&index).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        fs,
+        path::{Path, PathBuf},
+        thread,
+        time::Duration,
+    };
+
+    use super::{folder_mtimes, update_folder, SearchHit, SearchIndex, SearchIndexReport};
+
+    fn write_message(dir: &Path, id: &str, content: &str) -> PathBuf {
+        let path = dir.join(id);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn update(index: &SearchIndex, entries: &[(&str, &PathBuf)]) -> SearchIndexReport {
+        let entries: HashMap<String, PathBuf> = entries
+            .iter()
+            .map(|(id, path)| (id.to_string(), path.to_path_buf()))
+            .collect();
+        let mut report = SearchIndexReport::default();
+
+        let tx = index.conn.unchecked_transaction().unwrap();
+        update_folder(&tx, "INBOX", entries, &mut report).unwrap();
+        tx.commit().unwrap();
+
+        report
+    }
+
+    #[test]
+    fn index_and_search_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = SearchIndex::open(dir.path()).unwrap();
+
+        let invoice = write_message(
+            dir.path(),
+            "1",
+            "From: alice@localhost\r\nSubject: Invoice\r\n\r\nPlease find the invoice attached.\r\n",
+        );
+        let meeting = write_message(
+            dir.path(),
+            "2",
+            "From: bob@localhost\r\nSubject: Meeting\r\n\r\nSee you tomorrow.\r\n",
+        );
+
+        let report = update(&index, &[("1", &invoice), ("2", &meeting)]);
+        assert_eq!(report.indexed, 2);
+        assert_eq!(report.removed, 0);
+
+        let (hits, total) = index.search("invoice", None, 10, 0).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(
+            hits,
+            vec![SearchHit {
+                folder: "INBOX".into(),
+                id: "1".into(),
+            }]
+        );
+
+        let (hits, total) = index.search("addresses:bob", Some("INBOX"), 10, 0).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(hits[0].id, "2");
+
+        let (hits, total) = index.search("invoice", Some("Archive"), 10, 0).unwrap();
+        assert_eq!(total, 0);
+        assert!(hits.is_empty());
+
+        let (hits, total) = index.search("invoice OR meeting", None, 1, 1).unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(hits.len(), 1);
+
+        let err = index.search("invoice", None, 1, 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot search messages: page 2 out of bounds, last page is 1"
+        );
+
+        // indexed messages are not parsed again, and missing ones are
+        // removed from the index
+        let report = update(&index, &[("1", &invoice)]);
+        assert_eq!(report.indexed, 0);
+        assert_eq!(report.removed, 1);
+        assert_eq!(index.search("meeting", None, 10, 0).unwrap().1, 0);
+    }
+
+    #[test]
+    fn detect_folder_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(folder_mtimes(dir.path()), None);
+
+        fs::create_dir(dir.path().join("cur")).unwrap();
+        fs::create_dir(dir.path().join("new")).unwrap();
+        let mtimes = folder_mtimes(dir.path());
+        assert!(mtimes.is_some());
+        assert_eq!(folder_mtimes(dir.path()), mtimes);
+
+        thread::sleep(Duration::from_millis(20));
+        write_message(&dir.path().join("new"), "1", "Subject: New\r\n\r\n");
+        assert_ne!(folder_mtimes(dir.path()), mtimes);
+    }
+}
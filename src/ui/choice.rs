@@ -1,50 +1,19 @@
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{bail, Context, Result};
+#[cfg(feature = "outbox-subcmd")]
+use chrono::{DateTime, Local};
 use log::{debug, error};
 use std::io::{self, Write};
 
-pub enum PreEditChoice {
-    Edit,
-    Discard,
-    Quit,
-}
-
-pub fn pre_edit() -> Result<PreEditChoice> {
-    println!("A draft was found:");
-    print!("(e)dit, (d)iscard or (q)uit? ");
-    io::stdout().flush().context("cannot flush stdout")?;
-
-    let mut buf = String::new();
-    io::stdin()
-        .read_line(&mut buf)
-        .context("cannot read stdin")?;
-
-    match buf.bytes().next().map(|bytes| bytes as char) {
-        Some('e') => {
-            debug!("edit choice matched");
-            Ok(PreEditChoice::Edit)
-        }
-        Some('d') => {
-            debug!("discard choice matched");
-            Ok(PreEditChoice::Discard)
-        }
-        Some('q') => {
-            debug!("quit choice matched");
-            Ok(PreEditChoice::Quit)
-        }
-        Some(choice) => {
-            error!(r#"invalid choice "{}""#, choice);
-            Err(anyhow!(r#"invalid choice "{}""#, choice))
-        }
-        None => {
-            error!("empty choice");
-            Err(anyhow!("empty choice"))
-        }
-    }
-}
+#[cfg(feature = "outbox-subcmd")]
+use crate::outbox::schedule;
 
 pub enum PostEditChoice {
     #[cfg(feature = "message-send")]
     Send,
+    #[cfg(feature = "outbox-subcmd")]
+    Queue,
+    #[cfg(feature = "outbox-subcmd")]
+    Schedule,
     Edit,
     LocalDraft,
     #[cfg(feature = "message-add")]
@@ -53,6 +22,9 @@ pub enum PostEditChoice {
 }
 
 pub fn post_edit() -> Result<PostEditChoice> {
+    #[cfg(feature = "outbox-subcmd")]
+    print!("(s)end, (q)ueue, s(c)hedule, (e)dit, (l)ocal/(r)emote draft or (d)iscard? ");
+    #[cfg(not(feature = "outbox-subcmd"))]
     print!("(s)end, (e)dit, (l)ocal/(r)emote draft or (d)iscard? ");
     io::stdout().flush().context("cannot flush stdout")?;
 
@@ -67,6 +39,16 @@ pub fn post_edit() -> Result<PostEditChoice> {
             debug!("send choice matched");
             Ok(PostEditChoice::Send)
         }
+        #[cfg(feature = "outbox-subcmd")]
+        Some('q') => {
+            debug!("queue choice matched");
+            Ok(PostEditChoice::Queue)
+        }
+        #[cfg(feature = "outbox-subcmd")]
+        Some('c') => {
+            debug!("schedule choice matched");
+            Ok(PostEditChoice::Schedule)
+        }
         Some('l') => {
             debug!("save local draft choice matched");
             Ok(PostEditChoice::LocalDraft)
@@ -94,3 +76,16 @@ pub fn post_edit() -> Result<PostEditChoice> {
         }
     }
 }
+
+#[cfg(feature = "outbox-subcmd")]
+pub fn send_at() -> Result<DateTime<Local>> {
+    print!("Send at (YYYY-MM-DD HH:MM)? ");
+    io::stdout().flush().context("cannot flush stdout")?;
+
+    let mut buf = String::new();
+    io::stdin()
+        .read_line(&mut buf)
+        .context("cannot read stdin")?;
+
+    schedule::parse_send_at(&buf)
+}
@@ -1,8 +1,5 @@
 use anyhow::{Context, Result};
-use email::{
-    account::config::AccountConfig,
-    email::utils::{local_draft_path, remove_local_draft},
-};
+use email::account::config::AccountConfig;
 #[cfg(feature = "message-add")]
 use email::{
     flag::{Flag, Flags},
@@ -12,20 +9,22 @@ use log::debug;
 #[cfg(any(feature = "message-send", feature = "template-send"))]
 use mml::MmlCompilerBuilder;
 use process::SingleCmd;
-use std::{env, fs};
+use std::{env, fs, path::Path};
 
+#[cfg(feature = "message-edit")]
+use crate::message::draft;
+#[cfg(feature = "outbox-subcmd")]
+use crate::outbox::{self, schedule::Schedule, Outbox};
 use crate::{
     backend::Backend,
+    message::draft::{Drafts, RemoteDraft},
     printer::Printer,
-    ui::choice::{self, PostEditChoice, PreEditChoice},
+    ui::choice::{self, PostEditChoice},
 };
 
-pub async fn open_with_tpl(tpl: String) -> Result<String> {
-    let path = local_draft_path();
-
-    debug!("create draft");
-    fs::write(&path, tpl.as_bytes()).context(format!("cannot write local draft at {:?}", path))?;
-
+/// Open the editor on the given local draft, and return its content
+/// once the editor session finishes.
+pub async fn open_draft(path: &Path) -> Result<String> {
     debug!("open editor");
     let editor = env::var("EDITOR").context("cannot get editor from env var")?;
     SingleCmd::from(format!("{editor} {}", &path.to_string_lossy()))
@@ -36,49 +35,43 @@ pub async fn open_with_tpl(tpl: String) -> Result<String> {
 
     debug!("read draft");
     let content =
-        fs::read_to_string(&path).context(format!("cannot read local draft at {:?}", path))?;
+        fs::read_to_string(path).context(format!("cannot read local draft at {:?}", path))?;
 
     Ok(content)
 }
 
-pub async fn open_with_local_draft() -> Result<String> {
-    let path = local_draft_path();
-    let content =
-        fs::read_to_string(&path).context(format!("cannot read local draft at {:?}", path))?;
-    open_with_tpl(content).await
+/// Save the given template as a new local draft, then edit it.
+pub async fn edit_tpl_with_editor<P: Printer>(
+    config: &AccountConfig,
+    printer: &mut P,
+    backend: &Backend,
+    tpl: String,
+) -> Result<()> {
+    let drafts = Drafts::open(config)?;
+    let id = drafts.create(&tpl)?;
+    edit_draft_with_editor(config, printer, backend, &drafts, &id).await
 }
 
+/// Edit the given local draft, then ask what to do with it.
+///
+/// The draft is kept until it is sent, saved remotely or discarded,
+/// so an interrupted session can be resumed with `draft edit`.
+///
+/// When the draft has been loaded from a remote draft (see
+/// [`Drafts::remote`]), the remote draft is removed once the draft is
+/// sent or saved remotely, so that it is replaced rather than
+/// duplicated.
 #[allow(unused)]
-pub async fn edit_tpl_with_editor<P: Printer>(
+pub async fn edit_draft_with_editor<P: Printer>(
     config: &AccountConfig,
     printer: &mut P,
     backend: &Backend,
-    mut tpl: String,
+    drafts: &Drafts,
+    id: &str,
 ) -> Result<()> {
-    let draft = local_draft_path();
-    if draft.exists() {
-        loop {
-            match choice::pre_edit() {
-                Ok(choice) => match choice {
-                    PreEditChoice::Edit => {
-                        tpl = open_with_local_draft().await?;
-                        break;
-                    }
-                    PreEditChoice::Discard => {
-                        tpl = open_with_tpl(tpl).await?;
-                        break;
-                    }
-                    PreEditChoice::Quit => return Ok(()),
-                },
-                Err(err) => {
-                    println!("{}", err);
-                    continue;
-                }
-            }
-        }
-    } else {
-        tpl = open_with_tpl(tpl).await?;
-    }
+    let remote_draft = drafts.remote(id)?;
+    let path = drafts.path(id);
+    let mut tpl = open_draft(&path).await?;
 
     loop {
         match choice::post_edit() {
@@ -94,18 +87,82 @@ pub async fn edit_tpl_with_editor<P: Printer>(
 
                 let email = compiler.build(tpl.as_str())?.compile().await?.into_vec()?;
 
+                #[cfg(feature = "outbox-subcmd")]
+                if let Err(err) = outbox::send_or_queue_with(backend, &email).await {
+                    // the draft is not needed anymore once its
+                    // message is in the outbox
+                    if outbox::is_queued(&err) {
+                        drafts.remove(id)?;
+                        remove_remote_draft(config, backend, remote_draft.as_ref()).await?;
+                    }
+                    return Err(err);
+                }
+
+                #[cfg(not(feature = "outbox-subcmd"))]
                 backend.send_message(&email).await?;
 
-                remove_local_draft()?;
+                drafts.remove(id)?;
+                remove_remote_draft(config, backend, remote_draft.as_ref()).await?;
                 printer.print("Done!")?;
+
+                break;
+            }
+            #[cfg(feature = "outbox-subcmd")]
+            Ok(PostEditChoice::Queue) => {
+                #[allow(unused_mut)]
+                let mut compiler = MmlCompilerBuilder::new();
+
+                #[cfg(feature = "pgp")]
+                compiler.set_some_pgp(config.pgp.clone());
+
+                let email = compiler.build(tpl.as_str())?.compile().await?.into_vec()?;
+
+                let outbox_id = Outbox::open(config).await?.add(&email).await?;
+                drafts.remove(id)?;
+                remove_remote_draft(config, backend, remote_draft.as_ref()).await?;
+                printer.print(format!(
+                    "Message successfully queued to the outbox as {outbox_id}!"
+                ))?;
+                break;
+            }
+            #[cfg(feature = "outbox-subcmd")]
+            Ok(PostEditChoice::Schedule) => {
+                let send_at = match choice::send_at() {
+                    Ok(send_at) => send_at,
+                    Err(err) => {
+                        println!("{}", err);
+                        continue;
+                    }
+                };
+
+                #[allow(unused_mut)]
+                let mut compiler = MmlCompilerBuilder::new();
+
+                #[cfg(feature = "pgp")]
+                compiler.set_some_pgp(config.pgp.clone());
+
+                let email = compiler.build(tpl.as_str())?.compile().await?.into_vec()?;
+
+                let scheduled_id = match Schedule::open(config)?.add(&email, &send_at) {
+                    Ok(id) => id,
+                    Err(err) => {
+                        println!("{}", err);
+                        continue;
+                    }
+                };
+                drafts.remove(id)?;
+                remove_remote_draft(config, backend, remote_draft.as_ref()).await?;
+                printer.print(format!(
+                    "Message successfully scheduled at {send_at} as {scheduled_id}!"
+                ))?;
                 break;
             }
             Ok(PostEditChoice::Edit) => {
-                tpl = open_with_tpl(tpl).await?;
+                tpl = open_draft(&path).await?;
                 continue;
             }
             Ok(PostEditChoice::LocalDraft) => {
-                printer.print("Email successfully saved locally")?;
+                printer.print(format!("Email successfully saved locally as draft {id}"))?;
                 break;
             }
             #[cfg(feature = "message-add")]
@@ -125,12 +182,13 @@ pub async fn edit_tpl_with_editor<P: Printer>(
                         &Flags::from_iter([Flag::Seen, Flag::Draft]),
                     )
                     .await?;
-                remove_local_draft()?;
+                drafts.remove(id)?;
+                remove_remote_draft(config, backend, remote_draft.as_ref()).await?;
                 printer.print("Email successfully saved to drafts")?;
                 break;
             }
             Ok(PostEditChoice::Discard) => {
-                remove_local_draft()?;
+                drafts.remove(id)?;
                 break;
             }
             Err(err) => {
@@ -142,3 +200,22 @@ pub async fn edit_tpl_with_editor<P: Printer>(
 
     Ok(())
 }
+
+/// Remove the remote draft the local draft has been loaded from, if
+/// any.
+#[allow(unused)]
+async fn remove_remote_draft(
+    config: &AccountConfig,
+    backend: &Backend,
+    remote_draft: Option<&RemoteDraft>,
+) -> Result<()> {
+    #[cfg(feature = "message-edit")]
+    if let Some(remote_draft) = remote_draft {
+        let RemoteDraft { folder, id } = remote_draft;
+        draft::remove_remote_draft(backend.toml_account_config(), config, remote_draft)
+            .await
+            .with_context(|| format!("cannot remove previous draft {id} from {folder}"))?;
+    }
+
+    Ok(())
+}
@@ -116,6 +116,12 @@ impl Cell {
         self
     }
 
+    /// Applies the given color to the cell.
+    pub fn fg(mut self, color: Color) -> Self {
+        self.style.set_fg(Some(color));
+        self
+    }
+
     /// Applies the custom ansi color to the cell.
     pub fn ansi_256(mut self, code: u8) -> Self {
         self.style.set_fg(Some(Color::Ansi256(code)));
@@ -169,100 +175,141 @@ where
 
     /// Writes the table to the writer.
     fn print(writer: &mut dyn WriteColor, items: &[Self], opts: PrintTableOpts) -> Result<()> {
-        let is_format_flowed = matches!(opts.format, EmailTextPlainFormat::Flowed);
-        let max_width = match opts.format {
-            EmailTextPlainFormat::Fixed(width) => opts.max_width.unwrap_or(*width),
-            EmailTextPlainFormat::Flowed => 0,
-            EmailTextPlainFormat::Auto => opts
-                .max_width
-                .or_else(|| terminal_size().map(|(w, _)| w.0 as usize))
-                .unwrap_or(DEFAULT_TERM_WIDTH),
-        };
-        let mut table = vec![Self::head()];
-        let mut cell_widths: Vec<usize> =
-            table[0].0.iter().map(|cell| cell.unicode_width()).collect();
-        table.extend(
-            items
-                .iter()
-                .map(|item| {
-                    let row = item.row();
-                    row.0.iter().enumerate().for_each(|(i, cell)| {
-                        cell_widths[i] = cell_widths[i].max(cell.unicode_width());
-                    });
-                    row
-                })
-                .collect::<Vec<_>>(),
-        );
-        trace!("cell widths: {:?}", cell_widths);
-
-        let spaces_plus_separators_len = cell_widths.len() * 2 - 1;
-        let table_width = cell_widths.iter().sum::<usize>() + spaces_plus_separators_len;
-        trace!("table width: {}", table_width);
-
-        for row in table.iter_mut() {
-            let mut glue = Cell::default();
-            for (i, cell) in row.0.iter_mut().enumerate() {
-                glue.print(writer)?;
-
-                let table_is_overflowing = table_width > max_width;
-                if table_is_overflowing && !is_format_flowed && cell.is_shrinkable() {
-                    trace!("table is overflowing and cell is shrinkable");
-
-                    let shrink_width = table_width - max_width;
-                    trace!("shrink width: {}", shrink_width);
-                    let cell_width = if shrink_width + MAX_SHRINK_WIDTH < cell_widths[i] {
-                        cell_widths[i] - shrink_width
-                    } else {
-                        MAX_SHRINK_WIDTH
-                    };
-                    trace!("cell width: {}", cell_width);
-                    trace!("cell unicode width: {}", cell.unicode_width());
-
-                    let cell_is_overflowing = cell.unicode_width() > cell_width;
-                    if cell_is_overflowing {
-                        trace!("cell is overflowing");
-
-                        let mut value = String::new();
-                        let mut chars_width = 0;
-
-                        for c in cell.value.chars() {
-                            let char_width = UnicodeWidthStr::width(c.to_string().as_str());
-                            if chars_width + char_width >= cell_width {
-                                break;
-                            }
-
-                            chars_width += char_width;
-                            value.push(c);
+        print_rows(writer, Self::head(), items.iter().map(Self::row), opts)
+    }
+}
+
+/// Format the given amount of bytes in a human-readable way.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for u in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = u;
+    }
+
+    format!("{size:.1}{unit}")
+}
+
+/// Writes the given head and rows as a table to the writer.
+///
+/// This is useful for tables whose columns are only known at
+/// runtime. When the table exceeds the maximum width, the overflow is
+/// distributed among shrinkable columns, from left to right.
+pub fn print_rows(
+    writer: &mut dyn WriteColor,
+    head: Row,
+    rows: impl IntoIterator<Item = Row>,
+    opts: PrintTableOpts,
+) -> Result<()> {
+    let is_format_flowed = matches!(opts.format, EmailTextPlainFormat::Flowed);
+    let max_width = match opts.format {
+        EmailTextPlainFormat::Fixed(width) => opts.max_width.unwrap_or(*width),
+        EmailTextPlainFormat::Flowed => 0,
+        EmailTextPlainFormat::Auto => opts
+            .max_width
+            .or_else(|| terminal_size().map(|(w, _)| w.0 as usize))
+            .unwrap_or(DEFAULT_TERM_WIDTH),
+    };
+    let mut cell_widths: Vec<usize> = head.0.iter().map(|cell| cell.unicode_width()).collect();
+    let mut table = vec![head];
+    table.extend(
+        rows.into_iter()
+            .map(|row| {
+                row.0.iter().enumerate().for_each(|(i, cell)| {
+                    cell_widths[i] = cell_widths[i].max(cell.unicode_width());
+                });
+                row
+            })
+            .collect::<Vec<_>>(),
+    );
+    trace!("cell widths: {:?}", cell_widths);
+
+    let spaces_plus_separators_len = cell_widths.len() * 2 - 1;
+    let table_width = cell_widths.iter().sum::<usize>() + spaces_plus_separators_len;
+    trace!("table width: {}", table_width);
+
+    // Computes the width of shrinkable columns, which is only
+    // defined when the table is overflowing.
+    let table_is_overflowing = table_width > max_width;
+    let mut shrunk_widths: Vec<Option<usize>> = vec![None; cell_widths.len()];
+    if table_is_overflowing && !is_format_flowed {
+        trace!("table is overflowing");
+
+        let mut shrink_width = table_width - max_width;
+        trace!("shrink width: {}", shrink_width);
+        for (i, cell) in table[0].0.iter().enumerate() {
+            if cell.is_shrinkable() {
+                let shrink = shrink_width.min(cell_widths[i].saturating_sub(MAX_SHRINK_WIDTH));
+                shrink_width -= shrink;
+                shrunk_widths[i] = Some((cell_widths[i] - shrink).max(MAX_SHRINK_WIDTH));
+            }
+        }
+    }
+
+    for row in table.iter_mut() {
+        let mut glue = Cell::default();
+        for (i, cell) in row.0.iter_mut().enumerate() {
+            glue.print(writer)?;
+
+            if let Some(cell_width) = shrunk_widths[i].filter(|_| cell.is_shrinkable()) {
+                trace!("table is overflowing and cell is shrinkable");
+                trace!("cell width: {}", cell_width);
+                trace!("cell unicode width: {}", cell.unicode_width());
+
+                let cell_is_overflowing = cell.unicode_width() > cell_width;
+                if cell_is_overflowing {
+                    trace!("cell is overflowing");
+
+                    let mut value = String::new();
+                    let mut chars_width = 0;
+
+                    for c in cell.value.chars() {
+                        let char_width = UnicodeWidthStr::width(c.to_string().as_str());
+                        if chars_width + char_width >= cell_width {
+                            break;
                         }
 
-                        value.push_str("… ");
-                        trace!("chars width: {}", chars_width);
-                        trace!("shrunk value: {}", value);
-                        let spaces_count = cell_width - chars_width - 1;
-                        trace!("number of spaces added to shrunk value: {}", spaces_count);
-                        value.push_str(&" ".repeat(spaces_count));
-                        cell.value = value;
-                    } else {
-                        trace!("cell is not overflowing");
-                        let spaces_count = cell_width - cell.unicode_width() + 1;
-                        trace!("number of spaces added to value: {}", spaces_count);
-                        cell.value.push_str(&" ".repeat(spaces_count));
+                        chars_width += char_width;
+                        value.push(c);
                     }
+
+                    value.push_str("… ");
+                    trace!("chars width: {}", chars_width);
+                    trace!("shrunk value: {}", value);
+                    let spaces_count = cell_width - chars_width - 1;
+                    trace!("number of spaces added to shrunk value: {}", spaces_count);
+                    value.push_str(&" ".repeat(spaces_count));
+                    cell.value = value;
                 } else {
-                    trace!("table is not overflowing or cell is not shrinkable");
-                    trace!("cell width: {}", cell_widths[i]);
-                    trace!("cell unicode width: {}", cell.unicode_width());
-                    let spaces_count = cell_widths[i] - cell.unicode_width() + 1;
+                    trace!("cell is not overflowing");
+                    let spaces_count = cell_width - cell.unicode_width() + 1;
                     trace!("number of spaces added to value: {}", spaces_count);
                     cell.value.push_str(&" ".repeat(spaces_count));
                 }
-                cell.print(writer)?;
-                glue = Cell::new("│").ansi_256(8);
+            } else {
+                trace!("table is not overflowing or cell is not shrinkable");
+                trace!("cell width: {}", cell_widths[i]);
+                trace!("cell unicode width: {}", cell.unicode_width());
+                let spaces_count = cell_widths[i] - cell.unicode_width() + 1;
+                trace!("number of spaces added to value: {}", spaces_count);
+                cell.value.push_str(&" ".repeat(spaces_count));
             }
-            writeln!(writer)?;
+            cell.print(writer)?;
+            glue = Cell::new("│").ansi_256(8);
         }
-        Ok(())
+        writeln!(writer)?;
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -443,4 +490,25 @@ mod tests {
         ];
         assert_eq!(expected, writer.content);
     }
+
+    #[test]
+    fn shrink_many() {
+        let mut writer = StringWriter::default();
+        let head = Row::new()
+            .cell(Cell::new("ID"))
+            .cell(Cell::new("NAME").shrinkable())
+            .cell(Cell::new("DESC").shrinkable());
+        let row = Row::new()
+            .cell(Cell::new("1"))
+            .cell(Cell::new("loooooooong").shrinkable())
+            .cell(Cell::new("deeeeeeesc").shrinkable());
+        let opts = PrintTableOpts {
+            format: &EmailTextPlainFormat::Auto,
+            max_width: Some(20),
+        };
+        print_rows(&mut writer, head, [row], opts).unwrap();
+
+        let expected = concat!["ID │NAME  │DESC     \n", "1  │looo… │deeeeee… \n"];
+        assert_eq!(expected, writer.content);
+    }
 }